@@ -0,0 +1,447 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! An async client for the v2 kitchen api.
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), api::client::Error> {
+//! let client = api::client::Client::new("http://localhost:3030")?;
+//! client.login("alice", "secret").await?;
+//! for entry in client.recipes().await? {
+//!     println!("{}", entry.recipe_id());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use recipes::{parse::ParseError, unit::MeasureType, Recipe, RecipeEntry, RecipeTemplate};
+use reqwest::{Method, RequestBuilder, Url};
+use serde::de::DeserializeOwned;
+
+use crate::{FullPlanData, InventoryData, PlanSummary, Response, UserData};
+
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+
+/// Errors returned by the [Client].
+#[derive(Debug)]
+pub enum Error {
+    /// The request couldn't be made or the response couldn't be decoded.
+    Http(reqwest::Error),
+    /// The base url for the client is invalid.
+    Url(String),
+    /// The server returned an error.
+    Api {
+        status: u16,
+        message: String,
+    },
+    NotFound,
+    Unauthorized,
+    /// The server couldn't parse something we sent it.
+    Invalid(ParseError),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Http(err) => write!(f, "Request failed: {}", err),
+            Error::Url(msg) => write!(f, "Invalid url: {}", msg),
+            Error::Api { status, message } => write!(f, "Api error {}: {}", status, message),
+            Error::NotFound => write!(f, "Not found"),
+            Error::Unauthorized => write!(f, "Unauthorized"),
+            Error::Invalid(err) => write!(f, "Invalid input: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<reqwest::Error> for Error {
+    fn from(err: reqwest::Error) -> Self {
+        Error::Http(err)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Convert an api response into a Result.
+pub fn into_result<T>(resp: Response<T>) -> Result<T> {
+    match resp {
+        Response::Success(val) => Ok(val),
+        Response::Err { status, message } => Err(Error::Api { status, message }),
+        Response::NotFound => Err(Error::NotFound),
+        Response::Unauthorized => Err(Error::Unauthorized),
+        Response::Invalid(err) => Err(Error::Invalid(err)),
+    }
+}
+
+fn parse_root(root: &str) -> Result<Url> {
+    let root = Url::parse(root).map_err(|e| Error::Url(e.to_string()))?;
+    if root.cannot_be_a_base() {
+        return Err(Error::Url(format!("{} can not be a base url", root)));
+    }
+    Ok(root)
+}
+
+/// The url for a v2 api path under the root. The path segments are encoded.
+fn api_url(root: &Url, path: &[&str]) -> Url {
+    let mut url = root.clone();
+    // NOTE(jwall): parse_root checked that the root can be a base so this is
+    // safe.
+    url.path_segments_mut()
+        .expect("Root url can not be a base")
+        .pop_if_empty()
+        .extend(["api", "v2"])
+        .extend(path);
+    url
+}
+
+/// A client for the v2 api of a kitchen server.
+///
+/// Requests are authenticated either with the session cookie from
+/// [Client::login] or, if the server allows it, with HTTP Basic auth on every
+/// request using [Client::with_basic_auth].
+#[derive(Debug, Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    root: Url,
+    basic_auth: Option<(String, String)>,
+}
+
+impl Client {
+    /// Construct a client for the kitchen server at `root`.
+    /// e.g. `http://localhost:3030`
+    pub fn new<S: AsRef<str>>(root: S) -> Result<Self> {
+        let root = parse_root(root.as_ref())?;
+        let http = reqwest::Client::builder().cookie_store(true).build()?;
+        Ok(Self {
+            http,
+            root,
+            basic_auth: None,
+        })
+    }
+
+    /// Send HTTP Basic auth with every request instead of using a session.
+    pub fn with_basic_auth<U: Into<String>, P: Into<String>>(mut self, user: U, pass: P) -> Self {
+        self.basic_auth = Some((user.into(), pass.into()));
+        self
+    }
+
+    fn url(&self, path: &[&str]) -> Url {
+        api_url(&self.root, path)
+    }
+
+    fn request(&self, method: Method, path: &[&str]) -> RequestBuilder {
+        let req = self.http.request(method, self.url(path));
+        match &self.basic_auth {
+            Some((user, pass)) => req.basic_auth(user, Some(pass)),
+            None => req,
+        }
+    }
+
+    async fn send<T: DeserializeOwned>(&self, req: RequestBuilder) -> Result<T> {
+        // NOTE(jwall): The api returns a Response for errors as well so we
+        // decode the body regardless of the status code.
+        let resp: Response<T> = req.send().await?.json().await?;
+        into_result(resp)
+    }
+
+    /// Log in and keep the session for later requests.
+    pub async fn login(&self, user: &str, pass: &str) -> Result<UserData> {
+        let req = self
+            .http
+            .post(self.url(&["auth"]))
+            .basic_auth(user, Some(pass));
+        self.send(req).await
+    }
+
+    pub async fn logout(&self) -> Result<()> {
+        self.send(self.request(Method::DELETE, &["auth"])).await
+    }
+
+    pub async fn account(&self) -> Result<UserData> {
+        self.send(self.request(Method::GET, &["account"])).await
+    }
+
+    pub async fn recipes(&self) -> Result<Vec<RecipeEntry>> {
+        self.send(self.request(Method::GET, &["recipes"])).await
+    }
+
+    pub async fn search_recipes(&self, term: &str) -> Result<Vec<RecipeEntry>> {
+        let req = self
+            .request(Method::GET, &["recipes", "search"])
+            .query(&[("q", term)]);
+        self.send(req).await
+    }
+
+    pub async fn recipe(&self, recipe_id: &str) -> Result<Option<RecipeEntry>> {
+        self.send(self.request(Method::GET, &["recipe", recipe_id]))
+            .await
+    }
+
+    /// The recipe parsed by the server.
+    pub async fn parsed_recipe(&self, recipe_id: &str) -> Result<Recipe> {
+        self.send(self.request(Method::GET, &["recipe", recipe_id, "parsed"]))
+            .await
+    }
+
+    pub async fn save_recipes(&self, entries: &[RecipeEntry]) -> Result<()> {
+        let req = self.request(Method::POST, &["recipes"]).json(entries);
+        self.send(req).await
+    }
+
+    pub async fn delete_recipe(&self, recipe_id: &str) -> Result<()> {
+        self.send(self.request(Method::DELETE, &["recipe", recipe_id]))
+            .await
+    }
+
+    /// The most recent meal plan.
+    pub async fn plan(&self) -> Result<Vec<(String, i32)>> {
+        self.send(self.request(Method::GET, &["plan"])).await
+    }
+
+    pub async fn plan_for_date(&self, date: NaiveDate) -> Result<Vec<(String, i32)>> {
+        let date = date.to_string();
+        self.send(self.request(Method::GET, &["plan", "at", &date]))
+            .await
+    }
+
+    pub async fn save_plan_for_date(&self, date: NaiveDate, plan: &[(String, i32)]) -> Result<()> {
+        let date = date.to_string();
+        let req = self
+            .request(Method::POST, &["plan", "at", &date])
+            .json(plan);
+        self.send(req).await
+    }
+
+    /// The meal plan and inventory for a date in one request.
+    pub async fn full_plan_for_date(&self, date: NaiveDate) -> Result<FullPlanData> {
+        let date = date.to_string();
+        self.send(self.request(Method::GET, &["plan", "at", &date, "full"]))
+            .await
+    }
+
+    pub async fn delete_plan_for_date(&self, date: NaiveDate) -> Result<()> {
+        let date = date.to_string();
+        self.send(self.request(Method::DELETE, &["plan", "at", &date]))
+            .await
+    }
+
+    /// The meal plans after a date keyed by date.
+    pub async fn plans_since(
+        &self,
+        date: NaiveDate,
+    ) -> Result<BTreeMap<NaiveDate, Vec<(String, i32)>>> {
+        let date = date.to_string();
+        self.send(self.request(Method::GET, &["plan", "since", &date]))
+            .await
+    }
+
+    /// The dates of every saved meal plan.
+    pub async fn plan_dates(&self) -> Result<Vec<NaiveDate>> {
+        self.send(self.request(Method::GET, &["plan", "all"])).await
+    }
+
+    /// Summaries of the meal plans from `start` to `end` inclusive, newest
+    /// first.
+    pub async fn plan_summaries(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<PlanSummary>> {
+        let req = self
+            .request(Method::GET, &["plan", "summary"])
+            .query(&[("start", start.to_string()), ("end", end.to_string())]);
+        self.send(req).await
+    }
+
+    pub async fn inventory_for_date(&self, date: NaiveDate) -> Result<InventoryData> {
+        let date = date.to_string();
+        self.send(self.request(Method::GET, &["inventory", "at", &date]))
+            .await
+    }
+
+    pub async fn save_inventory_for_date(
+        &self,
+        date: NaiveDate,
+        inventory: &InventoryData,
+    ) -> Result<()> {
+        let date = date.to_string();
+        let req = self
+            .request(Method::POST, &["inventory", "at", &date])
+            .json(&(
+                &inventory.filtered_ingredients,
+                &inventory.modified_amts,
+                &inventory.extra_items,
+            ));
+        self.send(req).await
+    }
+
+    pub async fn category_mappings(&self) -> Result<Vec<(String, String)>> {
+        self.send(self.request(Method::GET, &["category_map"]))
+            .await
+    }
+
+    pub async fn save_category_mappings(&self, mappings: &[(String, String)]) -> Result<()> {
+        let req = self.request(Method::POST, &["category_map"]).json(mappings);
+        self.send(req).await
+    }
+
+    pub async fn measure_types(&self) -> Result<Vec<(String, MeasureType)>> {
+        self.send(self.request(Method::GET, &["measure_types"]))
+            .await
+    }
+
+    pub async fn save_measure_types(&self, measure_types: &[(String, MeasureType)]) -> Result<()> {
+        let req = self
+            .request(Method::POST, &["measure_types"])
+            .json(measure_types);
+        self.send(req).await
+    }
+
+    pub async fn staples(&self) -> Result<Option<String>> {
+        self.send(self.request(Method::GET, &["staples"])).await
+    }
+
+    pub async fn save_staples(&self, content: &str) -> Result<()> {
+        let req = self.request(Method::POST, &["staples"]).json(content);
+        self.send(req).await
+    }
+
+    pub async fn templates(&self) -> Result<Vec<RecipeTemplate>> {
+        self.send(self.request(Method::GET, &["templates"])).await
+    }
+
+    pub async fn template(&self, name: &str) -> Result<Option<RecipeTemplate>> {
+        self.send(self.request(Method::GET, &["template", name]))
+            .await
+    }
+
+    pub async fn save_template(&self, template: &RecipeTemplate) -> Result<()> {
+        let req = self.request(Method::POST, &["templates"]).json(template);
+        self.send(req).await
+    }
+
+    pub async fn delete_template(&self, name: &str) -> Result<()> {
+        self.send(self.request(Method::DELETE, &["template", name]))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::mock::mock_server;
+    use super::*;
+
+    #[tokio::test]
+    async fn test_recipes_decodes_entries() {
+        let (addr, seen) = mock_server(vec![(
+            200,
+            r#"{"Success":[{"id":"soup","text":"title: Soup","category":null,"serving_count":null}]}"#
+                .to_owned(),
+        )]);
+        let client = Client::new(&addr)
+            .unwrap()
+            .with_basic_auth("alice", "secret");
+        let entries = client.recipes().await.expect("Failed to fetch recipes");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].recipe_id(), "soup");
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen[0].0, "GET /api/v2/recipes HTTP/1.1");
+        assert!(seen[0]
+            .1
+            .iter()
+            .any(|h| h.to_lowercase().starts_with("authorization: basic ")));
+    }
+
+    #[tokio::test]
+    async fn test_save_plan_for_date_posts_json() {
+        let (addr, seen) = mock_server(vec![(200, r#"{"Success":null}"#.to_owned())]);
+        let client = Client::new(format!("{}/", addr)).unwrap();
+        let date = NaiveDate::from_ymd_opt(2022, 11, 1).unwrap();
+        client
+            .save_plan_for_date(date, &[("soup".to_owned(), 2)])
+            .await
+            .expect("Failed to save plan");
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen[0].0, "POST /api/v2/plan/at/2022-11-01 HTTP/1.1");
+        assert_eq!(seen[0].2, r#"[["soup",2]]"#);
+    }
+
+    #[tokio::test]
+    async fn test_error_responses_become_errors() {
+        let (addr, _) = mock_server(vec![
+            (401, r#""Unauthorized""#.to_owned()),
+            (404, r#""NotFound""#.to_owned()),
+            (500, r#"{"Err":{"status":500,"message":"boom"}}"#.to_owned()),
+        ]);
+        let client = Client::new(&addr).unwrap();
+        assert!(matches!(client.plan().await, Err(Error::Unauthorized)));
+        assert!(matches!(
+            client.recipe("missing soup").await,
+            Err(Error::NotFound)
+        ));
+        match client.staples().await {
+            Err(Error::Api { status, message }) => {
+                assert_eq!(status, 500);
+                assert_eq!(message, "boom");
+            }
+            result => panic!("Expected an api error but got {:?}", result),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_template_names_are_encoded() {
+        let (addr, seen) = mock_server(vec![
+            (
+                200,
+                r#"{"Success":{"name":"rye/wheat? #1","text":"title: {{name}}"}}"#.to_owned(),
+            ),
+            (200, r#"{"Success":null}"#.to_owned()),
+        ]);
+        let client = Client::new(&addr).unwrap();
+        let template = client
+            .template("rye/wheat? #1")
+            .await
+            .expect("Failed to fetch template")
+            .expect("Missing template");
+        assert_eq!(template.name, "rye/wheat? #1");
+        client
+            .delete_template("rye/wheat? #1")
+            .await
+            .expect("Failed to delete template");
+        let seen = seen.lock().unwrap();
+        assert_eq!(
+            seen[0].0,
+            "GET /api/v2/template/rye%2Fwheat%3F%20%231 HTTP/1.1"
+        );
+        assert_eq!(
+            seen[1].0,
+            "DELETE /api/v2/template/rye%2Fwheat%3F%20%231 HTTP/1.1"
+        );
+    }
+
+    #[test]
+    fn test_urls_encode_path_segments() {
+        let client = Client::new("http://localhost:3030/kitchen/").unwrap();
+        assert_eq!(
+            client.url(&["recipe", "tomato soup"]).as_str(),
+            "http://localhost:3030/kitchen/api/v2/recipe/tomato%20soup"
+        );
+        assert!(matches!(Client::new("not a url"), Err(Error::Url(_))));
+    }
+}
@@ -0,0 +1,265 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! A blocking client for the v2 kitchen api for scripts that don't want to
+//! bring along an async runtime.
+//!
+//! ```no_run
+//! # fn example() -> Result<(), api::client::Error> {
+//! let client = api::client::blocking::Client::new("http://localhost:3030")?;
+//! client.login("alice", "secret")?;
+//! for entry in client.recipes()? {
+//!     println!("{}", entry.recipe_id());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use recipes::{unit::MeasureType, Recipe, RecipeEntry, RecipeTemplate};
+use reqwest::blocking::RequestBuilder;
+use reqwest::{Method, Url};
+use serde::de::DeserializeOwned;
+
+use super::{api_url, into_result, parse_root, Result};
+use crate::{FullPlanData, InventoryData, PlanSummary, Response, UserData};
+
+/// A blocking client for the v2 api of a kitchen server. It has the same
+/// methods as the async [super::Client].
+#[derive(Debug, Clone)]
+pub struct Client {
+    http: reqwest::blocking::Client,
+    root: Url,
+    basic_auth: Option<(String, String)>,
+}
+
+impl Client {
+    /// Construct a client for the kitchen server at `root`.
+    /// e.g. `http://localhost:3030`
+    pub fn new<S: AsRef<str>>(root: S) -> Result<Self> {
+        let root = parse_root(root.as_ref())?;
+        let http = reqwest::blocking::Client::builder()
+            .cookie_store(true)
+            .build()?;
+        Ok(Self {
+            http,
+            root,
+            basic_auth: None,
+        })
+    }
+
+    /// Send HTTP Basic auth with every request instead of using a session.
+    pub fn with_basic_auth<U: Into<String>, P: Into<String>>(mut self, user: U, pass: P) -> Self {
+        self.basic_auth = Some((user.into(), pass.into()));
+        self
+    }
+
+    fn url(&self, path: &[&str]) -> Url {
+        api_url(&self.root, path)
+    }
+
+    fn request(&self, method: Method, path: &[&str]) -> RequestBuilder {
+        let req = self.http.request(method, self.url(path));
+        match &self.basic_auth {
+            Some((user, pass)) => req.basic_auth(user, Some(pass)),
+            None => req,
+        }
+    }
+
+    fn send<T: DeserializeOwned>(&self, req: RequestBuilder) -> Result<T> {
+        let resp: Response<T> = req.send()?.json()?;
+        into_result(resp)
+    }
+
+    /// Log in and keep the session for later requests.
+    pub fn login(&self, user: &str, pass: &str) -> Result<UserData> {
+        let req = self
+            .http
+            .post(self.url(&["auth"]))
+            .basic_auth(user, Some(pass));
+        self.send(req)
+    }
+
+    pub fn logout(&self) -> Result<()> {
+        self.send(self.request(Method::DELETE, &["auth"]))
+    }
+
+    pub fn account(&self) -> Result<UserData> {
+        self.send(self.request(Method::GET, &["account"]))
+    }
+
+    pub fn recipes(&self) -> Result<Vec<RecipeEntry>> {
+        self.send(self.request(Method::GET, &["recipes"]))
+    }
+
+    pub fn search_recipes(&self, term: &str) -> Result<Vec<RecipeEntry>> {
+        let req = self
+            .request(Method::GET, &["recipes", "search"])
+            .query(&[("q", term)]);
+        self.send(req)
+    }
+
+    pub fn recipe(&self, recipe_id: &str) -> Result<Option<RecipeEntry>> {
+        self.send(self.request(Method::GET, &["recipe", recipe_id]))
+    }
+
+    /// The recipe parsed by the server.
+    pub fn parsed_recipe(&self, recipe_id: &str) -> Result<Recipe> {
+        self.send(self.request(Method::GET, &["recipe", recipe_id, "parsed"]))
+    }
+
+    pub fn save_recipes(&self, entries: &[RecipeEntry]) -> Result<()> {
+        let req = self.request(Method::POST, &["recipes"]).json(entries);
+        self.send(req)
+    }
+
+    pub fn delete_recipe(&self, recipe_id: &str) -> Result<()> {
+        self.send(self.request(Method::DELETE, &["recipe", recipe_id]))
+    }
+
+    /// The most recent meal plan.
+    pub fn plan(&self) -> Result<Vec<(String, i32)>> {
+        self.send(self.request(Method::GET, &["plan"]))
+    }
+
+    pub fn plan_for_date(&self, date: NaiveDate) -> Result<Vec<(String, i32)>> {
+        let date = date.to_string();
+        self.send(self.request(Method::GET, &["plan", "at", &date]))
+    }
+
+    pub fn save_plan_for_date(&self, date: NaiveDate, plan: &[(String, i32)]) -> Result<()> {
+        let date = date.to_string();
+        let req = self
+            .request(Method::POST, &["plan", "at", &date])
+            .json(plan);
+        self.send(req)
+    }
+
+    /// The meal plan and inventory for a date in one request.
+    pub fn full_plan_for_date(&self, date: NaiveDate) -> Result<FullPlanData> {
+        let date = date.to_string();
+        self.send(self.request(Method::GET, &["plan", "at", &date, "full"]))
+    }
+
+    pub fn delete_plan_for_date(&self, date: NaiveDate) -> Result<()> {
+        let date = date.to_string();
+        self.send(self.request(Method::DELETE, &["plan", "at", &date]))
+    }
+
+    /// The meal plans after a date keyed by date.
+    pub fn plans_since(&self, date: NaiveDate) -> Result<BTreeMap<NaiveDate, Vec<(String, i32)>>> {
+        let date = date.to_string();
+        self.send(self.request(Method::GET, &["plan", "since", &date]))
+    }
+
+    /// The dates of every saved meal plan.
+    pub fn plan_dates(&self) -> Result<Vec<NaiveDate>> {
+        self.send(self.request(Method::GET, &["plan", "all"]))
+    }
+
+    /// Summaries of the meal plans from `start` to `end` inclusive, newest
+    /// first.
+    pub fn plan_summaries(&self, start: NaiveDate, end: NaiveDate) -> Result<Vec<PlanSummary>> {
+        let req = self
+            .request(Method::GET, &["plan", "summary"])
+            .query(&[("start", start.to_string()), ("end", end.to_string())]);
+        self.send(req)
+    }
+
+    pub fn inventory_for_date(&self, date: NaiveDate) -> Result<InventoryData> {
+        let date = date.to_string();
+        self.send(self.request(Method::GET, &["inventory", "at", &date]))
+    }
+
+    pub fn save_inventory_for_date(
+        &self,
+        date: NaiveDate,
+        inventory: &InventoryData,
+    ) -> Result<()> {
+        let date = date.to_string();
+        let req = self
+            .request(Method::POST, &["inventory", "at", &date])
+            .json(&(
+                &inventory.filtered_ingredients,
+                &inventory.modified_amts,
+                &inventory.extra_items,
+            ));
+        self.send(req)
+    }
+
+    pub fn category_mappings(&self) -> Result<Vec<(String, String)>> {
+        self.send(self.request(Method::GET, &["category_map"]))
+    }
+
+    pub fn save_category_mappings(&self, mappings: &[(String, String)]) -> Result<()> {
+        let req = self.request(Method::POST, &["category_map"]).json(mappings);
+        self.send(req)
+    }
+
+    pub fn measure_types(&self) -> Result<Vec<(String, MeasureType)>> {
+        self.send(self.request(Method::GET, &["measure_types"]))
+    }
+
+    pub fn save_measure_types(&self, measure_types: &[(String, MeasureType)]) -> Result<()> {
+        let req = self
+            .request(Method::POST, &["measure_types"])
+            .json(measure_types);
+        self.send(req)
+    }
+
+    pub fn staples(&self) -> Result<Option<String>> {
+        self.send(self.request(Method::GET, &["staples"]))
+    }
+
+    pub fn save_staples(&self, content: &str) -> Result<()> {
+        let req = self.request(Method::POST, &["staples"]).json(content);
+        self.send(req)
+    }
+
+    pub fn templates(&self) -> Result<Vec<RecipeTemplate>> {
+        self.send(self.request(Method::GET, &["templates"]))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::mock::mock_server;
+    use super::super::Error;
+    use super::*;
+
+    #[test]
+    fn test_blocking_recipes_decodes_entries() {
+        let (addr, seen) = mock_server(vec![
+            (
+                200,
+                r#"{"Success":[{"id":"soup","text":"title: Soup","category":null,"serving_count":null}]}"#
+                    .to_owned(),
+            ),
+            (401, r#""Unauthorized""#.to_owned()),
+        ]);
+        let client = Client::new(&addr)
+            .unwrap()
+            .with_basic_auth("alice", "secret");
+        let entries = client.recipes().expect("Failed to fetch recipes");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].recipe_id(), "soup");
+        assert!(matches!(client.recipes(), Err(Error::Unauthorized)));
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen[0].0, "GET /api/v2/recipes HTTP/1.1");
+        assert!(seen[0]
+            .1
+            .iter()
+            .any(|h| h.to_lowercase().starts_with("authorization: basic ")));
+    }
+}
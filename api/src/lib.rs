@@ -21,7 +21,13 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 
-use recipes::{IngredientKey, RecipeEntry};
+use recipes::{
+    parse::ParseError, unit::MeasureType, Ingredient, IngredientKey, Recipe, RecipeEntry,
+};
+
+#[cfg(feature = "client")]
+pub mod client;
+pub mod text;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub enum Response<T> {
@@ -29,6 +35,7 @@ pub enum Response<T> {
     Err { status: u16, message: String },
     NotFound,
     Unauthorized,
+    Invalid(ParseError),
 }
 
 impl<T> Response<T> {
@@ -73,6 +80,9 @@ where
             Self::Unauthorized => {
                 (StatusCode::UNAUTHORIZED, axum::Json::from(self)).into_response()
             }
+            Self::Invalid(_) => {
+                (StatusCode::UNPROCESSABLE_ENTITY, axum::Json::from(self)).into_response()
+            }
         }
     }
 }
@@ -114,14 +124,50 @@ pub struct UserData {
 
 pub type AccountResponse = Response<UserData>;
 
+/// A request to change the password for the logged in user.
+///
+/// NOTE: This intentionally does not implement Debug so the passwords don't
+/// end up in any logs.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PasswordChange {
+    pub current_password: String,
+    pub new_password: String,
+}
+
 impl From<UserData> for AccountResponse {
     fn from(user_data: UserData) -> Self {
         Response::Success(user_data)
     }
 }
 
+/// A parsed ingredient along with its category if one is assigned.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CategorizedIngredient {
+    pub ingredient: Ingredient,
+    pub category: Option<String>,
+}
+
+pub type ParsedStaplesResponse = Response<Vec<CategorizedIngredient>>;
+
 pub type RecipeEntryResponse = Response<Vec<RecipeEntry>>;
 
+pub type ParsedRecipeResponse = Response<Recipe>;
+
+/// The recipes that changed since a client's last sync.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RecipeChanges {
+    /// The server timestamp in milliseconds to use as the cursor for the
+    /// next sync.
+    pub as_of: i64,
+    /// The ids of every recipe the user has so deleted recipes can be
+    /// dropped.
+    pub ids: Vec<String>,
+    /// The recipes that were stored at or after the cursor.
+    pub changed: Vec<RecipeEntry>,
+}
+
+pub type RecipeChangesResponse = Response<RecipeChanges>;
+
 impl From<Vec<RecipeEntry>> for RecipeEntryResponse {
     fn from(entries: Vec<RecipeEntry>) -> Self {
         Response::Success(entries)
@@ -147,11 +193,35 @@ impl From<Option<Vec<(String, i32)>>> for PlanDataResponse {
 
 pub type PlanHistoryResponse = Response<BTreeMap<chrono::NaiveDate, Vec<(String, i32)>>>;
 
-#[derive(Serialize, Deserialize)]
+/// A short description of the meal plan for a date for listing plans without
+/// fetching each one.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PlanSummary {
+    pub date: chrono::NaiveDate,
+    pub recipe_count: i64,
+    /// The recipe counts multiplied by each recipe's serving count.
+    pub servings: i64,
+    /// Whether any inventory has been saved for the plan.
+    pub has_inventory: bool,
+}
+
+pub type PlanSummaryResponse = Response<Vec<PlanSummary>>;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct InventoryData {
     pub filtered_ingredients: Vec<IngredientKey>,
     pub modified_amts: Vec<(IngredientKey, String)>,
     pub extra_items: Vec<(String, String)>,
+    /// Ingredients that have been checked off while shopping for the plan.
+    #[serde(default)]
+    pub checked_items: Vec<IngredientKey>,
+    /// What ingredients were actually bought as for the plan. e.g. `1 pint`
+    #[serde(default)]
+    pub purchased_amts: Vec<(IngredientKey, String)>,
+    /// The most recent amount ingredients were bought as in earlier plans.
+    /// These are only hints and are never saved back.
+    #[serde(default)]
+    pub purchase_hints: Vec<(IngredientKey, String)>,
 }
 
 pub type InventoryResponse = Response<InventoryData>;
@@ -174,16 +244,51 @@ impl
             filtered_ingredients,
             modified_amts,
             extra_items,
+            checked_items: Vec::new(),
+            purchased_amts: Vec::new(),
+            purchase_hints: Vec::new(),
         }
     }
 }
 
+/// The inventory for a plan date as it is posted by the client. Clients that
+/// don't send the checked items or the purchased amounts leave the saved ones
+/// alone.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SaveInventoryData(
+    pub Vec<IngredientKey>,
+    pub Vec<(IngredientKey, String)>,
+    pub Vec<(String, String)>,
+    #[serde(default)] pub Option<Vec<IngredientKey>>,
+    #[serde(default)] pub Option<Vec<(IngredientKey, String)>>,
+);
+
 impl From<InventoryData> for InventoryResponse {
     fn from(inventory_data: InventoryData) -> Self {
         Response::Success(inventory_data)
     }
 }
 
+/// The meal plan and inventory for a date so a plan can be loaded in one
+/// request.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FullPlanData {
+    pub recipe_counts: Vec<(String, i32)>,
+    pub inventory: InventoryData,
+}
+
+pub type FullPlanResponse = Response<FullPlanData>;
+
+/// A named set of recipe counts that can be applied to the meal plan for any
+/// date.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PlanTemplate {
+    pub name: String,
+    pub recipe_counts: Vec<(String, i32)>,
+}
+
+pub type PlanTemplatesResponse = Response<Vec<PlanTemplate>>;
+
 pub type CategoryMappingResponse = Response<Vec<(String, String)>>;
 
 impl From<Vec<(String, String)>> for CategoryMappingResponse {
@@ -191,3 +296,72 @@ impl From<Vec<(String, String)>> for CategoryMappingResponse {
         Response::Success(mappings)
     }
 }
+
+/// Measure types forced for ingredients keyed by ingredient name.
+pub type MeasureTypesResponse = Response<Vec<(String, MeasureType)>>;
+
+/// The categories in the order the shopping list shows them.
+pub type CategoryOrderResponse = Response<Vec<String>>;
+
+/// The ui routes a user can choose to land on.
+pub const LANDING_ROUTES: [&str; 8] = [
+    "/ui/home",
+    "/ui/planning/select",
+    "/ui/planning/plan",
+    "/ui/planning/inventory",
+    "/ui/planning/cook",
+    "/ui/manage/ingredients",
+    "/ui/manage/staples",
+    "/ui/manage/new_recipe",
+];
+
+/// Settings for the logged in user.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct UserSettings {
+    /// The route to land on instead of the meal plan. Must be one of
+    /// `LANDING_ROUTES`.
+    pub default_route: Option<String>,
+    /// Whether staples are included in the shopping list. None until the
+    /// user chooses.
+    #[serde(default)]
+    pub use_staples: Option<bool>,
+}
+
+impl UserSettings {
+    /// Whether all of the settings have valid values.
+    pub fn is_valid(&self) -> bool {
+        match &self.default_route {
+            Some(route) => LANDING_ROUTES.contains(&route.as_str()),
+            None => true,
+        }
+    }
+
+    /// The route to land on if the user chose a known one.
+    pub fn landing_route(&self) -> Option<&str> {
+        self.default_route
+            .as_deref()
+            .filter(|route| LANDING_ROUTES.contains(route))
+    }
+}
+
+pub type UserSettingsResponse = Response<UserSettings>;
+
+/// A change to a user's data sent to their open sessions by
+/// `/api/v2/events`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ChangeEvent {
+    /// Recipes were saved or deleted.
+    RecipesChanged,
+    /// The meal plan for the date was saved or deleted.
+    PlanChanged(chrono::NaiveDate),
+}
+
+/// The header a ui session sends with its requests so the changes it makes
+/// aren't sent back to it by `/api/v2/events`.
+pub const ORIGIN_SESSION_HEADER: &str = "x-kitchen-session";
+
+/// The name of the meta tag the server injects the ui's log filter into.
+pub const LOG_FILTER_META: &str = "kitchen-log-filter";
+
+/// The name of the meta tag the server injects the session cookie name into.
+pub const SESSION_COOKIE_META: &str = "kitchen-session-cookie";
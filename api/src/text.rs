@@ -0,0 +1,227 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Limits on the free text users can save. The server enforces these and the
+//! web client uses the same limits so users see them before saving.
+
+/// The most characters a name like an extra item or a category can have.
+pub const MAX_NAME_CHARS: usize = 256;
+/// The most characters a free text amount can have.
+pub const MAX_AMOUNT_CHARS: usize = 64;
+
+/// Strip control characters and surrounding whitespace from user text.
+pub fn clean(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_control())
+        .collect::<String>()
+        .trim()
+        .to_owned()
+}
+
+/// Clean user text and check it against a limit in characters. Returns the
+/// cleaned text or the reason it was rejected.
+pub fn check(text: &str, max_chars: usize, required: bool) -> Result<String, String> {
+    let cleaned = clean(text);
+    if required && cleaned.is_empty() {
+        return Err("is empty".to_owned());
+    }
+    let len = cleaned.chars().count();
+    if len > max_chars {
+        return Err(format!(
+            "is {} characters long, the limit is {}",
+            len, max_chars
+        ));
+    }
+    Ok(cleaned)
+}
+
+/// The label, limit in characters, and whether a field of a pair is required.
+type Field<'a> = (&'a str, usize, bool);
+
+const EXTRA_AMOUNT: Field = ("amount", MAX_AMOUNT_CHARS, false);
+const EXTRA_NAME: Field = ("name", MAX_NAME_CHARS, true);
+const MAPPING_INGREDIENT: Field = ("ingredient", MAX_NAME_CHARS, true);
+const PURCHASED_AMOUNT: Field = ("amount", MAX_AMOUNT_CHARS, false);
+// NOTE(jwall): Clearing a category is saved as an empty category.
+const MAPPING_CATEGORY: Field = ("category", MAX_NAME_CHARS, false);
+
+/// Clean a list of (amount, name) extra items. Every offending entry is
+/// reported in the error.
+pub fn check_extras(extras: Vec<(String, String)>) -> Result<Vec<(String, String)>, Vec<String>> {
+    check_pairs(extras, "extra item", EXTRA_AMOUNT, EXTRA_NAME)
+}
+
+/// Clean the amounts ingredients were purchased as. Empty amounts are
+/// dropped. Every offending entry is reported in the error.
+pub fn check_purchased_amts<K>(amts: Vec<(K, String)>) -> Result<Vec<(K, String)>, Vec<String>> {
+    let mut problems = Vec::new();
+    let mut cleaned = Vec::with_capacity(amts.len());
+    for (idx, (key, amt)) in amts.into_iter().enumerate() {
+        let entry = format!("purchased amount {}", idx + 1);
+        match check_field(&mut problems, &entry, &amt, PURCHASED_AMOUNT) {
+            Some(amt) if !amt.is_empty() => cleaned.push((key, amt)),
+            _ => (),
+        }
+    }
+    if problems.is_empty() {
+        Ok(cleaned)
+    } else {
+        Err(problems)
+    }
+}
+
+/// Clean a list of (ingredient, category) mappings. Every offending entry is
+/// reported in the error.
+pub fn check_category_mappings(
+    mappings: Vec<(String, String)>,
+) -> Result<Vec<(String, String)>, Vec<String>> {
+    check_pairs(
+        mappings,
+        "category mapping",
+        MAPPING_INGREDIENT,
+        MAPPING_CATEGORY,
+    )
+}
+
+fn check_pairs(
+    pairs: Vec<(String, String)>,
+    what: &str,
+    first: Field,
+    second: Field,
+) -> Result<Vec<(String, String)>, Vec<String>> {
+    let mut problems = Vec::new();
+    let mut cleaned = Vec::with_capacity(pairs.len());
+    for (idx, (a, b)) in pairs.into_iter().enumerate() {
+        let entry = format!("{} {}", what, idx + 1);
+        let a = check_field(&mut problems, &entry, &a, first);
+        let b = check_field(&mut problems, &entry, &b, second);
+        if let (Some(a), Some(b)) = (a, b) {
+            cleaned.push((a, b));
+        }
+    }
+    if problems.is_empty() {
+        Ok(cleaned)
+    } else {
+        Err(problems)
+    }
+}
+
+fn check_field(
+    problems: &mut Vec<String>,
+    entry: &str,
+    text: &str,
+    (label, max_chars, required): Field,
+) -> Option<String> {
+    match check(text, max_chars, required) {
+        Ok(text) => Some(text),
+        Err(e) => {
+            problems.push(format!("{}: {} {}", entry, label, e));
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_clean_strips_control_characters() {
+        assert_eq!(clean("  paper\u{0}\u{7} towels\r\n"), "paper towels");
+        assert_eq!(clean("\t\u{1b}[31m"), "[31m");
+    }
+
+    #[test]
+    fn test_clean_keeps_emoji_and_rtl_text() {
+        assert_eq!(clean("🍋 lemons 🍋"), "🍋 lemons 🍋");
+        assert_eq!(clean(" \u{5dc}\u{5d7}\u{5dd} "), "\u{5dc}\u{5d7}\u{5dd}");
+        assert_eq!(clean("خبز"), "خبز");
+    }
+
+    #[test]
+    fn test_check_counts_characters_not_bytes() {
+        let emoji = "🍋".repeat(MAX_NAME_CHARS);
+        assert_eq!(check(&emoji, MAX_NAME_CHARS, true), Ok(emoji.clone()));
+        assert!(check(&format!("{}🍋", emoji), MAX_NAME_CHARS, true).is_err());
+    }
+
+    #[test]
+    fn test_check_rejects_long_and_empty_text() {
+        let long = "a".repeat(10_000);
+        assert_eq!(
+            check(&long, MAX_NAME_CHARS, true),
+            Err("is 10000 characters long, the limit is 256".to_owned())
+        );
+        assert_eq!(
+            check(" \u{0} ", MAX_NAME_CHARS, true),
+            Err("is empty".to_owned())
+        );
+        assert_eq!(check(" ", MAX_AMOUNT_CHARS, false), Ok(String::new()));
+    }
+
+    #[test]
+    fn test_check_extras_reports_every_entry() {
+        let extras = vec![
+            (" 2 ".to_owned(), "lemons\n".to_owned()),
+            ("1".to_owned(), "".to_owned()),
+            ("x".repeat(100), "limes".to_owned()),
+            ("".to_owned(), "paper towels".to_owned()),
+        ];
+        assert_eq!(
+            check_extras(extras.clone()),
+            Err(vec![
+                "extra item 2: name is empty".to_owned(),
+                "extra item 3: amount is 100 characters long, the limit is 64".to_owned(),
+            ])
+        );
+        assert_eq!(
+            check_extras(vec![extras[0].clone(), extras[3].clone()]),
+            Ok(vec![
+                ("2".to_owned(), "lemons".to_owned()),
+                ("".to_owned(), "paper towels".to_owned()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_check_category_mappings() {
+        assert_eq!(
+            check_category_mappings(vec![("onion ".to_owned(), "Produce".to_owned())]),
+            Ok(vec![("onion".to_owned(), "Produce".to_owned())])
+        );
+        assert_eq!(
+            check_category_mappings(vec![("onion".to_owned(), "\u{7f}".to_owned())]),
+            Ok(vec![("onion".to_owned(), "".to_owned())])
+        );
+        assert_eq!(
+            check_category_mappings(vec![(" ".to_owned(), "Produce".to_owned())]),
+            Err(vec!["category mapping 1: ingredient is empty".to_owned()])
+        );
+    }
+
+    #[test]
+    fn test_check_purchased_amts_drops_empty_amounts() {
+        assert_eq!(
+            check_purchased_amts(vec![(1, " 1 pint\u{7} ".to_owned()), (2, "  ".to_owned())]),
+            Ok(vec![(1, "1 pint".to_owned())])
+        );
+        assert_eq!(
+            check_purchased_amts(vec![(1, "a".repeat(MAX_AMOUNT_CHARS + 1))]),
+            Err(vec![format!(
+                "purchased amount 1: amount is {} characters long, the limit is {}",
+                MAX_AMOUNT_CHARS + 1,
+                MAX_AMOUNT_CHARS
+            )])
+        );
+    }
+}
@@ -0,0 +1,7 @@
+-- Add up migration script here
+create table recipe_templates (
+    user_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    template_text TEXT NOT NULL,
+    primary key(user_id, name)
+);
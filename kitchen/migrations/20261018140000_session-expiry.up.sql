@@ -0,0 +1,5 @@
+-- Add up migration script here
+ALTER TABLE sessions ADD COLUMN created_at INT;
+-- Sessions from before we tracked their age start their ttl now instead of
+-- being expired all at once.
+UPDATE sessions SET created_at = CAST(strftime('%s', 'now') AS INT) WHERE created_at IS NULL;
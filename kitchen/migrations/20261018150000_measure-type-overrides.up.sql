@@ -0,0 +1,7 @@
+-- Add up migration script here
+create table measure_type_overrides (
+    user_id TEXT NOT NULL,
+    ingredient_name TEXT NOT NULL,
+    measure_type TEXT NOT NULL,
+    primary key(user_id, ingredient_name)
+);
@@ -0,0 +1,8 @@
+-- Add up migration script here
+create table plan_templates (
+    user_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    recipe_id TEXT NOT NULL,
+    count INTEGER NOT NULL,
+    primary key(user_id, name, recipe_id)
+);
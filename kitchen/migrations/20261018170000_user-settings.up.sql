@@ -0,0 +1,5 @@
+-- Add up migration script here
+create table user_settings (
+    user_id TEXT NOT NULL PRIMARY KEY,
+    default_route TEXT
+);
@@ -0,0 +1,9 @@
+-- Add up migration script here
+create table checked_items(
+    user_id TEXT NOT NULL,
+    plan_date DATE NOT NULL,
+    name TEXT NOT NULL,
+    form TEXT NOT NULL,
+    measure_type TEXT NOT NULL,
+    primary key(user_id, plan_date, name, form, measure_type)
+);
@@ -0,0 +1,7 @@
+-- Add up migration script here
+create table category_order (
+    user_id TEXT NOT NULL,
+    category TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    primary key(user_id, category)
+);
@@ -0,0 +1,2 @@
+-- Add down migration script here
+ALTER TABLE user_settings DROP COLUMN use_staples;
@@ -0,0 +1,2 @@
+-- Add up migration script here
+ALTER TABLE user_settings ADD COLUMN use_staples INTEGER;
@@ -11,6 +11,7 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 use std::fs::File;
 use std::io::Read;
@@ -19,7 +20,11 @@ use std::path::Path;
 
 use csv;
 
-use recipes::{parse, IngredientAccumulator, Recipe};
+use recipes::{
+    parse,
+    unit::{Measure, UnitSystem},
+    IngredientAccumulator, Recipe,
+};
 use tracing::{error, info, instrument, warn};
 
 #[derive(Debug)]
@@ -89,38 +94,99 @@ where
     Ok(recipe_list)
 }
 
+/// Parse the value of a `--units` flag.
+pub fn parse_units(units: Option<&str>) -> Result<Option<UnitSystem>, ParseError> {
+    units
+        .map(|units| UnitSystem::try_from(units).map_err(ParseError::Syntax))
+        .transpose()
+}
+
+/// Convert the amounts in a recipe to the units from the `--units` flag.
+pub fn convert_units(r: Recipe, units: Option<&str>) -> Result<Recipe, ParseError> {
+    Ok(match parse_units(units)? {
+        Some(system) => r.to_system(system),
+        None => r,
+    })
+}
+
+/// Normalize an amount for display or convert it into the preferred units.
+fn display_amt(amt: &Measure, units: Option<UnitSystem>) -> Measure {
+    match units {
+        Some(system) => amt.clone().convert_to_system(system),
+        None => amt.normalize(),
+    }
+}
+
 pub fn output_recipe_info(r: Recipe, print_ingredients: bool) {
     println!("Title: {}", r.title);
     println!("");
     if print_ingredients {
         println!("Ingredients:");
         for (_, i) in r.get_ingredients() {
-            println!("\t* {} {}", i.amt, i.name);
+            println!("\t* {} {}", i.amt, i.name_for_display());
         }
     }
 }
 
-pub fn output_ingredients_list(rs: Vec<Recipe>) {
+/// Parse the sub-recipes the recipes use. A sub-recipe id is the path of its
+/// file relative to the menu list which `read_menu_list` made the working
+/// directory. Sub-recipes that can't be read are left out with a warning.
+fn read_sub_recipes(rs: &[Recipe]) -> BTreeMap<String, Recipe> {
+    let mut sub_recipes = BTreeMap::new();
+    let mut pending: Vec<String> = rs
+        .iter()
+        .flat_map(|r| r.steps.iter().flat_map(|s| s.sub_recipes.iter().cloned()))
+        .collect();
+    while let Some(id) = pending.pop() {
+        if sub_recipes.contains_key(&id) {
+            continue;
+        }
+        match parse_recipe(&id) {
+            Ok(recipe) => {
+                pending.extend(
+                    recipe
+                        .steps
+                        .iter()
+                        .flat_map(|s| s.sub_recipes.iter().cloned()),
+                );
+                sub_recipes.insert(id, recipe);
+            }
+            Err(err) => warn!(%id, ?err, "Failed to read sub-recipe"),
+        }
+    }
+    sub_recipes
+}
+
+fn accumulate_recipes(rs: Vec<Recipe>) -> IngredientAccumulator {
+    let sub_recipes = read_sub_recipes(&rs);
     let mut acc = IngredientAccumulator::new();
     for r in rs {
-        acc.accumulate_from(&r);
+        acc.accumulate_from_with(&r, &sub_recipes);
     }
-    for (_, (i, _)) in acc.ingredients() {
-        print!("{}", i.amt.normalize());
-        println!(" {}", i.name);
+    for warning in acc.warnings() {
+        warn!("{}", warning);
     }
+    acc
 }
 
-pub fn output_ingredients_csv(rs: Vec<Recipe>) {
-    let mut acc = IngredientAccumulator::new();
-    for r in rs {
-        acc.accumulate_from(&r);
+pub fn output_ingredients_list(rs: Vec<Recipe>, units: Option<UnitSystem>) {
+    let acc = accumulate_recipes(rs);
+    for (_, (i, _)) in acc.ingredients() {
+        print!("{}", display_amt(&i.amt, units));
+        println!(" {}", i.name_for_display());
     }
+}
+
+pub fn output_ingredients_csv(rs: Vec<Recipe>, units: Option<UnitSystem>) {
+    let acc = accumulate_recipes(rs);
     let out = std::io::stdout();
     let mut writer = csv::Writer::from_writer(out);
     for (_, (i, _)) in acc.ingredients() {
         writer
-            .write_record(&[format!("{}", i.amt.normalize()), i.name])
+            .write_record(&[
+                format!("{}", display_amt(&i.amt, units)),
+                i.name_for_display().to_owned(),
+            ])
             .expect("Failed to write csv.");
     }
 }
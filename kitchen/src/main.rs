@@ -15,6 +15,8 @@ use std::env;
 use std::io;
 use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 
 use clap;
 use clap::ArgMatches;
@@ -23,6 +25,7 @@ use tracing::{error, info, instrument, warn, Level};
 use tracing_subscriber::FmtSubscriber;
 
 mod cli;
+mod remote;
 mod web;
 
 fn create_app<'a>() -> clap::App<'a> {
@@ -34,21 +37,29 @@ fn create_app<'a>() -> clap::App<'a> {
         (@subcommand recipe =>
             (about: "parse a recipe file and output info about it")
             (@arg ingredients: -i --ingredients "Output the ingredients list.")
+            (@arg units: --units +takes_value "Convert amounts to metric or us units")
             (@arg INPUT: +required "Input recipe file to parse")
         )
         (@subcommand groceries =>
             (about: "print out a grocery list for a set of recipes")
             (@arg csv: --csv "output ingredients as csv")
+            (@arg units: --units +takes_value "Convert amounts to metric or us units")
             (@arg INPUT: +required "Input menu file to parse. One recipe file per line.")
         )
         (@subcommand serve =>
             (about: "Serve the interface via the web")
             (@arg recipe_dir: -d --dir +takes_value "Directory containing recipe files to use")
+            (@arg demo: --demo "Serve the example recipes from memory instead of a recipe directory")
             (@arg session_dir: --session_dir +takes_value +required "Session store directory to use")
             (@arg tls: --tls "Use TLS to serve.")
             (@arg cert_path: --cert +takes_value "Certificate path. Required if you specified --tls.")
             (@arg key_path: --cert_key +takes_value "Certificate key path. Required if you specified --tls")
             (@arg listen: --listen +takes_value "address and port to listen on 0.0.0.0:3030")
+            (@arg theme_color: --("theme-color") +takes_value "Theme color for the ui as a hex color or css color name")
+            (@arg session_ttl: --session_ttl +takes_value "How long login sessions last in seconds. Defaults to 30 days.")
+            (@arg ui_log_filter: --("ui-log-filter") +takes_value "Log filter for the ui like info,kitchen_wasm::api=debug")
+            (@arg allow_basic_api_auth: --("allow-basic-api-auth") "Accept HTTP Basic auth on the api routes without a session.")
+            (@arg session_cookie_name: --("session-cookie-name") +takes_value "Name of the session cookie. Instances on the same host need distinct names.")
         )
         (@subcommand add_user =>
             (about: "add users to to the interface")
@@ -57,6 +68,61 @@ fn create_app<'a>() -> clap::App<'a> {
             (@arg pass: -p --pass +takes_value +required "password to add for this user")
             (@arg session_dir: --session_dir +takes_value +required "Session store directory to use")
         )
+        (@subcommand change_password =>
+            (about: "change the password for a user")
+            (@arg user: -u --user +takes_value +required "username to change the password for")
+            (@arg pass: -p --pass +takes_value +required "new password for this user")
+            (@arg session_dir: --session_dir +takes_value +required "Session store directory to use")
+        )
+        (@subcommand import =>
+            (about: "import a directory or zip file of recipes for a user")
+            (@arg user: -u --user +takes_value +required "username to import recipes for")
+            (@arg session_dir: --session_dir +takes_value "Session store directory to use")
+            (@arg INPUT: +required "Directory or zip file of .txt recipe files and an optional categories.txt")
+        )
+        (@subcommand remote =>
+            (about: "work with the recipes on a running kitchen server")
+            (@setting SubcommandRequiredElseHelp)
+            (@arg url: --url +takes_value +required "Url of the kitchen server like http://localhost:3030")
+            (@arg user: -u --user +takes_value +required "username to log in as")
+            (@arg pass: -p --pass +takes_value +required "password to log in with")
+            (@subcommand list =>
+                (about: "list the recipes on the server")
+            )
+            (@subcommand fetch =>
+                (about: "download the recipes on the server into a directory")
+                (@arg DIR: +required "Directory to write the recipe files to")
+            )
+            (@subcommand push =>
+                (about: "upload recipe files to the server")
+                (@arg INPUT: +required "Directory or zip file of .txt recipe files")
+            )
+        )
+        (@subcommand db =>
+            (about: "maintain the recipes in the session store")
+            (@setting SubcommandRequiredElseHelp)
+            (@subcommand revalidate =>
+                (about: "re-parse the stored recipes and report the ones that fail")
+                (@arg user: -u --user +takes_value "Only check the recipes for this user")
+                (@arg session_dir: --session_dir +takes_value +required "Session store directory to use")
+                (@arg json: --json +takes_value "Also write the results to this JSON file")
+            )
+        )
+        (@subcommand template =>
+            (about: "inspect the recipe templates for a user")
+            (@setting SubcommandRequiredElseHelp)
+            (@subcommand list =>
+                (about: "list the recipe templates for a user")
+                (@arg user: -u --user +takes_value +required "username to list templates for")
+                (@arg session_dir: --session_dir +takes_value "Session store directory to use")
+            )
+            (@subcommand show =>
+                (about: "print the text of a recipe template")
+                (@arg user: -u --user +takes_value +required "username the template belongs to")
+                (@arg session_dir: --session_dir +takes_value "Session store directory to use")
+                (@arg NAME: +required "Name of the template to show")
+            )
+        )
     )
     .setting(clap::AppSettings::SubcommandRequiredElseHelp)
 }
@@ -99,7 +165,9 @@ fn main() {
     if let Some(matches) = matches.subcommand_matches("recipe") {
         // The input argument is required so if we made it here then it's safe to unrwap this value.
         let recipe_file = matches.value_of("INPUT").unwrap();
-        match cli::parse_recipe(recipe_file) {
+        match cli::parse_recipe(recipe_file)
+            .and_then(|r| cli::convert_units(r, matches.value_of("units")))
+        {
             Ok(r) => {
                 cli::output_recipe_info(r, matches.is_present("ingredients"));
             }
@@ -110,12 +178,14 @@ fn main() {
     } else if let Some(matches) = matches.subcommand_matches("groceries") {
         // The input argument is required so if we made it here then it's safe to unrwap this value.
         let menu_file = matches.value_of("INPUT").unwrap();
-        match cli::read_menu_list(menu_file) {
-            Ok(rs) => {
+        match cli::parse_units(matches.value_of("units"))
+            .and_then(|units| Ok((cli::read_menu_list(menu_file)?, units)))
+        {
+            Ok((rs, units)) => {
                 if matches.is_present("csv") {
-                    cli::output_ingredients_csv(rs);
+                    cli::output_ingredients_csv(rs, units);
                 } else {
-                    cli::output_ingredients_list(rs);
+                    cli::output_ingredients_list(rs, units);
                 }
             }
             Err(err) => {
@@ -123,10 +193,14 @@ fn main() {
             }
         }
     } else if let Some(matches) = matches.subcommand_matches("serve") {
-        let recipe_dir_path = if let Some(dir) = matches.value_of("recipe_dir") {
-            PathBuf::from(dir)
+        let recipe_store: Arc<dyn web::RecipeStore> = if matches.contains_id("demo") {
+            Arc::new(web::MemoryStore::demo())
+        } else if let Some(dir) = matches.value_of("recipe_dir") {
+            Arc::new(web::AsyncFileStore::new(dir))
         } else {
-            std::env::current_dir().expect("Unable to get current directory. Bailing out.")
+            Arc::new(web::AsyncFileStore::new(
+                std::env::current_dir().expect("Unable to get current directory. Bailing out."),
+            ))
         };
         let session_store_path: PathBuf = get_session_store_path(matches);
         let listen_socket: SocketAddr = if let Some(listen_socket) = matches.value_of("listen") {
@@ -137,11 +211,35 @@ fn main() {
         } else {
             "127.0.0.1:3030".parse().unwrap()
         };
+        let mut ui_config = web::UiConfig::default();
+        if let Some(theme_color) = matches.value_of("theme_color") {
+            ui_config = ui_config
+                .with_theme_color(theme_color)
+                .expect("--theme-color must be a hex color or css color name");
+        }
+        if let Some(log_filter) = matches.value_of("ui_log_filter") {
+            ui_config = ui_config
+                .with_log_filter(log_filter)
+                .expect("--ui-log-filter must be a filter like info,kitchen_wasm::api=debug");
+        }
+        if let Some(name) = matches.value_of("session_cookie_name") {
+            ui_config = ui_config.with_session_cookie_name(name).expect(
+                "--session-cookie-name must only contain letters, numbers, '_', '-', or '.'",
+            );
+        }
+        let session_ttl = match matches.value_of("session_ttl") {
+            Some(ttl) => Duration::from_secs(ttl.parse().expect(&format!(
+                "--session_ttl must be a number of seconds but got {}",
+                ttl
+            ))),
+            None => web::DEFAULT_SESSION_TTL,
+        };
+        let allow_basic_api_auth = matches.contains_id("allow_basic_api_auth");
         info!(listen=%listen_socket, "Launching web interface...");
         async_std::task::block_on(async {
             if matches.contains_id("tls") {
                 web::ui_main_tls(
-                    recipe_dir_path,
+                    recipe_store,
                     session_store_path,
                     listen_socket,
                     matches
@@ -150,10 +248,21 @@ fn main() {
                     matches
                         .value_of("key_path")
                         .expect("You must provide a key path with --cert_key"),
+                    ui_config,
+                    session_ttl,
+                    allow_basic_api_auth,
                 )
                 .await
             } else {
-                web::ui_main(recipe_dir_path, session_store_path, listen_socket).await
+                web::ui_main(
+                    recipe_store,
+                    session_store_path,
+                    listen_socket,
+                    ui_config,
+                    session_ttl,
+                    allow_basic_api_auth,
+                )
+                .await
             }
         });
     } else if let Some(matches) = matches.subcommand_matches("add_user") {
@@ -168,5 +277,115 @@ fn main() {
             )
             .await;
         });
+    } else if let Some(matches) = matches.subcommand_matches("change_password") {
+        let session_store_path: PathBuf = get_session_store_path(matches);
+        async_std::task::block_on(async {
+            web::change_password(
+                session_store_path,
+                matches.value_of("user").unwrap().to_owned(),
+                matches.value_of("pass").unwrap().to_owned(),
+            )
+            .await;
+        });
+    } else if let Some(matches) = matches.subcommand_matches("import") {
+        let session_store_path: PathBuf = get_session_store_path(matches);
+        let user = matches.value_of("user").unwrap().to_owned();
+        let source = PathBuf::from(matches.value_of("INPUT").unwrap());
+        async_std::task::block_on(async {
+            let summary = web::import_recipes(session_store_path, user, source).await;
+            for (file, err) in summary.skipped.iter() {
+                println!("Skipped {}: {}", file, err);
+            }
+            println!(
+                "Imported {} recipes, skipped {}",
+                summary.imported.len(),
+                summary.skipped.len()
+            );
+            if summary.categories {
+                println!("Imported categories");
+            }
+        });
+    } else if let Some(matches) = matches.subcommand_matches("remote") {
+        let client = match remote::connect(
+            matches.value_of("url").unwrap(),
+            matches.value_of("user").unwrap(),
+            matches.value_of("pass").unwrap(),
+        ) {
+            Ok(client) => client,
+            Err(err) => {
+                error!(%err, "Failed to log in");
+                std::process::exit(1);
+            }
+        };
+        let result = if matches.subcommand_matches("list").is_some() {
+            remote::list_recipes(&client, &mut io::stdout())
+        } else if let Some(matches) = matches.subcommand_matches("fetch") {
+            let dir = PathBuf::from(matches.value_of("DIR").unwrap());
+            remote::fetch_recipes(&client, &dir)
+                .map(|count| println!("Fetched {} recipes into {}", count, dir.display()))
+        } else if let Some(matches) = matches.subcommand_matches("push") {
+            let source = PathBuf::from(matches.value_of("INPUT").unwrap());
+            remote::push_recipes(&client, &source).map(|summary| {
+                for (file, err) in summary.skipped.iter() {
+                    println!("Skipped {}: {}", file, err);
+                }
+                println!(
+                    "Pushed {} recipes, skipped {}",
+                    summary.pushed,
+                    summary.skipped.len()
+                );
+            })
+        } else {
+            Ok(())
+        };
+        if let Err(err) = result {
+            error!(%err, "Remote command failed");
+            std::process::exit(1);
+        }
+    } else if let Some(matches) = matches.subcommand_matches("db") {
+        if let Some(matches) = matches.subcommand_matches("revalidate") {
+            let session_store_path: PathBuf = get_session_store_path(matches);
+            let user = matches.value_of("user").map(|u| u.to_owned());
+            let report = async_std::task::block_on(async {
+                web::revalidate_recipes(session_store_path, user).await
+            });
+            if let Err(err) = report.write_to(&mut io::stdout()) {
+                error!(%err, "Failed to write report");
+            }
+            if let Some(path) = matches.value_of("json") {
+                let written = std::fs::File::create(path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|f| {
+                        serde_json::to_writer_pretty(f, &report).map_err(|e| e.to_string())
+                    });
+                if let Err(err) = written {
+                    error!(path, %err, "Failed to write JSON report");
+                    std::process::exit(2);
+                }
+            }
+            if !report.failures.is_empty() {
+                std::process::exit(1);
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches("template") {
+        if let Some(matches) = matches.subcommand_matches("list") {
+            let session_store_path: PathBuf = get_session_store_path(matches);
+            let user = matches.value_of("user").unwrap().to_owned();
+            async_std::task::block_on(async {
+                for template in web::get_templates(session_store_path, user).await {
+                    println!("{}", template.name);
+                }
+            });
+        } else if let Some(matches) = matches.subcommand_matches("show") {
+            let session_store_path: PathBuf = get_session_store_path(matches);
+            let user = matches.value_of("user").unwrap().to_owned();
+            let name = matches.value_of("NAME").unwrap().to_owned();
+            async_std::task::block_on(async {
+                match web::get_template(session_store_path, user, name.clone()).await {
+                    Some(template) => println!("{}", template.text),
+                    None => error!(name, "No such template"),
+                }
+            });
+        }
     }
 }
@@ -0,0 +1,129 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Commands that talk to a running kitchen server.
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use client_api::client::{self, blocking::Client};
+use tracing::{debug, instrument};
+
+use crate::web::import::{validate_recipes, ImportFiles};
+
+#[derive(Debug)]
+pub enum Error {
+    IO(std::io::Error),
+    Client(client::Error),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::IO(err) => write!(f, "{}", err),
+            Error::Client(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::IO(err)
+    }
+}
+
+impl From<client::Error> for Error {
+    fn from(err: client::Error) -> Self {
+        Error::Client(err)
+    }
+}
+
+/// Connect to the server at `url` and log in.
+#[instrument(skip(pass))]
+pub fn connect(url: &str, user: &str, pass: &str) -> Result<Client, Error> {
+    let client = Client::new(url)?;
+    client.login(user, pass)?;
+    debug!("Logged in");
+    Ok(client)
+}
+
+/// Write the id and category of every recipe on the server.
+pub fn list_recipes<W: Write>(client: &Client, out: &mut W) -> Result<(), Error> {
+    for entry in client.recipes()? {
+        match entry.category() {
+            Some(category) => writeln!(out, "{}\t{}", entry.recipe_id(), category)?,
+            None => writeln!(out, "{}", entry.recipe_id())?,
+        }
+    }
+    Ok(())
+}
+
+/// Download every recipe on the server into `dir`. Files are named after the
+/// recipe ids with a `.txt` extension so they can be pushed or imported
+/// again. Returns the number of recipes written.
+pub fn fetch_recipes(client: &Client, dir: &Path) -> Result<usize, Error> {
+    fs::create_dir_all(dir)?;
+    let entries = client.recipes()?;
+    for entry in entries.iter() {
+        let id = entry.recipe_id();
+        let name = if id.ends_with(".txt") {
+            id.to_owned()
+        } else {
+            format!("{}.txt", id)
+        };
+        // NOTE(jwall): Recipe ids come from the server so we make sure they
+        // can't write outside of dir.
+        let name = name.replace(['/', '\\'], "_");
+        fs::write(dir.join(name), entry.recipe_text())?;
+    }
+    Ok(entries.len())
+}
+
+#[derive(Debug)]
+pub struct PushSummary {
+    pub pushed: usize,
+    pub skipped: Vec<(String, String)>,
+}
+
+/// Upload the recipe files in a directory or zip file to the server. Files
+/// that fail to parse are skipped and returned with their parse errors.
+pub fn push_recipes(client: &Client, source: &Path) -> Result<PushSummary, Error> {
+    let files = ImportFiles::from_path(source)?;
+    let (entries, skipped) = validate_recipes(files.recipes);
+    if !entries.is_empty() {
+        client.save_recipes(&entries)?;
+    }
+    Ok(PushSummary {
+        pushed: entries.len(),
+        skipped,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use client_api::client::mock::mock_server;
+
+    #[test]
+    fn test_list_recipes() {
+        let (addr, seen) = mock_server(vec![
+            (200, r#"{"Success":{"user_id":"alice"}}"#.to_owned()),
+            (200, r#"{"Success":[{"id":"soup","text":"title: Soup","category":"Entree","serving_count":null},{"id":"salad","text":"title: Salad","category":null,"serving_count":null}]}"#.to_owned()),
+        ]);
+        let client = connect(&addr, "alice", "secret").expect("Failed to connect");
+        let mut out = Vec::new();
+        list_recipes(&client, &mut out).expect("Failed to list recipes");
+        assert_eq!(String::from_utf8(out).unwrap(), "soup\tEntree\nsalad\n");
+        assert_eq!(seen.lock().unwrap()[1].0, "GET /api/v2/recipes HTTP/1.1");
+    }
+}
@@ -11,21 +11,179 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+use std::collections::hash_map::RandomState;
+use std::collections::BTreeMap;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::net::SocketAddr;
 use std::str::FromStr;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use async_session::{Session, SessionStore};
 use axum::{
-    extract::{Extension, Host},
+    extract::{ConnectInfo, Extension, FromRequest, Host, RequestParts, TypedHeader},
+    headers,
     http::{header, HeaderMap, StatusCode},
 };
 use axum_auth::AuthBasic;
 use client_api as api;
 use cookie::{Cookie, SameSite};
-use secrecy::Secret;
+use secrecy::{ExposeSecret, Secret};
 use tracing::{debug, error, info, instrument};
 
-use super::storage::{self, AuthStore, UserCreds};
+use super::storage::{self, AuthStore, UserCreds, UserId, UserIdFromSession};
+
+/// How long a successful Basic auth check is remembered for a connection.
+const BASIC_AUTH_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// How many failed credential checks a user gets within the
+/// `FAILED_AUTH_WINDOW` before further checks are refused.
+const MAX_FAILED_AUTH_ATTEMPTS: u32 = 5;
+
+const FAILED_AUTH_WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BasicAuthCheck {
+    Authorized,
+    Denied,
+    RateLimited,
+}
+
+type VerifiedKey = (Option<SocketAddr>, String, u64);
+
+#[derive(Default)]
+struct BasicAuthState {
+    verified: BTreeMap<VerifiedKey, Instant>,
+    failures: BTreeMap<String, (u32, Instant)>,
+}
+
+/// Checks HTTP Basic credentials. Failed checks are rate limited per user and
+/// successful checks are cached briefly per connection so a burst of api
+/// requests doesn't run argon2 for every request.
+pub struct BasicAuthChecker {
+    allow_api_auth: bool,
+    hasher: RandomState,
+    state: Mutex<BasicAuthState>,
+}
+
+impl BasicAuthChecker {
+    pub fn new(allow_api_auth: bool) -> Self {
+        Self {
+            allow_api_auth,
+            hasher: RandomState::new(),
+            state: Mutex::new(BasicAuthState::default()),
+        }
+    }
+
+    /// Whether Basic auth is accepted directly on the api routes.
+    pub fn allow_api_auth(&self) -> bool {
+        self.allow_api_auth
+    }
+
+    fn verified_key(&self, peer: Option<SocketAddr>, creds: &UserCreds) -> VerifiedKey {
+        // NOTE(jwall): We only keep a keyed hash of the password around so
+        // the password itself never outlives the request.
+        let mut hasher = self.hasher.build_hasher();
+        creds.pass.expose_secret().hash(&mut hasher);
+        (peer, creds.user_id().to_owned(), hasher.finish())
+    }
+
+    #[instrument(skip_all, fields(user=creds.user_id()))]
+    pub async fn check(
+        &self,
+        store: &storage::SqliteStore,
+        peer: Option<SocketAddr>,
+        creds: &UserCreds,
+    ) -> BasicAuthCheck {
+        let key = self.verified_key(peer, creds);
+        {
+            let state = self.state.lock().expect("Basic auth state lock poisoned");
+            let now = Instant::now();
+            if let Some((count, since)) = state.failures.get(creds.user_id()) {
+                if *count >= MAX_FAILED_AUTH_ATTEMPTS
+                    && now.duration_since(*since) < FAILED_AUTH_WINDOW
+                {
+                    debug!("Too many failed attempts");
+                    return BasicAuthCheck::RateLimited;
+                }
+            }
+            if let Some(verified_at) = state.verified.get(&key) {
+                if now.duration_since(*verified_at) < BASIC_AUTH_CACHE_TTL {
+                    debug!("Using cached credential check");
+                    return BasicAuthCheck::Authorized;
+                }
+            }
+        }
+        let valid = matches!(store.check_user_creds(creds).await, Ok(true));
+        let mut state = self.state.lock().expect("Basic auth state lock poisoned");
+        let now = Instant::now();
+        if valid {
+            state.failures.remove(creds.user_id());
+            state
+                .verified
+                .retain(|_, verified_at| now.duration_since(*verified_at) < BASIC_AUTH_CACHE_TTL);
+            state.verified.insert(key, now);
+            BasicAuthCheck::Authorized
+        } else {
+            state
+                .failures
+                .retain(|_, (_, since)| now.duration_since(*since) < FAILED_AUTH_WINDOW);
+            let (count, _) = state
+                .failures
+                .entry(creds.user_id().to_owned())
+                .or_insert((0, now));
+            *count += 1;
+            BasicAuthCheck::Denied
+        }
+    }
+}
+
+/// Authenticate an api request with HTTP Basic credentials if that has been
+/// allowed. Unlike the `/auth` handler this does not create a session.
+#[instrument(skip_all)]
+pub async fn user_id_from_basic_auth<B: Send>(
+    req: &mut RequestParts<B>,
+    store: &storage::SqliteStore,
+) -> std::result::Result<UserIdFromSession, (StatusCode, &'static str)> {
+    let checker = match Option::<Extension<Arc<BasicAuthChecker>>>::from_request(req)
+        .await
+        .expect("Unable to get extensions from request")
+    {
+        Some(Extension(checker)) if checker.allow_api_auth() => checker,
+        _ => return Ok(UserIdFromSession::NoUserId),
+    };
+    let auth = match Option::<AuthBasic>::from_request(req)
+        .await
+        .expect("Unable to get headers from request")
+    {
+        // NOTE(jwall): Converting to UserCreds without a password panics.
+        Some(auth) if auth.0 .1.is_some() => UserCreds::from(auth),
+        _ => {
+            debug!("no basic auth defined in headers.");
+            return Ok(UserIdFromSession::NoUserId);
+        }
+    };
+    let peer = Option::<ConnectInfo<SocketAddr>>::from_request(req)
+        .await
+        .expect("Unable to get connection info from request")
+        .map(|ConnectInfo(addr)| addr);
+    match checker.check(store, peer, &auth).await {
+        BasicAuthCheck::Authorized => {
+            info!(user_id = auth.user_id(), "Authenticated with basic auth");
+            Ok(UserIdFromSession::FoundUserId(UserId(
+                auth.user_id().to_owned(),
+            )))
+        }
+        BasicAuthCheck::Denied => {
+            debug!("Invalid credentials");
+            Ok(UserIdFromSession::NoUserId)
+        }
+        BasicAuthCheck::RateLimited => Err((
+            StatusCode::TOO_MANY_REQUESTS,
+            "Too many failed login attempts",
+        )),
+    }
+}
 
 impl From<UserCreds> for api::AccountResponse {
     fn from(auth: UserCreds) -> Self {
@@ -39,7 +197,9 @@ impl From<UserCreds> for api::AccountResponse {
 pub async fn handler(
     auth: AuthBasic,
     Host(domain): Host,
+    peer: Option<ConnectInfo<SocketAddr>>,
     Extension(session_store): Extension<Arc<storage::SqliteStore>>,
+    Extension(checker): Extension<Arc<BasicAuthChecker>>,
 ) -> (StatusCode, HeaderMap, axum::Json<api::AccountResponse>) {
     // NOTE(jwall): It is very important that you do **not** log the password
     // here. We convert the AuthBasic into UserCreds immediately to help prevent
@@ -47,7 +207,9 @@ pub async fn handler(
     let auth = storage::UserCreds::from(auth);
     info!("Handling authentication request");
     let mut headers = HeaderMap::new();
-    if let Ok(true) = session_store.check_user_creds(&auth).await {
+    let peer = peer.map(|ConnectInfo(addr)| addr);
+    let check = checker.check(&session_store, peer, &auth).await;
+    if check == BasicAuthCheck::Authorized {
         debug!("successfully authenticated user");
         // 1. Create a session identifier.
         let mut session = Session::new();
@@ -92,7 +254,7 @@ pub async fn handler(
             Ok(Some(value)) => value,
         };
         // 3. Construct the Session Cookie.
-        let cookie = Cookie::build(storage::AXUM_SESSION_COOKIE_NAME, cookie_value)
+        let cookie = Cookie::build(session_store.session_cookie_name().to_owned(), cookie_value)
             .same_site(SameSite::Strict)
             .domain(domain)
             .secure(true)
@@ -118,6 +280,17 @@ pub async fn handler(
         // Respond with 200 OK
         let resp: api::AccountResponse = auth.into();
         (StatusCode::OK, headers, axum::Json::from(resp))
+    } else if check == BasicAuthCheck::RateLimited {
+        debug!("Too many failed attempts");
+        let resp = api::AccountResponse::error(
+            StatusCode::TOO_MANY_REQUESTS.as_u16(),
+            "Too many failed login attempts",
+        );
+        (
+            StatusCode::TOO_MANY_REQUESTS,
+            headers,
+            axum::Json::from(resp),
+        )
     } else {
         debug!("Invalid credentials");
         let headers = HeaderMap::new();
@@ -151,3 +324,271 @@ impl From<AuthBasic> for storage::UserCreds {
         }
     }
 }
+
+#[instrument(skip_all)]
+pub async fn logout_handler(
+    cookies: Option<TypedHeader<headers::Cookie>>,
+    Host(domain): Host,
+    Extension(session_store): Extension<Arc<storage::SqliteStore>>,
+) -> (StatusCode, HeaderMap, axum::Json<api::EmptyResponse>) {
+    info!("Handling logout request");
+    if let Some(cookie_value) = cookies
+        .as_ref()
+        .and_then(|c| c.get(session_store.session_cookie_name()))
+    {
+        match session_store.load_session(cookie_value.to_owned()).await {
+            Ok(Some(session)) => {
+                if let Err(err) = session_store.destroy_session(session).await {
+                    error!(?err, "Unable to destroy session");
+                    let resp = api::EmptyResponse::error(
+                        StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        "Unable to destroy session",
+                    );
+                    return (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        HeaderMap::new(),
+                        axum::Json::from(resp),
+                    );
+                }
+                debug!("destroyed session");
+            }
+            Ok(None) => debug!("no session found for cookie"),
+            Err(err) => debug!(?err, "error loading session"),
+        }
+    }
+    // NOTE(jwall): We always clear the cookie even if there was no session for
+    // it so the client ends up logged out either way.
+    let mut cookie = Cookie::build(session_store.session_cookie_name().to_owned(), "")
+        .same_site(SameSite::Strict)
+        .domain(domain)
+        .secure(true)
+        .path("/")
+        .finish();
+    cookie.make_removal();
+    let mut headers = HeaderMap::new();
+    match cookie.to_string().parse() {
+        Ok(parsed_cookie) => {
+            headers.insert(header::SET_COOKIE, parsed_cookie);
+        }
+        Err(err) => {
+            error!(?err, "Unable to parse session cookie");
+            let resp = api::EmptyResponse::error(
+                StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                "Unable to parse session cookie",
+            );
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                headers,
+                axum::Json::from(resp),
+            );
+        }
+    }
+    (
+        StatusCode::OK,
+        headers,
+        axum::Json::from(api::EmptyResponse::success(())),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use axum::{
+        body::Body,
+        headers::HeaderMapExt,
+        http::Request,
+        routing::{get, post},
+        Router,
+    };
+    use tower::ServiceExt;
+
+    async fn make_store() -> Arc<storage::SqliteStore> {
+        let store = storage::make_test_store().await;
+        store
+            .store_user_creds(UserCreds {
+                id: UserId("alice".to_owned()),
+                pass: Secret::from("secret".to_owned()),
+            })
+            .await
+            .expect("Failed to store user creds");
+        store
+    }
+
+    async fn user_from_request(
+        allow_api_auth: bool,
+        pass: &str,
+    ) -> std::result::Result<UserIdFromSession, (StatusCode, &'static str)> {
+        let store = make_store().await;
+        let mut req = Request::builder()
+            .extension(store)
+            .extension(Arc::new(BasicAuthChecker::new(allow_api_auth)))
+            .body(())
+            .unwrap();
+        req.headers_mut()
+            .typed_insert(headers::Authorization::basic("alice", pass));
+        UserIdFromSession::from_request(&mut RequestParts::new(req)).await
+    }
+
+    async fn whoami(session: UserIdFromSession) -> StatusCode {
+        match session {
+            UserIdFromSession::FoundUserId(_) => StatusCode::OK,
+            UserIdFromSession::NoUserId => StatusCode::UNAUTHORIZED,
+        }
+    }
+
+    /// A router for a kitchen instance that shares the store with others but
+    /// uses its own session cookie.
+    fn make_instance(store: &storage::SqliteStore, cookie_name: &str) -> Router {
+        Router::new()
+            .route("/auth", post(handler).delete(logout_handler))
+            .route("/whoami", get(whoami))
+            .layer(Extension(Arc::new(
+                store.clone().with_session_cookie_name(cookie_name),
+            )))
+            .layer(Extension(Arc::new(BasicAuthChecker::new(false))))
+    }
+
+    /// Log in and return the session cookie as a `name=value` pair.
+    async fn login(router: &Router) -> String {
+        let mut req = Request::builder()
+            .method("POST")
+            .uri("/auth")
+            .header(header::HOST, "localhost")
+            .body(Body::empty())
+            .unwrap();
+        req.headers_mut()
+            .typed_insert(headers::Authorization::basic("alice", "secret"));
+        let resp = router.clone().oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let set_cookie = resp
+            .headers()
+            .get(header::SET_COOKIE)
+            .expect("Missing session cookie")
+            .to_str()
+            .unwrap();
+        set_cookie.split(';').next().unwrap().to_owned()
+    }
+
+    async fn send(router: &Router, method: &str, uri: &str, cookies: &str) -> StatusCode {
+        let req = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::HOST, "localhost")
+            .header(header::COOKIE, cookies)
+            .body(Body::empty())
+            .unwrap();
+        router.clone().oneshot(req).await.unwrap().status()
+    }
+
+    #[test]
+    fn test_session_cookie_names_isolate_instances() {
+        async_std::task::block_on(async {
+            let store = make_store().await;
+            let staging = make_instance(&store, "kitchen-staging");
+            let prod = make_instance(&store, "kitchen-prod");
+            let staging_cookie = login(&staging).await;
+            let prod_cookie = login(&prod).await;
+            assert!(staging_cookie.starts_with("kitchen-staging="));
+            assert!(prod_cookie.starts_with("kitchen-prod="));
+            // Each instance only honors its own cookie.
+            assert_eq!(
+                send(&prod, "GET", "/whoami", &staging_cookie).await,
+                StatusCode::UNAUTHORIZED
+            );
+            let both = format!("{}; {}", staging_cookie, prod_cookie);
+            assert_eq!(
+                send(&staging, "GET", "/whoami", &both).await,
+                StatusCode::OK
+            );
+            // Logging out of one instance leaves the other logged in.
+            assert_eq!(send(&prod, "DELETE", "/auth", &both).await, StatusCode::OK);
+            assert_eq!(
+                send(&prod, "GET", "/whoami", &prod_cookie).await,
+                StatusCode::UNAUTHORIZED
+            );
+            assert_eq!(
+                send(&staging, "GET", "/whoami", &staging_cookie).await,
+                StatusCode::OK
+            );
+        });
+    }
+
+    #[test]
+    fn test_basic_api_auth_success() {
+        async_std::task::block_on(async {
+            match user_from_request(true, "secret").await {
+                Ok(UserIdFromSession::FoundUserId(UserId(id))) => assert_eq!(id, "alice"),
+                result => panic!("Expected the user to be found but got {:?}", result),
+            }
+        });
+    }
+
+    #[test]
+    fn test_basic_api_auth_wrong_password() {
+        async_std::task::block_on(async {
+            match user_from_request(true, "wrong").await {
+                Ok(UserIdFromSession::NoUserId) => (),
+                result => panic!("Expected no user but got {:?}", result),
+            }
+        });
+    }
+
+    #[test]
+    fn test_basic_api_auth_disabled() {
+        async_std::task::block_on(async {
+            match user_from_request(false, "secret").await {
+                Ok(UserIdFromSession::NoUserId) => (),
+                result => panic!("Expected no user but got {:?}", result),
+            }
+        });
+    }
+
+    #[test]
+    fn test_basic_auth_checks_are_rate_limited() {
+        async_std::task::block_on(async {
+            let store = make_store().await;
+            let checker = BasicAuthChecker::new(true);
+            let wrong = UserCreds {
+                id: UserId("alice".to_owned()),
+                pass: Secret::from("wrong".to_owned()),
+            };
+            for _ in 0..MAX_FAILED_AUTH_ATTEMPTS {
+                assert_eq!(
+                    checker.check(&store, None, &wrong).await,
+                    BasicAuthCheck::Denied
+                );
+            }
+            let right = UserCreds {
+                id: UserId("alice".to_owned()),
+                pass: Secret::from("secret".to_owned()),
+            };
+            assert_eq!(
+                checker.check(&store, None, &right).await,
+                BasicAuthCheck::RateLimited
+            );
+        });
+    }
+
+    #[test]
+    fn test_basic_auth_checks_are_cached_per_connection() {
+        async_std::task::block_on(async {
+            let store = make_store().await;
+            let checker = BasicAuthChecker::new(true);
+            let creds = UserCreds {
+                id: UserId("alice".to_owned()),
+                pass: Secret::from("secret".to_owned()),
+            };
+            let peer: SocketAddr = "127.0.0.1:4000".parse().unwrap();
+            assert_eq!(
+                checker.check(&store, Some(peer), &creds).await,
+                BasicAuthCheck::Authorized
+            );
+            let other_peer: SocketAddr = "127.0.0.1:4001".parse().unwrap();
+            let key = checker.verified_key(Some(peer), &creds);
+            let other_key = checker.verified_key(Some(other_peer), &creds);
+            let state = checker.state.lock().unwrap();
+            assert!(state.verified.contains_key(&key));
+            assert!(!state.verified.contains_key(&other_key));
+        });
+    }
+}
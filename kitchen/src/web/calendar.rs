@@ -0,0 +1,160 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! iCalendar (RFC 5545) rendering of meal plans so they can be subscribed to
+//! from a calendar app.
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+
+const PRODID: &str = "-//zaphar//kitchen//EN";
+// RFC 5545 says content lines should be no longer than 75 octets.
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Render the meal plans as an iCalendar document with an all day event for
+/// every recipe planned on each date. Recipes missing from `titles` use
+/// their id as the summary.
+pub fn render_meal_plan_calendar(
+    plans: &BTreeMap<NaiveDate, Vec<(String, i32)>>,
+    titles: &BTreeMap<String, String>,
+) -> String {
+    let mut out = String::new();
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, &format!("PRODID:{}", PRODID));
+    push_line(&mut out, "CALSCALE:GREGORIAN");
+    push_line(&mut out, "X-WR-CALNAME:Meal Plan");
+    for (date, recipes) in plans.iter() {
+        let end = date.succ_opt().unwrap_or(*date);
+        for (recipe_id, count) in recipes.iter() {
+            if *count <= 0 {
+                continue;
+            }
+            let title = titles.get(recipe_id).unwrap_or(recipe_id);
+            push_line(&mut out, "BEGIN:VEVENT");
+            push_line(
+                &mut out,
+                &format!(
+                    "UID:{}-{}@kitchen",
+                    date.format("%Y%m%d"),
+                    escape_text(recipe_id)
+                ),
+            );
+            // NOTE(jwall): We don't track when plans were saved so the plan
+            // date doubles as the timestamp. This keeps the output stable
+            // between requests.
+            push_line(
+                &mut out,
+                &format!("DTSTAMP:{}T000000Z", date.format("%Y%m%d")),
+            );
+            push_line(
+                &mut out,
+                &format!("DTSTART;VALUE=DATE:{}", date.format("%Y%m%d")),
+            );
+            push_line(
+                &mut out,
+                &format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")),
+            );
+            push_line(&mut out, &format!("SUMMARY:{}", escape_text(title)));
+            push_line(&mut out, "END:VEVENT");
+        }
+    }
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+/// Append a content line folding it as necessary. Lines end with CRLF and
+/// continuation lines start with a single space.
+fn push_line(out: &mut String, line: &str) {
+    let mut len = 0;
+    for c in line.chars() {
+        if len + c.len_utf8() > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            // The leading space counts towards the line length.
+            len = 1;
+        }
+        out.push(c);
+        len += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+/// Escape a TEXT value.
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\r' => (),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn date(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2022, 11, d).unwrap()
+    }
+
+    #[test]
+    fn test_escape_text() {
+        assert_eq!(escape_text("Mac, Cheese; Peas"), "Mac\\, Cheese\\; Peas");
+        assert_eq!(escape_text("back\\slash"), "back\\\\slash");
+        assert_eq!(escape_text("two\r\nlines"), "two\\nlines");
+    }
+
+    #[test]
+    fn test_long_lines_are_folded() {
+        let mut out = String::new();
+        push_line(&mut out, &format!("SUMMARY:{}", "x".repeat(100)));
+        let lines: Vec<&str> = out.split("\r\n").collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].len(), MAX_LINE_OCTETS);
+        assert!(lines[1].starts_with(' '));
+        assert_eq!(lines[2], "");
+        assert_eq!(
+            out.replace("\r\n ", ""),
+            format!("SUMMARY:{}\r\n", "x".repeat(100))
+        );
+    }
+
+    #[test]
+    fn test_event_per_planned_recipe() {
+        let plans = BTreeMap::from([
+            (
+                date(1),
+                vec![("soup".to_owned(), 2), ("salad".to_owned(), 0)],
+            ),
+            (date(30), vec![("chili".to_owned(), 1)]),
+        ]);
+        let titles = BTreeMap::from([("soup".to_owned(), "Tomato Soup".to_owned())]);
+        let ics = render_meal_plan_calendar(&plans, &titles);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+        assert!(ics.contains("UID:20221101-soup@kitchen\r\n"));
+        assert!(ics.contains("DTSTART;VALUE=DATE:20221101\r\nDTEND;VALUE=DATE:20221102\r\n"));
+        assert!(ics.contains("SUMMARY:Tomato Soup\r\n"));
+        assert!(ics.contains("DTSTART;VALUE=DATE:20221130\r\nDTEND;VALUE=DATE:20221201\r\n"));
+        assert!(ics.contains("SUMMARY:chili\r\n"));
+        assert!(!ics.contains("salad"));
+    }
+}
@@ -0,0 +1,151 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Change notifications sent to a user's open sessions so edits on one
+//! device show up on the others.
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::sync::Mutex;
+
+use async_std::channel::{self, Receiver, Sender, TrySendError};
+use async_trait::async_trait;
+use axum::extract::{FromRequest, RequestParts};
+use client_api::{ChangeEvent, Response, ORIGIN_SESSION_HEADER};
+use tracing::warn;
+
+/// How many events a subscriber can fall behind before it misses some.
+const SUBSCRIBER_CAPACITY: usize = 32;
+
+/// The ui session a request came from if it told us.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct OriginSession(pub Option<String>);
+
+#[async_trait]
+impl<B> FromRequest<B> for OriginSession
+where
+    B: Send,
+{
+    type Rejection = Infallible;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        Ok(Self(
+            req.headers()
+                .get(ORIGIN_SESSION_HEADER)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.to_owned()),
+        ))
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct EventBus {
+    subscribers: Mutex<BTreeMap<String, Vec<(OriginSession, Sender<ChangeEvent>)>>>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Subscribe to the changes made to a user's data by sessions other than
+    /// `origin`.
+    pub fn subscribe(&self, user_id: &str, origin: OriginSession) -> Receiver<ChangeEvent> {
+        let (sender, receiver) = channel::bounded(SUBSCRIBER_CAPACITY);
+        self.subscribers
+            .lock()
+            .expect("Event bus lock poisoned")
+            .entry(user_id.to_owned())
+            .or_default()
+            .push((origin, sender));
+        receiver
+    }
+
+    /// Send a change to each of the user's subscribers except the session it
+    /// came from. Subscribers that have gone away are removed.
+    pub fn publish(&self, user_id: &str, origin: &OriginSession, event: ChangeEvent) {
+        let mut subscribers = self.subscribers.lock().expect("Event bus lock poisoned");
+        if let Some(senders) = subscribers.get_mut(user_id) {
+            senders.retain(|(session, sender)| {
+                if origin.0.is_some() && session == origin {
+                    return !sender.is_closed();
+                }
+                match sender.try_send(event.clone()) {
+                    Ok(()) => true,
+                    Err(TrySendError::Full(_)) => {
+                        warn!(user_id, ?event, "Subscriber is behind, dropping event");
+                        true
+                    }
+                    Err(TrySendError::Closed(_)) => false,
+                }
+            });
+            if senders.is_empty() {
+                subscribers.remove(user_id);
+            }
+        }
+    }
+
+    /// Publish `event` if `resp` is a success. `resp` is passed through.
+    pub fn publish_on_success<T>(
+        &self,
+        user_id: &str,
+        origin: &OriginSession,
+        resp: Response<T>,
+        event: ChangeEvent,
+    ) -> Response<T> {
+        if let Response::Success(_) = resp {
+            self.publish(user_id, origin, event);
+        }
+        resp
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_publish_skips_other_users_and_closed_subscribers() {
+        let events = EventBus::new();
+        let alice = events.subscribe("alice", OriginSession::default());
+        let bob = events.subscribe("bob", OriginSession::default());
+        drop(events.subscribe("alice", OriginSession::default()));
+        events.publish(
+            "alice",
+            &OriginSession::default(),
+            ChangeEvent::RecipesChanged,
+        );
+        assert_eq!(alice.try_recv(), Ok(ChangeEvent::RecipesChanged));
+        assert!(bob.try_recv().is_err());
+        let subscribers = events.subscribers.lock().unwrap();
+        assert_eq!(subscribers.get("alice").map(|s| s.len()), Some(1));
+    }
+
+    #[test]
+    fn test_publish_skips_the_origin_session() {
+        let events = EventBus::new();
+        let phone = OriginSession(Some("phone".to_owned()));
+        let laptop = OriginSession(Some("laptop".to_owned()));
+        let on_phone = events.subscribe("alice", phone.clone());
+        let on_laptop = events.subscribe("alice", laptop);
+        events.publish("alice", &phone, ChangeEvent::RecipesChanged);
+        assert!(on_phone.try_recv().is_err());
+        assert_eq!(on_laptop.try_recv(), Ok(ChangeEvent::RecipesChanged));
+        // Requests that don't say where they came from go to everyone.
+        events.publish(
+            "alice",
+            &OriginSession::default(),
+            ChangeEvent::RecipesChanged,
+        );
+        assert_eq!(on_phone.try_recv(), Ok(ChangeEvent::RecipesChanged));
+    }
+}
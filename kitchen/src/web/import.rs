@@ -0,0 +1,283 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Bulk import of recipe files from a directory or a zip archive.
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::Path;
+
+use recipes::RecipeEntry;
+use tracing::debug;
+
+use super::storage::{self, APIStore};
+
+const CATEGORIES_FILE: &str = "categories.txt";
+// Special files that are never recipes.
+const IGNORED_FILES: [&str; 1] = ["menu.txt"];
+
+/// The recipe files and categories read from an import source.
+#[derive(Debug, Default)]
+pub struct ImportFiles {
+    /// The id and contents of each recipe file.
+    pub recipes: Vec<(String, String)>,
+    pub categories: Option<String>,
+    /// The id and read error of each file that couldn't be read. e.g. files
+    /// that aren't utf-8.
+    pub unreadable: Vec<(String, String)>,
+}
+
+impl ImportFiles {
+    /// Read the `.txt` files from a directory, recursively, or from a zip
+    /// archive. Recipes are identified by their path like they are for a
+    /// recipe directory. See [`ImportFiles::recipe_id`].
+    pub fn from_path(path: &Path) -> io::Result<Self> {
+        let mut files = Self::default();
+        if path.is_dir() {
+            files.read_dir(path, &[])?;
+        } else {
+            files.read_zip(path)?;
+        }
+        files.recipes.sort();
+        Ok(files)
+    }
+
+    /// Whether a file should be read. Other files are skipped without reading
+    /// them so that stray binary files don't fail the import.
+    fn wants(name: &str) -> bool {
+        let wanted =
+            name.ends_with(".txt") && !name.starts_with('.') && !IGNORED_FILES.contains(&name);
+        if !wanted {
+            debug!(file = name, "Skipping file that isn't a recipe");
+        }
+        wanted
+    }
+
+    /// The id of a recipe from the parts of its path in the import. The id is
+    /// the path relative to the innermost `recipes` directory if the file is
+    /// in one and relative to the import root otherwise. So both
+    /// `recipes/desserts/brownies.txt` and `desserts/brownies.txt` are
+    /// `desserts/brownies.txt`.
+    fn recipe_id(parts: &[String]) -> String {
+        let dirs = &parts[..parts.len().saturating_sub(1)];
+        let start = dirs
+            .iter()
+            .rposition(|part| part == "recipes")
+            .map(|idx| idx + 1)
+            .unwrap_or(0);
+        parts[start..].join("/")
+    }
+
+    fn add_file(&mut self, parts: &[String], contents: String) {
+        if parts.last().map(|name| name == CATEGORIES_FILE) == Some(true) {
+            self.categories = Some(contents);
+        } else {
+            self.recipes.push((Self::recipe_id(parts), contents));
+        }
+    }
+
+    fn add_unreadable(&mut self, parts: &[String], err: io::Error) {
+        let id = Self::recipe_id(parts);
+        debug!(file = id, ?err, "Skipping file that couldn't be read");
+        self.unreadable.push((id, err.to_string()));
+    }
+
+    fn read_dir(&mut self, path: &Path, parents: &[String]) -> io::Result<()> {
+        for entry in fs::read_dir(path)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            let mut parts = parents.to_vec();
+            parts.push(name.clone());
+            if entry.file_type()?.is_dir() {
+                // NOTE(jwall): Hidden directories are things like .git and
+                // never hold recipes.
+                if !name.starts_with('.') {
+                    self.read_dir(&entry.path(), &parts)?;
+                }
+            } else if Self::wants(&name) {
+                match fs::read_to_string(entry.path()) {
+                    Ok(contents) => self.add_file(&parts, contents),
+                    Err(err) => self.add_unreadable(&parts, err),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn read_zip(&mut self, path: &Path) -> io::Result<()> {
+        let mut archive = zip::ZipArchive::new(File::open(path)?)?;
+        for idx in 0..archive.len() {
+            let mut file = archive.by_index(idx)?;
+            if file.is_dir() {
+                continue;
+            }
+            let parts: Vec<String> = match file.enclosed_name() {
+                Some(path) => path
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy().to_string())
+                    .collect(),
+                None => continue,
+            };
+            match parts.last() {
+                Some(name) if Self::wants(name) => (),
+                _ => continue,
+            }
+            if parts.iter().any(|part| part.starts_with('.')) {
+                continue;
+            }
+            let mut contents = String::new();
+            match file.read_to_string(&mut contents) {
+                Ok(_) => self.add_file(&parts, contents),
+                Err(err) => self.add_unreadable(&parts, err),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What happened to each file in an import.
+#[derive(Debug, Default, PartialEq)]
+pub struct ImportSummary {
+    pub imported: Vec<String>,
+    /// The id and parse error of each recipe that was skipped.
+    pub skipped: Vec<(String, String)>,
+    pub categories: bool,
+}
+
+/// Split recipe files into the entries that parse and the id and parse
+/// error of those that don't.
+pub fn validate_recipes(
+    recipe_files: Vec<(String, String)>,
+) -> (Vec<RecipeEntry>, Vec<(String, String)>) {
+    let mut entries = Vec::new();
+    let mut invalid = Vec::new();
+    for (name, contents) in recipe_files {
+        match recipes::parse::as_recipe(&contents) {
+            Ok(_) => entries.push(RecipeEntry::new(name, contents)),
+            Err(err) => invalid.push((name, err)),
+        }
+    }
+    (entries, invalid)
+}
+
+/// Store the recipes that parse for a user. Recipes that fail to parse are
+/// reported in the summary instead of failing the import.
+pub async fn import_files(
+    app_store: &storage::SqliteStore,
+    user_id: &str,
+    files: ImportFiles,
+) -> storage::Result<ImportSummary> {
+    let (entries, invalid) = validate_recipes(files.recipes);
+    let mut skipped = files.unreadable;
+    skipped.extend(invalid);
+    let mut summary = ImportSummary {
+        imported: entries.iter().map(|e| e.recipe_id().to_owned()).collect(),
+        skipped,
+        categories: false,
+    };
+    if !entries.is_empty() {
+        app_store.store_recipes_for_user(user_id, &entries).await?;
+    }
+    if let Some(categories) = files.categories {
+        app_store
+            .store_categories_for_user(user_id, &categories)
+            .await?;
+        summary.categories = true;
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::web::storage::{TempDir, TEST_RECIPE as SOUP};
+    use std::io::Write;
+
+    #[test]
+    fn test_import_directory_skips_bad_recipes() {
+        async_std::task::block_on(async {
+            let tmp = TempDir::new();
+            let dir = tmp.path();
+            fs::create_dir_all(dir.join("recipes")).unwrap();
+            fs::create_dir_all(dir.join("recipes").join("winter")).unwrap();
+            fs::write(dir.join("recipes").join("soup.txt"), SOUP).unwrap();
+            fs::write(dir.join("recipes").join("winter").join("soup.txt"), SOUP).unwrap();
+            fs::write(dir.join("recipes").join("broken.txt"), "not a recipe").unwrap();
+            fs::write(dir.join("recipes").join("latin1.txt"), b"caf\xe9").unwrap();
+            fs::write(dir.join("recipes").join("menu.txt"), "soup.txt").unwrap();
+            fs::write(dir.join("photo.jpg"), [0xff, 0xd8, 0xff]).unwrap();
+            fs::write(dir.join(CATEGORIES_FILE), "Soup: broth").unwrap();
+            let files = ImportFiles::from_path(&dir).expect("Failed to read import dir");
+            assert_eq!(files.recipes.len(), 3);
+            let app_store = storage::make_test_store().await;
+            let summary = import_files(&app_store, "alice", files)
+                .await
+                .expect("Failed to import");
+            assert_eq!(
+                summary.imported,
+                vec!["soup.txt".to_owned(), "winter/soup.txt".to_owned()]
+            );
+            assert_eq!(
+                summary
+                    .skipped
+                    .iter()
+                    .map(|(id, _)| id.as_str())
+                    .collect::<Vec<&str>>(),
+                vec!["latin1.txt", "broken.txt"]
+            );
+            assert!(summary.categories);
+            let stored = app_store
+                .get_recipes_for_user("alice")
+                .await
+                .expect("Failed to fetch recipes")
+                .unwrap_or_default();
+            assert_eq!(stored.len(), 2);
+            assert_eq!(
+                app_store
+                    .get_categories_for_user("alice")
+                    .await
+                    .expect("Failed to fetch categories"),
+                Some("Soup: broth".to_owned())
+            );
+        });
+    }
+
+    #[test]
+    fn test_import_zip() {
+        let tmp = TempDir::new();
+        fs::create_dir_all(tmp.path()).unwrap();
+        let path = tmp.path().join("import.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+        let options = zip::write::FileOptions::default();
+        zip.add_directory("recipes/", options).unwrap();
+        zip.start_file("recipes/soup.txt", options).unwrap();
+        zip.write_all(SOUP.as_bytes()).unwrap();
+        zip.start_file("desserts/pie.txt", options).unwrap();
+        zip.write_all(SOUP.as_bytes()).unwrap();
+        zip.start_file(CATEGORIES_FILE, options).unwrap();
+        zip.write_all(b"Soup: broth").unwrap();
+        zip.start_file("recipes/latin1.txt", options).unwrap();
+        zip.write_all(b"caf\xe9").unwrap();
+        zip.finish().unwrap();
+        let files = ImportFiles::from_path(&path).expect("Failed to read zip");
+        assert_eq!(
+            files.recipes,
+            vec![
+                ("desserts/pie.txt".to_owned(), SOUP.to_owned()),
+                ("soup.txt".to_owned(), SOUP.to_owned()),
+            ]
+        );
+        assert_eq!(files.categories, Some("Soup: broth".to_owned()));
+        assert_eq!(files.unreadable.len(), 1);
+        assert_eq!(files.unreadable[0].0, "latin1.txt");
+    }
+}
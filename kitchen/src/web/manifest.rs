@@ -0,0 +1,248 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Web app manifest and icon set for the kitchen ui.
+use client_api::{LOG_FILTER_META, SESSION_COOKIE_META};
+use serde::Serialize;
+
+/// The default theme color. This matches the `--main-color` in app.css.
+pub const DEFAULT_THEME_COLOR: &str = "#A9907E";
+
+/// The icons we ship in the `icons` directory of the ui assets.
+/// (file name, sizes, purpose)
+pub const ICON_SET: [(&str, &str, &str); 4] = [
+    ("icon-192.png", "192x192", "any"),
+    ("icon-512.png", "512x512", "any"),
+    ("maskable-192.png", "192x192", "maskable"),
+    ("maskable-512.png", "512x512", "maskable"),
+];
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ManifestIcon {
+    pub src: String,
+    pub sizes: &'static str,
+    #[serde(rename = "type")]
+    pub mime_type: &'static str,
+    pub purpose: &'static str,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct WebManifest {
+    pub name: &'static str,
+    pub short_name: &'static str,
+    pub start_url: &'static str,
+    pub display: &'static str,
+    pub background_color: String,
+    pub theme_color: String,
+    pub icons: Vec<ManifestIcon>,
+}
+
+/// Returns true if the file name is one of the icons in our icon set.
+pub fn is_icon(file: &str) -> bool {
+    ICON_SET.iter().any(|(f, _, _)| *f == file)
+}
+
+/// Construct the web manifest for the ui using the provided theme color.
+pub fn make_manifest(theme_color: &str) -> WebManifest {
+    WebManifest {
+        name: "Kitchen",
+        short_name: "Kitchen",
+        start_url: "/ui/planning/plan",
+        display: "standalone",
+        background_color: theme_color.to_owned(),
+        theme_color: theme_color.to_owned(),
+        icons: ICON_SET
+            .iter()
+            .map(|&(file, sizes, purpose)| ManifestIcon {
+                src: format!("/icons/{}", file),
+                sizes,
+                mime_type: "image/png",
+                purpose,
+            })
+            .collect(),
+    }
+}
+
+/// Validate a theme color. Since the color ends up in our index.html we only
+/// allow hex colors and plain css color names.
+pub fn validate_theme_color(color: &str) -> Result<String, String> {
+    let valid = if let Some(hex) = color.strip_prefix('#') {
+        [3, 4, 6, 8].contains(&hex.len()) && hex.chars().all(|c| c.is_ascii_hexdigit())
+    } else {
+        !color.is_empty() && color.chars().all(|c| c.is_ascii_alphabetic())
+    };
+    if valid {
+        Ok(color.to_owned())
+    } else {
+        Err(format!(
+            "Invalid theme color {}. Expected a hex color like #A9907E or a css color name",
+            color
+        ))
+    }
+}
+
+/// Inject the theme-color meta tag into the head of the index.html.
+pub fn inject_theme_color(html: &str, theme_color: &str) -> String {
+    inject_meta(html, "theme-color", theme_color)
+}
+
+/// Validate a ui log filter. The filter ends up in our index.html so we only
+/// allow the characters that a tracing target filter like
+/// `info,kitchen_wasm::api=debug` can contain.
+pub fn validate_log_filter(filter: &str) -> Result<String, String> {
+    let valid = !filter.is_empty()
+        && filter
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || ['_', '-', ':', ',', '='].contains(&c));
+    if valid {
+        Ok(filter.to_owned())
+    } else {
+        Err(format!(
+            "Invalid log filter {}. Expected a filter like info,kitchen_wasm::api=debug",
+            filter
+        ))
+    }
+}
+
+/// Inject the log filter meta tag into the head of the index.html.
+pub fn inject_log_filter(html: &str, filter: &str) -> String {
+    inject_meta(html, LOG_FILTER_META, filter)
+}
+
+/// Validate a session cookie name. Cookie names must be an RFC 6265 token and
+/// the name also ends up in our index.html so we only allow the token
+/// characters that are safe in an html attribute.
+pub fn validate_session_cookie_name(name: &str) -> Result<String, String> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || ['_', '-', '.'].contains(&c));
+    if valid {
+        Ok(name.to_owned())
+    } else {
+        Err(format!(
+            "Invalid session cookie name {}. Expected letters, numbers, '_', '-', or '.'",
+            name
+        ))
+    }
+}
+
+/// Inject the session cookie name meta tag into the head of the index.html.
+pub fn inject_session_cookie_name(html: &str, name: &str) -> String {
+    inject_meta(html, SESSION_COOKIE_META, name)
+}
+
+fn inject_meta(html: &str, name: &str, content: &str) -> String {
+    let meta = format!("<meta name=\"{}\" content=\"{}\">\n", name, content);
+    match html.find("</head>") {
+        Some(idx) => format!("{}{}{}", &html[..idx], meta, &html[idx..]),
+        None => html.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_manifest_uses_theme_color() {
+        let manifest = make_manifest("#123456");
+        assert_eq!(manifest.theme_color, "#123456");
+        assert_eq!(manifest.background_color, "#123456");
+        assert_eq!(manifest.icons.len(), ICON_SET.len());
+        assert!(manifest
+            .icons
+            .iter()
+            .any(|i| i.purpose == "maskable" && i.sizes == "512x512"));
+        for icon in manifest.icons.iter() {
+            assert!(icon.src.starts_with("/icons/"));
+            assert!(is_icon(icon.src.trim_start_matches("/icons/")));
+        }
+    }
+
+    #[test]
+    fn test_is_icon() {
+        assert!(is_icon("icon-192.png"));
+        assert!(!is_icon("favicon.ico"));
+        assert!(!is_icon("../index.html"));
+    }
+
+    #[test]
+    fn test_validate_theme_color() {
+        for good in vec!["#fff", "#A9907E", "#A9907EFF", "rebeccapurple"] {
+            assert_eq!(validate_theme_color(good), Ok(good.to_owned()));
+        }
+        for bad in vec![
+            "",
+            "#",
+            "#12345",
+            "#GGGGGG",
+            "red\"><script>",
+            "rgb(0, 0, 0)",
+        ] {
+            assert!(
+                validate_theme_color(bad).is_err(),
+                "{} should be invalid",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn test_inject_theme_color() {
+        let html = "<html>\n<head>\n<title>kitchen</title>\n</head>\n<body></body>\n</html>";
+        let injected = inject_theme_color(html, "#A9907E");
+        assert_eq!(
+            injected,
+            "<html>\n<head>\n<title>kitchen</title>\n<meta name=\"theme-color\" content=\"#A9907E\">\n</head>\n<body></body>\n</html>"
+        );
+        // Without a head we leave the html alone.
+        assert_eq!(inject_theme_color("<p></p>", "#fff"), "<p></p>");
+    }
+
+    #[test]
+    fn test_validate_log_filter() {
+        for good in vec!["warn", "info,kitchen_wasm::api=debug"] {
+            assert_eq!(validate_log_filter(good), Ok(good.to_owned()));
+        }
+        for bad in vec!["", "info\"><script>", "debug kitchen_wasm"] {
+            assert!(
+                validate_log_filter(bad).is_err(),
+                "{} should be invalid",
+                bad
+            );
+        }
+        assert_eq!(
+            inject_log_filter("<head></head>", "warn"),
+            "<head><meta name=\"kitchen-log-filter\" content=\"warn\">\n</head>"
+        );
+    }
+
+    #[test]
+    fn test_validate_session_cookie_name() {
+        for good in vec!["kitchen-session-cookie", "kitchen_staging.v2"] {
+            assert_eq!(validate_session_cookie_name(good), Ok(good.to_owned()));
+        }
+        for bad in vec!["", "kitchen session", "a=b", "x\"><script>", "k;c"] {
+            assert!(
+                validate_session_cookie_name(bad).is_err(),
+                "{} should be invalid",
+                bad
+            );
+        }
+        assert_eq!(
+            inject_session_cookie_name("<head></head>", "kitchen-staging"),
+            "<head><meta name=\"kitchen-session-cookie\" content=\"kitchen-staging\">\n</head>"
+        );
+    }
+}
@@ -14,62 +14,139 @@
 use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use std::{collections::BTreeSet, net::SocketAddr};
 
 use axum::{
     body::{boxed, Full},
-    extract::{Extension, Json, Path},
+    extract::{Extension, Json, Path, Query},
     http::{header, StatusCode},
-    response::{IntoResponse, Redirect, Response},
-    routing::{get, Router},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Html, IntoResponse, Redirect, Response,
+    },
+    routing::{get, post, Router},
 };
 use chrono::NaiveDate;
 use client_api as api;
+use futures::StreamExt;
 use metrics_process::Collector;
 use mime_guess;
-use recipes::{IngredientKey, RecipeEntry};
+use recipes::{unit::MeasureType, IngredientKey, RecipeEntry, RecipeTemplate};
 use rust_embed::RustEmbed;
+use serde::Deserialize;
 use storage::{APIStore, AuthStore};
 use tower::ServiceBuilder;
 use tower_http::trace::TraceLayer;
-use tracing::{debug, info, instrument};
+use tracing::{debug, error, info, instrument};
 
 mod auth;
+mod calendar;
+mod events;
+pub mod import;
+mod manifest;
 mod metrics;
+mod pdf;
+pub mod revalidate;
+mod shopping_list;
 mod storage;
 
+pub use storage::{
+    file_store::{AsyncFileStore, RecipeStore},
+    memory_store::MemoryStore,
+    DEFAULT_SESSION_TTL,
+};
+
 #[derive(RustEmbed)]
 #[folder = "../web/dist"]
 struct UiAssets;
 
 pub struct StaticFile<T>(pub T);
 
+/// A StaticFile that doesn't change for a given path and can be cached by the
+/// browser for a long time.
+pub struct CachedStaticFile<T>(pub T);
+
+fn static_file_response(path: String, cache_control: Option<&'static str>) -> Response {
+    match UiAssets::get(path.as_str()) {
+        Some(content) => {
+            let body = boxed(Full::from(content.data));
+            let mime = mime_guess::from_path(path).first_or_octet_stream();
+            let mut builder = Response::builder().header(header::CONTENT_TYPE, mime.as_ref());
+            if let Some(cache_control) = cache_control {
+                builder = builder.header(header::CACHE_CONTROL, cache_control);
+            }
+            builder.body(body).unwrap()
+        }
+        None => Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(boxed(Full::from("404")))
+            .unwrap(),
+    }
+}
+
 impl<T> IntoResponse for StaticFile<T>
 where
     T: Into<String>,
 {
     fn into_response(self) -> Response {
-        let path = self.0.into();
-
-        match UiAssets::get(path.as_str()) {
-            Some(content) => {
-                let body = boxed(Full::from(content.data));
-                let mime = mime_guess::from_path(path).first_or_octet_stream();
-                Response::builder()
-                    .header(header::CONTENT_TYPE, mime.as_ref())
-                    .body(body)
-                    .unwrap()
-            }
-            None => Response::builder()
-                .status(StatusCode::NOT_FOUND)
-                .body(boxed(Full::from("404")))
-                .unwrap(),
+        static_file_response(self.0.into(), None)
+    }
+}
+
+impl<T> IntoResponse for CachedStaticFile<T>
+where
+    T: Into<String>,
+{
+    fn into_response(self) -> Response {
+        static_file_response(self.0.into(), Some("public, max-age=31536000, immutable"))
+    }
+}
+
+/// Configuration for the ui.
+#[derive(Debug, Clone)]
+pub struct UiConfig {
+    pub theme_color: String,
+    /// A tracing filter for the ui so targeted debug logging can be turned on
+    /// without rebuilding it.
+    pub log_filter: Option<String>,
+    /// The name of the session cookie. Instances sharing a host need
+    /// distinct names since cookies don't distinguish between ports.
+    pub session_cookie_name: String,
+}
+
+impl UiConfig {
+    pub fn with_theme_color(mut self, theme_color: &str) -> Result<Self, String> {
+        self.theme_color = manifest::validate_theme_color(theme_color)?;
+        Ok(self)
+    }
+
+    pub fn with_log_filter(mut self, log_filter: &str) -> Result<Self, String> {
+        self.log_filter = Some(manifest::validate_log_filter(log_filter)?);
+        Ok(self)
+    }
+
+    pub fn with_session_cookie_name(mut self, name: &str) -> Result<Self, String> {
+        self.session_cookie_name = manifest::validate_session_cookie_name(name)?;
+        Ok(self)
+    }
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            theme_color: manifest::DEFAULT_THEME_COLOR.to_owned(),
+            log_filter: None,
+            session_cookie_name: storage::AXUM_SESSION_COOKIE_NAME.to_owned(),
         }
     }
 }
 
 #[instrument]
-async fn ui_static_assets(Path(path): Path<String>) -> impl IntoResponse {
+async fn ui_static_assets(
+    Extension(ui_config): Extension<Arc<UiConfig>>,
+    Path(path): Path<String>,
+) -> Response {
     info!("Serving ui path");
 
     let mut path = path.trim_start_matches("/");
@@ -77,19 +154,59 @@ async fn ui_static_assets(Path(path): Path<String>) -> impl IntoResponse {
         path = "index.html";
     }
     debug!(path = path, "Serving transformed path");
-    StaticFile(path.to_owned())
+    if path == "index.html" {
+        ui_index(&ui_config).into_response()
+    } else {
+        StaticFile(path.to_owned()).into_response()
+    }
+}
+
+fn ui_index(ui_config: &UiConfig) -> Response {
+    match UiAssets::get("index.html") {
+        Some(content) => {
+            let html = String::from_utf8_lossy(content.data.as_ref());
+            let mut html = manifest::inject_theme_color(&html, &ui_config.theme_color);
+            if let Some(log_filter) = &ui_config.log_filter {
+                html = manifest::inject_log_filter(&html, log_filter);
+            }
+            html = manifest::inject_session_cookie_name(&html, &ui_config.session_cookie_name);
+            Html(html).into_response()
+        }
+        None => StaticFile("index.html").into_response(),
+    }
+}
+
+#[instrument]
+async fn ui_manifest(Extension(ui_config): Extension<Arc<UiConfig>>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "application/manifest+json")],
+        Json(manifest::make_manifest(&ui_config.theme_color)),
+    )
+}
+
+#[instrument]
+async fn ui_icon(Path(icon): Path<String>) -> Response {
+    if manifest::is_icon(&icon) {
+        CachedStaticFile(format!("icons/{}", icon)).into_response()
+    } else {
+        StatusCode::NOT_FOUND.into_response()
+    }
 }
 
 #[instrument]
 async fn api_recipe_entry(
-    Extension(store): Extension<Arc<storage::file_store::AsyncFileStore>>,
+    Extension(store): Extension<Arc<dyn RecipeStore>>,
     Extension(app_store): Extension<Arc<storage::SqliteStore>>,
     session: storage::UserIdFromSession,
     Path(recipe_id): Path<String>,
 ) -> api::Response<Option<RecipeEntry>> {
-    use storage::{UserId, UserIdFromSession::*};
+    use storage::{file_store, UserId, UserIdFromSession::*};
     match session {
-        NoUserId => store.get_recipe_entry(recipe_id).await.into(),
+        NoUserId => match store.get_recipe_entry(&recipe_id).await {
+            Ok(Some(entry)) => api::Response::Success(Some(entry)),
+            Ok(None) | Err(file_store::Error::NotFound) => api::Response::NotFound,
+            Err(err) => api::Response::error(500, err.to_string()),
+        },
         FoundUserId(UserId(id)) => app_store
             .get_recipe_entry_for_user(id, recipe_id)
             .await
@@ -97,24 +214,97 @@ async fn api_recipe_entry(
     }
 }
 
+/// The recipe parsed on the server so clients don't have to parse it.
+#[instrument]
+async fn api_recipe_parsed(
+    Extension(store): Extension<Arc<dyn RecipeStore>>,
+    Extension(app_store): Extension<Arc<storage::SqliteStore>>,
+    session: storage::UserIdFromSession,
+    Path(recipe_id): Path<String>,
+) -> api::ParsedRecipeResponse {
+    use storage::{file_store, UserId, UserIdFromSession::*};
+    let entry = match session {
+        NoUserId => match store.get_recipe_entry(&recipe_id).await {
+            Ok(entry) => entry,
+            Err(file_store::Error::NotFound) => None,
+            Err(err) => return api::Response::error(500, err.to_string()),
+        },
+        FoundUserId(UserId(id)) => match app_store.get_recipe_entry_for_user(id, recipe_id).await {
+            Ok(entry) => entry,
+            Err(err) => return api::Response::error(500, format!("{:?}", err)),
+        },
+    };
+    match entry {
+        Some(entry) => match recipes::parse::as_recipe_detailed(entry.recipe_text()) {
+            Ok(recipe) => api::Response::success(recipe),
+            Err(err) => api::Response::Invalid(err),
+        },
+        None => api::Response::NotFound,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct DeleteRecipeParams {
+    #[serde(default)]
+    force: bool,
+}
+
+/// Delete recipes for a user. Recipes that are in saved meal plans are only
+/// deleted when forced since deleting them also removes them from those plans.
+async fn delete_recipes(
+    app_store: &storage::SqliteStore,
+    user_id: &str,
+    recipe_ids: Vec<String>,
+    force: bool,
+) -> api::EmptyResponse {
+    if !force {
+        match app_store
+            .fetch_plan_dates_for_recipes(user_id, &recipe_ids)
+            .await
+        {
+            Ok(dates) if !dates.is_empty() => {
+                let dates: Vec<String> = dates.iter().map(|d| d.to_string()).collect();
+                return api::EmptyResponse::error(
+                    StatusCode::CONFLICT.as_u16(),
+                    format!(
+                        "Recipe is in the meal plans for {}. Use ?force=true to delete it anyway.",
+                        dates.join(", ")
+                    ),
+                );
+            }
+            Ok(_) => (),
+            Err(e) => return api::EmptyResponse::error(500, format!("{:?}", e)),
+        }
+    }
+    app_store
+        .delete_recipes_for_user(user_id, &recipe_ids)
+        .await
+        .into()
+}
+
 async fn api_recipe_delete(
     Extension(app_store): Extension<Arc<storage::SqliteStore>>,
+    Extension(events): Extension<Arc<events::EventBus>>,
     session: storage::UserIdFromSession,
+    origin: events::OriginSession,
     Path(recipe_id): Path<String>,
+    Query(params): Query<DeleteRecipeParams>,
 ) -> api::EmptyResponse {
     use storage::{UserId, UserIdFromSession::*};
     match session {
         NoUserId => api::EmptyResponse::Unauthorized,
-        FoundUserId(UserId(id)) => app_store
-            .delete_recipes_for_user(&id, &vec![recipe_id])
-            .await
-            .into(),
+        FoundUserId(UserId(id)) => events.publish_on_success(
+            &id,
+            &origin,
+            delete_recipes(&app_store, &id, vec![recipe_id], params.force).await,
+            api::ChangeEvent::RecipesChanged,
+        ),
     }
 }
 
 #[instrument]
 async fn api_recipes(
-    Extension(store): Extension<Arc<storage::file_store::AsyncFileStore>>,
+    Extension(store): Extension<Arc<dyn RecipeStore>>,
     Extension(app_store): Extension<Arc<storage::SqliteStore>>,
     session: storage::UserIdFromSession,
 ) -> api::RecipeEntryResponse {
@@ -126,6 +316,80 @@ async fn api_recipes(
     }
 }
 
+/// Fetch the recipes that were stored at or after the `since` cursor. The
+/// cursor is the `as_of` timestamp from a previous sync.
+#[instrument]
+async fn api_recipe_changes(
+    Extension(store): Extension<Arc<dyn RecipeStore>>,
+    Extension(app_store): Extension<Arc<storage::SqliteStore>>,
+    session: storage::UserIdFromSession,
+    Path(since): Path<i64>,
+) -> api::RecipeChangesResponse {
+    use storage::{UserId, UserIdFromSession::*};
+    // NOTE(jwall): The timestamp is taken before we query so that a recipe
+    // stored while we query is sent again on the next sync instead of missed.
+    let as_of = chrono::Utc::now().timestamp_millis();
+    let changes = match session {
+        // NOTE(jwall): The default recipes have no timestamps so they are
+        // always sent in full.
+        NoUserId => match store.get_recipes().await {
+            Ok(entries) => {
+                let changed = entries.unwrap_or_default();
+                let ids = changed.iter().map(|e| e.recipe_id().to_owned()).collect();
+                Ok((ids, changed))
+            }
+            Err(e) => return api::RecipeChangesResponse::error(500, format!("{:?}", e)),
+        },
+        FoundUserId(UserId(id)) => app_store.get_recipe_changes_for_user(&id, since).await,
+    };
+    changes
+        .map(|(ids, changed)| api::RecipeChanges {
+            as_of,
+            ids,
+            changed,
+        })
+        .into()
+}
+
+#[derive(Debug, Deserialize)]
+struct RecipeSearchParams {
+    q: String,
+}
+
+/// Check that recipe text parses without saving it. Parse failures are
+/// returned with their line and column.
+#[instrument(skip_all)]
+async fn api_validate_recipe(
+    session: storage::UserIdFromSession,
+    Json(recipe_text): Json<String>,
+) -> api::EmptyResponse {
+    use storage::UserIdFromSession::*;
+    match session {
+        NoUserId => api::Response::Unauthorized,
+        FoundUserId(_) => match recipes::parse::as_recipe_detailed(&recipe_text) {
+            Ok(_) => api::Response::success(()),
+            Err(err) => api::Response::Invalid(err),
+        },
+    }
+}
+
+#[instrument]
+async fn api_search_recipes(
+    Extension(app_store): Extension<Arc<storage::SqliteStore>>,
+    session: storage::UserIdFromSession,
+    Query(params): Query<RecipeSearchParams>,
+) -> api::RecipeEntryResponse {
+    use storage::{UserId, UserIdFromSession::FoundUserId};
+    if let FoundUserId(UserId(id)) = session {
+        app_store
+            .search_recipes_for_user(id.as_str(), params.q.as_str())
+            .await
+            .into()
+    } else {
+        api::RecipeEntryResponse::Unauthorized
+    }
+}
+
 #[instrument]
 async fn api_category_mappings(
     Extension(app_store): Extension<Arc<storage::SqliteStore>>,
@@ -148,6 +412,10 @@ async fn api_save_category_mappings(
     Json(mappings): Json<Vec<(String, String)>>,
 ) -> api::EmptyResponse {
     use storage::UserIdFromSession::*;
+    let mappings = match api::text::check_category_mappings(mappings) {
+        Ok(mappings) => mappings,
+        Err(problems) => return rejected_text(problems),
+    };
     match session {
         NoUserId => api::Response::Unauthorized,
         FoundUserId(user_id) => match app_store
@@ -163,16 +431,79 @@ async fn api_save_category_mappings(
     }
 }
 
+#[instrument]
+async fn api_measure_types(
+    Extension(app_store): Extension<Arc<storage::SqliteStore>>,
+    session: storage::UserIdFromSession,
+) -> api::MeasureTypesResponse {
+    use storage::UserIdFromSession::*;
+    match session {
+        NoUserId => api::Response::Unauthorized,
+        FoundUserId(user_id) => app_store
+            .get_measure_types_for_user(&user_id.0)
+            .await
+            .into(),
+    }
+}
+
+#[instrument]
+async fn api_save_measure_types(
+    Extension(app_store): Extension<Arc<storage::SqliteStore>>,
+    session: storage::UserIdFromSession,
+    Json(measure_types): Json<Vec<(String, MeasureType)>>,
+) -> api::EmptyResponse {
+    use storage::UserIdFromSession::*;
+    match session {
+        NoUserId => api::Response::Unauthorized,
+        FoundUserId(user_id) => app_store
+            .save_measure_types_for_user(&user_id.0, &measure_types)
+            .await
+            .into(),
+    }
+}
+
+#[instrument]
+async fn api_category_order(
+    Extension(app_store): Extension<Arc<storage::SqliteStore>>,
+    session: storage::UserIdFromSession,
+) -> api::CategoryOrderResponse {
+    use storage::UserIdFromSession::*;
+    match session {
+        NoUserId => api::Response::Unauthorized,
+        FoundUserId(user_id) => app_store.get_category_order(&user_id.0).await.into(),
+    }
+}
+
+#[instrument]
+async fn api_save_category_order(
+    Extension(app_store): Extension<Arc<storage::SqliteStore>>,
+    session: storage::UserIdFromSession,
+    Json(order): Json<Vec<String>>,
+) -> api::EmptyResponse {
+    use storage::UserIdFromSession::*;
+    match session {
+        NoUserId => api::Response::Unauthorized,
+        FoundUserId(user_id) => app_store
+            .save_category_order(&user_id.0, &order)
+            .await
+            .into(),
+    }
+}
+
 #[instrument]
 async fn api_categories(
-    Extension(store): Extension<Arc<storage::file_store::AsyncFileStore>>,
+    Extension(store): Extension<Arc<dyn RecipeStore>>,
     Extension(app_store): Extension<Arc<storage::SqliteStore>>,
     session: storage::UserIdFromSession,
 ) -> api::Response<String> {
     // Select Categories based on the user-id if it exists or serve the default if it does not.
-    use storage::{UserId, UserIdFromSession::*};
+    use storage::{file_store, UserId, UserIdFromSession::*};
     match session {
-        NoUserId => store.get_categories().await.into(),
+        NoUserId => match store.get_categories().await {
+            Ok(categories) => api::Response::Success(categories.unwrap_or_default()),
+            Err(file_store::Error::NotFound) => api::Response::NotFound,
+            Err(err) => api::Response::error(500, err.to_string()),
+        },
         FoundUserId(UserId(id)) => app_store.get_categories_for_user(id.as_str()).await.into(),
     }
 }
@@ -195,15 +526,22 @@ async fn api_save_categories(
 
 async fn api_save_recipes(
     Extension(app_store): Extension<Arc<storage::SqliteStore>>,
+    Extension(events): Extension<Arc<events::EventBus>>,
     session: storage::UserIdFromSession,
+    origin: events::OriginSession,
     Json(recipes): Json<Vec<RecipeEntry>>,
 ) -> api::EmptyResponse {
     use storage::{UserId, UserIdFromSession::FoundUserId};
     if let FoundUserId(UserId(id)) = session {
-        app_store
-            .store_recipes_for_user(id.as_str(), &recipes)
-            .await
-            .into()
+        events.publish_on_success(
+            &id,
+            &origin,
+            app_store
+                .store_recipes_for_user(id.as_str(), &recipes)
+                .await
+                .into(),
+            api::ChangeEvent::RecipesChanged,
+        )
     } else {
         api::EmptyResponse::Unauthorized
     }
@@ -222,6 +560,61 @@ async fn api_plan_for_date(
     }
 }
 
+/// Load the meal plan and inventory for a date.
+async fn full_plan_for_date(
+    app_store: &storage::SqliteStore,
+    user_id: &str,
+    date: NaiveDate,
+) -> storage::Result<api::FullPlanData> {
+    let recipe_counts = app_store
+        .fetch_meal_plan_for_date(user_id, date)
+        .await?
+        .unwrap_or_default();
+    let inventory = inventory_for_date(app_store, user_id, date).await?;
+    Ok(api::FullPlanData {
+        recipe_counts,
+        inventory,
+    })
+}
+
+/// Load the inventory for a date along with the items checked off for it, what
+/// they were purchased as, and hints from earlier plans.
+async fn inventory_for_date(
+    app_store: &storage::SqliteStore,
+    user_id: &str,
+    date: NaiveDate,
+) -> storage::Result<api::InventoryData> {
+    let mut inventory: api::InventoryData = app_store
+        .fetch_inventory_for_date(user_id, date)
+        .await?
+        .into();
+    inventory.checked_items = app_store
+        .fetch_checked_items_for_date(user_id, date)
+        .await?;
+    inventory.purchased_amts = app_store
+        .fetch_purchased_amts_for_date(user_id, date)
+        .await?;
+    inventory.purchase_hints = app_store
+        .fetch_purchase_hints(user_id, date)
+        .await?
+        .into_iter()
+        .collect();
+    Ok(inventory)
+}
+
+async fn api_full_plan_for_date(
+    Extension(app_store): Extension<Arc<storage::SqliteStore>>,
+    session: storage::UserIdFromSession,
+    Path(date): Path<chrono::NaiveDate>,
+) -> api::FullPlanResponse {
+    use storage::{UserId, UserIdFromSession::FoundUserId};
+    if let FoundUserId(UserId(id)) = session {
+        full_plan_for_date(&app_store, &id, date).await.into()
+    } else {
+        api::Response::Unauthorized
+    }
+}
+
 async fn api_plan(
     Extension(app_store): Extension<Arc<storage::SqliteStore>>,
     session: storage::UserIdFromSession,
@@ -247,6 +640,101 @@ async fn api_plan_since(
     }
 }
 
+#[derive(Debug, Deserialize)]
+struct CalendarParams {
+    since: Option<NaiveDate>,
+}
+
+/// Render the meal plans for a user as an iCalendar document. Only plans on
+/// or after `since` are included when it's provided.
+async fn meal_plan_calendar(
+    app_store: &storage::SqliteStore,
+    user_id: &str,
+    since: Option<NaiveDate>,
+) -> storage::Result<String> {
+    // NOTE(jwall): fetch_meal_plans_since only returns plans after the date
+    // so we start from the day before to include since itself.
+    let after = match since {
+        Some(since) => since.pred_opt().unwrap_or(since),
+        None => NaiveDate::from_ymd_opt(1970, 1, 1).unwrap(),
+    };
+    let plans = app_store
+        .fetch_meal_plans_since(user_id, after)
+        .await?
+        .unwrap_or_default();
+    let mut titles = BTreeMap::new();
+    for entry in app_store
+        .get_recipes_for_user(user_id)
+        .await?
+        .unwrap_or_default()
+    {
+        match recipes::parse::as_recipe(entry.recipe_text()) {
+            Ok(recipe) => {
+                titles.insert(entry.recipe_id().to_owned(), recipe.title);
+            }
+            Err(err) => error!(
+                recipe_id = entry.recipe_id(),
+                ?err,
+                "Failed to parse recipe"
+            ),
+        }
+    }
+    Ok(calendar::render_meal_plan_calendar(&plans, &titles))
+}
+
+#[instrument(skip_all)]
+async fn api_plan_calendar(
+    Extension(app_store): Extension<Arc<storage::SqliteStore>>,
+    session: storage::UserIdFromSession,
+    Query(params): Query<CalendarParams>,
+) -> Response {
+    use storage::{UserId, UserIdFromSession::FoundUserId};
+    if let FoundUserId(UserId(id)) = session {
+        match meal_plan_calendar(&app_store, &id, params.since).await {
+            Ok(ics) => (
+                [(header::CONTENT_TYPE, "text/calendar; charset=utf-8")],
+                ics,
+            )
+                .into_response(),
+            Err(err) => api::EmptyResponse::error(
+                StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                format!("{:?}", err),
+            )
+            .into_response(),
+        }
+    } else {
+        api::EmptyResponse::Unauthorized.into_response()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct EventsParams {
+    /// The ui session subscribing. An EventSource can't set headers so this
+    /// stands in for the origin session header.
+    session: Option<String>,
+}
+
+/// Stream the changes to the user's data as server-sent events so their other
+/// open sessions can refresh.
+#[instrument(skip_all)]
+async fn api_events(
+    Extension(events): Extension<Arc<events::EventBus>>,
+    session: storage::UserIdFromSession,
+    Query(params): Query<EventsParams>,
+) -> Response {
+    use storage::{UserId, UserIdFromSession::FoundUserId};
+    if let FoundUserId(UserId(id)) = session {
+        let stream = events
+            .subscribe(&id, events::OriginSession(params.session))
+            .map(|event| Event::default().json_data(event));
+        Sse::new(stream)
+            .keep_alive(KeepAlive::default())
+            .into_response()
+    } else {
+        api::EmptyResponse::Unauthorized.into_response()
+    }
+}
+
 async fn api_all_plans(
     Extension(app_store): Extension<Arc<storage::SqliteStore>>,
     session: storage::UserIdFromSession,
@@ -259,17 +747,59 @@ async fn api_all_plans(
     }
 }
 
-async fn api_delete_plan_for_date(
+#[derive(Debug, Deserialize)]
+struct PlanSummaryParams {
+    start: NaiveDate,
+    end: NaiveDate,
+}
+
+#[instrument(skip_all, fields(start=?params.start, end=?params.end))]
+async fn api_plan_summary(
     Extension(app_store): Extension<Arc<storage::SqliteStore>>,
     session: storage::UserIdFromSession,
-    Path(date): Path<chrono::NaiveDate>,
-) -> api::EmptyResponse {
+    Query(params): Query<PlanSummaryParams>,
+) -> api::PlanSummaryResponse {
     use storage::{UserId, UserIdFromSession::FoundUserId};
     if let FoundUserId(UserId(id)) = session {
         app_store
-            .delete_meal_plan_for_date(id.as_str(), date)
+            .fetch_plan_summaries(&id, params.start, params.end)
             .await
+            .map(|rows| {
+                rows.into_iter()
+                    .map(
+                        |(date, recipe_count, servings, has_inventory)| api::PlanSummary {
+                            date,
+                            recipe_count,
+                            servings,
+                            has_inventory,
+                        },
+                    )
+                    .collect::<Vec<api::PlanSummary>>()
+            })
             .into()
+    } else {
+        api::PlanSummaryResponse::Unauthorized
+    }
+}
+
+async fn api_delete_plan_for_date(
+    Extension(app_store): Extension<Arc<storage::SqliteStore>>,
+    Extension(events): Extension<Arc<events::EventBus>>,
+    session: storage::UserIdFromSession,
+    origin: events::OriginSession,
+    Path(date): Path<chrono::NaiveDate>,
+) -> api::EmptyResponse {
+    use storage::{UserId, UserIdFromSession::FoundUserId};
+    if let FoundUserId(UserId(id)) = session {
+        events.publish_on_success(
+            &id,
+            &origin,
+            app_store
+                .delete_meal_plan_for_date(id.as_str(), date)
+                .await
+                .into(),
+            api::ChangeEvent::PlanChanged(date),
+        )
     } else {
         api::EmptyResponse::Unauthorized
     }
@@ -277,16 +807,23 @@ async fn api_delete_plan_for_date(
 
 async fn api_save_plan_for_date(
     Extension(app_store): Extension<Arc<storage::SqliteStore>>,
+    Extension(events): Extension<Arc<events::EventBus>>,
     session: storage::UserIdFromSession,
+    origin: events::OriginSession,
     Path(date): Path<chrono::NaiveDate>,
     Json(meal_plan): Json<Vec<(String, i32)>>,
 ) -> api::EmptyResponse {
     use storage::{UserId, UserIdFromSession::FoundUserId};
     if let FoundUserId(UserId(id)) = session {
-        app_store
-            .save_meal_plan(id.as_str(), &meal_plan, date)
-            .await
-            .into()
+        events.publish_on_success(
+            &id,
+            &origin,
+            app_store
+                .save_meal_plan(id.as_str(), &meal_plan, date)
+                .await
+                .into(),
+            api::ChangeEvent::PlanChanged(date),
+        )
     } else {
         api::EmptyResponse::Unauthorized
     }
@@ -294,15 +831,23 @@ async fn api_save_plan_for_date(
 
 async fn api_save_plan(
     Extension(app_store): Extension<Arc<storage::SqliteStore>>,
+    Extension(events): Extension<Arc<events::EventBus>>,
     session: storage::UserIdFromSession,
+    origin: events::OriginSession,
     Json(meal_plan): Json<Vec<(String, i32)>>,
 ) -> api::EmptyResponse {
     use storage::{UserId, UserIdFromSession::FoundUserId};
     if let FoundUserId(UserId(id)) = session {
-        app_store
-            .save_meal_plan(id.as_str(), &meal_plan, chrono::Local::now().date_naive())
-            .await
-            .into()
+        let date = chrono::Local::now().date_naive();
+        events.publish_on_success(
+            &id,
+            &origin,
+            app_store
+                .save_meal_plan(id.as_str(), &meal_plan, date)
+                .await
+                .into(),
+            api::ChangeEvent::PlanChanged(date),
+        )
     } else {
         api::EmptyResponse::Unauthorized
     }
@@ -334,29 +879,153 @@ async fn api_inventory_for_date(
 ) -> api::InventoryResponse {
     use storage::{UserId, UserIdFromSession::FoundUserId};
     if let FoundUserId(UserId(id)) = session {
-        app_store
-            .fetch_inventory_for_date(id, date)
-            .await
-            .map(|d| {
-                let data: api::InventoryData = d.into();
-                data
-            })
-            .into()
+        inventory_for_date(&app_store, &id, date).await.into()
     } else {
         api::Response::Unauthorized
     }
 }
 
-async fn api_inventory(
-    Extension(app_store): Extension<Arc<storage::SqliteStore>>,
-    session: storage::UserIdFromSession,
-) -> api::Response<(Vec<IngredientKey>, Vec<(IngredientKey, String)>)> {
-    use storage::{UserId, UserIdFromSession::FoundUserId};
-    if let FoundUserId(UserId(id)) = session {
-        app_store
-            .fetch_latest_inventory_data(id)
-            .await
-            .map(|(filtered, modified, _)| (filtered, modified))
+/// Reconstruct the shopping list for the meal plan on a date from the stored
+/// plan, recipes, staples, categories, and inventory.
+async fn shopping_list_for_date(
+    app_store: &storage::SqliteStore,
+    user_id: &str,
+    date: NaiveDate,
+) -> storage::Result<Option<shopping_list::ShoppingListGroups>> {
+    let plan = match app_store.fetch_meal_plan_for_date(user_id, date).await? {
+        Some(plan) => plan,
+        None => return Ok(None),
+    };
+    // NOTE(jwall): Parsed recipes aren't Send so everything has to be fetched
+    // before we parse anything.
+    let entries = app_store
+        .get_recipes_for_user(user_id)
+        .await?
+        .unwrap_or_default();
+    let (_, use_staples) = app_store.get_settings_for_user(user_id).await?;
+    // NOTE(jwall): Staples are included unless the user turned them off.
+    let staples = match use_staples {
+        Some(false) => None,
+        _ => app_store.fetch_staples(user_id).await?,
+    };
+    let categories: BTreeMap<String, String> = app_store
+        .get_category_mappings_for_user(user_id)
+        .await?
+        .unwrap_or_default()
+        .into_iter()
+        .collect();
+    let measure_types: BTreeMap<String, MeasureType> = app_store
+        .get_measure_types_for_user(user_id)
+        .await?
+        .into_iter()
+        .collect();
+    let inventory: api::InventoryData = app_store
+        .fetch_inventory_for_date(user_id, date)
+        .await?
+        .into();
+    Ok(Some(build_shopping_list(
+        plan,
+        entries,
+        staples,
+        &categories,
+        &measure_types,
+        &inventory,
+    )))
+}
+
+/// Parse the fetched recipes and staples into the shopping list groups.
+fn build_shopping_list(
+    plan: Vec<(String, i32)>,
+    entries: Vec<RecipeEntry>,
+    staples: Option<String>,
+    categories: &BTreeMap<String, String>,
+    measure_types: &BTreeMap<String, MeasureType>,
+    inventory: &api::InventoryData,
+) -> shopping_list::ShoppingListGroups {
+    // NOTE(jwall): Planned recipes can use any of the user's recipes as a
+    // sub-recipe so we parse all of them.
+    let mut all_recipes = BTreeMap::new();
+    for entry in entries {
+        match recipes::parse::as_recipe(entry.recipe_text()) {
+            Ok(recipe) => {
+                all_recipes.insert(entry.recipe_id().to_owned(), recipe);
+            }
+            Err(err) => error!(
+                recipe_id = entry.recipe_id(),
+                ?err,
+                "Failed to parse recipe"
+            ),
+        }
+    }
+    let mut planned = Vec::new();
+    for (recipe_id, count) in plan {
+        if count <= 0 {
+            continue;
+        }
+        match all_recipes.get(&recipe_id) {
+            Some(recipe) => planned.push((recipe.clone(), count)),
+            None => error!(%recipe_id, "Planned recipe not found"),
+        }
+    }
+    let staples = match staples {
+        Some(content) => recipes::parse::as_ingredient_list(&content).unwrap_or_else(|err| {
+            error!(?err, "Failed to parse staples");
+            Vec::new()
+        }),
+        None => Vec::new(),
+    };
+    shopping_list::shopping_list_groups(
+        &planned,
+        &all_recipes,
+        &staples,
+        categories,
+        measure_types,
+        inventory,
+    )
+}
+
+#[instrument(skip_all, fields(date=%date))]
+async fn api_shopping_list_pdf(
+    Extension(app_store): Extension<Arc<storage::SqliteStore>>,
+    session: storage::UserIdFromSession,
+    Path(date): Path<NaiveDate>,
+) -> Response {
+    use storage::{UserId, UserIdFromSession::FoundUserId};
+    if let FoundUserId(UserId(id)) = session {
+        match shopping_list_for_date(&app_store, &id, date).await {
+            Ok(Some(groups)) => (
+                [
+                    (header::CONTENT_TYPE, "application/pdf".to_owned()),
+                    (
+                        header::CONTENT_DISPOSITION,
+                        format!("inline; filename=\"shopping_list_{}.pdf\"", date),
+                    ),
+                ],
+                shopping_list::render_shopping_list_pdf(&date, &groups),
+            )
+                .into_response(),
+            Ok(None) => api::EmptyResponse::NotFound.into_response(),
+            Err(err) => api::EmptyResponse::error(
+                StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                format!("{:?}", err),
+            )
+            .into_response(),
+        }
+    } else {
+        api::EmptyResponse::Unauthorized.into_response()
+    }
+}
+
+async fn api_inventory(
+    Extension(app_store): Extension<Arc<storage::SqliteStore>>,
+    session: storage::UserIdFromSession,
+) -> api::Response<(Vec<IngredientKey>, Vec<(IngredientKey, String)>)> {
+    use storage::{UserId, UserIdFromSession::FoundUserId};
+    if let FoundUserId(UserId(id)) = session {
+        app_store
+            .fetch_latest_inventory_data(id)
+            .await
+            .map(|(filtered, modified, _)| (filtered, modified))
             .into()
     } else {
         api::Response::Unauthorized
@@ -367,24 +1036,71 @@ async fn api_save_inventory_for_date(
     Extension(app_store): Extension<Arc<storage::SqliteStore>>,
     session: storage::UserIdFromSession,
     Path(date): Path<NaiveDate>,
-    Json((filtered_ingredients, modified_amts, extra_items)): Json<(
-        Vec<IngredientKey>,
-        Vec<(IngredientKey, String)>,
-        Vec<(String, String)>,
-    )>,
+    Json(inventory): Json<api::SaveInventoryData>,
 ) -> api::EmptyResponse {
     use storage::{UserId, UserIdFromSession::FoundUserId};
+    let api::SaveInventoryData(
+        filtered_ingredients,
+        modified_amts,
+        extra_items,
+        checked_items,
+        purchased_amts,
+    ) = inventory;
+    let extra_items = match api::text::check_extras(extra_items) {
+        Ok(extra_items) => extra_items,
+        Err(problems) => return rejected_text(problems),
+    };
+    let purchased_amts = match purchased_amts.map(api::text::check_purchased_amts) {
+        Some(Ok(purchased_amts)) => Some(purchased_amts),
+        Some(Err(problems)) => return rejected_text(problems),
+        None => None,
+    };
     if let FoundUserId(UserId(id)) = session {
         let filtered_ingredients = filtered_ingredients.into_iter().collect();
         let modified_amts = modified_amts.into_iter().collect();
-        app_store
+        let saved = app_store
             .save_inventory_data_for_date(
-                id,
+                &id,
                 &date,
                 filtered_ingredients,
                 modified_amts,
                 extra_items,
             )
+            .await;
+        let saved = match (saved, checked_items) {
+            (Ok(()), Some(checked_items)) => {
+                app_store
+                    .save_checked_items_for_date(&id, &date, checked_items.into_iter().collect())
+                    .await
+            }
+            (saved, _) => saved,
+        };
+        let saved = match (saved, purchased_amts) {
+            (Ok(()), Some(purchased_amts)) => {
+                app_store
+                    .save_purchased_amts_for_date(&id, &date, purchased_amts.into_iter().collect())
+                    .await
+            }
+            (saved, _) => saved,
+        };
+        saved.into()
+    } else {
+        api::EmptyResponse::Unauthorized
+    }
+}
+
+/// Save only the items checked off for a plan date. The shopping list saves
+/// them on every tap so they don't carry the rest of the inventory.
+async fn api_save_checked_items_for_date(
+    Extension(app_store): Extension<Arc<storage::SqliteStore>>,
+    session: storage::UserIdFromSession,
+    Path(date): Path<NaiveDate>,
+    Json(checked_items): Json<Vec<IngredientKey>>,
+) -> api::EmptyResponse {
+    use storage::{UserId, UserIdFromSession::FoundUserId};
+    if let FoundUserId(UserId(id)) = session {
+        app_store
+            .save_checked_items_for_date(&id, &date, checked_items.into_iter().collect())
             .await
             .into()
     } else {
@@ -392,6 +1108,14 @@ async fn api_save_inventory_for_date(
     }
 }
 
+/// The response for user text that was rejected listing every problem with it.
+fn rejected_text<T>(problems: Vec<String>) -> api::Response<T> {
+    api::Response::error(
+        StatusCode::UNPROCESSABLE_ENTITY.as_u16(),
+        problems.join("; "),
+    )
+}
+
 async fn save_inventory_data(
     app_store: Arc<storage::SqliteStore>,
     id: String,
@@ -415,6 +1139,10 @@ async fn api_save_inventory_v2(
     )>,
 ) -> api::EmptyResponse {
     use storage::{UserId, UserIdFromSession::FoundUserId};
+    let extra_items = match api::text::check_extras(extra_items) {
+        Ok(extra_items) => extra_items,
+        Err(problems) => return rejected_text(problems),
+    };
     if let FoundUserId(UserId(id)) = session {
         let filtered_ingredients = filtered_ingredients.into_iter().collect();
         let modified_amts = modified_amts.into_iter().collect();
@@ -467,6 +1195,46 @@ async fn api_user_account(session: storage::UserIdFromSession) -> api::AccountRe
     }
 }
 
+#[instrument(skip_all)]
+async fn api_change_password(
+    Extension(app_store): Extension<Arc<storage::SqliteStore>>,
+    session: storage::UserIdFromSession,
+    Json(change): Json<api::PasswordChange>,
+) -> api::EmptyResponse {
+    use storage::{UserCreds, UserId, UserIdFromSession::FoundUserId};
+    if let FoundUserId(UserId(id)) = session {
+        if change.new_password.is_empty() {
+            return api::EmptyResponse::error(
+                StatusCode::BAD_REQUEST.as_u16(),
+                "New password can not be empty",
+            );
+        }
+        let current_creds = UserCreds {
+            id: UserId(id.clone()),
+            pass: secrecy::Secret::from(change.current_password),
+        };
+        match app_store.check_user_creds(&current_creds).await {
+            Ok(true) => {
+                let new_creds = UserCreds {
+                    id: UserId(id),
+                    pass: secrecy::Secret::from(change.new_password),
+                };
+                app_store.update_user_creds(new_creds).await.into()
+            }
+            Ok(false) => {
+                info!("Current password did not match for password change");
+                api::EmptyResponse::Unauthorized
+            }
+            Err(err) => api::EmptyResponse::error(
+                StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                format!("{:?}", err),
+            ),
+        }
+    } else {
+        api::EmptyResponse::Unauthorized
+    }
+}
+
 async fn api_staples(
     Extension(app_store): Extension<Arc<storage::SqliteStore>>,
     session: storage::UserIdFromSession,
@@ -479,6 +1247,48 @@ async fn api_staples(
     }
 }
 
+async fn api_parsed_staples(
+    Extension(app_store): Extension<Arc<storage::SqliteStore>>,
+    session: storage::UserIdFromSession,
+) -> api::ParsedStaplesResponse {
+    use storage::{UserId, UserIdFromSession::FoundUserId};
+    if let FoundUserId(UserId(user_id)) = session {
+        let content = match app_store.fetch_staples(user_id.as_str()).await {
+            Ok(content) => content.unwrap_or_default(),
+            Err(err) => {
+                return api::Response::error(
+                    StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                    format!("{:?}", err),
+                )
+            }
+        };
+        let categories: BTreeMap<String, String> =
+            match app_store.get_category_mappings_for_user(&user_id).await {
+                Ok(mappings) => mappings.unwrap_or_default().into_iter().collect(),
+                Err(err) => {
+                    return api::Response::error(
+                        StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        format!("{:?}", err),
+                    )
+                }
+            };
+        match recipes::parse::as_ingredient_list_detailed(&content) {
+            Ok(staples) => api::Response::success(
+                staples
+                    .into_iter()
+                    .map(|ingredient| api::CategorizedIngredient {
+                        category: categories.get(&ingredient.name).cloned(),
+                        ingredient,
+                    })
+                    .collect(),
+            ),
+            Err(err) => api::Response::Invalid(err),
+        }
+    } else {
+        api::Response::Unauthorized
+    }
+}
+
 async fn api_save_staples(
     Extension(app_store): Extension<Arc<storage::SqliteStore>>,
     session: storage::UserIdFromSession,
@@ -492,6 +1302,204 @@ async fn api_save_staples(
     }
 }
 
+async fn api_templates(
+    Extension(app_store): Extension<Arc<storage::SqliteStore>>,
+    session: storage::UserIdFromSession,
+) -> api::Response<Vec<RecipeTemplate>> {
+    use storage::{UserId, UserIdFromSession::FoundUserId};
+    if let FoundUserId(UserId(id)) = session {
+        app_store.get_templates_for_user(&id).await.into()
+    } else {
+        api::Response::Unauthorized
+    }
+}
+
+async fn api_save_template(
+    Extension(app_store): Extension<Arc<storage::SqliteStore>>,
+    session: storage::UserIdFromSession,
+    Json(template): Json<RecipeTemplate>,
+) -> api::EmptyResponse {
+    use storage::{UserId, UserIdFromSession::FoundUserId};
+    if let FoundUserId(UserId(id)) = session {
+        if let Err(e) = template.validate() {
+            return api::EmptyResponse::error(StatusCode::BAD_REQUEST.as_u16(), e);
+        }
+        app_store
+            .save_template_for_user(&id, &template)
+            .await
+            .into()
+    } else {
+        api::EmptyResponse::Unauthorized
+    }
+}
+
+async fn api_plan_templates(
+    Extension(app_store): Extension<Arc<storage::SqliteStore>>,
+    session: storage::UserIdFromSession,
+) -> api::PlanTemplatesResponse {
+    use storage::{UserId, UserIdFromSession::FoundUserId};
+    if let FoundUserId(UserId(id)) = session {
+        app_store
+            .list_plan_templates(&id)
+            .await
+            .map(|templates| {
+                templates
+                    .into_iter()
+                    .map(|(name, recipe_counts)| api::PlanTemplate {
+                        name,
+                        recipe_counts,
+                    })
+                    .collect::<Vec<api::PlanTemplate>>()
+            })
+            .into()
+    } else {
+        api::Response::Unauthorized
+    }
+}
+
+async fn api_save_plan_template(
+    Extension(app_store): Extension<Arc<storage::SqliteStore>>,
+    session: storage::UserIdFromSession,
+    Json(template): Json<api::PlanTemplate>,
+) -> api::EmptyResponse {
+    use storage::{UserId, UserIdFromSession::FoundUserId};
+    if let FoundUserId(UserId(id)) = session {
+        if template.name.trim().is_empty() {
+            return api::EmptyResponse::error(
+                StatusCode::BAD_REQUEST.as_u16(),
+                "Plan template name can not be empty",
+            );
+        }
+        app_store
+            .save_plan_template(&id, template.name.trim(), &template.recipe_counts)
+            .await
+            .into()
+    } else {
+        api::EmptyResponse::Unauthorized
+    }
+}
+
+async fn api_apply_plan_template(
+    Extension(app_store): Extension<Arc<storage::SqliteStore>>,
+    Extension(events): Extension<Arc<events::EventBus>>,
+    session: storage::UserIdFromSession,
+    origin: events::OriginSession,
+    Path((name, date)): Path<(String, chrono::NaiveDate)>,
+) -> api::PlanDataResponse {
+    use storage::{UserId, UserIdFromSession::FoundUserId};
+    if let FoundUserId(UserId(id)) = session {
+        let resp = match app_store.apply_plan_template(&id, &name, date).await {
+            Ok(Some(recipe_counts)) => api::Response::Success(recipe_counts),
+            Ok(None) => api::Response::NotFound,
+            Err(e) => api::Response::error(500, format!("{:?}", e)),
+        };
+        events.publish_on_success(&id, &origin, resp, api::ChangeEvent::PlanChanged(date))
+    } else {
+        api::Response::Unauthorized
+    }
+}
+
+async fn api_settings(
+    Extension(app_store): Extension<Arc<storage::SqliteStore>>,
+    session: storage::UserIdFromSession,
+) -> api::UserSettingsResponse {
+    use storage::{UserId, UserIdFromSession::FoundUserId};
+    if let FoundUserId(UserId(id)) = session {
+        match app_store.get_settings_for_user(&id).await {
+            Ok((default_route, use_staples)) => api::Response::Success(api::UserSettings {
+                default_route,
+                use_staples,
+            }),
+            Err(e) => api::Response::error(500, format!("{:?}", e)),
+        }
+    } else {
+        api::Response::Unauthorized
+    }
+}
+
+async fn api_save_settings(
+    Extension(app_store): Extension<Arc<storage::SqliteStore>>,
+    session: storage::UserIdFromSession,
+    Json(settings): Json<api::UserSettings>,
+) -> api::EmptyResponse {
+    use storage::{UserId, UserIdFromSession::FoundUserId};
+    if let FoundUserId(UserId(id)) = session {
+        if !settings.is_valid() {
+            return api::EmptyResponse::error(
+                StatusCode::BAD_REQUEST.as_u16(),
+                "Default route must be one of the known ui routes",
+            );
+        }
+        app_store
+            .save_settings_for_user(&id, settings.default_route.as_deref(), settings.use_staples)
+            .await
+            .into()
+    } else {
+        api::EmptyResponse::Unauthorized
+    }
+}
+
+/// The route we land on when there is no session or the user hasn't chosen
+/// one.
+const DEFAULT_LANDING_ROUTE: &str = "/ui/home";
+
+/// The route to redirect `/` to for a session.
+async fn landing_route(
+    app_store: &storage::SqliteStore,
+    session: storage::UserIdFromSession,
+) -> String {
+    use storage::{UserId, UserIdFromSession::FoundUserId};
+    if let FoundUserId(UserId(id)) = session {
+        match app_store.get_settings_for_user(&id).await {
+            Ok((default_route, _)) => {
+                let settings = api::UserSettings {
+                    default_route,
+                    ..Default::default()
+                };
+                if let Some(route) = settings.landing_route() {
+                    return route.to_owned();
+                }
+            }
+            Err(err) => error!(?err, "Failed to fetch default route"),
+        }
+    }
+    DEFAULT_LANDING_ROUTE.to_owned()
+}
+
+#[instrument]
+async fn landing_redirect(
+    Extension(app_store): Extension<Arc<storage::SqliteStore>>,
+    session: storage::UserIdFromSession,
+) -> Redirect {
+    Redirect::temporary(&landing_route(app_store.as_ref(), session).await)
+}
+
+async fn api_template(
+    Extension(app_store): Extension<Arc<storage::SqliteStore>>,
+    session: storage::UserIdFromSession,
+    Path(name): Path<String>,
+) -> api::Response<Option<RecipeTemplate>> {
+    use storage::{UserId, UserIdFromSession::FoundUserId};
+    if let FoundUserId(UserId(id)) = session {
+        app_store.get_template_for_user(&id, &name).await.into()
+    } else {
+        api::Response::Unauthorized
+    }
+}
+
+async fn api_template_delete(
+    Extension(app_store): Extension<Arc<storage::SqliteStore>>,
+    session: storage::UserIdFromSession,
+    Path(name): Path<String>,
+) -> api::EmptyResponse {
+    use storage::{UserId, UserIdFromSession::FoundUserId};
+    if let FoundUserId(UserId(id)) = session {
+        app_store.delete_template_for_user(&id, &name).await.into()
+    } else {
+        api::EmptyResponse::Unauthorized
+    }
+}
+
 fn mk_v1_routes() -> Router {
     Router::new()
         .route("/recipes", get(api_recipes).post(api_save_recipes))
@@ -510,11 +1518,15 @@ fn mk_v1_routes() -> Router {
 fn mk_v2_routes() -> Router {
     Router::new()
         .route("/recipes", get(api_recipes).post(api_save_recipes))
+        .route("/recipes/search", get(api_search_recipes))
+        .route("/recipes/since/:since", get(api_recipe_changes))
+        .route("/recipe/validate", post(api_validate_recipe))
         // recipe entry api path route
         .route(
             "/recipe/:recipe_id",
             get(api_recipe_entry).delete(api_recipe_delete),
         )
+        .route("/recipe/:recipe_id/parsed", get(api_recipe_parsed))
         // mealplan api path routes
         .route("/plan", get(api_plan).post(api_save_plan))
         .route("/plan/since/:date", get(api_plan_since))
@@ -524,7 +1536,18 @@ fn mk_v2_routes() -> Router {
                 .post(api_save_plan_for_date)
                 .delete(api_delete_plan_for_date),
         )
+        .route("/plan/at/:date/full", get(api_full_plan_for_date))
         .route("/plan/all", get(api_all_plans))
+        .route("/plan/summary", get(api_plan_summary))
+        .route(
+            "/plan/templates",
+            get(api_plan_templates).post(api_save_plan_template),
+        )
+        .route(
+            "/plan/templates/:name/apply/:date",
+            post(api_apply_plan_template),
+        )
+        .route("/plan/calendar.ics", get(api_plan_calendar))
         .route(
             "/inventory",
             get(api_inventory_v2).post(api_save_inventory_v2),
@@ -533,20 +1556,70 @@ fn mk_v2_routes() -> Router {
             "/inventory/at/:date",
             get(api_inventory_for_date).post(api_save_inventory_for_date),
         )
+        .route(
+            "/inventory/at/:date/checked",
+            post(api_save_checked_items_for_date),
+        )
+        .route(
+            "/plan/at/:date/shopping_list.pdf",
+            get(api_shopping_list_pdf),
+        )
         // TODO(jwall): This is now deprecated but will still work
         .route("/categories", get(api_categories).post(api_save_categories))
         .route(
             "/category_map",
             get(api_category_mappings).post(api_save_category_mappings),
         )
+        .route(
+            "/measure_types",
+            get(api_measure_types).post(api_save_measure_types),
+        )
+        .route(
+            "/category_order",
+            get(api_category_order).post(api_save_category_order),
+        )
+        .route("/settings", get(api_settings).post(api_save_settings))
         .route("/staples", get(api_staples).post(api_save_staples))
+        .route("/staples/parsed", get(api_parsed_staples))
+        .route("/templates", get(api_templates).post(api_save_template))
+        .route(
+            "/template/:name",
+            get(api_template).delete(api_template_delete),
+        )
+        .route("/events", get(api_events))
         // All the routes above require a UserId.
-        .route("/auth", get(auth::handler).post(auth::handler))
+        .route(
+            "/auth",
+            get(auth::handler)
+                .post(auth::handler)
+                .delete(auth::logout_handler),
+        )
         .route("/account", get(api_user_account))
+        .route("/account/password", post(api_change_password))
+}
+
+/// How often we sweep the session store for expired sessions.
+const SESSION_SWEEP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+fn spawn_session_sweeper(app_store: Arc<storage::SqliteStore>) {
+    async_std::task::spawn(async move {
+        loop {
+            if let Err(err) = app_store.clear_expired_sessions().await {
+                error!(?err, "Failed to clear expired sessions");
+            }
+            async_std::task::sleep(SESSION_SWEEP_INTERVAL).await;
+        }
+    });
 }
 
-#[instrument(fields(recipe_dir=?recipe_dir_path), skip_all)]
-pub async fn make_router(recipe_dir_path: PathBuf, store_path: PathBuf) -> Router {
+#[instrument(fields(?store), skip_all)]
+pub async fn make_router(
+    store: Arc<dyn RecipeStore>,
+    store_path: PathBuf,
+    ui_config: UiConfig,
+    session_ttl: Duration,
+    allow_basic_api_auth: bool,
+) -> Router {
     let handle = metrics_exporter_prometheus::PrometheusBuilder::new()
         .install_recorder()
         .expect("Failed to install Prometheus Recorder");
@@ -554,21 +1627,23 @@ pub async fn make_router(recipe_dir_path: PathBuf, store_path: PathBuf) -> Route
     let collector = Collector::default();
     collector.describe();
     let metrics_trace_layer = metrics::make_layer(|b: &axum::body::Bytes| b.len() as u64);
-    let store = Arc::new(storage::file_store::AsyncFileStore::new(
-        recipe_dir_path.clone(),
-    ));
     let app_store = Arc::new(
         storage::SqliteStore::new(store_path)
             .await
-            .expect("Unable to create app_store"),
+            .expect("Unable to create app_store")
+            .with_session_ttl(session_ttl)
+            .with_session_cookie_name(ui_config.session_cookie_name.clone()),
     );
     app_store
         .run_migrations()
         .await
         .expect("Failed to run database migrations");
+    spawn_session_sweeper(app_store.clone());
     Router::new()
-        .route("/", get(|| async { Redirect::temporary("/ui/plan") }))
+        .route("/", get(landing_redirect))
         .route("/favicon.ico", get(|| async { StaticFile("favicon.ico") }))
+        .route("/manifest.webmanifest", get(ui_manifest))
+        .route("/icons/:icon", get(ui_icon))
         .route("/ui/*path", get(ui_static_assets))
         // TODO(jwall): We should use route_layer to enforce the authorization
         // requirements here.
@@ -594,19 +1669,34 @@ pub async fn make_router(recipe_dir_path: PathBuf, store_path: PathBuf) -> Route
                 .layer(TraceLayer::new_for_http())
                 .layer(metrics_trace_layer)
                 .layer(Extension(store))
-                .layer(Extension(app_store)),
+                .layer(Extension(app_store))
+                .layer(Extension(Arc::new(events::EventBus::new())))
+                .layer(Extension(Arc::new(auth::BasicAuthChecker::new(
+                    allow_basic_api_auth,
+                ))))
+                .layer(Extension(Arc::new(ui_config))),
         )
 }
 
-#[instrument(fields(recipe_dir=?recipe_dir_path,listen=?listen_socket), skip_all)]
+#[instrument(fields(store=?store,listen=?listen_socket), skip_all)]
 pub async fn ui_main_tls(
-    recipe_dir_path: PathBuf,
+    store: Arc<dyn RecipeStore>,
     store_path: PathBuf,
     listen_socket: SocketAddr,
     cert_path: &str,
     key_path: &str,
+    ui_config: UiConfig,
+    session_ttl: Duration,
+    allow_basic_api_auth: bool,
 ) {
-    let router = make_router(recipe_dir_path, store_path).await;
+    let router = make_router(
+        store,
+        store_path,
+        ui_config,
+        session_ttl,
+        allow_basic_api_auth,
+    )
+    .await;
     info!(
         http = format!("https://{}", listen_socket),
         "Starting server"
@@ -615,24 +1705,113 @@ pub async fn ui_main_tls(
         .await
         .expect("Failed to parse config from pem files");
     axum_server::bind_rustls(listen_socket, config)
-        .serve(router.into_make_service())
+        .serve(router.into_make_service_with_connect_info::<SocketAddr>())
         .await
         .expect("Failed to start tls service");
 }
 
-#[instrument(fields(recipe_dir=?recipe_dir_path,listen=?listen_socket), skip_all)]
-pub async fn ui_main(recipe_dir_path: PathBuf, store_path: PathBuf, listen_socket: SocketAddr) {
-    let router = make_router(recipe_dir_path, store_path).await;
+#[instrument(fields(store=?store,listen=?listen_socket), skip_all)]
+pub async fn ui_main(
+    store: Arc<dyn RecipeStore>,
+    store_path: PathBuf,
+    listen_socket: SocketAddr,
+    ui_config: UiConfig,
+    session_ttl: Duration,
+    allow_basic_api_auth: bool,
+) {
+    let router = make_router(
+        store,
+        store_path,
+        ui_config,
+        session_ttl,
+        allow_basic_api_auth,
+    )
+    .await;
     info!(
         http = format!("http://{}", listen_socket),
         "Starting server"
     );
     axum_server::bind(listen_socket)
-        .serve(router.into_make_service())
+        .serve(router.into_make_service_with_connect_info::<SocketAddr>())
         .await
         .expect("Failed to start service");
 }
 
+pub async fn get_templates(store_path: PathBuf, username: String) -> Vec<RecipeTemplate> {
+    let app_store = storage::SqliteStore::new(store_path)
+        .await
+        .expect("Unable to create app_store");
+    app_store
+        .get_templates_for_user(&username)
+        .await
+        .expect("Failed to fetch templates")
+}
+
+pub async fn get_template(
+    store_path: PathBuf,
+    username: String,
+    name: String,
+) -> Option<RecipeTemplate> {
+    let app_store = storage::SqliteStore::new(store_path)
+        .await
+        .expect("Unable to create app_store");
+    app_store
+        .get_template_for_user(&username, &name)
+        .await
+        .expect("Failed to fetch template")
+}
+
+pub async fn change_password(store_path: PathBuf, username: String, password: String) {
+    let app_store = storage::SqliteStore::new(store_path)
+        .await
+        .expect("Unable to create app_store");
+    let user_creds = storage::UserCreds {
+        id: storage::UserId(username.clone()),
+        pass: secrecy::Secret::from(password),
+    };
+    match app_store.update_user_creds(user_creds).await {
+        Ok(()) => (),
+        Err(storage::Error::NoRecords) => {
+            panic!("No such user {}. Add them with add_user first.", username)
+        }
+        Err(err) => panic!("Failed to update user creds: {:?}", err),
+    }
+}
+
+/// Import the recipe files in a directory or zip archive for a user.
+pub async fn import_recipes(
+    store_path: PathBuf,
+    username: String,
+    source: PathBuf,
+) -> import::ImportSummary {
+    let app_store = storage::SqliteStore::new(store_path)
+        .await
+        .expect("Unable to create app_store");
+    if !app_store
+        .user_exists(&username)
+        .await
+        .expect("Failed to check for existing user")
+    {
+        panic!("No such user {}. Add them with add_user first.", username);
+    }
+    let files = import::ImportFiles::from_path(&source).expect("Unable to read recipe files");
+    import::import_files(&app_store, &username, files)
+        .await
+        .expect("Failed to import recipes")
+}
+
+pub async fn revalidate_recipes(
+    store_path: PathBuf,
+    username: Option<String>,
+) -> revalidate::RevalidateReport {
+    let app_store = storage::SqliteStore::new(store_path)
+        .await
+        .expect("Unable to create app_store");
+    revalidate::revalidate_recipes(&app_store, username.as_deref())
+        .await
+        .expect("Failed to revalidate recipes")
+}
+
 pub async fn add_user(
     store_path: PathBuf,
     username: String,
@@ -642,6 +1821,10 @@ pub async fn add_user(
     let app_store = storage::SqliteStore::new(store_path)
         .await
         .expect("Unable to create app_store");
+    let user_exists = app_store
+        .user_exists(&username)
+        .await
+        .expect("Failed to check for existing user");
     let user_creds = storage::UserCreds {
         id: storage::UserId(username.clone()),
         pass: secrecy::Secret::from(password),
@@ -650,8 +1833,13 @@ pub async fn add_user(
         .store_user_creds(user_creds)
         .await
         .expect("Failed to store user creds");
+    if user_exists {
+        info!(user = username, "Updated existing user");
+    } else {
+        info!(user = username, "Created user");
+    }
     if let Some(path) = recipe_dir_path {
-        let store = storage::file_store::AsyncFileStore::new(path);
+        let store = AsyncFileStore::new(path);
         if let Some(recipes) = store
             .get_recipes()
             .await
@@ -675,3 +1863,1127 @@ pub async fn add_user(
         // TODO(jwall): Load all the recipes into our sqlite database
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use storage::{UserId, UserIdFromSession::FoundUserId};
+
+    const USER: &str = "alice";
+
+    fn event_bus() -> Extension<Arc<events::EventBus>> {
+        Extension(Arc::new(events::EventBus::new()))
+    }
+
+    fn session() -> storage::UserIdFromSession {
+        FoundUserId(UserId(USER.to_owned()))
+    }
+
+    async fn make_planned_store(date: NaiveDate) -> Arc<storage::SqliteStore> {
+        let app_store = storage::make_test_store().await;
+        app_store
+            .store_recipes_for_user(
+                USER,
+                &vec![
+                    RecipeEntry::new("soup", "title: Soup\n\nstep:\n\n1 cup broth\n\nHeat.\n"),
+                    RecipeEntry::new(
+                        "salad",
+                        "title: Salad\n\nstep:\n\n1 head lettuce\n\nToss.\n",
+                    ),
+                ],
+            )
+            .await
+            .expect("Failed to store recipes");
+        app_store
+            .save_meal_plan(
+                USER,
+                &vec![("soup".to_owned(), 2), ("salad".to_owned(), 1)],
+                date,
+            )
+            .await
+            .expect("Failed to save meal plan");
+        app_store
+    }
+
+    async fn fetch_plan(
+        app_store: &Arc<storage::SqliteStore>,
+        date: NaiveDate,
+    ) -> Vec<(String, i32)> {
+        match api_plan_for_date(Extension(app_store.clone()), session(), Path(date)).await {
+            api::Response::Success(plan) => plan,
+            resp => panic!("Expected a plan but got {:?}", resp),
+        }
+    }
+
+    #[test]
+    fn test_deleting_planned_recipe_is_a_conflict() {
+        async_std::task::block_on(async {
+            let date = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+            let app_store = make_planned_store(date).await;
+            let resp = api_recipe_delete(
+                Extension(app_store.clone()),
+                event_bus(),
+                session(),
+                events::OriginSession::default(),
+                Path("soup".to_owned()),
+                Query(DeleteRecipeParams { force: false }),
+            )
+            .await;
+            match resp {
+                api::Response::Err { status, message } => {
+                    assert_eq!(status, 409);
+                    assert!(message.contains("2026-10-18"), "{}", message);
+                }
+                resp => panic!("Expected a conflict but got {:?}", resp),
+            }
+            let mut plan = fetch_plan(&app_store, date).await;
+            plan.sort();
+            assert_eq!(plan, vec![("salad".to_owned(), 1), ("soup".to_owned(), 2)]);
+            assert!(app_store
+                .get_recipe_entry_for_user(USER, "soup")
+                .await
+                .expect("Failed to fetch recipe")
+                .is_some());
+        });
+    }
+
+    #[test]
+    fn test_force_deleting_planned_recipe_removes_it_from_plans() {
+        async_std::task::block_on(async {
+            let date = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+            let app_store = make_planned_store(date).await;
+            let resp = api_recipe_delete(
+                Extension(app_store.clone()),
+                event_bus(),
+                session(),
+                events::OriginSession::default(),
+                Path("soup".to_owned()),
+                Query(DeleteRecipeParams { force: true }),
+            )
+            .await;
+            assert!(matches!(resp, api::Response::Success(())), "{:?}", resp);
+            assert_eq!(
+                fetch_plan(&app_store, date).await,
+                vec![("salad".to_owned(), 1)]
+            );
+            assert!(app_store
+                .get_recipe_entry_for_user(USER, "soup")
+                .await
+                .expect("Failed to fetch recipe")
+                .is_none());
+        });
+    }
+
+    #[test]
+    fn test_deleting_unplanned_recipe() {
+        async_std::task::block_on(async {
+            let date = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+            let app_store = make_planned_store(date).await;
+            app_store
+                .store_recipes_for_user(USER, &vec![RecipeEntry::new("stew", "title: Stew\n")])
+                .await
+                .expect("Failed to store recipe");
+            let resp = api_recipe_delete(
+                Extension(app_store.clone()),
+                event_bus(),
+                session(),
+                events::OriginSession::default(),
+                Path("stew".to_owned()),
+                Query(DeleteRecipeParams { force: false }),
+            )
+            .await;
+            assert!(matches!(resp, api::Response::Success(())), "{:?}", resp);
+        });
+    }
+
+    #[test]
+    fn test_shopping_list_for_date_honors_inventory() {
+        async_std::task::block_on(async {
+            let date = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+            let app_store = storage::make_test_store().await;
+            app_store
+                .store_recipes_for_user(
+                    USER,
+                    &vec![RecipeEntry::new(
+                        "chili",
+                        "title: Chili\n\n\nstep:\n\n1 lb beef\n1 onion\n\nCook.\n",
+                    )],
+                )
+                .await
+                .expect("Failed to store recipe");
+            app_store
+                .save_meal_plan(USER, &vec![("chili".to_owned(), 2)], date)
+                .await
+                .expect("Failed to save meal plan");
+            app_store
+                .save_category_mappings_for_user(
+                    USER,
+                    &vec![("beef".to_owned(), "Meat".to_owned())],
+                )
+                .await
+                .expect("Failed to save categories");
+            let onion = IngredientKey::new("onion".to_owned(), None, "Count".to_owned());
+            app_store
+                .save_inventory_data_for_date(
+                    USER,
+                    &date,
+                    BTreeSet::from([onion]),
+                    BTreeMap::new(),
+                    vec![("2".to_owned(), "lemons".to_owned())],
+                )
+                .await
+                .expect("Failed to save inventory");
+            let groups = shopping_list_for_date(&app_store, USER, date)
+                .await
+                .expect("Failed to build shopping list")
+                .expect("Missing shopping list");
+            assert_eq!(
+                groups,
+                BTreeMap::from([
+                    (
+                        "Meat".to_owned(),
+                        vec![("2 lbs".to_owned(), "beef".to_owned())]
+                    ),
+                    (
+                        "Misc".to_owned(),
+                        vec![("2".to_owned(), "lemons".to_owned())]
+                    ),
+                ])
+            );
+            let missing = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+            assert!(shopping_list_for_date(&app_store, USER, missing)
+                .await
+                .expect("Failed to build shopping list")
+                .is_none());
+        });
+    }
+
+    #[test]
+    fn test_shopping_list_for_date_honors_use_staples() {
+        async_std::task::block_on(async {
+            let date = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+            let app_store = storage::make_test_store().await;
+            app_store
+                .store_recipes_for_user(
+                    USER,
+                    &vec![RecipeEntry::new(
+                        "soup",
+                        "title: Soup\n\n\nstep:\n\n1 onion\n\nSimmer.\n",
+                    )],
+                )
+                .await
+                .expect("Failed to store recipe");
+            app_store
+                .save_meal_plan(USER, &vec![("soup".to_owned(), 1)], date)
+                .await
+                .expect("Failed to save meal plan");
+            app_store
+                .save_staples(USER, "2 lemons\n")
+                .await
+                .expect("Failed to save staples");
+            let ingredient_names = |groups: shopping_list::ShoppingListGroups| {
+                groups
+                    .into_values()
+                    .flatten()
+                    .map(|(_, name)| name)
+                    .collect::<Vec<String>>()
+            };
+            let groups = shopping_list_for_date(&app_store, USER, date)
+                .await
+                .expect("Failed to build shopping list")
+                .expect("Missing shopping list");
+            assert_eq!(ingredient_names(groups), vec!["lemons", "onion"]);
+            app_store
+                .save_settings_for_user(USER, None, Some(false))
+                .await
+                .expect("Failed to save settings");
+            let groups = shopping_list_for_date(&app_store, USER, date)
+                .await
+                .expect("Failed to build shopping list")
+                .expect("Missing shopping list");
+            assert_eq!(ingredient_names(groups), vec!["onion"]);
+        });
+    }
+
+    #[test]
+    fn test_meal_plan_calendar_honors_since() {
+        async_std::task::block_on(async {
+            let first = NaiveDate::from_ymd_opt(2026, 10, 11).unwrap();
+            let second = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+            let app_store = make_planned_store(first).await;
+            app_store
+                .save_meal_plan(USER, &vec![("soup".to_owned(), 1)], second)
+                .await
+                .expect("Failed to save meal plan");
+            let ics = meal_plan_calendar(&app_store, USER, None)
+                .await
+                .expect("Failed to render calendar");
+            assert_eq!(ics.matches("BEGIN:VEVENT").count(), 3);
+            assert!(ics.contains("DTSTART;VALUE=DATE:20261011\r\n"));
+            let ics = meal_plan_calendar(&app_store, USER, Some(second))
+                .await
+                .expect("Failed to render calendar");
+            assert_eq!(ics.matches("BEGIN:VEVENT").count(), 1);
+            assert!(ics.contains("UID:20261018-soup@kitchen\r\n"));
+        });
+    }
+
+    #[test]
+    fn test_full_plan_matches_individual_responses() {
+        async_std::task::block_on(async {
+            let date = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+            let app_store = make_planned_store(date).await;
+            let onion = IngredientKey::new("onion".to_owned(), None, "Count".to_owned());
+            app_store
+                .save_inventory_data_for_date(
+                    USER,
+                    &date,
+                    BTreeSet::from([onion.clone()]),
+                    BTreeMap::from([(onion, "3".to_owned())]),
+                    vec![("2".to_owned(), "lemons".to_owned())],
+                )
+                .await
+                .expect("Failed to save inventory");
+            for date in [date, NaiveDate::from_ymd_opt(2026, 10, 19).unwrap()] {
+                let full = match api_full_plan_for_date(
+                    Extension(app_store.clone()),
+                    session(),
+                    Path(date),
+                )
+                .await
+                {
+                    api::Response::Success(full) => full,
+                    resp => panic!("Expected a full plan but got {:?}", resp),
+                };
+                assert_eq!(full.recipe_counts, fetch_plan(&app_store, date).await);
+                match api_inventory_for_date(Extension(app_store.clone()), session(), Path(date))
+                    .await
+                {
+                    api::Response::Success(inventory) => assert_eq!(full.inventory, inventory),
+                    resp => panic!("Expected inventory but got {:?}", resp),
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn test_plan_summary_covers_the_requested_dates() {
+        async_std::task::block_on(async {
+            let date = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+            let later = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+            let shopped = NaiveDate::from_ymd_opt(2026, 10, 12).unwrap();
+            let earlier = NaiveDate::from_ymd_opt(2026, 10, 1).unwrap();
+            let app_store = make_planned_store(date).await;
+            for day in [later, shopped, earlier] {
+                app_store
+                    .save_meal_plan(USER, &vec![("soup".to_owned(), 1)], day)
+                    .await
+                    .expect("Failed to save meal plan");
+            }
+            app_store
+                .save_inventory_data_for_date(
+                    USER,
+                    &date,
+                    BTreeSet::new(),
+                    BTreeMap::new(),
+                    vec![("2".to_owned(), "lemons".to_owned())],
+                )
+                .await
+                .expect("Failed to save inventory");
+            // Only buying something still counts as inventory for the plan.
+            let broth = IngredientKey::new("broth".to_owned(), None, "Volume".to_owned());
+            app_store
+                .save_purchased_amts_for_date(
+                    USER,
+                    &shopped,
+                    BTreeMap::from([(broth, "1 quart".to_owned())]),
+                )
+                .await
+                .expect("Failed to save purchased amounts");
+            let params = PlanSummaryParams {
+                start: NaiveDate::from_ymd_opt(2026, 10, 10).unwrap(),
+                end: later,
+            };
+            match api_plan_summary(Extension(app_store.clone()), session(), Query(params)).await {
+                api::Response::Success(summaries) => assert_eq!(
+                    summaries,
+                    vec![
+                        api::PlanSummary {
+                            date: later,
+                            recipe_count: 1,
+                            servings: 1,
+                            has_inventory: false,
+                        },
+                        api::PlanSummary {
+                            date,
+                            recipe_count: 2,
+                            servings: 3,
+                            has_inventory: true,
+                        },
+                        api::PlanSummary {
+                            date: shopped,
+                            recipe_count: 1,
+                            servings: 1,
+                            has_inventory: true,
+                        },
+                    ]
+                ),
+                resp => panic!("Expected plan summaries but got {:?}", resp),
+            }
+        });
+    }
+
+    #[test]
+    fn test_saving_a_plan_publishes_a_change_event() {
+        async_std::task::block_on(async {
+            let date = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+            let app_store = make_planned_store(date).await;
+            let events = Arc::new(events::EventBus::new());
+            let phone = events::OriginSession(Some("phone".to_owned()));
+            let alice = events.subscribe(USER, events::OriginSession(Some("laptop".to_owned())));
+            let on_phone = events.subscribe(USER, phone.clone());
+            let bob = events.subscribe("bob", events::OriginSession::default());
+            let resp = api_save_plan_for_date(
+                Extension(app_store.clone()),
+                Extension(events.clone()),
+                session(),
+                phone.clone(),
+                Path(date),
+                Json(vec![("soup".to_owned(), 1)]),
+            )
+            .await;
+            assert!(matches!(resp, api::Response::Success(_)), "{:?}", resp);
+            let resp = api_save_recipes(
+                Extension(app_store.clone()),
+                Extension(events.clone()),
+                session(),
+                phone.clone(),
+                Json(vec![RecipeEntry::new(
+                    "stew",
+                    "title: Stew\n\nstep:\n\n1 cup broth\n\nSimmer.\n",
+                )]),
+            )
+            .await;
+            assert!(matches!(resp, api::Response::Success(_)), "{:?}", resp);
+            assert_eq!(alice.recv().await, Ok(api::ChangeEvent::PlanChanged(date)));
+            assert_eq!(alice.recv().await, Ok(api::ChangeEvent::RecipesChanged));
+            assert!(on_phone.try_recv().is_err());
+            assert!(bob.try_recv().is_err());
+        });
+    }
+
+    #[test]
+    fn test_save_inventory_rejects_bad_extras() {
+        async_std::task::block_on(async {
+            let date = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+            let app_store = make_planned_store(date).await;
+            let resp = api_save_inventory_for_date(
+                Extension(app_store.clone()),
+                session(),
+                Path(date),
+                Json(api::SaveInventoryData(
+                    Vec::new(),
+                    Vec::new(),
+                    vec![
+                        ("2".to_owned(), "lemons".to_owned()),
+                        ("1".to_owned(), " \u{7} ".to_owned()),
+                        ("1".to_owned(), "x".repeat(10_000)),
+                    ],
+                    None,
+                    None,
+                )),
+            )
+            .await;
+            match resp {
+                api::Response::Err { status, message } => {
+                    assert_eq!(status, 422);
+                    assert_eq!(
+                        message,
+                        "extra item 2: name is empty; \
+                        extra item 3: name is 10000 characters long, the limit is 256"
+                    );
+                }
+                resp => panic!("Expected the extras to be rejected but got {:?}", resp),
+            }
+            let resp = api_save_inventory_for_date(
+                Extension(app_store.clone()),
+                session(),
+                Path(date),
+                Json(api::SaveInventoryData(
+                    Vec::new(),
+                    Vec::new(),
+                    vec![("2\r\n".to_owned(), " lemons 🍋".to_owned())],
+                    None,
+                    None,
+                )),
+            )
+            .await;
+            assert!(matches!(resp, api::Response::Success(())));
+            match api_inventory_for_date(Extension(app_store.clone()), session(), Path(date)).await
+            {
+                api::Response::Success(inventory) => assert_eq!(
+                    inventory.extra_items,
+                    vec![("2".to_owned(), "lemons 🍋".to_owned())]
+                ),
+                resp => panic!("Expected inventory but got {:?}", resp),
+            }
+        });
+    }
+
+    #[test]
+    fn test_checked_items_are_saved_per_plan_date() {
+        async_std::task::block_on(async {
+            let date = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+            let other_date = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+            let app_store = make_planned_store(date).await;
+            let onion = IngredientKey::new("onion".to_owned(), None, "Count".to_owned());
+            let save = |checked_items| {
+                api_save_inventory_for_date(
+                    Extension(app_store.clone()),
+                    session(),
+                    Path(date),
+                    Json(api::SaveInventoryData(
+                        Vec::new(),
+                        Vec::new(),
+                        Vec::new(),
+                        checked_items,
+                        None,
+                    )),
+                )
+            };
+            let checked = |date| {
+                let app_store = app_store.clone();
+                async move {
+                    match api_inventory_for_date(Extension(app_store), session(), Path(date)).await
+                    {
+                        api::Response::Success(inventory) => inventory.checked_items,
+                        resp => panic!("Expected inventory but got {:?}", resp),
+                    }
+                }
+            };
+            let resp = save(Some(vec![onion.clone(), onion.clone()])).await;
+            assert!(matches!(resp, api::Response::Success(())));
+            assert_eq!(checked(date).await, vec![onion.clone()]);
+            assert!(checked(other_date).await.is_empty());
+            // Clients that don't send checked items leave them alone.
+            let resp = save(None).await;
+            assert!(matches!(resp, api::Response::Success(())));
+            assert_eq!(checked(date).await, vec![onion.clone()]);
+            match api_full_plan_for_date(Extension(app_store.clone()), session(), Path(date)).await
+            {
+                api::Response::Success(full) => {
+                    assert_eq!(full.inventory.checked_items, vec![onion.clone()])
+                }
+                resp => panic!("Expected a plan but got {:?}", resp),
+            }
+            let resp = save(Some(Vec::new())).await;
+            assert!(matches!(resp, api::Response::Success(())));
+            assert!(checked(date).await.is_empty());
+        });
+    }
+
+    #[test]
+    fn test_save_checked_items_leaves_the_inventory_alone() {
+        async_std::task::block_on(async {
+            let date = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+            let app_store = make_planned_store(date).await;
+            let onion = IngredientKey::new("onion".to_owned(), None, "Count".to_owned());
+            let resp = api_save_inventory_for_date(
+                Extension(app_store.clone()),
+                session(),
+                Path(date),
+                Json(api::SaveInventoryData(
+                    vec![onion.clone()],
+                    Vec::new(),
+                    vec![("2".to_owned(), "lemons".to_owned())],
+                    None,
+                    None,
+                )),
+            )
+            .await;
+            assert!(matches!(resp, api::Response::Success(())));
+            let resp = api_save_checked_items_for_date(
+                Extension(app_store.clone()),
+                session(),
+                Path(date),
+                Json(vec![onion.clone()]),
+            )
+            .await;
+            assert!(matches!(resp, api::Response::Success(())));
+            match api_inventory_for_date(Extension(app_store.clone()), session(), Path(date)).await
+            {
+                api::Response::Success(inventory) => {
+                    assert_eq!(inventory.checked_items, vec![onion.clone()]);
+                    assert_eq!(inventory.filtered_ingredients, vec![onion.clone()]);
+                    assert_eq!(
+                        inventory.extra_items,
+                        vec![("2".to_owned(), "lemons".to_owned())]
+                    );
+                }
+                resp => panic!("Expected inventory but got {:?}", resp),
+            }
+        });
+    }
+
+    #[test]
+    fn test_purchase_hints_come_from_the_latest_earlier_plan() {
+        async_std::task::block_on(async {
+            let first = NaiveDate::from_ymd_opt(2026, 10, 4).unwrap();
+            let second = NaiveDate::from_ymd_opt(2026, 10, 11).unwrap();
+            let later = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+            let app_store = make_planned_store(later).await;
+            let cream = IngredientKey::new("heavy cream".to_owned(), None, "Volume".to_owned());
+            let onion = IngredientKey::new("onion".to_owned(), None, "Count".to_owned());
+            let save = |date, purchased_amts| {
+                api_save_inventory_for_date(
+                    Extension(app_store.clone()),
+                    session(),
+                    Path(date),
+                    Json(api::SaveInventoryData(
+                        Vec::new(),
+                        Vec::new(),
+                        Vec::new(),
+                        None,
+                        purchased_amts,
+                    )),
+                )
+            };
+            let inventory = |date| {
+                let app_store = app_store.clone();
+                async move {
+                    match api_inventory_for_date(Extension(app_store), session(), Path(date)).await
+                    {
+                        api::Response::Success(inventory) => inventory,
+                        resp => panic!("Expected inventory but got {:?}", resp),
+                    }
+                }
+            };
+            let resp = save(
+                first,
+                Some(vec![
+                    (cream.clone(), "1 pint".to_owned()),
+                    (onion.clone(), "1 bag".to_owned()),
+                ]),
+            )
+            .await;
+            assert!(matches!(resp, api::Response::Success(())));
+            let resp = save(
+                second,
+                Some(vec![
+                    (cream.clone(), " 1 quart ".to_owned()),
+                    (onion.clone(), "".to_owned()),
+                ]),
+            )
+            .await;
+            assert!(matches!(resp, api::Response::Success(())));
+
+            let second_inventory = inventory(second).await;
+            assert_eq!(
+                second_inventory.purchased_amts,
+                vec![(cream.clone(), "1 quart".to_owned())]
+            );
+            let later_inventory = inventory(later).await;
+            assert!(later_inventory.purchased_amts.is_empty());
+            assert_eq!(
+                later_inventory.purchase_hints,
+                vec![
+                    (cream.clone(), "1 quart".to_owned()),
+                    (onion.clone(), "1 bag".to_owned()),
+                ]
+            );
+            // Hints only come from earlier plans.
+            assert!(inventory(first).await.purchase_hints.is_empty());
+
+            // Clients that don't send purchased amounts leave them alone.
+            let resp = save(second, None).await;
+            assert!(matches!(resp, api::Response::Success(())));
+            assert_eq!(inventory(second).await.purchased_amts.len(), 1);
+            let resp = save(second, Some(vec![(cream.clone(), "x".repeat(1000))])).await;
+            assert!(matches!(resp, api::Response::Err { .. }), "{:?}", resp);
+        });
+    }
+
+    #[test]
+    fn test_plan_templates_apply_to_any_date() {
+        async_std::task::block_on(async {
+            let date = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+            let app_store = make_planned_store(date).await;
+            let resp = api_save_plan_template(
+                Extension(app_store.clone()),
+                session(),
+                Json(api::PlanTemplate {
+                    name: "weeknight".to_owned(),
+                    recipe_counts: vec![("salad".to_owned(), 0), ("soup".to_owned(), 3)],
+                }),
+            )
+            .await;
+            assert!(matches!(resp, api::Response::Success(())), "{:?}", resp);
+            match api_plan_templates(Extension(app_store.clone()), session()).await {
+                api::Response::Success(templates) => assert_eq!(
+                    templates,
+                    vec![api::PlanTemplate {
+                        name: "weeknight".to_owned(),
+                        recipe_counts: vec![("soup".to_owned(), 3)],
+                    }]
+                ),
+                resp => panic!("Expected plan templates but got {:?}", resp),
+            }
+            let next_week = NaiveDate::from_ymd_opt(2026, 10, 25).unwrap();
+            for date in [date, next_week] {
+                let resp = api_apply_plan_template(
+                    Extension(app_store.clone()),
+                    event_bus(),
+                    session(),
+                    events::OriginSession::default(),
+                    Path(("weeknight".to_owned(), date)),
+                )
+                .await;
+                assert!(matches!(resp, api::Response::Success(_)), "{:?}", resp);
+                assert_eq!(
+                    fetch_plan(&app_store, date).await,
+                    vec![("soup".to_owned(), 3)]
+                );
+            }
+            let resp = api_apply_plan_template(
+                Extension(app_store.clone()),
+                event_bus(),
+                session(),
+                events::OriginSession::default(),
+                Path(("missing".to_owned(), date)),
+            )
+            .await;
+            assert!(matches!(resp, api::Response::NotFound), "{:?}", resp);
+        });
+    }
+
+    #[test]
+    fn test_measure_types_replace_existing_overrides() {
+        async_std::task::block_on(async {
+            let app_store = storage::make_test_store().await;
+            for measure_types in [
+                vec![
+                    ("garlic".to_owned(), MeasureType::Count),
+                    ("flour".to_owned(), MeasureType::Weight),
+                ],
+                vec![("garlic".to_owned(), MeasureType::Volume)],
+            ] {
+                let resp = api_save_measure_types(
+                    Extension(app_store.clone()),
+                    session(),
+                    Json(measure_types),
+                )
+                .await;
+                assert!(matches!(resp, api::Response::Success(())), "{:?}", resp);
+            }
+            match api_measure_types(Extension(app_store.clone()), session()).await {
+                api::Response::Success(measure_types) => assert_eq!(
+                    measure_types,
+                    vec![("garlic".to_owned(), MeasureType::Volume)]
+                ),
+                resp => panic!("Expected measure types but got {:?}", resp),
+            }
+        });
+    }
+
+    #[test]
+    fn test_category_order_replaces_existing_order() {
+        async_std::task::block_on(async {
+            let app_store = storage::make_test_store().await;
+            match api_category_order(Extension(app_store.clone()), session()).await {
+                api::Response::Success(order) => assert!(order.is_empty()),
+                resp => panic!("Expected an empty order but got {:?}", resp),
+            }
+            for order in [
+                vec![
+                    "Dairy".to_owned(),
+                    "Produce".to_owned(),
+                    "Bakery".to_owned(),
+                ],
+                vec!["Produce".to_owned(), "Dairy".to_owned()],
+            ] {
+                let resp =
+                    api_save_category_order(Extension(app_store.clone()), session(), Json(order))
+                        .await;
+                assert!(matches!(resp, api::Response::Success(())), "{:?}", resp);
+            }
+            match api_category_order(Extension(app_store.clone()), session()).await {
+                api::Response::Success(order) => {
+                    assert_eq!(order, vec!["Produce".to_owned(), "Dairy".to_owned()])
+                }
+                resp => panic!("Expected a category order but got {:?}", resp),
+            }
+        });
+    }
+
+    #[test]
+    fn test_landing_route_honors_default_route() {
+        async_std::task::block_on(async {
+            let app_store = storage::make_test_store().await;
+            assert_eq!(
+                landing_route(&app_store, session()).await,
+                DEFAULT_LANDING_ROUTE
+            );
+            let resp = api_save_settings(
+                Extension(app_store.clone()),
+                session(),
+                Json(api::UserSettings {
+                    default_route: Some("/ui/planning/inventory".to_owned()),
+                    ..Default::default()
+                }),
+            )
+            .await;
+            assert!(matches!(resp, api::Response::Success(())), "{:?}", resp);
+            assert_eq!(
+                landing_route(&app_store, session()).await,
+                "/ui/planning/inventory"
+            );
+            assert_eq!(
+                landing_route(&app_store, storage::UserIdFromSession::NoUserId).await,
+                DEFAULT_LANDING_ROUTE
+            );
+            match api_settings(Extension(app_store.clone()), session()).await {
+                api::Response::Success(settings) => assert_eq!(
+                    settings.default_route,
+                    Some("/ui/planning/inventory".to_owned())
+                ),
+                resp => panic!("Expected settings but got {:?}", resp),
+            }
+        });
+    }
+
+    #[test]
+    fn test_save_settings_rejects_unknown_routes() {
+        async_std::task::block_on(async {
+            let app_store = storage::make_test_store().await;
+            let resp = api_save_settings(
+                Extension(app_store.clone()),
+                session(),
+                Json(api::UserSettings {
+                    default_route: Some("https://example.com/".to_owned()),
+                    ..Default::default()
+                }),
+            )
+            .await;
+            match resp {
+                api::Response::Err { status, .. } => assert_eq!(status, 400),
+                resp => panic!("Expected an error but got {:?}", resp),
+            }
+            assert_eq!(
+                landing_route(&app_store, session()).await,
+                DEFAULT_LANDING_ROUTE
+            );
+        });
+    }
+
+    #[test]
+    fn test_validate_recipe_reports_position() {
+        async_std::task::block_on(async {
+            let resp = api_validate_recipe(
+                session(),
+                Json("title: Soup\n\nstep:\n\n1 cup broth\n\nHeat.\n".to_owned()),
+            )
+            .await;
+            assert!(matches!(resp, api::Response::Success(())), "{:?}", resp);
+            let resp =
+                api_validate_recipe(session(), Json("title: Soup\n\nA soup.\n".to_owned())).await;
+            match resp {
+                api::Response::Invalid(err) => {
+                    assert_eq!(err.message, "Missing recipe steps");
+                    assert_eq!(err.line, 4);
+                }
+                resp => panic!("Expected a parse error but got {:?}", resp),
+            }
+            let resp = api_validate_recipe(
+                storage::UserIdFromSession::NoUserId,
+                Json("title: Soup".to_owned()),
+            )
+            .await;
+            assert!(matches!(resp, api::Response::Unauthorized), "{:?}", resp);
+        });
+    }
+
+    #[test]
+    fn test_recipe_entry_from_files_is_not_found_when_missing() {
+        async_std::task::block_on(async {
+            let dir = storage::TempDir::new();
+            let store: Arc<dyn RecipeStore> = Arc::new(AsyncFileStore::new(dir.path()));
+            store
+                .store_recipe_entry(&RecipeEntry::new("soup.txt", "title: Soup"))
+                .await
+                .expect("Failed to store recipe");
+            let app_store = storage::make_test_store().await;
+            let resp = api_recipe_entry(
+                Extension(store.clone()),
+                Extension(app_store.clone()),
+                storage::UserIdFromSession::NoUserId,
+                Path("soup.txt".to_owned()),
+            )
+            .await;
+            assert!(
+                matches!(resp, api::Response::Success(Some(ref e)) if e.recipe_id() == "soup.txt"),
+                "{:?}",
+                resp
+            );
+            let resp = api_recipe_entry(
+                Extension(store.clone()),
+                Extension(app_store.clone()),
+                storage::UserIdFromSession::NoUserId,
+                Path("stew.txt".to_owned()),
+            )
+            .await;
+            assert!(matches!(resp, api::Response::NotFound), "{:?}", resp);
+            let resp = api_categories(
+                Extension(store),
+                Extension(app_store),
+                storage::UserIdFromSession::NoUserId,
+            )
+            .await;
+            assert!(matches!(resp, api::Response::NotFound), "{:?}", resp);
+        });
+    }
+
+    #[test]
+    fn test_recipe_parsed_returns_recipe_or_parse_error() {
+        async_std::task::block_on(async {
+            let dir = storage::TempDir::new();
+            let store: Arc<dyn RecipeStore> = Arc::new(AsyncFileStore::new(dir.path()));
+            let app_store = storage::make_test_store().await;
+            app_store
+                .store_recipes_for_user(
+                    USER,
+                    &vec![
+                        RecipeEntry::new("soup", storage::TEST_RECIPE),
+                        RecipeEntry::new("broken", "not a recipe"),
+                    ],
+                )
+                .await
+                .expect("Failed to store recipes");
+            let parsed = |id: &str| {
+                api_recipe_parsed(
+                    Extension(store.clone()),
+                    Extension(app_store.clone()),
+                    session(),
+                    Path(id.to_owned()),
+                )
+            };
+            match parsed("soup").await {
+                api::Response::Success(recipe) => {
+                    assert_eq!(recipe.title, "Soup");
+                    assert_eq!(recipe.steps.len(), 1);
+                    assert_eq!(recipe.steps[0].ingredients[0].name, "broth");
+                }
+                resp => panic!("Expected a recipe but got {:?}", resp),
+            }
+            let resp = parsed("broken").await;
+            assert!(matches!(resp, api::Response::Invalid(_)), "{:?}", resp);
+            assert_eq!(
+                resp.into_response().status(),
+                StatusCode::UNPROCESSABLE_ENTITY
+            );
+            let resp = parsed("stew").await;
+            assert!(matches!(resp, api::Response::NotFound), "{:?}", resp);
+        });
+    }
+
+    #[test]
+    fn test_recipe_changes_only_sends_recipes_stored_since_the_cursor() {
+        async_std::task::block_on(async {
+            let dir = storage::TempDir::new();
+            let store: Arc<dyn RecipeStore> = Arc::new(AsyncFileStore::new(dir.path()));
+            let app_store =
+                make_planned_store(NaiveDate::from_ymd_opt(2026, 10, 18).unwrap()).await;
+            let changes = |since: i64| {
+                api_recipe_changes(
+                    Extension(store.clone()),
+                    Extension(app_store.clone()),
+                    session(),
+                    Path(since),
+                )
+            };
+            let ids = |entries: &Vec<RecipeEntry>| {
+                entries
+                    .iter()
+                    .map(|e| e.recipe_id().to_owned())
+                    .collect::<BTreeSet<String>>()
+            };
+            app_store
+                .store_recipes_for_user(USER, &vec![RecipeEntry::new("stew", "title: Stew")])
+                .await
+                .expect("Failed to store recipes");
+            // NOTE(jwall): Recipes stored in the same millisecond as the
+            // cursor are sent again so we wait for the clock to move on.
+            async_std::task::sleep(Duration::from_millis(5)).await;
+            let first = match changes(0).await {
+                api::Response::Success(changes) => changes,
+                resp => panic!("Expected recipe changes but got {:?}", resp),
+            };
+            assert_eq!(first.ids.len(), 3);
+            assert_eq!(ids(&first.changed).len(), 3);
+            app_store
+                .store_recipes_for_user(
+                    USER,
+                    &vec![RecipeEntry::new(
+                        "soup",
+                        "title: Soup\n\nstep:\n\n2 cup broth\n\nHeat.\n",
+                    )],
+                )
+                .await
+                .expect("Failed to store recipes");
+            app_store
+                .delete_recipes_for_user(USER, &vec!["salad".to_owned()])
+                .await
+                .expect("Failed to delete recipe");
+            let second = match changes(first.as_of).await {
+                api::Response::Success(changes) => changes,
+                resp => panic!("Expected recipe changes but got {:?}", resp),
+            };
+            assert_eq!(
+                second.ids.into_iter().collect::<BTreeSet<String>>(),
+                BTreeSet::from(["soup".to_owned(), "stew".to_owned()])
+            );
+            assert_eq!(ids(&second.changed), BTreeSet::from(["soup".to_owned()]));
+            assert!(second.changed[0].recipe_text().contains("2 cup broth"));
+        });
+    }
+
+    #[test]
+    fn test_update_user_creds_only_updates_existing_users() {
+        async_std::task::block_on(async {
+            let app_store = storage::make_test_store().await;
+            let creds = |id: &str, pass: &str| storage::UserCreds {
+                id: UserId(id.to_owned()),
+                pass: secrecy::Secret::from(pass.to_owned()),
+            };
+            assert!(matches!(
+                app_store.update_user_creds(creds("bob", "secret")).await,
+                Err(storage::Error::NoRecords)
+            ));
+            assert!(!app_store
+                .user_exists("bob")
+                .await
+                .expect("Failed to check user"));
+            app_store
+                .store_user_creds(creds(USER, "old"))
+                .await
+                .expect("Failed to store creds");
+            app_store
+                .update_user_creds(creds(USER, "new"))
+                .await
+                .expect("Failed to update creds");
+            assert!(app_store
+                .check_user_creds(&creds(USER, "new"))
+                .await
+                .expect("Failed to check creds"));
+            assert!(!app_store
+                .check_user_creds(&creds(USER, "old"))
+                .await
+                .expect("Failed to check creds"));
+        });
+    }
+
+    #[test]
+    fn test_templates_are_stored_per_user() {
+        async_std::task::block_on(async {
+            let app_store = storage::make_test_store().await;
+            let bread = RecipeTemplate::new(
+                "bread",
+                "title: {{name}}\n\nstep:\n\n1 cup flour\n\nKnead.\n",
+            );
+            app_store
+                .save_template_for_user(USER, &bread)
+                .await
+                .expect("Failed to save template");
+            let mut updated = bread.clone();
+            updated.text = updated.text.replace("Knead.", "Knead well.");
+            app_store
+                .save_template_for_user(USER, &updated)
+                .await
+                .expect("Failed to save template");
+            assert_eq!(
+                app_store
+                    .get_templates_for_user(USER)
+                    .await
+                    .expect("Failed to fetch templates"),
+                vec![updated.clone()]
+            );
+            assert_eq!(
+                app_store
+                    .get_template_for_user(USER, "bread")
+                    .await
+                    .expect("Failed to fetch template"),
+                Some(updated)
+            );
+            assert!(app_store
+                .get_templates_for_user("bob")
+                .await
+                .expect("Failed to fetch templates")
+                .is_empty());
+            app_store
+                .delete_template_for_user(USER, "bread")
+                .await
+                .expect("Failed to delete template");
+            assert_eq!(
+                app_store
+                    .get_template_for_user(USER, "bread")
+                    .await
+                    .expect("Failed to fetch template"),
+                None
+            );
+        });
+    }
+
+    #[test]
+    fn test_template_routes_decode_names() {
+        use axum::{body::Body, headers::HeaderMapExt, http::Request};
+        use tower::ServiceExt;
+        async_std::task::block_on(async {
+            let app_store = storage::make_test_store().await;
+            app_store
+                .store_user_creds(storage::UserCreds {
+                    id: UserId(USER.to_owned()),
+                    pass: secrecy::Secret::from("secret".to_owned()),
+                })
+                .await
+                .expect("Failed to store user creds");
+            let router = mk_v2_routes()
+                .layer(Extension(app_store.clone()))
+                .layer(Extension(Arc::new(auth::BasicAuthChecker::new(true))));
+            let send = |method: &str, uri: &str, body: Body| {
+                let mut req = Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .header(axum::http::header::CONTENT_TYPE, "application/json")
+                    .body(body)
+                    .unwrap();
+                req.headers_mut()
+                    .typed_insert(axum::headers::Authorization::basic(USER, "secret"));
+                router.clone().oneshot(req)
+            };
+            let template = RecipeTemplate::new(
+                "rye/wheat? #1",
+                "title: {{name}}\n\nstep:\n\n1 cup flour\n\nKnead.\n",
+            );
+            let body = Body::from(serde_json::to_string(&template).unwrap());
+            let resp = send("POST", "/templates", body).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::OK);
+            assert_eq!(
+                app_store
+                    .get_templates_for_user(USER)
+                    .await
+                    .expect("Failed to fetch templates"),
+                vec![template]
+            );
+            // NOTE(jwall): Deleting only works if the name in the path was
+            // decoded back to the stored name.
+            let resp = send("DELETE", "/template/rye%2Fwheat%3F%20%231", Body::empty())
+                .await
+                .unwrap();
+            assert_eq!(resp.status(), StatusCode::OK);
+            assert!(app_store
+                .get_templates_for_user(USER)
+                .await
+                .expect("Failed to fetch templates")
+                .is_empty());
+        });
+    }
+}
@@ -0,0 +1,306 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! A minimal PDF writer for printable text documents. It only knows how to lay
+//! out lines of text in the standard Helvetica fonts which is all we need for
+//! things like shopping lists.
+
+// US Letter in points.
+const PAGE_WIDTH: usize = 612;
+const PAGE_HEIGHT: usize = 792;
+const MARGIN: usize = 54;
+const LINE_HEIGHT: usize = 16;
+const LINES_PER_PAGE: usize = (PAGE_HEIGHT - 2 * MARGIN) / LINE_HEIGHT;
+// NOTE(jwall): We don't have font metrics so this is a conservative guess at
+// how many characters of body text fit across the page.
+const MAX_LINE_CHARS: usize = 85;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Line {
+    Title(String),
+    Heading(String),
+    Text(String),
+    Blank,
+}
+
+impl Line {
+    fn font(&self) -> (&'static str, usize) {
+        match self {
+            Line::Title(_) => ("F2", 16),
+            Line::Heading(_) => ("F2", 13),
+            Line::Text(_) | Line::Blank => ("F1", 11),
+        }
+    }
+
+    fn text(&self) -> &str {
+        match self {
+            Line::Title(t) | Line::Heading(t) | Line::Text(t) => t,
+            Line::Blank => "",
+        }
+    }
+}
+
+/// A document made up of lines of text that renders to a PDF.
+#[derive(Debug, Default)]
+pub struct TextDocument {
+    lines: Vec<Line>,
+}
+
+impl TextDocument {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn title<S: Into<String>>(&mut self, title: S) -> &mut Self {
+        self.lines.push(Line::Title(title.into()));
+        self
+    }
+
+    pub fn heading<S: Into<String>>(&mut self, heading: S) -> &mut Self {
+        self.lines.push(Line::Heading(heading.into()));
+        self
+    }
+
+    /// Add a line of text. Text too long for the page is wrapped on whitespace.
+    pub fn text<S: AsRef<str>>(&mut self, text: S) -> &mut Self {
+        let mut current = String::new();
+        for word in text.as_ref().split_whitespace() {
+            if !current.is_empty() && current.len() + word.len() + 1 > MAX_LINE_CHARS {
+                self.lines.push(Line::Text(std::mem::take(&mut current)));
+            }
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(word);
+        }
+        self.lines.push(Line::Text(current));
+        self
+    }
+
+    pub fn blank(&mut self) -> &mut Self {
+        self.lines.push(Line::Blank);
+        self
+    }
+
+    fn pages(&self) -> Vec<&[Line]> {
+        let mut pages = Vec::new();
+        let mut start = 0;
+        while start < self.lines.len() {
+            let mut end = std::cmp::min(start + LINES_PER_PAGE, self.lines.len());
+            // Don't leave a heading stranded at the bottom of a page.
+            if end < self.lines.len() && end - start > 1 {
+                if let Line::Heading(_) = self.lines[end - 1] {
+                    end -= 1;
+                }
+            }
+            pages.push(&self.lines[start..end]);
+            start = end;
+        }
+        if pages.is_empty() {
+            pages.push(&self.lines[0..0]);
+        }
+        pages
+    }
+
+    /// Render the document as the bytes of a PDF file.
+    pub fn render(&self) -> Vec<u8> {
+        let pages = self.pages();
+        // NOTE(jwall): Objects 1 through 4 are the catalog, the page tree and
+        // the two fonts. Each page is then a page object followed by its
+        // content stream.
+        let page_ids: Vec<usize> = (0..pages.len()).map(|i| 5 + i * 2).collect();
+        let mut objects = vec![
+            "<< /Type /Catalog /Pages 2 0 R >>".to_owned(),
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                page_ids
+                    .iter()
+                    .map(|id| format!("{} 0 R", id))
+                    .collect::<Vec<String>>()
+                    .join(" "),
+                pages.len()
+            ),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+                .to_owned(),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>"
+                .to_owned(),
+        ];
+        for (page, id) in pages.iter().zip(page_ids.iter()) {
+            objects.push(format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                PAGE_WIDTH,
+                PAGE_HEIGHT,
+                id + 1
+            ));
+            let content = page_content(page);
+            objects.push(format!(
+                "<< /Length {} >>\nstream\n{}\nendstream",
+                content.len(),
+                content
+            ));
+        }
+        let mut out = b"%PDF-1.4\n".to_vec();
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (idx, body) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", idx + 1, body).as_bytes());
+        }
+        let xref_offset = out.len();
+        out.extend_from_slice(format!("xref\n0 {}\n", objects.len() + 1).as_bytes());
+        out.extend_from_slice(b"0000000000 65535 f \n");
+        for offset in offsets {
+            out.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+        }
+        out.extend_from_slice(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+                objects.len() + 1,
+                xref_offset
+            )
+            .as_bytes(),
+        );
+        out
+    }
+}
+
+fn page_content(lines: &[Line]) -> String {
+    let mut content = String::new();
+    let mut y = PAGE_HEIGHT - MARGIN;
+    for line in lines {
+        y -= LINE_HEIGHT;
+        if let Line::Blank = line {
+            continue;
+        }
+        let (font, size) = line.font();
+        content.push_str(&format!(
+            "BT /{} {} Tf {} {} Td ({}) Tj ET\n",
+            font,
+            size,
+            MARGIN,
+            y,
+            escape_text(line.text())
+        ));
+    }
+    content
+}
+
+/// Escape text for a PDF string literal. The fonts use WinAnsiEncoding so
+/// Latin-1 characters like `½` are written as octal escapes and anything else
+/// is replaced with `?`.
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | '(' | ')' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            ' '..='~' => escaped.push(c),
+            '\t' => escaped.push(' '),
+            '\u{a0}'..='\u{ff}' => escaped.push_str(&format!("\\{:03o}", c as u32)),
+            _ => escaped.push('?'),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn render_string(doc: &TextDocument) -> String {
+        String::from_utf8(doc.render()).unwrap()
+    }
+
+    #[test]
+    fn test_escape_text() {
+        assert_eq!(escape_text("salt (kosher)"), "salt \\(kosher\\)");
+        assert_eq!(escape_text("back\\slash"), "back\\\\slash");
+        assert_eq!(escape_text("1½ cups"), "1\\275 cups");
+        assert_eq!(escape_text("5 🍋"), "5 ?");
+    }
+
+    #[test]
+    fn test_render_is_a_pdf() {
+        let mut doc = TextDocument::new();
+        doc.title("Shopping list")
+            .heading("Produce")
+            .text("2 lemons");
+        let pdf = render_string(&doc);
+        assert!(pdf.starts_with("%PDF-1.4\n"));
+        assert!(pdf.ends_with("%%EOF\n"));
+        assert!(pdf.contains("(Shopping list) Tj"));
+        assert!(pdf.contains("(2 lemons) Tj"));
+        assert_eq!(pdf.matches("/Type /Page ").count(), 1);
+    }
+
+    #[test]
+    fn test_xref_offsets_point_at_objects() {
+        let mut doc = TextDocument::new();
+        doc.title("Shopping list").text("1 onion");
+        let pdf = render_string(&doc);
+        let xref = pdf.find("\nxref\n").unwrap() + 1;
+        let startxref: usize = pdf
+            .split("startxref\n")
+            .nth(1)
+            .unwrap()
+            .lines()
+            .next()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(startxref, xref);
+        let entries: Vec<&str> = pdf[xref..]
+            .lines()
+            .skip(3)
+            .take_while(|l| l.ends_with(" n "))
+            .collect();
+        assert_eq!(entries.len(), 6);
+        for (idx, entry) in entries.iter().enumerate() {
+            let offset: usize = entry[0..10].parse().unwrap();
+            assert!(pdf[offset..].starts_with(&format!("{} 0 obj\n", idx + 1)));
+        }
+    }
+
+    #[test]
+    fn test_long_documents_span_pages() {
+        let mut doc = TextDocument::new();
+        for i in 0..(LINES_PER_PAGE * 2) {
+            doc.text(format!("item {}", i));
+        }
+        doc.heading("Last");
+        let pdf = render_string(&doc);
+        assert_eq!(pdf.matches("/Type /Page ").count(), 3);
+        assert!(pdf.contains("/Count 3"));
+    }
+
+    #[test]
+    fn test_headings_are_not_stranded() {
+        let mut doc = TextDocument::new();
+        for i in 0..(LINES_PER_PAGE - 1) {
+            doc.text(format!("item {}", i));
+        }
+        doc.heading("Dairy").text("1 cup milk");
+        let pages = doc.pages();
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[1][0], Line::Heading("Dairy".to_owned()));
+    }
+
+    #[test]
+    fn test_long_text_wraps() {
+        let mut doc = TextDocument::new();
+        doc.text("word ".repeat(40));
+        assert_eq!(doc.lines.len(), 3);
+        assert!(doc.lines.iter().all(|l| l.text().len() <= MAX_LINE_CHARS));
+    }
+}
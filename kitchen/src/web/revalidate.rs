@@ -0,0 +1,178 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Re-parse the stored recipes to find the ones a parser change broke.
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use recipes::parse::{self, ParseError};
+use serde::Serialize;
+
+use super::storage::{self, APIStore};
+
+/// A stored recipe that no longer parses.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RecipeFailure {
+    pub user_id: String,
+    pub recipe_id: String,
+    pub error: ParseError,
+}
+
+/// The results of re-parsing the stored recipes.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct RevalidateReport {
+    /// The number of recipes that were checked.
+    pub checked: usize,
+    pub failures: Vec<RecipeFailure>,
+}
+
+impl RevalidateReport {
+    fn check(&mut self, user_id: &str, recipe_id: &str, text: &str) {
+        self.checked += 1;
+        if let Err(error) = parse::as_recipe_detailed(text) {
+            self.failures.push(RecipeFailure {
+                user_id: user_id.to_owned(),
+                recipe_id: recipe_id.to_owned(),
+                error,
+            });
+        }
+    }
+
+    /// The failures grouped by their error message.
+    pub fn by_error(&self) -> BTreeMap<&str, Vec<&RecipeFailure>> {
+        let mut groups: BTreeMap<&str, Vec<&RecipeFailure>> = BTreeMap::new();
+        for failure in self.failures.iter() {
+            groups
+                .entry(failure.error.message.as_str())
+                .or_default()
+                .push(failure);
+        }
+        groups
+    }
+
+    /// Write the failures grouped by error followed by a summary line.
+    pub fn write_to<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        for (message, failures) in self.by_error() {
+            writeln!(out, "{} ({})", message, failures.len())?;
+            for failure in failures {
+                writeln!(
+                    out,
+                    "\t{}/{} at line {} column {}",
+                    failure.user_id, failure.recipe_id, failure.error.line, failure.error.column
+                )?;
+            }
+        }
+        writeln!(
+            out,
+            "Checked {} recipes, {} failed",
+            self.checked,
+            self.failures.len()
+        )
+    }
+}
+
+/// Re-parse the stored recipes for a user or for every user with recipes.
+pub async fn revalidate_recipes(
+    app_store: &storage::SqliteStore,
+    user_id: Option<&str>,
+) -> storage::Result<RevalidateReport> {
+    let users = match user_id {
+        Some(user_id) => vec![user_id.to_owned()],
+        None => app_store.users_with_recipes().await?,
+    };
+    let mut report = RevalidateReport::default();
+    for user_id in users {
+        let entries = app_store
+            .get_recipes_for_user(&user_id)
+            .await?
+            .unwrap_or_default();
+        for entry in entries {
+            report.check(&user_id, entry.recipe_id(), entry.recipe_text());
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::web::storage::TEST_RECIPE as SOUP;
+    use recipes::RecipeEntry;
+
+    #[test]
+    fn test_revalidate_reports_recipes_that_fail() {
+        async_std::task::block_on(async {
+            let app_store = storage::make_test_store().await;
+            app_store
+                .store_recipes_for_user(
+                    "alice",
+                    &vec![
+                        RecipeEntry::new("soup", SOUP),
+                        RecipeEntry::new("broken", "not a recipe"),
+                    ],
+                )
+                .await
+                .expect("Failed to store recipes");
+            app_store
+                .store_recipes_for_user("bob", &vec![RecipeEntry::new("also_broken", "nope")])
+                .await
+                .expect("Failed to store recipes");
+
+            let report = revalidate_recipes(&app_store, None)
+                .await
+                .expect("Failed to revalidate");
+            assert_eq!(report.checked, 3);
+            let mut failed = report
+                .failures
+                .iter()
+                .map(|f| (f.user_id.as_str(), f.recipe_id.as_str()))
+                .collect::<Vec<(&str, &str)>>();
+            failed.sort();
+            assert_eq!(failed, vec![("alice", "broken"), ("bob", "also_broken")]);
+            let grouped = report.by_error().values().map(|fs| fs.len()).sum::<usize>();
+            assert_eq!(grouped, 2);
+            let mut out = Vec::new();
+            report.write_to(&mut out).expect("Failed to write report");
+            let out = String::from_utf8(out).unwrap();
+            assert!(out.contains("alice/broken at line 1"), "{}", out);
+            assert!(out.ends_with("Checked 3 recipes, 2 failed\n"), "{}", out);
+
+            let report = revalidate_recipes(&app_store, Some("bob"))
+                .await
+                .expect("Failed to revalidate");
+            assert_eq!(report.checked, 1);
+            assert_eq!(report.failures.len(), 1);
+        });
+    }
+
+    #[test]
+    fn test_revalidate_good_recipes_have_no_failures() {
+        async_std::task::block_on(async {
+            let app_store = storage::make_test_store().await;
+            app_store
+                .store_recipes_for_user("alice", &vec![RecipeEntry::new("soup", SOUP)])
+                .await
+                .expect("Failed to store recipes");
+            let report = revalidate_recipes(&app_store, None)
+                .await
+                .expect("Failed to revalidate");
+            assert_eq!(
+                report,
+                RevalidateReport {
+                    checked: 1,
+                    failures: Vec::new()
+                }
+            );
+        });
+    }
+}
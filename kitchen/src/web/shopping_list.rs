@@ -0,0 +1,266 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::NaiveDate;
+use client_api as api;
+use recipes::{
+    density::DensityTable,
+    unit::{MeasureType, QuantityDisplay, DEFAULT_PRECISION},
+    Ingredient, IngredientAccumulator, IngredientKey, Recipe,
+};
+
+use super::pdf::TextDocument;
+
+/// The category for ingredients that haven't been assigned one.
+const UNCATEGORIZED: &str = "Other";
+/// The category extra items are listed under. This matches the shopping list
+/// in the UI.
+const EXTRAS_CATEGORY: &str = "Misc";
+
+/// A shopping list grouped by category. Each item is an amount and a name.
+pub type ShoppingListGroups = BTreeMap<String, Vec<(String, String)>>;
+
+/// Accumulate the ingredients for the planned recipes and the staples the
+/// same way the shopping list in the UI does. Sub-recipes are looked up by id
+/// in `all_recipes`. Filtered ingredients are left out, modified amounts
+/// replace the accumulated amounts, and the extra items are added to the end.
+pub fn shopping_list_groups(
+    recipes: &[(Recipe, i32)],
+    all_recipes: &BTreeMap<String, Recipe>,
+    staples: &[Ingredient],
+    categories: &BTreeMap<String, String>,
+    measure_types: &BTreeMap<String, MeasureType>,
+    inventory: &api::InventoryData,
+) -> ShoppingListGroups {
+    let mut acc = IngredientAccumulator::new()
+        .with_densities(DensityTable::default())
+        .with_measure_types(measure_types.clone());
+    for (recipe, count) in recipes.iter() {
+        for _ in 0..*count {
+            acc.accumulate_from_with(recipe, all_recipes);
+        }
+    }
+    acc.accumulate_ingredients_for("Staples", staples.iter());
+    let filtered: BTreeSet<&IngredientKey> = inventory.filtered_ingredients.iter().collect();
+    let modified: BTreeMap<&IngredientKey, &str> = inventory
+        .modified_amts
+        .iter()
+        .map(|(k, amt)| (k, amt.as_str()))
+        .collect();
+    let mut groups = ShoppingListGroups::new();
+    for (key, (ingredient, _)) in acc.ingredients() {
+        if filtered.contains(&key) {
+            continue;
+        }
+        let amt = match modified.get(&key) {
+            Some(amt) => amt.to_string(),
+            None => ingredient
+                .amt
+                .normalize()
+                .round_to(DEFAULT_PRECISION)
+                .display_compound(QuantityDisplay::Fraction),
+        };
+        let name = match &ingredient.form {
+            Some(form) => format!("{} ({})", ingredient.name_for_display(), form),
+            None => ingredient.name_for_display().to_owned(),
+        };
+        let category = categories
+            .get(&ingredient.name)
+            .or_else(|| categories.get(key.name()))
+            .filter(|c| !c.is_empty())
+            .cloned()
+            .unwrap_or_else(|| UNCATEGORIZED.to_owned());
+        groups.entry(category).or_default().push((amt, name));
+    }
+    for items in groups.values_mut() {
+        items.sort_by(|(_, n1), (_, n2)| n1.cmp(n2));
+    }
+    for (amt, name) in inventory.extra_items.iter() {
+        if amt.trim().is_empty() && name.trim().is_empty() {
+            continue;
+        }
+        groups
+            .entry(EXTRAS_CATEGORY.to_owned())
+            .or_default()
+            .push((amt.clone(), name.clone()));
+    }
+    groups
+}
+
+/// Render a shopping list as a printable PDF.
+pub fn render_shopping_list_pdf(date: &NaiveDate, groups: &ShoppingListGroups) -> Vec<u8> {
+    let mut doc = TextDocument::new();
+    doc.title(format!("Shopping List for {}", date));
+    if groups.is_empty() {
+        doc.blank().text("Nothing to buy.");
+    }
+    for (category, items) in groups.iter() {
+        doc.blank().heading(category);
+        for (amt, name) in items {
+            if amt.trim().is_empty() {
+                doc.text(format!("[ ] {}", name));
+            } else {
+                doc.text(format!("[ ] {} {}", amt, name));
+            }
+        }
+    }
+    doc.render()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use recipes::{unit::Measure, Step};
+
+    fn recipe(title: &str, ingredients: Vec<Ingredient>) -> Recipe {
+        Recipe::new(title, None).with_steps(vec![
+            Step::new(None, "Do the thing").with_ingredients(ingredients)
+        ])
+    }
+
+    fn inventory(
+        filtered_ingredients: Vec<IngredientKey>,
+        modified_amts: Vec<(IngredientKey, String)>,
+        extra_items: Vec<(String, String)>,
+    ) -> api::InventoryData {
+        (filtered_ingredients, modified_amts, extra_items).into()
+    }
+
+    #[test]
+    fn test_shopping_list_groups_by_category() {
+        let onion = Ingredient::new("onion", None, Measure::count(1));
+        let beef = Ingredient::new("beef", None, Measure::lb(1.into()));
+        let salt = Ingredient::new("salt", None, Measure::tsp(1.into()));
+        let recipes = vec![(recipe("chili", vec![onion, beef]), 2)];
+        let categories = BTreeMap::from([
+            ("onion".to_owned(), "Produce".to_owned()),
+            ("beef".to_owned(), "Meat".to_owned()),
+        ]);
+        let groups = shopping_list_groups(
+            &recipes,
+            &BTreeMap::new(),
+            &[salt],
+            &categories,
+            &BTreeMap::new(),
+            &inventory(vec![], vec![], vec![]),
+        );
+        assert_eq!(
+            groups,
+            BTreeMap::from([
+                (
+                    "Meat".to_owned(),
+                    vec![("2 lbs".to_owned(), "beef".to_owned())]
+                ),
+                (
+                    "Other".to_owned(),
+                    vec![("1 tsp".to_owned(), "salt".to_owned())]
+                ),
+                (
+                    "Produce".to_owned(),
+                    vec![("2".to_owned(), "onion".to_owned())]
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_shopping_list_honors_inventory() {
+        let onion = Ingredient::new("onion", None, Measure::count(1));
+        let beef = Ingredient::new("beef", None, Measure::lb(1.into()));
+        let garlic = Ingredient::new("garlic", Some("minced".to_owned()), Measure::count(2));
+        let recipes = vec![(
+            recipe("chili", vec![onion.clone(), beef.clone(), garlic]),
+            1,
+        )];
+        let groups = shopping_list_groups(
+            &recipes,
+            &BTreeMap::new(),
+            &[],
+            &BTreeMap::new(),
+            &BTreeMap::new(),
+            &inventory(
+                vec![onion.key()],
+                vec![(beef.key(), "3 lbs".to_owned())],
+                vec![("1 bag".to_owned(), "tortilla chips".to_owned())],
+            ),
+        );
+        assert_eq!(
+            groups,
+            BTreeMap::from([
+                (
+                    "Misc".to_owned(),
+                    vec![("1 bag".to_owned(), "tortilla chips".to_owned())]
+                ),
+                (
+                    "Other".to_owned(),
+                    vec![
+                        ("3 lbs".to_owned(), "beef".to_owned()),
+                        ("2".to_owned(), "garlic (minced)".to_owned()),
+                    ]
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_shopping_list_includes_sub_recipes() {
+        let dough = recipe(
+            "Pizza Dough",
+            vec![Ingredient::new("flour", None, Measure::cup(2.into()))],
+        );
+        let cheese = Ingredient::new("cheese", None, Measure::cup(1.into()));
+        let pizza = Recipe::new("Pizza", None).with_steps(vec![Step::new(None, "Top it")
+            .with_ingredients(vec![cheese])
+            .with_sub_recipes(vec!["pizza_dough".to_owned()])]);
+        let all_recipes = BTreeMap::from([
+            ("pizza".to_owned(), pizza.clone()),
+            ("pizza_dough".to_owned(), dough),
+        ]);
+        let groups = shopping_list_groups(
+            &[(pizza, 1)],
+            &all_recipes,
+            &[],
+            &BTreeMap::new(),
+            &BTreeMap::new(),
+            &inventory(vec![], vec![], vec![]),
+        );
+        assert_eq!(
+            groups,
+            BTreeMap::from([(
+                "Other".to_owned(),
+                vec![
+                    ("1 cup".to_owned(), "cheese".to_owned()),
+                    // NOTE(jwall): Amounts are normalized like the rest of
+                    // the shopping list.
+                    ("1 pint".to_owned(), "flour".to_owned()),
+                ]
+            )])
+        );
+    }
+
+    #[test]
+    fn test_render_shopping_list_pdf() {
+        let groups = BTreeMap::from([(
+            "Produce".to_owned(),
+            vec![("2".to_owned(), "lemons".to_owned())],
+        )]);
+        let date = NaiveDate::from_ymd_opt(2022, 11, 1).unwrap();
+        let pdf = String::from_utf8(render_shopping_list_pdf(&date, &groups)).unwrap();
+        assert!(pdf.starts_with("%PDF-1.4\n"));
+        assert!(pdf.contains("(Shopping List for 2022-11-01) Tj"));
+        assert!(pdf.contains("(Produce) Tj"));
+        assert!(pdf.contains("([ ] 2 lemons) Tj"));
+    }
+}
@@ -0,0 +1 @@
+delete from recipe_templates where user_id = ? and name = ?
\ No newline at end of file
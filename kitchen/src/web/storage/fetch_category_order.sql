@@ -0,0 +1 @@
+select category from category_order where user_id = ? order by position
@@ -0,0 +1,8 @@
+select
+    checked_items.name,
+    checked_items.form,
+    checked_items.measure_type
+from checked_items
+where
+     user_id = ?
+     and plan_date = ?
\ No newline at end of file
@@ -0,0 +1 @@
+select ingredient_name, measure_type from measure_type_overrides where user_id = ? order by ingredient_name
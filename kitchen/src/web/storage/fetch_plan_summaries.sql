@@ -0,0 +1,35 @@
+select
+    plan_table.plan_date as "plan_date!: NaiveDate",
+    (select count(*)
+        from plan_recipes
+        where plan_recipes.user_id = plan_table.user_id
+            and plan_recipes.plan_date = plan_table.plan_date
+            and plan_recipes.count > 0) as "recipe_count!: i64",
+    (select coalesce(sum(plan_recipes.count * coalesce(recipes.serving_count, 1)), 0)
+        from plan_recipes
+        left join recipes
+            on recipes.user_id = plan_recipes.user_id
+            and recipes.recipe_id = plan_recipes.recipe_id
+        where plan_recipes.user_id = plan_table.user_id
+            and plan_recipes.plan_date = plan_table.plan_date) as "servings!: i64",
+    (exists(select 1 from filtered_ingredients
+            where filtered_ingredients.user_id = plan_table.user_id
+                and filtered_ingredients.plan_date = plan_table.plan_date)
+        or exists(select 1 from modified_amts
+            where modified_amts.user_id = plan_table.user_id
+                and modified_amts.plan_date = plan_table.plan_date)
+        or exists(select 1 from extra_items
+            where extra_items.user_id = plan_table.user_id
+                and extra_items.plan_date = plan_table.plan_date)
+        or exists(select 1 from checked_items
+            where checked_items.user_id = plan_table.user_id
+                and checked_items.plan_date = plan_table.plan_date)
+        or exists(select 1 from purchased_amts
+            where purchased_amts.user_id = plan_table.user_id
+                and purchased_amts.plan_date = plan_table.plan_date)) as "has_inventory!: bool"
+from plan_table
+where
+    plan_table.user_id = ?
+    and date(plan_table.plan_date) >= ?
+    and date(plan_table.plan_date) <= ?
+order by plan_table.plan_date desc
\ No newline at end of file
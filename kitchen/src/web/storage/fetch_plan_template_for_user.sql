@@ -0,0 +1 @@
+select recipe_id, count from plan_templates where user_id = ? and name = ? order by recipe_id
\ No newline at end of file
@@ -0,0 +1 @@
+select name, recipe_id, count from plan_templates where user_id = ? order by name, recipe_id
\ No newline at end of file
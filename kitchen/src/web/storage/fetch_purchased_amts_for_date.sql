@@ -0,0 +1,9 @@
+select
+    purchased_amts.name,
+    purchased_amts.form,
+    purchased_amts.measure_type,
+    purchased_amts.purchased
+from purchased_amts
+where
+     user_id = ?
+     and plan_date = ?
\ No newline at end of file
@@ -0,0 +1 @@
+select name, template_text from recipe_templates where user_id = ? and name = ?
\ No newline at end of file
@@ -0,0 +1 @@
+select name, template_text from recipe_templates where user_id = ? order by name
\ No newline at end of file
@@ -12,38 +12,89 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 use async_std::{
-    fs::{read_dir, read_to_string, DirEntry, File},
+    fs::{create_dir_all, read_dir, read_to_string, remove_file, rename, write, DirEntry, File},
     io::{self, ReadExt},
     path::PathBuf,
     stream::StreamExt,
 };
+use async_trait::async_trait;
 use tracing::warn;
 use tracing::{debug, instrument};
 
 use super::RecipeEntry;
 
-#[allow(dead_code)]
+/// Errors reading or writing the files in an `AsyncFileStore`.
 #[derive(Debug)]
-pub struct Error(String);
+pub enum Error {
+    /// The file doesn't exist.
+    NotFound,
+    Io(std::io::Error),
+    /// The file isn't valid UTF-8.
+    InvalidEncoding,
+    Other(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::NotFound => write!(f, "File not found"),
+            Error::Io(err) => write!(f, "Io error: {}", err),
+            Error::InvalidEncoding => write!(f, "File is not valid UTF-8"),
+            Error::Other(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
 
 impl From<std::io::Error> for Error {
     fn from(item: std::io::Error) -> Self {
-        Error(format!("{:?}", item))
+        match item.kind() {
+            std::io::ErrorKind::NotFound => Error::NotFound,
+            // NOTE(jwall): read_to_string reports invalid UTF-8 as InvalidData.
+            std::io::ErrorKind::InvalidData => Error::InvalidEncoding,
+            _ => Error::Io(item),
+        }
     }
 }
 
 impl From<String> for Error {
     fn from(item: String) -> Self {
-        Error(item)
+        Error::Other(item)
     }
 }
 
 impl From<std::string::FromUtf8Error> for Error {
-    fn from(item: std::string::FromUtf8Error) -> Self {
-        Error(format!("{:?}", item))
+    fn from(_: std::string::FromUtf8Error) -> Self {
+        Error::InvalidEncoding
     }
 }
 
+/// A store of recipe files and their categories.
+#[async_trait]
+pub trait RecipeStore: Send + Sync + std::fmt::Debug {
+    /// The categories file contents if there is one.
+    async fn get_categories(&self) -> Result<Option<String>, Error>;
+
+    async fn get_recipes(&self) -> Result<Option<Vec<RecipeEntry>>, Error>;
+
+    async fn get_recipe_entry(&self, id: &str) -> Result<Option<RecipeEntry>, Error>;
+
+    /// Add or replace the recipe for the entry's id.
+    async fn store_recipe_entry(&self, entry: &RecipeEntry) -> Result<(), Error>;
+
+    /// Remove the recipe for `id`. Removing a recipe that doesn't exist is
+    /// not an error.
+    async fn delete_recipe(&self, id: &str) -> Result<(), Error>;
+}
+
 #[derive(Clone, Debug)]
 pub struct AsyncFileStore {
     path: PathBuf,
@@ -62,12 +113,30 @@ impl AsyncFileStore {
         recipe_path.push("recipes");
         recipe_path
     }
+
+    fn get_recipe_path(&self, id: &str) -> Result<PathBuf, Error> {
+        // NOTE(jwall): The id is a path relative to the recipes directory so
+        // it must not be able to point anywhere outside of it.
+        if id.contains('\\')
+            || id
+                .split('/')
+                .any(|part| part.is_empty() || part.starts_with('.'))
+        {
+            return Err(Error::Other(format!("Invalid recipe id {:?}", id)));
+        }
+        let mut recipe_path = self.get_recipe_path_root();
+        recipe_path.extend(id.split('/'));
+        Ok(recipe_path)
+    }
 }
 
-// TODO(jwall): We need to model our own set of errors for this.
-impl AsyncFileStore {
+/// Files in the recipes directory that aren't recipes.
+const IGNORED_FILES: [&str; 2] = ["menu.txt", "categories.txt"];
+
+#[async_trait]
+impl RecipeStore for AsyncFileStore {
     #[instrument(skip_all)]
-    pub async fn get_categories(&self) -> Result<Option<String>, Error> {
+    async fn get_categories(&self) -> Result<Option<String>, Error> {
         let mut category_path = PathBuf::new();
         category_path.push(&self.path);
         category_path.push("categories.txt");
@@ -79,54 +148,227 @@ impl AsyncFileStore {
         Ok(Some(String::from_utf8(contents)?))
     }
 
-    pub async fn get_recipes(&self) -> Result<Option<Vec<RecipeEntry>>, Error> {
-        let mut recipe_path = PathBuf::new();
-        recipe_path.push(&self.path);
-        recipe_path.push("recipes");
-        let mut entries = read_dir(&recipe_path).await?;
+    /// Read every recipe under the recipes directory including the ones in
+    /// subdirectories. The id of a recipe is its path relative to the recipes
+    /// directory. e.g. `desserts/brownies.txt`
+    async fn get_recipes(&self) -> Result<Option<Vec<RecipeEntry>>, Error> {
         let mut entry_vec = Vec::new();
-        // Special files that we ignore when fetching recipes
-        let filtered = vec!["menu.txt", "categories.txt"];
-        while let Some(res) = entries.next().await {
-            let entry: DirEntry = res?;
-
-            if !entry.file_type().await?.is_dir()
-                && !filtered
-                    .iter()
-                    .any(|&s| s == entry.file_name().to_string_lossy().to_string())
-            {
-                // add it to the entry
+        // NOTE(jwall): Each directory is queued with the id prefix of the
+        // recipes in it.
+        let mut dirs = vec![(self.get_recipe_path_root(), String::new())];
+        while let Some((dir, prefix)) = dirs.pop() {
+            let mut entries = read_dir(&dir).await?;
+            while let Some(res) = entries.next().await {
+                let entry: DirEntry = res?;
                 let file_name = entry.file_name().to_string_lossy().to_string();
-                debug!("adding recipe file {}", file_name);
-                let recipe_contents = read_to_string(entry.path()).await?;
-                entry_vec.push(RecipeEntry::new(file_name, recipe_contents));
-            } else {
-                warn!(
-                    file = %entry.path().to_string_lossy(),
-                    "skipping file not a recipe",
-                );
+                let id = format!("{}{}", prefix, file_name);
+                // NOTE(jwall): Hidden files and directories are things like
+                // .git or our temp files and never recipes.
+                if file_name.starts_with('.') {
+                    debug!(file = %entry.path().to_string_lossy(), "skipping hidden file");
+                } else if entry.file_type().await?.is_dir() {
+                    dirs.push((entry.path(), format!("{}/", id)));
+                } else if IGNORED_FILES.contains(&file_name.as_str()) {
+                    warn!(
+                        file = %entry.path().to_string_lossy(),
+                        "skipping file not a recipe",
+                    );
+                } else {
+                    debug!("adding recipe file {}", id);
+                    let recipe_contents = read_to_string(entry.path()).await?;
+                    entry_vec.push(RecipeEntry::new(id, recipe_contents));
+                }
             }
         }
         Ok(Some(entry_vec))
     }
 
-    pub async fn get_recipe_entry<S: AsRef<str> + Send>(
-        &self,
-        id: S,
-    ) -> Result<Option<RecipeEntry>, Error> {
-        let mut recipe_path = self.get_recipe_path_root();
-        recipe_path.push(id.as_ref());
+    async fn get_recipe_entry(&self, id: &str) -> Result<Option<RecipeEntry>, Error> {
+        let recipe_path = match self.get_recipe_path(id) {
+            Ok(recipe_path) => recipe_path,
+            Err(_) => return Ok(None),
+        };
         if recipe_path.exists().await && recipe_path.is_file().await {
             debug!("Found recipe file {}", recipe_path.to_string_lossy());
             let recipe_contents = read_to_string(recipe_path).await?;
             return Ok(Some(RecipeEntry {
-                id: id.as_ref().to_owned(),
+                id: id.to_owned(),
                 text: recipe_contents,
                 category: None,
                 serving_count: None,
+                tags: Vec::new(),
             }));
         } else {
             return Ok(None);
         }
     }
+
+    /// Write the recipe text to `recipes/<id>`, replacing any existing file.
+    /// Directories in the id are created as needed.
+    ///
+    /// The text is written to a temporary file first and then renamed into
+    /// place so readers never see a partially written recipe.
+    #[instrument(skip_all, fields(recipe_id=entry.recipe_id()))]
+    async fn store_recipe_entry(&self, entry: &RecipeEntry) -> Result<(), Error> {
+        let recipe_path = self.get_recipe_path(entry.recipe_id())?;
+        let (dir, file_name) = match (recipe_path.parent(), recipe_path.file_name()) {
+            (Some(dir), Some(file_name)) => (dir.to_owned(), file_name.to_string_lossy()),
+            _ => return Err(format!("Invalid recipe path {:?}", recipe_path).into()),
+        };
+        create_dir_all(&dir).await?;
+        let mut tmp_path = dir;
+        tmp_path.push(format!(".{}.tmp", file_name));
+        write(&tmp_path, entry.recipe_text()).await?;
+        if let Err(err) = rename(&tmp_path, &recipe_path).await {
+            let _ = remove_file(&tmp_path).await;
+            return Err(err.into());
+        }
+        debug!("Wrote recipe file {}", recipe_path.to_string_lossy());
+        Ok(())
+    }
+
+    /// Remove the recipe file for `id`. Removing a recipe that doesn't exist
+    /// is not an error.
+    #[instrument(skip(self))]
+    async fn delete_recipe(&self, id: &str) -> Result<(), Error> {
+        let recipe_path = self.get_recipe_path(id)?;
+        match remove_file(&recipe_path).await {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::web::storage::{TempDir, TEST_RECIPE as SOUP};
+
+    #[test]
+    fn test_store_and_delete_recipe_files() {
+        async_std::task::block_on(async {
+            let tmp = TempDir::new();
+            let dir = tmp.path();
+            let store = AsyncFileStore::new(dir.clone());
+            store
+                .store_recipe_entry(&RecipeEntry::new("soup.txt", "old soup"))
+                .await
+                .expect("Failed to store recipe");
+            store
+                .store_recipe_entry(&RecipeEntry::new("soup.txt", SOUP))
+                .await
+                .expect("Failed to replace recipe");
+            let entry = store
+                .get_recipe_entry("soup.txt")
+                .await
+                .expect("Failed to read recipe")
+                .expect("Recipe is missing");
+            assert_eq!(entry.recipe_text(), SOUP);
+            let recipes = store
+                .get_recipes()
+                .await
+                .expect("Failed to read recipes")
+                .unwrap_or_default();
+            assert_eq!(recipes.len(), 1, "temp files should not be left behind");
+
+            store
+                .delete_recipe("soup.txt")
+                .await
+                .expect("Failed to delete recipe");
+            assert!(store
+                .get_recipe_entry("soup.txt")
+                .await
+                .expect("Failed to read recipe")
+                .is_none());
+            store
+                .delete_recipe("soup.txt")
+                .await
+                .expect("Deleting a missing recipe should succeed");
+
+            for id in ["../escape", "a/../../b", "/abs", "a//b", ".hidden", ""] {
+                assert!(store
+                    .store_recipe_entry(&RecipeEntry::new(id, SOUP))
+                    .await
+                    .is_err());
+            }
+        });
+    }
+
+    #[test]
+    fn test_get_recipes_walks_subdirectories() {
+        async_std::task::block_on(async {
+            let tmp = TempDir::new();
+            let dir = tmp.path();
+            let recipes = dir.join("recipes");
+            std::fs::create_dir_all(recipes.join("desserts").join("cakes")).unwrap();
+            std::fs::create_dir_all(recipes.join(".git")).unwrap();
+            std::fs::write(recipes.join("soup.txt"), SOUP).unwrap();
+            std::fs::write(recipes.join("menu.txt"), "soup.txt").unwrap();
+            std::fs::write(recipes.join("desserts").join("brownies.txt"), SOUP).unwrap();
+            std::fs::write(recipes.join("desserts").join("categories.txt"), "").unwrap();
+            std::fs::write(
+                recipes.join("desserts").join("cakes").join("carrot.txt"),
+                SOUP,
+            )
+            .unwrap();
+            std::fs::write(recipes.join(".git").join("HEAD"), "ref: main").unwrap();
+            let store = AsyncFileStore::new(dir);
+            let mut ids = store
+                .get_recipes()
+                .await
+                .expect("Failed to read recipes")
+                .unwrap_or_default()
+                .into_iter()
+                .map(|e| e.recipe_id().to_owned())
+                .collect::<Vec<String>>();
+            ids.sort();
+            assert_eq!(
+                ids,
+                vec![
+                    "desserts/brownies.txt",
+                    "desserts/cakes/carrot.txt",
+                    "soup.txt"
+                ]
+            );
+            let entry = store
+                .get_recipe_entry("desserts/cakes/carrot.txt")
+                .await
+                .expect("Failed to read recipe");
+            assert!(entry.is_some());
+
+            store
+                .store_recipe_entry(&RecipeEntry::new("breads/rye.txt", SOUP))
+                .await
+                .expect("Failed to store nested recipe");
+            assert!(store
+                .get_recipe_entry("breads/rye.txt")
+                .await
+                .expect("Failed to read recipe")
+                .is_some());
+        });
+    }
+
+    #[test]
+    fn test_errors_keep_their_kind() {
+        async_std::task::block_on(async {
+            let tmp = TempDir::new();
+            let dir = tmp.path();
+            let recipes = dir.join("recipes");
+            std::fs::create_dir_all(&recipes).unwrap();
+            let store = AsyncFileStore::new(dir.clone());
+            assert!(matches!(store.get_categories().await, Err(Error::NotFound)));
+            std::fs::write(dir.join("categories.txt"), [0xff, 0xfe]).unwrap();
+            assert!(matches!(
+                store.get_categories().await,
+                Err(Error::InvalidEncoding)
+            ));
+            std::fs::write(recipes.join("soup.txt"), [0xff, 0xfe]).unwrap();
+            assert!(matches!(
+                store.get_recipe_entry("soup.txt").await,
+                Err(Error::InvalidEncoding)
+            ));
+            assert_eq!(Error::NotFound.to_string(), "File not found");
+        });
+    }
 }
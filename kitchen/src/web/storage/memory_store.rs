@@ -0,0 +1,159 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! A `RecipeStore` that keeps its recipes in memory. It's for tests and for
+//! serving a demo without a recipe directory.
+use std::collections::HashMap;
+
+use async_std::sync::RwLock;
+use async_trait::async_trait;
+use tracing::instrument;
+
+use super::file_store::{Error, RecipeStore};
+use super::RecipeEntry;
+
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    recipes: RwLock<HashMap<String, RecipeEntry>>,
+    categories: Option<String>,
+}
+
+impl MemoryStore {
+    pub fn new(recipes: Vec<RecipeEntry>) -> Self {
+        Self {
+            recipes: RwLock::new(
+                recipes
+                    .into_iter()
+                    .map(|entry| (entry.recipe_id().to_owned(), entry))
+                    .collect(),
+            ),
+            categories: None,
+        }
+    }
+
+    pub fn with_categories<S: Into<String>>(mut self, categories: S) -> Self {
+        self.categories = Some(categories.into());
+        self
+    }
+
+    /// A store with the example recipes and categories.
+    pub fn demo() -> Self {
+        Self::new(vec![
+            RecipeEntry::new(
+                "cornbread_dressing.txt",
+                include_str!("../../../../examples/recipes.txt/cornbread_dressing.txt"),
+            ),
+            RecipeEntry::new(
+                "meatloaf.txt",
+                include_str!("../../../../examples/recipes.txt/meatloaf.txt"),
+            ),
+        ])
+        .with_categories(include_str!("../../../../examples/categories.txt"))
+    }
+}
+
+#[async_trait]
+impl RecipeStore for MemoryStore {
+    async fn get_categories(&self) -> Result<Option<String>, Error> {
+        Ok(self.categories.clone())
+    }
+
+    async fn get_recipes(&self) -> Result<Option<Vec<RecipeEntry>>, Error> {
+        let mut entries = self
+            .recipes
+            .read()
+            .await
+            .values()
+            .cloned()
+            .collect::<Vec<RecipeEntry>>();
+        entries.sort_by(|a, b| a.recipe_id().cmp(b.recipe_id()));
+        Ok(Some(entries))
+    }
+
+    async fn get_recipe_entry(&self, id: &str) -> Result<Option<RecipeEntry>, Error> {
+        Ok(self.recipes.read().await.get(id).cloned())
+    }
+
+    #[instrument(skip_all, fields(recipe_id=entry.recipe_id()))]
+    async fn store_recipe_entry(&self, entry: &RecipeEntry) -> Result<(), Error> {
+        if entry.recipe_id().is_empty() {
+            return Err(Error::Other("Recipe ids can not be empty".to_owned()));
+        }
+        self.recipes
+            .write()
+            .await
+            .insert(entry.recipe_id().to_owned(), entry.clone());
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn delete_recipe(&self, id: &str) -> Result<(), Error> {
+        self.recipes.write().await.remove(id);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_memory_store_round_trip() {
+        async_std::task::block_on(async {
+            let store = MemoryStore::new(vec![
+                RecipeEntry::new("stew", "title: Stew"),
+                RecipeEntry::new("soup", "title: Soup"),
+            ]);
+            assert_eq!(store.get_categories().await.unwrap(), None);
+            let ids = store
+                .get_recipes()
+                .await
+                .unwrap()
+                .unwrap_or_default()
+                .into_iter()
+                .map(|e| e.recipe_id().to_owned())
+                .collect::<Vec<String>>();
+            assert_eq!(ids, vec!["soup", "stew"]);
+
+            store
+                .store_recipe_entry(&RecipeEntry::new("soup", "title: Better Soup"))
+                .await
+                .expect("Failed to store recipe");
+            let soup = store.get_recipe_entry("soup").await.unwrap().unwrap();
+            assert_eq!(soup.recipe_text(), "title: Better Soup");
+            store.delete_recipe("soup").await.unwrap();
+            assert!(store.get_recipe_entry("soup").await.unwrap().is_none());
+            store
+                .delete_recipe("soup")
+                .await
+                .expect("Deleting a missing recipe should succeed");
+        });
+    }
+
+    #[test]
+    fn test_demo_store_recipes_parse() {
+        async_std::task::block_on(async {
+            let store = MemoryStore::demo();
+            assert!(store.get_categories().await.unwrap().is_some());
+            let recipes = store.get_recipes().await.unwrap().unwrap_or_default();
+            assert_eq!(recipes.len(), 2);
+            for entry in recipes {
+                assert!(
+                    recipes::parse::as_recipe(entry.recipe_text()).is_ok(),
+                    "{}",
+                    entry.recipe_id()
+                );
+            }
+        });
+    }
+}
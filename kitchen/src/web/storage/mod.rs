@@ -30,7 +30,7 @@ use axum::{
 };
 use chrono::NaiveDate;
 use ciborium;
-use recipes::{IngredientKey, RecipeEntry};
+use recipes::{unit::MeasureType, IngredientKey, RecipeEntry, RecipeTemplate};
 use secrecy::{ExposeSecret, Secret};
 use serde::{Deserialize, Serialize};
 use sqlx::{
@@ -42,11 +42,16 @@ use tracing::{debug, error, info, instrument};
 
 mod error;
 pub mod file_store;
+pub mod memory_store;
 
 pub use error::*;
 
+/// The default name of the session cookie.
 pub const AXUM_SESSION_COOKIE_NAME: &'static str = "kitchen-session-cookie";
 
+/// How long a session lasts if no other ttl is configured.
+pub const DEFAULT_SESSION_TTL: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
 // TODO(jwall): Should this move to the recipe crate?
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UserId(pub String);
@@ -70,11 +75,50 @@ impl UserCreds {
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Construct a LIKE pattern matching any text containing the term. LIKE
+/// wildcards in the term are escaped so they match literally.
+fn make_like_pattern(term: &str) -> String {
+    let escaped = term
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+/// Tags are stored as a single comma separated column.
+fn tags_to_column(tags: &Vec<String>) -> Option<String> {
+    if tags.is_empty() {
+        None
+    } else {
+        Some(tags.join(","))
+    }
+}
+
+fn tags_from_column(tags: Option<&str>) -> Vec<String> {
+    tags.map(|tags| {
+        tags.split(',')
+            .map(|t| t.trim())
+            .filter(|t| !t.is_empty())
+            .map(|t| t.to_owned())
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
 fn make_id_key(cookie_value: &str) -> async_session::Result<String> {
     debug!("deserializing cookie");
     Ok(Session::id_from_cookie_value(cookie_value)?)
 }
 
+/// Hash a password with a freshly generated salt.
+fn hash_pass(pass: &Secret<String>) -> String {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(pass.expose_secret().as_bytes(), &salt)
+        .expect("failed to hash password")
+        .to_string()
+}
+
 #[instrument(skip_all, fields(hash=payload))]
 fn check_pass(payload: &String, pass: &Secret<String>) -> bool {
     let parsed_hash = PasswordHash::new(&payload).expect("Invalid Password Hash");
@@ -102,10 +146,42 @@ pub trait APIStore {
         mappings: &Vec<(String, String)>,
     ) -> Result<()>;
 
+    async fn get_measure_types_for_user(&self, user_id: &str)
+        -> Result<Vec<(String, MeasureType)>>;
+
+    async fn save_measure_types_for_user(
+        &self,
+        user_id: &str,
+        measure_types: &Vec<(String, MeasureType)>,
+    ) -> Result<()>;
+
+    async fn get_category_order(&self, user_id: &str) -> Result<Vec<String>>;
+
+    async fn save_category_order(&self, user_id: &str, order: &Vec<String>) -> Result<()>;
+
     async fn get_recipes_for_user(&self, user_id: &str) -> Result<Option<Vec<RecipeEntry>>>;
 
+    /// Fetch the ids of all of a user's recipes along with the recipes that
+    /// were stored at or after `since`, a timestamp in milliseconds.
+    async fn get_recipe_changes_for_user(
+        &self,
+        user_id: &str,
+        since: i64,
+    ) -> Result<(Vec<String>, Vec<RecipeEntry>)>;
+
+    async fn search_recipes_for_user(&self, user_id: &str, term: &str) -> Result<Vec<RecipeEntry>>;
+
+    /// Delete the recipes for a user. The recipes are also removed from any
+    /// saved meal plans.
     async fn delete_recipes_for_user(&self, user_id: &str, recipes: &Vec<String>) -> Result<()>;
 
+    /// Fetch the dates of any saved meal plans that include one of the recipes.
+    async fn fetch_plan_dates_for_recipes(
+        &self,
+        user_id: &str,
+        recipes: &Vec<String>,
+    ) -> Result<Vec<NaiveDate>>;
+
     async fn store_recipes_for_user(&self, user_id: &str, recipes: &Vec<RecipeEntry>)
         -> Result<()>;
 
@@ -139,6 +215,16 @@ pub trait APIStore {
         user_id: S,
     ) -> Result<Option<Vec<NaiveDate>>>;
 
+    /// Summarize the meal plans between `start` and `end` inclusive, newest
+    /// first. Each summary is the plan date, the number of recipes, the
+    /// total servings and whether the plan has any saved inventory.
+    async fn fetch_plan_summaries<S: AsRef<str> + Send>(
+        &self,
+        user_id: S,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<(NaiveDate, i64, i64, bool)>>;
+
     async fn delete_meal_plan_for_date<S: AsRef<str> + Send>(
         &self,
         user_id: S,
@@ -188,9 +274,92 @@ pub trait APIStore {
         extra_items: Vec<(String, String)>,
     ) -> Result<()>;
 
+    async fn fetch_checked_items_for_date<S: AsRef<str> + Send>(
+        &self,
+        user_id: S,
+        date: NaiveDate,
+    ) -> Result<Vec<IngredientKey>>;
+
+    async fn save_checked_items_for_date<S: AsRef<str> + Send>(
+        &self,
+        user_id: S,
+        date: &NaiveDate,
+        checked_items: BTreeSet<IngredientKey>,
+    ) -> Result<()>;
+
+    async fn fetch_purchased_amts_for_date<S: AsRef<str> + Send>(
+        &self,
+        user_id: S,
+        date: NaiveDate,
+    ) -> Result<Vec<(IngredientKey, String)>>;
+
+    async fn save_purchased_amts_for_date<S: AsRef<str> + Send>(
+        &self,
+        user_id: S,
+        date: &NaiveDate,
+        purchased_amts: BTreeMap<IngredientKey, String>,
+    ) -> Result<()>;
+
+    /// The most recent amount each ingredient was purchased as in the plans
+    /// before a date.
+    async fn fetch_purchase_hints<S: AsRef<str> + Send>(
+        &self,
+        user_id: S,
+        date: NaiveDate,
+    ) -> Result<BTreeMap<IngredientKey, String>>;
+
     async fn fetch_staples<S: AsRef<str> + Send>(&self, user_id: S) -> Result<Option<String>>;
 
     async fn save_staples<S: AsRef<str> + Send>(&self, user_id: S, content: S) -> Result<()>;
+
+    async fn get_templates_for_user(&self, user_id: &str) -> Result<Vec<RecipeTemplate>>;
+
+    async fn get_template_for_user(
+        &self,
+        user_id: &str,
+        name: &str,
+    ) -> Result<Option<RecipeTemplate>>;
+
+    async fn save_template_for_user(&self, user_id: &str, template: &RecipeTemplate) -> Result<()>;
+
+    async fn delete_template_for_user(&self, user_id: &str, name: &str) -> Result<()>;
+
+    /// Save the recipe counts as a named plan template replacing any existing
+    /// template with that name. Recipes with a count of zero aren't stored.
+    async fn save_plan_template(
+        &self,
+        user_id: &str,
+        name: &str,
+        recipe_counts: &[(String, i32)],
+    ) -> Result<()>;
+
+    /// The plan templates for a user keyed by name.
+    async fn list_plan_templates(
+        &self,
+        user_id: &str,
+    ) -> Result<BTreeMap<String, Vec<(String, i32)>>>;
+
+    /// Replace the meal plan on a date with the recipe counts from a plan
+    /// template. Returns the applied recipe counts or None if there is no
+    /// template with that name.
+    async fn apply_plan_template(
+        &self,
+        user_id: &str,
+        name: &str,
+        date: NaiveDate,
+    ) -> Result<Option<Vec<(String, i32)>>>;
+
+    /// The settings a user has chosen. The route to land on and whether to
+    /// include staples in their shopping list. Settings they haven't chosen
+    /// are None.
+    async fn get_settings_for_user(&self, user_id: &str) -> Result<(Option<String>, Option<bool>)>;
+
+    async fn save_settings_for_user(
+        &self,
+        user_id: &str,
+        route: Option<&str>,
+        use_staples: Option<bool>,
+    ) -> Result<()>;
 }
 
 #[async_trait]
@@ -198,8 +367,15 @@ pub trait AuthStore: SessionStore {
     /// Check user credentials against the user store.
     async fn check_user_creds(&self, user_creds: &UserCreds) -> Result<bool>;
 
+    /// Check whether a user exists in the user store.
+    async fn user_exists(&self, user_id: &str) -> Result<bool>;
+
     /// Insert or update user credentials in the user store.
     async fn store_user_creds(&self, user_creds: UserCreds) -> Result<()>;
+
+    /// Update the credentials of an existing user. Fails with
+    /// `Error::NoRecords` if the user doesn't exist.
+    async fn update_user_creds(&self, user_creds: UserCreds) -> Result<()>;
 }
 
 #[async_trait]
@@ -217,10 +393,11 @@ where
         let cookies = Option::<TypedHeader<Cookie>>::from_request(req)
             .await
             .expect("Unable to get headers fromrequest");
-        // TODO(jwall): We should really validate the expiration and such on this cookie.
+        // NOTE(jwall): Expired sessions are rejected by the session store so we
+        // don't have to validate the expiration here.
         if let Some(session_cookie) = cookies
             .as_ref()
-            .and_then(|c| c.get(AXUM_SESSION_COOKIE_NAME))
+            .and_then(|c| c.get(session_store.session_cookie_name()))
         {
             debug!(?session_cookie, "processing session cookie");
             match session_store.load_session(session_cookie.to_owned()).await {
@@ -230,22 +407,43 @@ where
                         return Ok(Self::FoundUserId(user_id));
                     } else {
                         error!("No user id found in session");
-                        return Ok(Self::NoUserId);
                     }
                 }
                 Ok(None) => {
                     debug!("no session defined in headers.");
-                    return Ok(Self::NoUserId);
                 }
                 Err(e) => {
                     debug!(err=?e, "error deserializing session");
-                    return Ok(Self::NoUserId);
                 }
             }
         } else {
             debug!("no cookies defined in headers.");
-            return Ok(Self::NoUserId);
         }
+        // NOTE(jwall): Without a session we fall back to basic auth which is
+        // only accepted when it has been allowed for the api.
+        super::auth::user_id_from_basic_auth(req, &session_store).await
+    }
+}
+
+/// A purchased amount as it is stored in the purchased_amts table.
+struct PurchasedAmtRow {
+    name: String,
+    form: String,
+    measure_type: String,
+    purchased: String,
+}
+
+impl PurchasedAmtRow {
+    fn into_pair(self) -> (IngredientKey, String) {
+        let form = if self.form.is_empty() {
+            None
+        } else {
+            Some(self.form)
+        };
+        (
+            IngredientKey::new(self.name, form, self.measure_type),
+            self.purchased,
+        )
     }
 }
 
@@ -253,6 +451,8 @@ where
 pub struct SqliteStore {
     pool: Arc<SqlitePool>,
     url: String,
+    session_ttl: Duration,
+    session_cookie_name: String,
 }
 
 impl SqliteStore {
@@ -265,7 +465,30 @@ impl SqliteStore {
             .create_if_missing(true);
         info!(?options, "Connecting to sqlite db");
         let pool = Arc::new(sqlx::SqlitePool::connect_with(options).await?);
-        Ok(Self { pool, url })
+        Ok(Self {
+            pool,
+            url,
+            session_ttl: DEFAULT_SESSION_TTL,
+            session_cookie_name: AXUM_SESSION_COOKIE_NAME.to_owned(),
+        })
+    }
+
+    /// Set how long sessions stored in this store last.
+    pub fn with_session_ttl(mut self, session_ttl: Duration) -> Self {
+        self.session_ttl = session_ttl;
+        self
+    }
+
+    /// Set the name of the cookie sessions from this store are stored in.
+    /// Instances on the same host need distinct names or their sessions will
+    /// clobber each other.
+    pub fn with_session_cookie_name<S: Into<String>>(mut self, name: S) -> Self {
+        self.session_cookie_name = name.into();
+        self
+    }
+
+    pub fn session_cookie_name(&self) -> &str {
+        &self.session_cookie_name
     }
 
     #[instrument(fields(conn_string=self.url), skip_all)]
@@ -276,6 +499,26 @@ impl SqliteStore {
             .await?;
         Ok(())
     }
+
+    /// The ids of every user that has stored recipes.
+    pub async fn users_with_recipes(&self) -> sqlx::Result<Vec<String>> {
+        let rows = sqlx::query!("select distinct user_id from recipes order by user_id")
+            .fetch_all(self.pool.as_ref())
+            .await?;
+        Ok(rows.into_iter().map(|row| row.user_id).collect())
+    }
+
+    /// Delete all the sessions that are older than the session ttl. Returns the
+    /// number of sessions that were deleted.
+    #[instrument(fields(conn_string=self.url), skip_all)]
+    pub async fn clear_expired_sessions(&self) -> sqlx::Result<u64> {
+        let cutoff = chrono::Utc::now().timestamp() - self.session_ttl.as_secs() as i64;
+        let result = sqlx::query!("delete from sessions where created_at < ?", cutoff)
+            .execute(self.pool.as_ref())
+            .await?;
+        info!(count = result.rows_affected(), "Cleared expired sessions");
+        Ok(result.rows_affected())
+    }
 }
 
 #[async_trait]
@@ -291,20 +534,30 @@ impl SessionStore for SqliteStore {
         {
             debug!(sesion_id = id, "found session key");
             let session: Session = ciborium::de::from_reader(payload.as_slice())?;
+            if session.is_expired() {
+                debug!(sesion_id = id, "session is expired");
+                self.destroy_session(session).await?;
+                return Ok(None);
+            }
             return Ok(Some(session));
         }
         return Ok(None);
     }
 
     #[instrument(fields(conn_string=self.url), skip_all)]
-    async fn store_session(&self, session: Session) -> async_session::Result<Option<String>> {
+    async fn store_session(&self, mut session: Session) -> async_session::Result<Option<String>> {
+        if session.expiry().is_none() {
+            session.expire_in(self.session_ttl);
+        }
         let id = session.id();
+        let created_at = chrono::Utc::now().timestamp();
         let mut payload: Vec<u8> = Vec::new();
         ciborium::ser::into_writer(&session, &mut payload)?;
         sqlx::query!(
-            "insert into sessions (id, session_value) values (?, ?)",
+            "insert into sessions (id, session_value, created_at) values (?, ?, ?)",
             id,
-            payload
+            payload,
+            created_at
         )
         .execute(self.pool.as_ref())
         .await?;
@@ -346,22 +599,40 @@ impl AuthStore for SqliteStore {
         Ok(false)
     }
 
+    #[instrument(skip(self))]
+    async fn user_exists(&self, user_id: &str) -> Result<bool> {
+        Ok(
+            sqlx::query_scalar!("select id from users where id = ?", user_id)
+                .fetch_optional(self.pool.as_ref())
+                .await?
+                .is_some(),
+        )
+    }
+
     #[instrument(fields(user=%user_creds.id.0, conn_string=self.url), skip_all)]
     async fn store_user_creds(&self, user_creds: UserCreds) -> Result<()> {
-        let salt = SaltString::generate(&mut OsRng);
-        let password_hash = Argon2::default()
-            .hash_password(user_creds.pass.expose_secret().as_bytes(), &salt)
-            .expect("failed to hash password");
         let id = user_creds.user_id().to_owned();
-        let password_hashed = password_hash.to_string();
-        debug!("adding password for user");
-        sqlx::query!(
-            "insert into users (id, password_hashed) values (?, ?)",
-            id,
-            password_hashed,
-        )
-        .execute(self.pool.as_ref())
-        .await?;
+        // NOTE(jwall): hash_pass generates a new salt every time.
+        let password_hashed = hash_pass(&user_creds.pass);
+        debug!("storing password for user");
+        sqlx::query_file!("src/web/storage/store_user_creds.sql", id, password_hashed)
+            .execute(self.pool.as_ref())
+            .await?;
+        Ok(())
+    }
+
+    #[instrument(fields(user=%user_creds.id.0, conn_string=self.url), skip_all)]
+    async fn update_user_creds(&self, user_creds: UserCreds) -> Result<()> {
+        let id = user_creds.user_id().to_owned();
+        let password_hashed = hash_pass(&user_creds.pass);
+        debug!("updating password for user");
+        let result =
+            sqlx::query_file!("src/web/storage/update_user_creds.sql", password_hashed, id)
+                .execute(self.pool.as_ref())
+                .await?;
+        if result.rows_affected() == 0 {
+            return Err(Error::NoRecords);
+        }
         Ok(())
     }
 }
@@ -426,6 +697,79 @@ impl APIStore for SqliteStore {
         Ok(())
     }
 
+    async fn get_measure_types_for_user(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<(String, MeasureType)>> {
+        let rows = sqlx::query_file!("src/web/storage/fetch_measure_types_for_user.sql", user_id)
+            .fetch_all(self.pool.as_ref())
+            .await?;
+        let mut measure_types = Vec::new();
+        for row in rows {
+            match MeasureType::try_from(row.measure_type.as_str()) {
+                Ok(measure_type) => measure_types.push((row.ingredient_name, measure_type)),
+                Err(err) => error!(ingredient = %row.ingredient_name, %err, "Skipping override"),
+            }
+        }
+        Ok(measure_types)
+    }
+
+    /// Replaces all of the measure type overrides for a user.
+    async fn save_measure_types_for_user(
+        &self,
+        user_id: &str,
+        measure_types: &Vec<(String, MeasureType)>,
+    ) -> Result<()> {
+        let mut transaction = self.pool.as_ref().begin().await?;
+        sqlx::query!(
+            "delete from measure_type_overrides where user_id = ?",
+            user_id
+        )
+        .execute(&mut *transaction)
+        .await?;
+        for (name, measure_type) in measure_types.iter() {
+            let measure_type = measure_type.to_string();
+            sqlx::query_file!(
+                "src/web/storage/save_measure_type_for_user.sql",
+                user_id,
+                name,
+                measure_type,
+            )
+            .execute(&mut *transaction)
+            .await?;
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    async fn get_category_order(&self, user_id: &str) -> Result<Vec<String>> {
+        let rows = sqlx::query_file!("src/web/storage/fetch_category_order.sql", user_id)
+            .fetch_all(self.pool.as_ref())
+            .await?;
+        Ok(rows.into_iter().map(|row| row.category).collect())
+    }
+
+    /// Replaces the category order for a user.
+    async fn save_category_order(&self, user_id: &str, order: &Vec<String>) -> Result<()> {
+        let mut transaction = self.pool.as_ref().begin().await?;
+        sqlx::query!("delete from category_order where user_id = ?", user_id)
+            .execute(&mut *transaction)
+            .await?;
+        for (position, category) in order.iter().enumerate() {
+            let position = position as i64;
+            sqlx::query_file!(
+                "src/web/storage/save_category_order.sql",
+                user_id,
+                category,
+                position,
+            )
+            .execute(&mut *transaction)
+            .await?;
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+
     async fn get_recipe_entry_for_user<S: AsRef<str> + Send>(
         &self,
         user_id: S,
@@ -434,7 +778,7 @@ impl APIStore for SqliteStore {
         let id = id.as_ref();
         let user_id = user_id.as_ref();
         let entry = sqlx::query!(
-            "select recipe_id, recipe_text, category, serving_count from recipes where user_id = ? and recipe_id = ?",
+            "select recipe_id, recipe_text, category, serving_count, tags from recipes where user_id = ? and recipe_id = ?",
             user_id,
             id,
         )
@@ -447,6 +791,7 @@ impl APIStore for SqliteStore {
                 text: row.recipe_text.clone().unwrap_or_else(|| String::new()),
                 category: row.category.clone(),
                 serving_count: row.serving_count.clone(),
+                tags: tags_from_column(row.tags.as_deref()),
             }
         })
         .nth(0);
@@ -455,7 +800,7 @@ impl APIStore for SqliteStore {
 
     async fn get_recipes_for_user(&self, user_id: &str) -> Result<Option<Vec<RecipeEntry>>> {
         let rows = sqlx::query!(
-            "select recipe_id, recipe_text, category, serving_count from recipes where user_id = ?",
+            "select recipe_id, recipe_text, category, serving_count, tags from recipes where user_id = ?",
             user_id,
         )
         .fetch_all(self.pool.as_ref())
@@ -467,12 +812,76 @@ impl APIStore for SqliteStore {
                 text: row.recipe_text.clone().unwrap_or_else(|| String::new()),
                 category: row.category.clone(),
                 serving_count: row.serving_count.clone(),
+                tags: tags_from_column(row.tags.as_deref()),
             }
         })
         .collect();
         Ok(Some(rows))
     }
 
+    async fn get_recipe_changes_for_user(
+        &self,
+        user_id: &str,
+        since: i64,
+    ) -> Result<(Vec<String>, Vec<RecipeEntry>)> {
+        let ids = sqlx::query!("select recipe_id from recipes where user_id = ?", user_id,)
+            .fetch_all(self.pool.as_ref())
+            .await?
+            .into_iter()
+            .map(|row| row.recipe_id)
+            .collect();
+        let changed = sqlx::query!(
+            "select recipe_id, recipe_text, category, serving_count, tags from recipes where user_id = ? and updated_at >= ?",
+            user_id,
+            since,
+        )
+        .fetch_all(self.pool.as_ref())
+        .await?
+        .into_iter()
+        .map(|row| RecipeEntry {
+            id: row.recipe_id,
+            text: row.recipe_text.unwrap_or_else(|| String::new()),
+            category: row.category,
+            serving_count: row.serving_count,
+            tags: tags_from_column(row.tags.as_deref()),
+        })
+        .collect();
+        Ok((ids, changed))
+    }
+
+    async fn search_recipes_for_user(&self, user_id: &str, term: &str) -> Result<Vec<RecipeEntry>> {
+        struct Row {
+            recipe_id: String,
+            recipe_text: Option<String>,
+            category: Option<String>,
+            serving_count: Option<i64>,
+            tags: Option<String>,
+        }
+        // NOTE(jwall): The title is part of the recipe text so matching on the
+        // text covers titles and ingredient names. Sqlite's LIKE is case
+        // insensitive by default.
+        let pattern = make_like_pattern(term);
+        let rows: Vec<Row> = sqlx::query_file_as!(
+            Row,
+            "src/web/storage/search_recipes_for_user.sql",
+            user_id,
+            pattern,
+            pattern,
+        )
+        .fetch_all(self.pool.as_ref())
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| RecipeEntry {
+                id: row.recipe_id,
+                text: row.recipe_text.unwrap_or_else(|| String::new()),
+                category: row.category,
+                serving_count: row.serving_count,
+                tags: tags_from_column(row.tags.as_deref()),
+            })
+            .collect())
+    }
+
     async fn store_recipes_for_user(
         &self,
         user_id: &str,
@@ -483,14 +892,20 @@ impl APIStore for SqliteStore {
             let recipe_text = entry.recipe_text().to_owned();
             let category = entry.category();
             let serving_count = entry.serving_count();
+            let tags = tags_to_column(entry.tags());
+            // NOTE(jwall): The updated_at timestamp is the cursor clients use
+            // to fetch only the recipes that changed since their last sync.
+            let updated_at = chrono::Utc::now().timestamp_millis();
             sqlx::query!(
-                "insert into recipes (user_id, recipe_id, recipe_text, category, serving_count) values (?, ?, ?, ?, ?)
-    on conflict(user_id, recipe_id) do update set recipe_text=excluded.recipe_text, category=excluded.category",
+                "insert into recipes (user_id, recipe_id, recipe_text, category, serving_count, tags, updated_at) values (?, ?, ?, ?, ?, ?, ?)
+    on conflict(user_id, recipe_id) do update set recipe_text=excluded.recipe_text, category=excluded.category, tags=excluded.tags, updated_at=excluded.updated_at",
                 user_id,
                 recipe_id,
                 recipe_text,
                 category,
                 serving_count,
+                tags,
+                updated_at,
             )
             .execute(self.pool.as_ref())
             .await?;
@@ -508,11 +923,42 @@ impl APIStore for SqliteStore {
             )
             .execute(&mut *transaction)
             .await?;
+            sqlx::query!(
+                "delete from plan_recipes where user_id = ? and recipe_id = ?",
+                user_id,
+                recipe_id,
+            )
+            .execute(&mut *transaction)
+            .await?;
         }
         transaction.commit().await?;
         Ok(())
     }
 
+    async fn fetch_plan_dates_for_recipes(
+        &self,
+        user_id: &str,
+        recipes: &Vec<String>,
+    ) -> Result<Vec<NaiveDate>> {
+        struct Row {
+            pub plan_date: NaiveDate,
+        }
+        let mut dates = BTreeSet::new();
+        for recipe_id in recipes {
+            let rows = sqlx::query_as!(
+                Row,
+                r#"select distinct plan_date as "plan_date: NaiveDate" from plan_recipes
+where user_id = ? and recipe_id = ?"#,
+                user_id,
+                recipe_id,
+            )
+            .fetch_all(self.pool.as_ref())
+            .await?;
+            dates.extend(rows.into_iter().map(|row| row.plan_date));
+        }
+        Ok(dates.into_iter().collect())
+    }
+
     async fn store_categories_for_user(&self, user_id: &str, categories: &str) -> Result<()> {
         sqlx::query!(
             "insert into categories (user_id, category_text) values (?, ?)
@@ -580,6 +1026,41 @@ impl APIStore for SqliteStore {
         Ok(Some(result))
     }
 
+    async fn fetch_plan_summaries<S: AsRef<str> + Send>(
+        &self,
+        user_id: S,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<(NaiveDate, i64, i64, bool)>> {
+        let user_id = user_id.as_ref();
+        struct Row {
+            pub plan_date: NaiveDate,
+            pub recipe_count: i64,
+            pub servings: i64,
+            pub has_inventory: bool,
+        }
+        let rows = sqlx::query_file_as!(
+            Row,
+            r#"src/web/storage/fetch_plan_summaries.sql"#,
+            user_id,
+            start,
+            end,
+        )
+        .fetch_all(self.pool.as_ref())
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                (
+                    row.plan_date,
+                    row.recipe_count,
+                    row.servings,
+                    row.has_inventory,
+                )
+            })
+            .collect())
+    }
+
     async fn fetch_meal_plans_since<S: AsRef<str> + Send>(
         &self,
         user_id: S,
@@ -661,6 +1142,20 @@ impl APIStore for SqliteStore {
         )
         .execute(&mut *transaction)
         .await?;
+        sqlx::query!(
+            "delete from checked_items where user_id = ? and plan_date = ?",
+            user_id,
+            date
+        )
+        .execute(&mut *transaction)
+        .await?;
+        sqlx::query!(
+            "delete from purchased_amts where user_id = ? and plan_date = ?",
+            user_id,
+            date
+        )
+        .execute(&mut *transaction)
+        .await?;
         transaction.commit().await?;
         Ok(())
     }
@@ -972,6 +1467,151 @@ impl APIStore for SqliteStore {
         Ok(())
     }
 
+    async fn fetch_checked_items_for_date<S: AsRef<str> + Send>(
+        &self,
+        user_id: S,
+        date: NaiveDate,
+    ) -> Result<Vec<IngredientKey>> {
+        let user_id = user_id.as_ref();
+        struct CheckedItemRow {
+            name: String,
+            form: String,
+            measure_type: String,
+        }
+        let rows: Vec<CheckedItemRow> = sqlx::query_file_as!(
+            CheckedItemRow,
+            "src/web/storage/fetch_checked_items_for_date.sql",
+            user_id,
+            date,
+        )
+        .fetch_all(self.pool.as_ref())
+        .await?;
+        let mut checked_items = Vec::new();
+        for row in rows {
+            checked_items.push(IngredientKey::new(
+                row.name,
+                if row.form.is_empty() {
+                    None
+                } else {
+                    Some(row.form)
+                },
+                row.measure_type,
+            ));
+        }
+        Ok(checked_items)
+    }
+
+    /// Replaces all of the checked items for a user's plan date.
+    async fn save_checked_items_for_date<S: AsRef<str> + Send>(
+        &self,
+        user_id: S,
+        date: &NaiveDate,
+        checked_items: BTreeSet<IngredientKey>,
+    ) -> Result<()> {
+        let user_id = user_id.as_ref();
+        let mut transaction = self.pool.as_ref().begin().await?;
+        sqlx::query!(
+            "delete from checked_items where user_id = ? and plan_date = ?",
+            user_id,
+            date
+        )
+        .execute(&mut *transaction)
+        .await?;
+        for key in checked_items {
+            let name = key.name();
+            let form = key.form();
+            let measure_type = key.measure_type();
+            sqlx::query_file!(
+                "src/web/storage/save_checked_items_for_date.sql",
+                user_id,
+                date,
+                name,
+                form,
+                measure_type,
+            )
+            .execute(&mut *transaction)
+            .await?;
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    async fn fetch_purchased_amts_for_date<S: AsRef<str> + Send>(
+        &self,
+        user_id: S,
+        date: NaiveDate,
+    ) -> Result<Vec<(IngredientKey, String)>> {
+        let user_id = user_id.as_ref();
+        let rows: Vec<PurchasedAmtRow> = sqlx::query_file_as!(
+            PurchasedAmtRow,
+            "src/web/storage/fetch_purchased_amts_for_date.sql",
+            user_id,
+            date,
+        )
+        .fetch_all(self.pool.as_ref())
+        .await?;
+        Ok(rows.into_iter().map(PurchasedAmtRow::into_pair).collect())
+    }
+
+    /// Replaces all of the purchased amounts for a user's plan date.
+    async fn save_purchased_amts_for_date<S: AsRef<str> + Send>(
+        &self,
+        user_id: S,
+        date: &NaiveDate,
+        purchased_amts: BTreeMap<IngredientKey, String>,
+    ) -> Result<()> {
+        let user_id = user_id.as_ref();
+        let mut transaction = self.pool.as_ref().begin().await?;
+        sqlx::query!(
+            "delete from purchased_amts where user_id = ? and plan_date = ?",
+            user_id,
+            date
+        )
+        .execute(&mut *transaction)
+        .await?;
+        for (key, purchased) in purchased_amts {
+            let name = key.name();
+            let form = key.form();
+            let measure_type = key.measure_type();
+            let purchased = purchased.trim();
+            if purchased.is_empty() {
+                continue;
+            }
+            sqlx::query_file!(
+                "src/web/storage/save_purchased_amts_for_date.sql",
+                user_id,
+                date,
+                name,
+                form,
+                measure_type,
+                purchased,
+            )
+            .execute(&mut *transaction)
+            .await?;
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    async fn fetch_purchase_hints<S: AsRef<str> + Send>(
+        &self,
+        user_id: S,
+        date: NaiveDate,
+    ) -> Result<BTreeMap<IngredientKey, String>> {
+        let user_id = user_id.as_ref();
+        let rows: Vec<PurchasedAmtRow> = sqlx::query_file_as!(
+            PurchasedAmtRow,
+            "src/web/storage/fetch_purchase_history.sql",
+            user_id,
+            date,
+        )
+        .fetch_all(self.pool.as_ref())
+        .await?;
+        // NOTE(jwall): The rows are ordered by plan date so later purchases
+        // replace earlier ones.
+        Ok(rows.into_iter().map(PurchasedAmtRow::into_pair).collect())
+    }
+
     async fn save_inventory_data<S: AsRef<str> + Send>(
         &self,
         user_id: S,
@@ -1042,4 +1682,188 @@ impl APIStore for SqliteStore {
         }
         Ok(None)
     }
+
+    async fn get_templates_for_user(&self, user_id: &str) -> Result<Vec<RecipeTemplate>> {
+        let templates = sqlx::query_file!("src/web/storage/fetch_templates_for_user.sql", user_id)
+            .fetch_all(self.pool.as_ref())
+            .await?
+            .into_iter()
+            .map(|row| RecipeTemplate::new(row.name, row.template_text))
+            .collect();
+        Ok(templates)
+    }
+
+    async fn get_template_for_user(
+        &self,
+        user_id: &str,
+        name: &str,
+    ) -> Result<Option<RecipeTemplate>> {
+        let template =
+            sqlx::query_file!("src/web/storage/fetch_template_for_user.sql", user_id, name)
+                .fetch_optional(self.pool.as_ref())
+                .await?
+                .map(|row| RecipeTemplate::new(row.name, row.template_text));
+        Ok(template)
+    }
+
+    async fn save_template_for_user(&self, user_id: &str, template: &RecipeTemplate) -> Result<()> {
+        sqlx::query_file!(
+            "src/web/storage/save_template_for_user.sql",
+            user_id,
+            template.name,
+            template.text,
+        )
+        .execute(self.pool.as_ref())
+        .await?;
+        Ok(())
+    }
+
+    async fn delete_template_for_user(&self, user_id: &str, name: &str) -> Result<()> {
+        sqlx::query_file!(
+            "src/web/storage/delete_template_for_user.sql",
+            user_id,
+            name
+        )
+        .execute(self.pool.as_ref())
+        .await?;
+        Ok(())
+    }
+
+    async fn save_plan_template(
+        &self,
+        user_id: &str,
+        name: &str,
+        recipe_counts: &[(String, i32)],
+    ) -> Result<()> {
+        let mut transaction = self.pool.as_ref().begin().await?;
+        sqlx::query!(
+            "delete from plan_templates where user_id = ? and name = ?",
+            user_id,
+            name,
+        )
+        .execute(&mut *transaction)
+        .await?;
+        for (recipe_id, count) in recipe_counts.iter().filter(|(_, count)| *count > 0) {
+            sqlx::query_file!(
+                "src/web/storage/save_plan_template_for_user.sql",
+                user_id,
+                name,
+                recipe_id,
+                count
+            )
+            .execute(&mut *transaction)
+            .await?;
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    async fn list_plan_templates(
+        &self,
+        user_id: &str,
+    ) -> Result<BTreeMap<String, Vec<(String, i32)>>> {
+        let rows = sqlx::query_file!("src/web/storage/fetch_plan_templates_for_user.sql", user_id)
+            .fetch_all(self.pool.as_ref())
+            .await?;
+        let mut templates: BTreeMap<String, Vec<(String, i32)>> = BTreeMap::new();
+        for row in rows {
+            templates
+                .entry(row.name)
+                .or_default()
+                .push((row.recipe_id, row.count as i32));
+        }
+        Ok(templates)
+    }
+
+    async fn apply_plan_template(
+        &self,
+        user_id: &str,
+        name: &str,
+        date: NaiveDate,
+    ) -> Result<Option<Vec<(String, i32)>>> {
+        let recipe_counts: Vec<(String, i32)> = sqlx::query_file!(
+            "src/web/storage/fetch_plan_template_for_user.sql",
+            user_id,
+            name
+        )
+        .fetch_all(self.pool.as_ref())
+        .await?
+        .into_iter()
+        .map(|row| (row.recipe_id, row.count as i32))
+        .collect();
+        if recipe_counts.is_empty() {
+            return Ok(None);
+        }
+        self.save_meal_plan(user_id, &recipe_counts, date).await?;
+        Ok(Some(recipe_counts))
+    }
+
+    async fn get_settings_for_user(&self, user_id: &str) -> Result<(Option<String>, Option<bool>)> {
+        let row = sqlx::query!(
+            r#"select default_route, use_staples as "use_staples: bool" from user_settings where user_id = ?"#,
+            user_id
+        )
+        .fetch_optional(self.pool.as_ref())
+        .await?;
+        Ok(row
+            .map(|row| (row.default_route, row.use_staples))
+            .unwrap_or_default())
+    }
+
+    async fn save_settings_for_user(
+        &self,
+        user_id: &str,
+        route: Option<&str>,
+        use_staples: Option<bool>,
+    ) -> Result<()> {
+        sqlx::query_file!(
+            "src/web/storage/save_settings_for_user.sql",
+            user_id,
+            route,
+            use_staples
+        )
+        .execute(self.pool.as_ref())
+        .await?;
+        Ok(())
+    }
+}
+
+/// Create a store in a fresh temporary directory with the migrations applied.
+#[cfg(test)]
+pub async fn make_test_store() -> Arc<SqliteStore> {
+    let path = std::env::temp_dir().join(format!("kitchen-test-{}", uuid::Uuid::new_v4()));
+    let store = SqliteStore::new(path)
+        .await
+        .expect("Unable to create store");
+    store
+        .run_migrations()
+        .await
+        .expect("Failed to run migrations");
+    Arc::new(store)
+}
+
+/// A small valid recipe for tests.
+#[cfg(test)]
+pub const TEST_RECIPE: &str = "title: Soup\n\n\nstep:\n\n1 cup broth\n\nHeat.\n";
+
+/// A temporary directory for tests that is removed when it is dropped.
+#[cfg(test)]
+pub struct TempDir(std::path::PathBuf);
+
+#[cfg(test)]
+impl TempDir {
+    pub fn new() -> Self {
+        Self(std::env::temp_dir().join(format!("kitchen-files-{}", uuid::Uuid::new_v4())))
+    }
+
+    pub fn path(&self) -> std::path::PathBuf {
+        self.0.clone()
+    }
+}
+
+#[cfg(test)]
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
 }
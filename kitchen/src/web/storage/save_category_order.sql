@@ -0,0 +1,3 @@
+insert into category_order
+    (user_id, category, position)
+    values (?, ?, ?)
@@ -0,0 +1,2 @@
+insert into checked_items(user_id, plan_date, name, form, measure_type)
+    values (?, ?, ?, ?, ?) on conflict(user_id, plan_date, name, form, measure_type) DO NOTHING
\ No newline at end of file
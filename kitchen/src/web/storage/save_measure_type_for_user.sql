@@ -0,0 +1,3 @@
+insert into measure_type_overrides
+    (user_id, ingredient_name, measure_type)
+    values (?, ?, ?)
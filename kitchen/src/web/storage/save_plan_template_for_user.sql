@@ -0,0 +1 @@
+insert into plan_templates (user_id, name, recipe_id, count) values (?, ?, ?, ?)
\ No newline at end of file
@@ -0,0 +1,2 @@
+insert into purchased_amts(user_id, plan_date, name, form, measure_type, purchased)
+    values (?, ?, ?, ?, ?, ?) on conflict(user_id, plan_date, name, form, measure_type) do update set purchased=excluded.purchased
\ No newline at end of file
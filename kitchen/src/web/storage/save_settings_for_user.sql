@@ -0,0 +1,4 @@
+insert into user_settings (user_id, default_route, use_staples)
+    values (?, ?, ?)
+    on conflict (user_id)
+        do update set default_route=excluded.default_route, use_staples=excluded.use_staples
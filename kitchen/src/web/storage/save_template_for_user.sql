@@ -0,0 +1,2 @@
+insert into recipe_templates (user_id, name, template_text) values (?, ?, ?)
+    on conflict(user_id, name) do update set template_text = excluded.template_text
\ No newline at end of file
@@ -0,0 +1,4 @@
+select recipe_id, recipe_text, category, serving_count, tags from recipes
+where
+    user_id = ?
+    and (recipe_id like ? escape '\' or recipe_text like ? escape '\')
\ No newline at end of file
@@ -0,0 +1,2 @@
+insert into users (id, password_hashed) values (?, ?)
+    on conflict(id) do update set password_hashed = excluded.password_hashed
\ No newline at end of file
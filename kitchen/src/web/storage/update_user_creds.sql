@@ -0,0 +1 @@
+update users set password_hashed = ? where id = ?
\ No newline at end of file
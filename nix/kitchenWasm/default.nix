@@ -37,6 +37,7 @@ stdenv.mkDerivation {
 		sh ../scripts/wasm-sourcemap.sh
         cp -r index.html $out
         cp -r favicon.ico $out
+        cp -r icons $out
         rm -rf $out/release
         rm -rf $out/wasm32-unknown-unknown
     '';
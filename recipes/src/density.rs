@@ -0,0 +1,101 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+/*!
+Densities for common ingredients so that volume and weight measures of the
+same ingredient can be combined.
+*/
+use std::collections::BTreeMap;
+
+use num_rational::Ratio;
+
+use crate::unit::{Measure, Quantity, VolumeMeasure, WeightMeasure};
+
+/// A registry of ingredient densities in grams per ml keyed by ingredient name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DensityTable {
+    densities: BTreeMap<String, Quantity>,
+}
+
+impl DensityTable {
+    /// Constructs an empty density table.
+    pub fn new() -> Self {
+        Self {
+            densities: BTreeMap::new(),
+        }
+    }
+
+    /// Constructs a density table with densities for a few common ingredients.
+    pub fn with_defaults() -> Self {
+        let mut table = Self::new();
+        // NOTE(jwall): These are all derived from the weight of 1 cup (240 ml).
+        table.insert("flour", Quantity::Frac(Ratio::new(125, 240)));
+        table.insert("sugar", Quantity::Frac(Ratio::new(200, 240)));
+        table.insert("butter", Quantity::Frac(Ratio::new(227, 240)));
+        table.insert("rice", Quantity::Frac(Ratio::new(185, 240)));
+        table
+    }
+
+    /// Registers the density in grams per ml for an ingredient, replacing any
+    /// existing density for it.
+    pub fn insert<S: AsRef<str>>(&mut self, name: S, grams_per_ml: Quantity) {
+        self.densities
+            .insert(name.as_ref().to_lowercase(), grams_per_ml);
+    }
+
+    /// Returns the density in grams per ml for an ingredient if one is known.
+    pub fn get<S: AsRef<str>>(&self, name: S) -> Option<&Quantity> {
+        self.densities.get(&name.as_ref().to_lowercase())
+    }
+
+    /// Converts a measure of the named ingredient between weight and volume
+    /// with `Measure::convert_with_density`. Returns None if the density of
+    /// the ingredient isn't known or the measure can't be converted.
+    pub fn convert<S: AsRef<str>>(&self, name: S, amt: &Measure) -> Option<Measure> {
+        let density = match *self.get(name)? {
+            Quantity::Whole(n) => Ratio::from_integer(n),
+            Quantity::Frac(r) => r,
+        };
+        amt.convert_with_density(density)
+    }
+
+    /// Converts a volume measure of the named ingredient to a weight measure in
+    /// grams. Returns None if the measure isn't a volume or the density of the
+    /// ingredient isn't known.
+    pub fn to_weight<S: AsRef<str>>(&self, name: S, amt: &Measure) -> Option<Measure> {
+        match amt {
+            Measure::Volume(vm) => self
+                .get(name)
+                .map(|density| Measure::Weight(WeightMeasure::Gram(vm.get_ml() * *density))),
+            _ => None,
+        }
+    }
+
+    /// Converts a weight measure of the named ingredient to a volume measure in
+    /// ml. Returns None if the measure isn't a weight or the density of the
+    /// ingredient isn't known.
+    pub fn to_volume<S: AsRef<str>>(&self, name: S, amt: &Measure) -> Option<Measure> {
+        match amt {
+            Measure::Weight(wm) => self
+                .get(name)
+                .map(|density| Measure::Volume(VolumeMeasure::ML(wm.get_grams() / *density))),
+            _ => None,
+        }
+    }
+}
+
+impl Default for DensityTable {
+    fn default() -> Self {
+        Self::with_defaults()
+    }
+}
@@ -0,0 +1,258 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Import recipes from other formats into the kitchen recipe text format.
+use std::fmt::Display;
+
+use abortable_parser::{do_each, eoi, make_fn, Offsetable, Result as ParseResult, StrIter};
+use serde_json::Value;
+
+use crate::{parse, parse::ingredient, unit::Measure, Ingredient, RecipeEntry};
+
+#[derive(Debug)]
+pub enum Error {
+    /// The input was not valid json.
+    Json(serde_json::Error),
+    /// The json did not contain a schema.org Recipe.
+    NoRecipe,
+    /// The recipe was missing a required field.
+    MissingField(&'static str),
+    /// The generated recipe text failed to parse.
+    Parse(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Json(e) => write!(f, "Invalid json: {}", e),
+            Error::NoRecipe => write!(f, "No schema.org Recipe found"),
+            Error::MissingField(field) => write!(f, "Recipe is missing the {} field", field),
+            Error::Parse(e) => write!(f, "Imported recipe failed to parse: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Json(e)
+    }
+}
+
+make_fn!(
+    whole_ingredient<StrIter, Ingredient>,
+    do_each!(
+        i => ingredient,
+        _ => eoi,
+        (i)
+    )
+);
+
+/// Convert a schema.org/Recipe JSON-LD document into a RecipeEntry.
+///
+/// Each of the `recipeInstructions` becomes a step and all of the
+/// `recipeIngredient` entries are attached to the first step. Ingredients
+/// that we can't parse are imported as a count of 1 so nothing gets dropped.
+pub fn from_json_ld(json: &str) -> Result<RecipeEntry, Error> {
+    let doc: Value = serde_json::from_str(json)?;
+    let recipe = find_recipe(&doc).ok_or(Error::NoRecipe)?;
+    let title = recipe
+        .get("name")
+        .and_then(Value::as_str)
+        .map(clean_text)
+        .filter(|t| !t.is_empty())
+        .ok_or(Error::MissingField("name"))?;
+    let ingredients: Vec<String> = string_list(recipe.get("recipeIngredient"))
+        .iter()
+        .map(|i| normalize_ingredient(i))
+        .filter(|i| !i.is_empty())
+        .collect();
+    let mut instructions = Vec::new();
+    if let Some(v) = recipe.get("recipeInstructions") {
+        collect_instructions(v, &mut instructions);
+    }
+    if instructions.is_empty() {
+        return Err(Error::MissingField("recipeInstructions"));
+    }
+
+    let mut text = format!("title: {}\n", title);
+    let tags = string_list(recipe.get("keywords"))
+        .iter()
+        .flat_map(|k| k.split(','))
+        .map(|k| clean_text(k).replace(':', ""))
+        .filter(|k| !k.is_empty())
+        .collect::<Vec<String>>();
+    if !tags.is_empty() {
+        text.push_str(&format!("tags: {}\n", tags.join(", ")));
+    }
+    match recipe
+        .get("description")
+        .and_then(Value::as_str)
+        .map(clean_text)
+        .filter(|d| !d.is_empty())
+    {
+        Some(desc) => text.push_str(&format!("\n{}\n\n", desc)),
+        None => text.push_str("\n\n"),
+    }
+    let steps = instructions
+        .iter()
+        .enumerate()
+        .map(|(idx, instruction)| {
+            if idx == 0 && !ingredients.is_empty() {
+                format!("step:\n\n{}\n\n{}\n", ingredients.join("\n"), instruction)
+            } else {
+                format!("step:\n\n\n{}\n", instruction)
+            }
+        })
+        .collect::<Vec<String>>();
+    text.push_str(&steps.join("\n"));
+    // NOTE(jwall): Make sure we generated something we can actually use.
+    parse::as_recipe(&text).map_err(Error::Parse)?;
+
+    let id = title.to_lowercase().replace(" ", "_");
+    let mut entry = RecipeEntry::new(id, text);
+    if let Some(category) = string_list(recipe.get("recipeCategory")).into_iter().next() {
+        entry.set_category(category);
+    }
+    entry.serving_count = recipe.get("recipeYield").and_then(parse_yield);
+    entry.set_tags(tags);
+    Ok(entry)
+}
+
+fn is_recipe(v: &Value) -> bool {
+    match v.get("@type") {
+        Some(Value::String(t)) => t == "Recipe",
+        Some(Value::Array(ts)) => ts.iter().any(|t| t.as_str() == Some("Recipe")),
+        _ => false,
+    }
+}
+
+/// Find the first Recipe in the document. Sites embed it at the top level, in
+/// a list, or in an `@graph`.
+fn find_recipe(v: &Value) -> Option<&Value> {
+    match v {
+        Value::Object(obj) => {
+            if is_recipe(v) {
+                Some(v)
+            } else {
+                obj.get("@graph").and_then(find_recipe)
+            }
+        }
+        Value::Array(items) => items.iter().find_map(find_recipe),
+        _ => None,
+    }
+}
+
+/// Collapse whitespace and newlines in text so it fits on a single line.
+fn clean_text(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// Some fields may be either a single string or a list of strings.
+fn string_list(v: Option<&Value>) -> Vec<String> {
+    match v {
+        Some(Value::String(s)) => vec![clean_text(s)],
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(Value::as_str)
+            .map(clean_text)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn collect_instructions(v: &Value, out: &mut Vec<String>) {
+    match v {
+        Value::String(s) => {
+            out.extend(s.lines().map(clean_instruction).filter(|s| !s.is_empty()));
+        }
+        Value::Array(items) => {
+            for item in items {
+                collect_instructions(item, out);
+            }
+        }
+        Value::Object(obj) => {
+            // HowToSection's have their steps in itemListElement and HowToStep's
+            // have their instructions in text.
+            if let Some(items) = obj.get("itemListElement") {
+                collect_instructions(items, out);
+            } else if let Some(text) = obj.get("text").and_then(Value::as_str) {
+                let text = clean_instruction(text);
+                if !text.is_empty() {
+                    out.push(text);
+                }
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Clean up an instruction so it can stand on its own as a step. Numbered
+/// instructions would look like ingredients to our parser so we strip the
+/// numbering.
+fn clean_instruction(text: &str) -> String {
+    let text = clean_text(text);
+    let rest = text.trim_start_matches(|c: char| c.is_ascii_digit());
+    if rest.len() != text.len() {
+        if let Some(rest) = rest.strip_prefix(|c: char| c == '.' || c == ')' || c == ':') {
+            return rest.trim().to_owned();
+        }
+    }
+    text
+}
+
+/// Normalize an ingredient through our parser. If we can't parse it then we
+/// import it as a count of 1 with the whole line as the name.
+fn normalize_ingredient(line: &str) -> String {
+    let line = clean_text(line);
+    if line.is_empty() {
+        return line;
+    }
+    // NOTE(jwall): Our units are all lowercase but sites tend to capitalize
+    // them. A few of the short unit aliases are case sensitive though so we
+    // keep any unit we recognize before lowercasing the rest of the line.
+    let lowered = match parse::measure(StrIter::new(&line)) {
+        ParseResult::Complete(rest, measure) if !matches!(measure, Measure::Count(_)) => format!(
+            "{} {}",
+            measure,
+            line[rest.get_offset()..].trim_start().to_lowercase()
+        ),
+        _ => line.to_lowercase(),
+    };
+    if let ParseResult::Complete(_, i) = whole_ingredient(StrIter::new(&lowered)) {
+        if !i.name.is_empty() {
+            return format!("{}", i);
+        }
+    }
+    let name = clean_text(&line.replace(|c: char| c == '(' || c == ')', " "));
+    if name.starts_with(char::is_alphabetic) {
+        format!("1 {}", name)
+    } else {
+        // NOTE(jwall): Use an explicit unit so that any leading numbers in the
+        // name don't get parsed as part of the quantity.
+        format!("1 cnt {}", name)
+    }
+}
+
+fn parse_yield(v: &Value) -> Option<i64> {
+    match v {
+        Value::Number(n) => n.as_i64(),
+        Value::String(s) => s
+            .split_whitespace()
+            .next()
+            .and_then(|n| n.parse::<i64>().ok()),
+        Value::Array(items) => items.iter().find_map(parse_yield),
+        _ => None,
+    }
+}
@@ -11,7 +11,10 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+pub mod density;
+pub mod import;
 pub mod parse;
+pub mod serialize;
 pub mod unit;
 
 use std::collections::{BTreeMap, BTreeSet};
@@ -19,13 +22,25 @@ use std::collections::{BTreeMap, BTreeSet};
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
+use density::DensityTable;
 use unit::*;
 use Measure::*;
 
-#[derive(Debug, Clone, PartialEq)]
+/// A recipe planned for a specific day of a `Mealplan`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlannedRecipe {
+    pub date: NaiveDate,
+    pub recipe: Recipe,
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Mealplan {
     pub start_date: Option<NaiveDate>,
     pub recipes: Vec<Recipe>,
+    /// Recipes assigned to specific days of the plan.
+    #[serde(default)]
+    pub days: Vec<PlannedRecipe>,
 }
 
 impl Mealplan {
@@ -33,6 +48,7 @@ impl Mealplan {
         Self {
             start_date: None,
             recipes: Vec::new(),
+            days: Vec::new(),
         }
     }
 
@@ -47,6 +63,55 @@ impl Mealplan {
     {
         self.recipes.extend(recipes.into_iter())
     }
+
+    /// Plan `count` servings of a recipe for the given day.
+    pub fn add_recipe_on(&mut self, date: NaiveDate, recipe: Recipe, count: u32) {
+        self.days.push(PlannedRecipe {
+            date,
+            recipe,
+            count,
+        });
+    }
+
+    /// Plan `count` servings of a recipe for the day `offset` days after the
+    /// start date. Returns false if the plan has no start date.
+    pub fn add_recipe_on_day(&mut self, offset: u32, recipe: Recipe, count: u32) -> bool {
+        match self
+            .start_date
+            .and_then(|d| d.checked_add_days(chrono::Days::new(offset as u64)))
+        {
+            Some(date) => {
+                self.add_recipe_on(date, recipe, count);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The recipes and their counts planned for a day.
+    pub fn recipes_for(&self, date: NaiveDate) -> Vec<(&Recipe, u32)> {
+        self.days
+            .iter()
+            .filter(|p| p.date == date)
+            .map(|p| (&p.recipe, p.count))
+            .collect()
+    }
+
+    /// Accumulate the ingredients for the recipes planned from `start` to
+    /// `end` inclusive.
+    pub fn shopping_list_between(&self, start: NaiveDate, end: NaiveDate) -> IngredientAccumulator {
+        let mut acc = IngredientAccumulator::new();
+        for planned in self
+            .days
+            .iter()
+            .filter(|p| p.date >= start && p.date <= end)
+        {
+            for _ in 0..planned.count {
+                acc.accumulate_from(&planned.recipe);
+            }
+        }
+        acc
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -55,6 +120,8 @@ pub struct RecipeEntry {
     pub text: String,
     pub category: Option<String>,
     pub serving_count: Option<i64>,
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 impl RecipeEntry {
@@ -64,6 +131,7 @@ impl RecipeEntry {
             text: text.into(),
             category: None,
             serving_count: None,
+            tags: Vec::new(),
         }
     }
 
@@ -94,14 +162,80 @@ impl RecipeEntry {
     pub fn serving_count(&self) -> Option<i64> {
         self.serving_count.clone()
     }
+
+    pub fn set_tags<Iter, S>(&mut self, tags: Iter)
+    where
+        Iter: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.tags = tags.into_iter().map(|t| t.into()).collect();
+    }
+
+    pub fn tags(&self) -> &Vec<String> {
+        &self.tags
+    }
+}
+
+/// A named recipe text skeleton used as the starting point for new recipes.
+/// The text may contain `{{placeholder}}` markers. `{{name}}` is replaced with
+/// the title of the new recipe.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RecipeTemplate {
+    pub name: String,
+    pub text: String,
+}
+
+impl RecipeTemplate {
+    pub fn new<NS: Into<String>, TS: Into<String>>(name: NS, text: TS) -> Self {
+        Self {
+            name: name.into(),
+            text: text.into(),
+        }
+    }
+
+    /// Render the recipe text for a new recipe with the given title. Any other
+    /// placeholders are left for the user to fill in.
+    pub fn render(&self, title: &str) -> String {
+        self.text.replace("{{name}}", title)
+    }
+
+    /// Validate that the template parses as a recipe once the placeholder
+    /// markers have been stripped.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Template names can not be empty".to_owned());
+        }
+        parse::as_recipe(&strip_placeholders(&self.text)).map(|_| ())
+    }
+}
+
+/// Strip the markers from any `{{placeholder}}` in the text leaving just the
+/// placeholder name behind.
+fn strip_placeholders(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        match rest[start..].find("}}") {
+            Some(end) => {
+                out.push_str(&rest[..start]);
+                out.push_str(rest[start + 2..start + end].trim());
+                rest = &rest[start + end + 2..];
+            }
+            None => break,
+        }
+    }
+    out.push_str(rest);
+    out
 }
 
 /// A Recipe with a title, description, and a series of steps.
-#[derive(Debug, Clone, PartialEq, PartialOrd, Ord, Eq)]
+#[derive(Debug, Clone, PartialEq, PartialOrd, Ord, Eq, Serialize, Deserialize)]
 pub struct Recipe {
     pub title: String,
     pub desc: Option<String>,
     pub serving_count: Option<i64>,
+    #[serde(default)]
+    pub tags: Vec<String>,
     pub steps: Vec<Step>,
 }
 
@@ -112,9 +246,19 @@ impl Recipe {
             desc: desc.map(|s| s.into()),
             steps: Vec::new(),
             serving_count: Default::default(),
+            tags: Vec::new(),
         }
     }
 
+    pub fn with_tags<Iter, S>(mut self, tags: Iter) -> Self
+    where
+        Iter: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.tags = tags.into_iter().map(|t| t.into()).collect();
+        self
+    }
+
     pub fn with_steps<Iter>(mut self, steps: Iter) -> Self
     where
         Iter: IntoIterator<Item = Step>,
@@ -136,6 +280,11 @@ impl Recipe {
         self.steps.push(step);
     }
 
+    /// The sum of the prep times of every step.
+    pub fn total_prep_time(&self) -> std::time::Duration {
+        self.steps.iter().filter_map(|s| s.prep_time).sum()
+    }
+
     /// Get entire ingredients list for each step of the recipe. With duplicate
     /// ingredients added together.
     pub fn get_ingredients(&self) -> BTreeMap<IngredientKey, Ingredient> {
@@ -146,6 +295,94 @@ impl Recipe {
             .map(|(k, v)| (k, v.0))
             .collect()
     }
+
+    /// Render this recipe as markdown for sharing.
+    pub fn to_markdown(&self) -> String {
+        serialize::as_markdown(self)
+    }
+
+    /// Render this recipe as plain text for sharing.
+    pub fn to_text(&self) -> String {
+        serialize::as_plain_text(self)
+    }
+
+    /// A copy of this recipe with every volume and weight in metric units.
+    pub fn to_metric(&self) -> Self {
+        self.map_amounts(Measure::into_metric)
+    }
+
+    /// A copy of this recipe with every volume and weight in US customary
+    /// units.
+    pub fn to_us(&self) -> Self {
+        self.map_amounts(Measure::into_us)
+    }
+
+    /// A copy of this recipe with every volume and weight in `system` units.
+    pub fn to_system(&self, system: UnitSystem) -> Self {
+        self.map_amounts(|amt| amt.convert_to_system(system))
+    }
+
+    fn map_amounts<F: Fn(Measure) -> Measure>(&self, f: F) -> Self {
+        let mut recipe = self.clone();
+        for step in recipe.steps.iter_mut() {
+            for ingredient in step.ingredients.iter_mut() {
+                ingredient.amt = f(ingredient.amt.clone());
+            }
+        }
+        recipe
+    }
+
+    /// Get the ingredients needed for each step of the recipe keyed by the
+    /// step's index. Repeats of an ingredient within a step are combined but
+    /// steps are kept separate.
+    pub fn ingredients_by_step(&self) -> Vec<(usize, BTreeMap<IngredientKey, Ingredient>)> {
+        self.steps
+            .iter()
+            .enumerate()
+            .map(|(idx, step)| {
+                let mut acc = IngredientAccumulator::new();
+                acc.accumulate_ingredients_for(&self.title, step.ingredients.iter());
+                let ingredients = acc
+                    .ingredients()
+                    .into_iter()
+                    .map(|(k, v)| (k, v.0))
+                    .collect();
+                (idx, ingredients)
+            })
+            .collect()
+    }
+
+    /// Get the ingredients list for each section of the recipe in the order
+    /// the sections first appear. Steps without a section are grouped under
+    /// `None`.
+    pub fn get_ingredients_by_section(
+        &self,
+    ) -> Vec<(Option<String>, BTreeMap<IngredientKey, Ingredient>)> {
+        let mut sections: Vec<(Option<String>, IngredientAccumulator)> = Vec::new();
+        for step in self.steps.iter() {
+            let idx = match sections.iter().position(|(s, _)| *s == step.section) {
+                Some(idx) => idx,
+                None => {
+                    sections.push((step.section.clone(), IngredientAccumulator::new()));
+                    sections.len() - 1
+                }
+            };
+            sections[idx]
+                .1
+                .accumulate_ingredients_for(&self.title, step.ingredients.iter());
+        }
+        sections
+            .into_iter()
+            .map(|(section, acc)| {
+                let ingredients = acc
+                    .ingredients()
+                    .into_iter()
+                    .map(|(k, v)| (k, v.0))
+                    .collect();
+                (section, ingredients)
+            })
+            .collect()
+    }
 }
 
 impl TryFrom<&RecipeEntry> for Recipe {
@@ -154,18 +391,115 @@ impl TryFrom<&RecipeEntry> for Recipe {
     fn try_from(value: &RecipeEntry) -> Result<Self, Self::Error> {
         let mut parsed = parse::as_recipe(&value.text)?;
         parsed.serving_count = value.serving_count.clone();
+        // NOTE(jwall): Tags from the recipe text win but we keep any extra
+        // tags that were stored with the entry.
+        for tag in value.tags.iter() {
+            if !parsed.tags.contains(tag) {
+                parsed.tags.push(tag.clone());
+            }
+        }
         Ok(parsed)
     }
 }
 
 pub struct IngredientAccumulator {
     inner: BTreeMap<IngredientKey, (Ingredient, BTreeSet<String>)>,
+    /// The amount each recipe contributed to an ingredient.
+    breakdown: BTreeMap<IngredientKey, BTreeMap<String, Measure>>,
+    /// Ingredients whose amounts couldn't be combined.
+    warnings: BTreeSet<String>,
+    /// Densities used to combine volume and weight amounts of an ingredient.
+    densities: Option<DensityTable>,
+    /// Measure types to force for specific ingredients keyed by name.
+    measure_types: BTreeMap<String, MeasureType>,
 }
 
 impl IngredientAccumulator {
     pub fn new() -> Self {
         Self {
             inner: BTreeMap::new(),
+            breakdown: BTreeMap::new(),
+            warnings: BTreeSet::new(),
+            densities: None,
+            measure_types: BTreeMap::new(),
+        }
+    }
+
+    /// Use the provided density table to convert volume amounts of known
+    /// ingredients to weights when they need to be combined with weight
+    /// amounts of the same ingredient. Volumes that don't need combining are
+    /// left alone.
+    pub fn with_densities(mut self, densities: DensityTable) -> Self {
+        self.densities = Some(densities);
+        self
+    }
+
+    /// Force the measure type of specific ingredients keyed by name. Amounts
+    /// are converted to count by dropping their unit and between volume and
+    /// weight using the densities. Amounts that can't be converted are left
+    /// alone.
+    pub fn with_measure_types(mut self, measure_types: BTreeMap<String, MeasureType>) -> Self {
+        self.measure_types = measure_types;
+        self
+    }
+
+    /// The amount of an ingredient converted to the measure type forced for it.
+    fn convert_amt(&self, i: &Ingredient) -> Option<Measure> {
+        let densities = self.densities.as_ref();
+        match (self.measure_types.get(&i.name), &i.amt) {
+            (Some(MeasureType::Count), Count(_)) => None,
+            (Some(MeasureType::Count), amt) => Some(Count(amt.quantity())),
+            (Some(MeasureType::Volume), amt) => densities.and_then(|d| d.to_volume(&i.name, amt)),
+            (Some(MeasureType::Weight), amt) => densities.and_then(|d| d.to_weight(&i.name, amt)),
+            (None, _) => None,
+        }
+    }
+
+    /// Fold the volume amount of an ingredient into its weight amount when
+    /// the recipes use both and we know its density.
+    fn merge_by_density(&mut self) {
+        let densities = match &self.densities {
+            Some(densities) => densities,
+            None => return,
+        };
+        let volume_keys = self
+            .inner
+            .keys()
+            .filter(|k| *k.measure_type() == MeasureType::Volume.to_string())
+            .cloned()
+            .collect::<Vec<IngredientKey>>();
+        for key in volume_keys {
+            let weight_key = IngredientKey(
+                key.0.clone(),
+                key.1.clone(),
+                MeasureType::Weight.to_string(),
+            );
+            let merged = match (self.inner.get(&key), self.inner.get(&weight_key)) {
+                (Some((volume, _)), Some((weight, _))) => densities
+                    .to_weight(&volume.name, &volume.amt)
+                    .and_then(|amt| weight.amt.checked_add(&amt)),
+                _ => None,
+            };
+            let merged = match merged {
+                Some(merged) => merged,
+                None => continue,
+            };
+            let (_, recipes) = self.inner.remove(&key).unwrap();
+            let (weight, set) = self.inner.get_mut(&weight_key).unwrap();
+            weight.amt = merged;
+            set.extend(recipes);
+            let contributions = self.breakdown.remove(&key).unwrap_or_default();
+            let weights = self.breakdown.entry(weight_key).or_default();
+            for (recipe, amt) in contributions {
+                let amt = densities.to_weight(&weight.name, &amt).unwrap_or(amt);
+                let total = match weights.get(&recipe) {
+                    Some(existing) => existing
+                        .checked_add(&amt)
+                        .unwrap_or_else(|| existing.clone()),
+                    None => amt,
+                };
+                weights.insert(recipe, total);
+            }
         }
     }
 
@@ -175,34 +509,59 @@ impl IngredientAccumulator {
         S: Into<String>,
     {
         let recipe_title = recipe_title.into();
+        let mut divided = BTreeSet::new();
         for i in ingredients {
-            let key = i.key();
-            if !self.inner.contains_key(&key) {
-                let mut set = BTreeSet::new();
-                set.insert(recipe_title.clone());
-                self.inner.insert(key, (i.clone(), set));
-            } else {
-                let amts = match (&self.inner[&key].0.amt, &i.amt) {
-                    (Volume(rvm), Volume(lvm)) => vec![Volume(lvm + rvm)],
-                    (Count(lqty), Count(rqty)) => vec![Count(lqty + rqty)],
-                    (Weight(lqty), Weight(rqty)) => vec![Weight(lqty + rqty)],
-                    (Package(lnm, lqty), Package(rnm, rqty)) => {
-                        if lnm == rnm {
-                            vec![Package(lnm.clone(), lqty + rqty)]
-                        } else {
-                            vec![
-                                Package(lnm.clone(), lqty.clone()),
-                                Package(rnm.clone(), rqty.clone()),
-                            ]
+            // NOTE(jwall): A divided ingredient is listed again in the steps
+            // that use part of it. Only the first listing counts.
+            if i.divided && !divided.insert(i.key()) {
+                continue;
+            }
+            let converted = self
+                .convert_amt(i)
+                .map(|amt| Ingredient { amt, ..i.clone() });
+            let i = converted.as_ref().unwrap_or(i);
+            let mut key = i.key();
+            if let Some((existing, _)) = self.inner.get(&key) {
+                if !can_add_measures(&existing.amt, &i.amt) {
+                    // NOTE(jwall): We can't combine these amounts so we keep
+                    // them separate under a distinct key and warn about it.
+                    self.warnings.insert(format!(
+                        "{} in {} is measured in {} which can't be combined with {}",
+                        i.name,
+                        recipe_title,
+                        measure_label(&i.amt),
+                        measure_label(&existing.amt),
+                    ));
+                    key = mismatched_key(&key, &i.amt);
+                }
+            }
+            let contributions = self.breakdown.entry(key.clone()).or_default();
+            let contribution = match contributions.get(&recipe_title) {
+                Some(amt) => amt.checked_add(&i.amt).unwrap_or_else(|| amt.clone()),
+                None => i.amt.clone(),
+            };
+            contributions.insert(recipe_title.clone(), contribution);
+            match self.inner.get_mut(&key) {
+                Some((existing, set)) => {
+                    match existing.amt.checked_add(&i.amt) {
+                        Some(amt) => existing.amt = amt,
+                        None => {
+                            self.warnings.insert(format!(
+                                "{} in {} is too large to add to the shopping list",
+                                i.name, recipe_title,
+                            ));
                         }
                     }
-                    _ => unreachable!(),
-                };
-                for amt in amts {
-                    self.inner.get_mut(&key).map(|(i, set)| {
-                        i.amt = amt;
-                        set.insert(recipe_title.clone());
-                    });
+                    // NOTE(jwall): Prefer the quoted casing if any recipe has it.
+                    if existing.display_name.is_none() {
+                        existing.display_name = i.display_name.clone();
+                    }
+                    set.insert(recipe_title.clone());
+                }
+                None => {
+                    let mut set = BTreeSet::new();
+                    set.insert(recipe_title.clone());
+                    self.inner.insert(key, (i.clone(), set));
                 }
             }
         }
@@ -215,18 +574,126 @@ impl IngredientAccumulator {
         );
     }
 
-    pub fn ingredients(self) -> BTreeMap<IngredientKey, (Ingredient, BTreeSet<String>)> {
+    /// Accumulate the ingredients of a recipe and of every recipe it uses as
+    /// a sub-recipe. Sub-recipes are looked up by id in `recipes`. References
+    /// to unknown recipes and to a recipe that is already being expanded are
+    /// skipped with a warning.
+    pub fn accumulate_from_with(&mut self, r: &Recipe, recipes: &BTreeMap<String, Recipe>) {
+        // NOTE(jwall): The recipe may be in the map itself so we seed the
+        // cycle guard with its id to catch recipes that refer back to it.
+        let mut expanding = recipes
+            .iter()
+            .filter(|(_, recipe)| *recipe == r)
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<String>>();
+        self.accumulate_with_subs(r, recipes, &mut expanding);
+    }
+
+    fn accumulate_with_subs(
+        &mut self,
+        r: &Recipe,
+        recipes: &BTreeMap<String, Recipe>,
+        expanding: &mut Vec<String>,
+    ) {
+        self.accumulate_from(r);
+        for id in r.steps.iter().flat_map(|s| s.sub_recipes.iter()) {
+            if expanding.contains(id) {
+                self.warnings.insert(format!(
+                    "{} uses recipe {} which uses it in turn",
+                    r.title, id
+                ));
+                continue;
+            }
+            match recipes.get(id) {
+                Some(sub) => {
+                    expanding.push(id.clone());
+                    self.accumulate_with_subs(sub, recipes, expanding);
+                    expanding.pop();
+                }
+                None => {
+                    self.warnings
+                        .insert(format!("{} uses unknown recipe {}", r.title, id));
+                }
+            }
+        }
+    }
+
+    pub fn ingredients(mut self) -> BTreeMap<IngredientKey, (Ingredient, BTreeSet<String>)> {
+        self.merge_by_density();
+        self.inner
+    }
+
+    /// Warnings for any ingredients whose amounts couldn't be combined. These
+    /// ingredients are kept as separate entries.
+    pub fn warnings(&self) -> &BTreeSet<String> {
+        &self.warnings
+    }
+
+    /// Returns the accumulated ingredients along with the amount that each
+    /// recipe contributed to them.
+    pub fn ingredients_with_breakdown(
+        mut self,
+    ) -> BTreeMap<IngredientKey, (Ingredient, BTreeMap<String, Measure>)> {
+        self.merge_by_density();
         self.inner
+            .into_iter()
+            .map(|(k, (i, _))| {
+                let breakdown = self.breakdown.remove(&k).unwrap_or_default();
+                (k, (i, breakdown))
+            })
+            .collect()
+    }
+}
+
+/// Merge ingredients with the same key into one entry with their combined
+/// amount. The merged ingredients are sorted by key.
+pub fn merge_ingredients<I: IntoIterator<Item = Ingredient>>(ingredients: I) -> Vec<Ingredient> {
+    let ingredients = ingredients.into_iter().collect::<Vec<Ingredient>>();
+    let mut acc = IngredientAccumulator::new();
+    acc.accumulate_ingredients_for("", ingredients.iter());
+    acc.ingredients().into_values().map(|(i, _)| i).collect()
+}
+
+/// A human readable label for the kind of measure.
+fn measure_label(amt: &Measure) -> String {
+    match amt {
+        Package(nm, _) => nm.to_string(),
+        _ => amt.measure_type().to_lowercase(),
+    }
+}
+
+/// A distinct key for an ingredient whose amount doesn't match the amount
+/// already accumulated under its key.
+fn mismatched_key(key: &IngredientKey, amt: &Measure) -> IngredientKey {
+    let measure_type = match amt {
+        Package(nm, _) => format!("Package({})", nm),
+        _ => amt.measure_type(),
+    };
+    IngredientKey(key.0.clone(), key.1.clone(), measure_type)
+}
+
+/// Whether two measures are the same kind of measure and can be added together.
+fn can_add_measures(lhs: &Measure, rhs: &Measure) -> bool {
+    match (lhs, rhs) {
+        (Volume(_), Volume(_)) | (Count(_), Count(_)) | (Weight(_), Weight(_)) => true,
+        (Package(lnm, _), Package(rnm, _)) => lnm == rnm,
+        _ => false,
     }
 }
 
 /// A Recipe step. It has the time for the step if there is one, instructions, and an ingredients
 /// list.
-#[derive(Debug, Clone, PartialEq, PartialOrd, Ord, Eq)]
+#[derive(Debug, Clone, PartialEq, PartialOrd, Ord, Eq, Serialize, Deserialize)]
 pub struct Step {
     pub prep_time: Option<std::time::Duration>,
     pub instructions: String,
     pub ingredients: Vec<Ingredient>,
+    /// The named section of the recipe this step belongs to. e.g. `Sauce`
+    #[serde(default)]
+    pub section: Option<String>,
+    /// The ids of other recipes used in this step. e.g. `recipe:pizza_dough`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sub_recipes: Vec<String>,
 }
 
 impl Step {
@@ -235,9 +702,16 @@ impl Step {
             prep_time,
             instructions: instructions.into(),
             ingredients: Vec::new(),
+            section: None,
+            sub_recipes: Vec::new(),
         }
     }
 
+    pub fn with_section<S: Into<String>>(mut self, section: S) -> Step {
+        self.section = Some(section.into());
+        self
+    }
+
     pub fn with_ingredients<Iter>(mut self, ingredients: Iter) -> Step
     where
         Iter: IntoIterator<Item = Ingredient>,
@@ -256,16 +730,43 @@ impl Step {
     pub fn add_ingredient(&mut self, ingredient: Ingredient) {
         self.ingredients.push(ingredient);
     }
+
+    pub fn with_sub_recipes<Iter>(mut self, ids: Iter) -> Step
+    where
+        Iter: IntoIterator<Item = String>,
+    {
+        self.sub_recipes.extend(ids);
+        self
+    }
 }
 
 /// Unique identifier for an Ingredient. Ingredients are identified by name, form,
 /// and measurement type. (Volume, Count, Weight)
+///
+/// The name and form are normalized so that differences in case, whitespace,
+/// and plurals don't produce distinct keys. Deserialized keys are normalized
+/// too so keys stored before the normalization rules changed still match.
 #[derive(PartialEq, PartialOrd, Eq, Ord, Clone, Hash, Debug, Deserialize, Serialize)]
+#[serde(from = "(String, Option<String>, String)")]
 pub struct IngredientKey(String, Option<String>, String);
 
+impl From<(String, Option<String>, String)> for IngredientKey {
+    fn from((name, form, measure_type): (String, Option<String>, String)) -> Self {
+        Self::new(name, form, measure_type)
+    }
+}
+
 impl IngredientKey {
     pub fn new(name: String, form: Option<String>, measure_type: String) -> Self {
-        Self(name, form, measure_type)
+        let form = form
+            .map(|f| {
+                f.split_whitespace()
+                    .map(|p| p.to_lowercase())
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            })
+            .filter(|f| !f.is_empty());
+        Self(parse::normalize_key_name(&name), form, measure_type)
     }
 
     pub fn name(&self) -> &String {
@@ -281,14 +782,43 @@ impl IngredientKey {
     }
 }
 
+/// The prefix of an ingredient line that uses another recipe. e.g.
+/// `recipe:pizza_dough`
+pub const RECIPE_REF_PREFIX: &str = "recipe:";
+
+/// The modifier that marks an ingredient as divided between steps.
+pub const DIVIDED: &str = "divided";
+
+/// Split the divided marker off of an ingredient modifier. e.g. `chopped,
+/// divided` is the form `chopped` of a divided ingredient.
+pub fn split_divided(modifier: &str) -> (Option<String>, bool) {
+    if modifier.trim().eq_ignore_ascii_case(DIVIDED) {
+        return (None, true);
+    }
+    match modifier.rsplit_once(',') {
+        Some((form, marker)) if marker.trim().eq_ignore_ascii_case(DIVIDED) => {
+            (Some(form.trim().to_owned()), true)
+        }
+        _ => (Some(modifier.to_owned()), false),
+    }
+}
+
 /// Ingredient in a recipe. The `name` and `form` fields with the measurement type
 /// uniquely identify an ingredient.
-#[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord)]
+#[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord, Serialize, Deserialize)]
 pub struct Ingredient {
     pub id: Option<i64>, // TODO(jwall): use uuid instead?
     pub name: String,
     pub form: Option<String>,
     pub amt: Measure,
+    /// The name as it was written in the recipe when it was quoted to preserve
+    /// its casing. The `name` is still used to identify the ingredient.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    /// Whether the amount is divided between steps. e.g. `1 cup sugar
+    /// (divided)`. A divided ingredient is only counted once per recipe.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub divided: bool,
 }
 
 impl Ingredient {
@@ -298,6 +828,8 @@ impl Ingredient {
             name: name.into(),
             form,
             amt,
+            display_name: None,
+            divided: false,
         }
     }
 
@@ -312,12 +844,41 @@ impl Ingredient {
             name: name.into(),
             form,
             amt,
+            display_name: None,
+            divided: false,
+        }
+    }
+
+    /// Show this Ingredient with a different name than the one it's matched
+    /// on. e.g. `Green Onions (Scallions)` for `green onion`.
+    pub fn with_display_name<S: Into<String>>(mut self, display_name: S) -> Self {
+        self.display_name = Some(display_name.into());
+        self
+    }
+
+    /// Mark this Ingredient as divided between steps.
+    pub fn divided(mut self) -> Self {
+        self.divided = true;
+        self
+    }
+
+    /// The form as written in recipe text including the divided marker.
+    pub fn form_text(&self) -> Option<String> {
+        match (&self.form, self.divided) {
+            (Some(form), true) => Some(format!("{}, {}", form, DIVIDED)),
+            (None, true) => Some(DIVIDED.to_owned()),
+            (form, false) => form.clone(),
         }
     }
 
+    /// The name to show for this Ingredient.
+    pub fn name_for_display(&self) -> &str {
+        self.display_name.as_deref().unwrap_or(&self.name)
+    }
+
     /// Unique identifier for this Ingredient.
     pub fn key(&self) -> IngredientKey {
-        return IngredientKey(
+        return IngredientKey::new(
             self.name.clone(),
             self.form.clone(),
             self.amt.measure_type(),
@@ -327,8 +888,8 @@ impl Ingredient {
 
 impl std::fmt::Display for Ingredient {
     fn fmt(&self, w: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(w, "{} {}", self.amt, self.name)?;
-        if let Some(f) = &self.form {
+        write!(w, "{} {}", self.amt, self.name_for_display())?;
+        if let Some(f) = self.form_text() {
             write!(w, " ({})", f)?;
         }
         Ok(())
@@ -17,28 +17,70 @@ use std::time::Duration;
 
 use abortable_parser::{
     ascii_digit, consume_all, discard, do_each, either, eoi, make_fn, must, not, optional, peek,
-    repeat, separated, text_token, trap, until, with_err, Error, Positioned, Result, StrIter,
+    repeat, separated, text_token, trap, until, with_err, Error, Offsetable, Positioned, Result,
+    StrIter,
 };
 use inflector::Inflector;
 use num_rational::Ratio;
+use serde::{Deserialize, Serialize};
 
 use crate::{
+    split_divided,
     unit::{Measure, Measure::*, Quantity, VolumeMeasure::*, WeightMeasure::*},
-    Ingredient, Recipe, Step,
+    Ingredient, Recipe, Step, RECIPE_REF_PREFIX,
 };
 
+/// A parse failure along with where in the input it happened.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParseError {
+    pub message: String,
+    /// The byte offset into the input.
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // TODO(jwall): It would be nice if we can display out the context line as well here.
+        write!(
+            f,
+            "{} at line {} column {}",
+            self.message, self.line, self.column
+        )
+    }
+}
+
+impl<'a> From<Error<StrIter<'a>>> for ParseError {
+    fn from(err: Error<StrIter<'a>>) -> Self {
+        let context = err.get_context();
+        Self {
+            message: err.get_msg().to_string(),
+            offset: context.get_offset(),
+            line: context.line(),
+            column: context.column(),
+        }
+    }
+}
+
 fn format_err(err: Error<StrIter>) -> String {
-    let msg = err.get_msg();
-    let context = err.get_context();
-    let (line, column) = (context.line(), context.column());
-    // TODO(jwall): It would be nice if we can display out the context line as well here.
-    format!("{} at line {} column {}", msg, line, column)
+    ParseError::from(err).to_string()
 }
 
 pub fn as_recipe(i: &str) -> std::result::Result<Recipe, String> {
+    as_recipe_detailed(i).map_err(|e| e.to_string())
+}
+
+/// Parse a recipe returning the position of any parse failure.
+pub fn as_recipe_detailed(i: &str) -> std::result::Result<Recipe, ParseError> {
     match recipe(StrIter::new(i)) {
-        Result::Abort(e) | Result::Fail(e) => Err(format_err(e)),
-        Result::Incomplete(_) => Err(format!("Incomplete recipe can not parse")),
+        Result::Abort(e) | Result::Fail(e) => Err(e.into()),
+        Result::Incomplete(ctx) => Err(ParseError {
+            message: "Incomplete recipe can not parse".to_owned(),
+            offset: ctx.get_offset(),
+            line: ctx.line(),
+            column: ctx.column(),
+        }),
         Result::Complete(_, r) => Ok(r),
     }
 }
@@ -67,6 +109,53 @@ pub fn as_ingredient_list(i: &str) -> std::result::Result<Vec<Ingredient>, Strin
     }
 }
 
+/// Parse a single ingredient line. Fails if anything besides whitespace is
+/// left over after the ingredient.
+pub fn as_ingredient(i: &str) -> std::result::Result<Ingredient, String> {
+    match ingredient(StrIter::new(i)) {
+        Result::Abort(e) | Result::Fail(e) => Err(format_err(e)),
+        Result::Incomplete(_) => Err(format!("Incomplete ingredient can not parse")),
+        Result::Complete(mut rest, m) => loop {
+            let mut next = rest.clone();
+            match next.next() {
+                Some(c) if c.is_ascii_whitespace() => rest = next,
+                Some(_) => return Err(format!("Invalid ingredient: {}", i.trim())),
+                None => return Ok(m),
+            }
+        },
+    }
+}
+
+/// Parse an ingredient list returning the position of any parse failure.
+/// Unlike `as_ingredient_list` this fails if anything besides whitespace is
+/// left over after the list.
+pub fn as_ingredient_list_detailed(i: &str) -> std::result::Result<Vec<Ingredient>, ParseError> {
+    match ingredient_list(StrIter::new(i)) {
+        Result::Abort(e) | Result::Fail(e) => Err(e.into()),
+        Result::Incomplete(ctx) => Err(ParseError {
+            message: "Incomplete ingredient list can not parse".to_owned(),
+            offset: ctx.get_offset(),
+            line: ctx.line(),
+            column: ctx.column(),
+        }),
+        Result::Complete(mut rest, m) => loop {
+            let mut next = rest.clone();
+            match next.next() {
+                Some(c) if c.is_ascii_whitespace() => rest = next,
+                Some(_) => {
+                    return Err(ParseError {
+                        message: "Invalid ingredient".to_owned(),
+                        offset: rest.get_offset(),
+                        line: rest.line(),
+                        column: rest.column(),
+                    })
+                }
+                None => return Ok(m),
+            }
+        },
+    }
+}
+
 make_fn!(
     pub categories<StrIter, BTreeMap<String, String>>,
     do_each!(
@@ -137,7 +226,7 @@ make_fn!(
             discard!(eoi),
             discard!(peek!(text_token!("\n")))
         ),
-        (normalize_name(ingredient))
+        (normalize_key_name(ingredient))
     )
 
 );
@@ -146,15 +235,18 @@ make_fn!(
     pub recipe<StrIter, Recipe>,
     do_each!(
         title => must!(title),
-        _ => optional!(para_separator),
+        tags => optional!(tags),
+        // NOTE(jwall): The title and tags consume their own newline so a
+        // single blank line is enough to separate them from what follows.
+        _ => optional!(either!(discard!(para_separator), discard!(text_token!("\n")))),
         desc => optional!(do_each!(
-            _ => peek!(not!(step_prefix)),
+            _ => peek!(not!(either!(discard!(section_prefix), discard!(step_prefix)))),
             desc => description,
             (desc)
         )),
         _ => optional!(para_separator),
         steps => step_list,
-        (Recipe::new(title, desc).with_steps(steps))
+        (Recipe::new(title, desc).with_tags(tags.unwrap_or_default()).with_steps(steps))
     )
 );
 
@@ -169,6 +261,22 @@ make_fn!(
     )
 );
 
+make_fn!(
+    pub tags<StrIter, Vec<String>>,
+    do_each!(
+        _ => text_token!("tags:"),
+        _ => optional!(ws),
+        tags => until!(text_token!("\n")),
+        _ => text_token!("\n"),
+        (tags
+            .split(',')
+            .map(|t| t.trim())
+            .filter(|t| !t.is_empty())
+            .map(|t| t.to_owned())
+            .collect())
+    )
+);
+
 make_fn!(
     para_separator<StrIter, &str>,
     do_each!(
@@ -188,34 +296,53 @@ make_fn!(
 );
 
 make_fn!(
-    pub step_time<StrIter, Duration>,
+    step_time_part<StrIter, Duration>,
     do_each!(
         cnt => num,
         _ => optional!(ws),
         u => either!(
             text_token!("ms"),
+            text_token!("seconds"),
+            text_token!("second"),
+            text_token!("secs"),
             text_token!("sec"),
             text_token!("s"),
+            text_token!("minutes"),
+            text_token!("minute"),
+            text_token!("mins"),
             text_token!("min"),
             text_token!("m"),
+            text_token!("hours"),
+            text_token!("hour"),
             text_token!("hrs"),
             text_token!("hr"),
             text_token!("h")
         ),
         (
-            Duration::from_secs(
-                match u {
-                    "ms" => cnt / 1000,
-                    "s" | "sec" => cnt.into(),
-                    "m" | "min" => dbg!(cnt) * 60,
-                    "h" | "hr" | "hrs" => cnt * 60 * 60,
-                    _ => unreachable!(),
-                }.into()
-            )
+            match u {
+                "ms" => Duration::from_millis(cnt.into()),
+                "s" | "sec" | "secs" | "second" | "seconds" => Duration::from_secs(cnt.into()),
+                "m" | "min" | "mins" | "minute" | "minutes" => Duration::from_secs(u64::from(cnt) * 60),
+                "h" | "hr" | "hrs" | "hour" | "hours" => Duration::from_secs(u64::from(cnt) * 60 * 60),
+                _ => unreachable!(),
+            }
         )
     )
 );
 
+make_fn!(
+    pub step_time<StrIter, Duration>,
+    do_each!(
+        first => step_time_part,
+        rest => repeat!(do_each!(
+            _ => optional!(ws),
+            part => step_time_part,
+            (part)
+        )),
+        (rest.into_iter().fold(first, |total, part| total + part))
+    )
+);
+
 make_fn!(
     pub step_prefix<StrIter, Option<Duration>>,
     do_each!(
@@ -223,7 +350,7 @@ make_fn!(
         dur => optional!(do_each!(
             _ => ws,
             dur => step_time,
-            (dbg!(dur))
+            (dur)
         )),
         _ => optional!(ws),
         _ => para_separator,
@@ -231,15 +358,50 @@ make_fn!(
     )
 );
 
+make_fn!(
+    pub section_prefix<StrIter, &str>,
+    do_each!(
+        _ => text_token!("section:"),
+        _ => optional!(ws),
+        section => until!(text_token!("\n")),
+        _ => text_token!("\n"),
+        _ => repeat!(text_token!("\n")),
+        (section.trim())
+    )
+);
+
 make_fn!(
     pub step<StrIter, Step>,
     do_each!(
+        section => optional!(section_prefix),
         dur => step_prefix,
-        ingredients => with_err!(must!(ingredient_list), "Missing ingredient list"),
-        _ => para_separator,
+        lines => with_err!(must!(either!(
+            do_each!(
+                lines => step_line_list,
+                _ => para_separator,
+                (lines)
+            ),
+            // NOTE(jwall): A step without ingredients leaves a blank line
+            // where the ingredient list would be.
+            do_each!(
+                _ => optional!(ws),
+                _ => text_token!("\n"),
+                (Vec::new())
+            )
+        )), "Missing ingredient list"),
         desc => description,
         _ => either!(discard!(para_separator), eoi),
-        (Step::new(dur, desc).with_ingredients(ingredients))
+        ({
+            let mut step = Step::new(dur, desc);
+            for line in lines {
+                match line {
+                    StepLine::Ingredient(i) => step.add_ingredient(i),
+                    StepLine::Recipe(id) => step.sub_recipes.push(id),
+                }
+            }
+            step.section = section.filter(|s| !s.is_empty()).map(|s| s.to_owned());
+            step
+        })
     )
 );
 
@@ -251,6 +413,16 @@ make_fn!(
         ({
             let mut steps = vec![first_step];
             steps.extend(rest);
+            // NOTE(jwall): A section applies to every step after it until the
+            // next section.
+            let mut section = None;
+            for step in steps.iter_mut() {
+                if step.section.is_some() {
+                    section = step.section.clone();
+                } else {
+                    step.section = section.clone();
+                }
+            }
             steps
         })
     )
@@ -287,7 +459,7 @@ make_fn!(num<StrIter, u32>,
 );
 
 make_fn!(
-    pub ratio<StrIter, Ratio<u32>>,
+    ascii_ratio<StrIter, Ratio<u32>>,
     do_each!(
         // First we assert non-zero numerator
         //_ => nonzero,
@@ -298,9 +470,93 @@ make_fn!(
     )
 );
 
-make_fn!(unit<StrIter, String>,
+macro_rules! vulgar_fraction {
+    ($i:expr, $c:expr, $numer:expr, $denom:expr) => {
+        do_each!($i,
+            _ => text_token!($c),
+            (Ratio::new($numer, $denom))
+        )
+    };
+}
+
+make_fn!(
+    pub unicode_fraction<StrIter, Ratio<u32>>,
+    either!(
+        vulgar_fraction!("½", 1, 2),
+        vulgar_fraction!("⅓", 1, 3),
+        vulgar_fraction!("⅔", 2, 3),
+        vulgar_fraction!("¼", 1, 4),
+        vulgar_fraction!("¾", 3, 4),
+        vulgar_fraction!("⅕", 1, 5),
+        vulgar_fraction!("⅖", 2, 5),
+        vulgar_fraction!("⅗", 3, 5),
+        vulgar_fraction!("⅘", 4, 5),
+        vulgar_fraction!("⅙", 1, 6),
+        vulgar_fraction!("⅚", 5, 6),
+        vulgar_fraction!("⅐", 1, 7),
+        vulgar_fraction!("⅛", 1, 8),
+        vulgar_fraction!("⅜", 3, 8),
+        vulgar_fraction!("⅝", 5, 8),
+        vulgar_fraction!("⅞", 7, 8),
+        vulgar_fraction!("⅑", 1, 9),
+        vulgar_fraction!("⅒", 1, 10)
+    )
+);
+
+make_fn!(
+    pub ratio<StrIter, Ratio<u32>>,
+    either!(ascii_ratio, unicode_fraction)
+);
+
+macro_rules! unit_alias {
+    ($i:expr, $alias:expr, $unit:expr) => {
+        do_each!($i,
+            _ => text_token!($alias),
+            // NOTE(jwall): Aliases are short so they must be followed by
+            // whitespace or the end of the input to keep them from eating
+            // the start of a name.
+            _ => peek!(either!(discard!(ws), eoi)),
+            ($unit.to_owned())
+        )
+    };
+}
+
+make_fn!(
+    unit_alias<StrIter, String>,
+    either!(
+        unit_alias!("imperial fl oz", "impfloz"),
+        unit_alias!("imperial floz", "impfloz"),
+        unit_alias!("imperial pints", "imppint"),
+        unit_alias!("imperial pint", "imppint"),
+        unit_alias!("imp fl oz", "impfloz"),
+        unit_alias!("imp floz", "impfloz"),
+        unit_alias!("imp pints", "imppint"),
+        unit_alias!("imp pint", "imppint"),
+        unit_alias!("fl. oz.", "floz"),
+        unit_alias!("fl oz", "floz"),
+        unit_alias!("oz.", "oz"),
+        unit_alias!("g.", "g"),
+        unit_alias!("tbls", "tbsp"),
+        unit_alias!("tbl", "tbsp"),
+        unit_alias!("T", "tbsp"),
+        unit_alias!("t", "tsp"),
+        unit_alias!("c", "cup"),
+        unit_alias!("pt", "pint"),
+        unit_alias!("qt", "qrt"),
+        unit_alias!("each", "cnt"),
+        unit_alias!("ea.", "cnt"),
+        unit_alias!("ea", "cnt")
+    )
+);
+
+make_fn!(
+    full_unit<StrIter, String>,
     do_each!(
         u => either!(
+            text_token!("pinches"),
+            text_token!("pinch"),
+            text_token!("dashes"),
+            text_token!("dash"),
             text_token!("tsps"),
             text_token!("tsp"),
             text_token!("teaspoons"),
@@ -342,11 +598,18 @@ make_fn!(unit<StrIter, String>,
             text_token!("bag"),
             text_token!("can")
             ),
-        _ => ws,
         (u.to_lowercase().to_singular())
     )
 );
 
+make_fn!(unit<StrIter, String>,
+    do_each!(
+        u => either!(unit_alias, full_unit),
+        _ => either!(discard!(ws), eoi),
+        (u)
+    )
+);
+
 make_fn!(
     pub quantity<StrIter, Quantity>,
      either!(
@@ -357,6 +620,14 @@ make_fn!(
             _ => ws,
             (Quantity::Whole(whole) + Quantity::Frac(frac))
         ),
+        // Unicode fractions are commonly written directly after the whole
+        // number with no separating whitespace. e.g. 1½
+        do_each!(
+            whole => num,
+            frac => unicode_fraction,
+            _ => ws,
+            (Quantity::Whole(whole) + Quantity::Frac(frac))
+        ),
         do_each!(
             frac => ratio,
             _ => ws,
@@ -370,11 +641,55 @@ make_fn!(
     )
 );
 
+/// The measure for an amount of a singular unit. Returns None for units we
+/// don't know.
+fn unit_measure(unit: &str, qty: Quantity) -> Option<Measure> {
+    Some(match unit {
+        "tbsp" | "tablespoon" => Volume(Tbsp(qty)),
+        "pinch" => Volume(Pinch(qty)),
+        "dash" => Volume(Dash(qty)),
+        "tsp" | "teaspoon" => Volume(Tsp(qty)),
+        "floz" => Volume(Floz(qty)),
+        "impfloz" => Volume(ImpFloz(qty)),
+        "imppint" => Volume(ImpPint(qty)),
+        "ml" => Volume(ML(qty)),
+        "ltr" | "liter" => Volume(Ltr(qty)),
+        "cup" | "cp" => Volume(Cup(qty)),
+        "qrt" | "quart" => Volume(Qrt(qty)),
+        "pint" | "pnt" => Volume(Pint(qty)),
+        "gal" => Volume(Gal(qty)),
+        "cnt" | "count" => Count(qty),
+        "lb" | "pound" => Weight(Pound(qty)),
+        "oz" => Weight(Oz(qty)),
+        "kg" | "kilogram" => Weight(Kilogram(qty)),
+        "g" | "gram" => Weight(Gram(qty)),
+        "pkg" | "package" | "can" | "bag" | "bottle" | "bot" => Measure::pkg(unit, qty),
+        _ => return None,
+    })
+}
+
+/// A unit we know how to measure with. Unknown units fail so that they are
+/// left as part of the ingredient name.
+fn known_unit(i: StrIter) -> Result<StrIter, String> {
+    match unit(i.clone()) {
+        Result::Complete(rest, u) => {
+            if unit_measure(&u, Quantity::Whole(1)).is_some() {
+                Result::Complete(rest, u)
+            } else {
+                Result::Fail(Error::new(format!("Unknown unit: {}", u), Box::new(i)))
+            }
+        }
+        Result::Fail(e) => Result::Fail(e),
+        Result::Abort(e) => Result::Abort(e),
+        Result::Incomplete(i) => Result::Incomplete(i),
+    }
+}
+
 make_fn!(
     pub measure_parts<StrIter, (Quantity, Option<String>)>,
     do_each!(
         qty => quantity,
-        unit => optional!(unit),
+        unit => optional!(known_unit),
         ((qty, unit))
     )
 );
@@ -382,32 +697,11 @@ make_fn!(
 pub fn measure(i: StrIter) -> abortable_parser::Result<StrIter, Measure> {
     match measure_parts(i) {
         Result::Complete(i, (qty, unit)) => {
-            let count = Count(qty.clone());
-            return Result::Complete(
-                i.clone(),
-                unit.map(|s| match s.as_str() {
-                    "tbsp" | "tablespoon" => Volume(Tbsp(qty)),
-                    "tsp" | "teaspoon" => Volume(Tsp(qty)),
-                    "floz" => Volume(Floz(qty)),
-                    "ml" => Volume(ML(qty)),
-                    "ltr" | "liter" => Volume(Ltr(qty)),
-                    "cup" | "cp" => Volume(Cup(qty)),
-                    "qrt" | "quart" => Volume(Qrt(qty)),
-                    "pint" | "pnt" => Volume(Pint(qty)),
-                    "gal" => Volume(Gal(qty)),
-                    "cnt" | "count" => Count(qty),
-                    "lb" | "pound" => Weight(Pound(qty)),
-                    "oz" => Weight(Oz(qty)),
-                    "kg" | "kilogram" => Weight(Kilogram(qty)),
-                    "g" | "gram" => Weight(Gram(qty)),
-                    "pkg" | "package" | "can" | "bag" | "bottle" | "bot" => Measure::pkg(s, qty),
-                    _u => {
-                        eprintln!("Invalid unit: {}", _u);
-                        unreachable!()
-                    }
-                })
-                .unwrap_or(count),
-            );
+            let measure = match unit {
+                Some(u) => unit_measure(&u, qty.clone()).unwrap_or(Count(qty)),
+                None => Count(qty),
+            };
+            return Result::Complete(i, measure);
         }
         Result::Fail(e) => {
             return Result::Fail(e);
@@ -433,19 +727,230 @@ pub fn normalize_name(name: &str) -> String {
     return name.trim().to_lowercase().to_owned();
 }
 
-make_fn!(
-    pub ingredient_name<StrIter, String>,
-    do_each!(
-        name => until!(either!(
-            discard!(text_token!("\n")),
-            eoi,
-            discard!(text_token!("(")))),
-        (normalize_name(name))
-    )
-);
+/// Words that look plural but that the inflector mangles when singularizing
+/// them. e.g. "hummus" would become "hummu".
+pub const SINGULAR_EXCEPTIONS: &[&str] = &[
+    "asparagus",
+    "bass",
+    "brussels",
+    "citrus",
+    "couscous",
+    "grits",
+    "hibiscus",
+    "hummus",
+    "lemongrass",
+    "molasses",
+    "octopus",
+    "swiss",
+    "watercress",
+];
+
+/// Singularize a lowercase word unless it is one of the exceptions.
+pub fn singularize(word: &str, exceptions: &[&str]) -> String {
+    if exceptions.contains(&word) {
+        return word.to_owned();
+    }
+    // NOTE(jwall): Inflector turns `tomatoes` into `tomat`.
+    if let Some(stem) = word.strip_suffix("oes") {
+        return format!("{}o", stem);
+    }
+    word.to_singular()
+}
+
+/// Inflect the head noun of a name for a count of items. The head noun is
+/// the last word before any parenthetical or comma. The rest of the name and
+/// its casing are left alone. e.g. `green onions` or `eggs (beaten)`
+///
+/// Names that already have a plural word before the head noun count
+/// something other than the head noun so they are left alone. e.g. `cloves
+/// garlic` is not `cloves garlics`.
+pub fn inflect_name(name: &str, plural: bool) -> String {
+    let split = name.find(|c| c == '(' || c == ',').unwrap_or(name.len());
+    let (head, rest) = name.split_at(split);
+    let mut parts: Vec<String> = head.split_whitespace().map(|p| p.to_owned()).collect();
+    let counted_elsewhere = parts.len() > 1
+        && parts[..parts.len() - 1].iter().any(|word| {
+            let word = word.to_lowercase();
+            singularize(&word, SINGULAR_EXCEPTIONS) != word
+        });
+    if parts.is_empty() || counted_elsewhere {
+        return name.to_owned();
+    }
+    if let Some(last) = parts.last_mut() {
+        if !SINGULAR_EXCEPTIONS.contains(&last.to_lowercase().as_str()) {
+            let singular = singularize(last, &[]);
+            *last = if plural {
+                singular.to_plural()
+            } else {
+                singular
+            };
+        }
+    }
+    let mut inflected = parts.join(" ");
+    if !rest.is_empty() {
+        if !rest.starts_with(',') {
+            inflected.push(' ');
+        }
+        inflected.push_str(rest);
+    }
+    inflected
+}
+
+/// Normalize an ingredient name for use in an `IngredientKey`. The name is
+/// lowercased, its whitespace is collapsed, and the last word is singularized
+/// so that "Green Onions" and "green onion" identify the same ingredient.
+/// Words in [SINGULAR_EXCEPTIONS] are left alone.
+pub fn normalize_key_name(name: &str) -> String {
+    normalize_key_name_with(name, SINGULAR_EXCEPTIONS)
+}
+
+/// Like [normalize_key_name] but with a custom list of words to leave alone
+/// when singularizing.
+pub fn normalize_key_name_with(name: &str, exceptions: &[&str]) -> String {
+    let mut parts: Vec<String> = name.split_whitespace().map(|p| p.to_lowercase()).collect();
+    if let Some(last) = parts.last_mut() {
+        *last = singularize(last, exceptions);
+    }
+    parts.join(" ")
+}
+
+/// Returns the bytes of the rest of the current line without consuming them.
+fn peek_line(i: &StrIter) -> Vec<u8> {
+    let mut line = Vec::new();
+    let mut rest = i.clone();
+    while let Some(c) = rest.next() {
+        if *c == b'\n' {
+            break;
+        }
+        line.push(*c);
+    }
+    line
+}
+
+fn advance<'a>(i: &StrIter<'a>, count: usize) -> StrIter<'a> {
+    let mut rest = i.clone();
+    for _ in 0..count {
+        rest.next();
+    }
+    rest
+}
+
+/// Finds the index of the `)` that closes the `(` at the start of the line.
+fn closing_paren(line: &[u8]) -> Option<usize> {
+    if line.first() != Some(&b'(') {
+        return None;
+    }
+    let mut depth = 0;
+    for (idx, c) in line.iter().enumerate() {
+        match c {
+            b'(' => depth += 1,
+            b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(idx);
+                }
+            }
+            _ => (),
+        }
+    }
+    None
+}
+
+/// Finds the first comma in a line that isn't inside parentheses or quotes.
+fn trailing_comma(line: &[u8]) -> Option<usize> {
+    let mut depth = 0;
+    let mut quoted = false;
+    for (idx, c) in line.iter().enumerate() {
+        match c {
+            b'"' => quoted = !quoted,
+            b'(' if !quoted => depth += 1,
+            b')' if !quoted && depth > 0 => depth -= 1,
+            b',' if !quoted && depth == 0 => return Some(idx),
+            _ => (),
+        }
+    }
+    None
+}
+
+/// Finds where the ingredient name ends in a line. A comma outside of any
+/// parentheses starts the modifier. e.g. `onion, chopped`. Otherwise only the
+/// last parenthesized group on the line is the modifier so balanced
+/// parentheses before it are part of the name. e.g. `milk (2%) (cold)`.
+/// Failing that the name ends at the first `(` that is never closed.
+fn ingredient_name_end(line: &[u8]) -> usize {
+    if let Some(idx) = trailing_comma(line) {
+        return idx;
+    }
+    let mut end = line.len();
+    while end > 0 && line[end - 1].is_ascii_whitespace() {
+        end -= 1;
+    }
+    if end > 0 && line[end - 1] == b')' {
+        let mut depth = 0;
+        for (idx, c) in line[0..end].iter().enumerate().rev() {
+            match c {
+                b')' => depth += 1,
+                b'(' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return idx;
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+    let mut open = Vec::new();
+    for (idx, c) in line.iter().enumerate() {
+        match c {
+            b'(' => open.push(idx),
+            b')' => {
+                open.pop();
+            }
+            _ => (),
+        }
+    }
+    open.first().cloned().unwrap_or(line.len())
+}
+
+/// Split an ingredient name into the name that identifies the ingredient and
+/// the name to display. Names wrapped in quotes keep their casing for display.
+/// e.g. `"Parmigiano-Reggiano"`
+///
+/// The quotes are removed before the name is normalized so a quoted name
+/// identifies the same ingredient as the unquoted one.
+fn split_display_name(name: &str) -> (String, Option<String>) {
+    let trimmed = name.trim();
+    if trimmed.len() >= 2 && trimmed.starts_with('"') && trimmed.ends_with('"') {
+        let display = trimmed[1..trimmed.len() - 1]
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ");
+        return (normalize_name(&display), Some(display));
+    }
+    (normalize_name(name), None)
+}
+
+fn ingredient_name_parts(
+    i: StrIter,
+) -> abortable_parser::Result<StrIter, (String, Option<String>)> {
+    let line = peek_line(&i);
+    let end = ingredient_name_end(&line);
+    let name = String::from_utf8_lossy(&line[0..end]);
+    Result::Complete(advance(&i, end), split_display_name(&name))
+}
+
+pub fn ingredient_name(i: StrIter) -> abortable_parser::Result<StrIter, String> {
+    match ingredient_name_parts(i) {
+        Result::Complete(i, (name, _)) => Result::Complete(i, name),
+        Result::Fail(e) => Result::Fail(e),
+        Result::Abort(e) => Result::Abort(e),
+        Result::Incomplete(i) => Result::Incomplete(i),
+    }
+}
 
 make_fn!(
-    ingredient_modifier<StrIter, &str>,
+    unclosed_ingredient_modifier<StrIter, &str>,
     do_each!(
         _ => text_token!("("),
         modifier => must!(until!(text_token!(")"))),
@@ -454,15 +959,45 @@ make_fn!(
     )
 );
 
+fn ingredient_modifier(i: StrIter) -> abortable_parser::Result<StrIter, String> {
+    let line = peek_line(&i);
+    if line.first() == Some(&b',') {
+        let modifier = String::from_utf8_lossy(&line[1..]).trim().to_owned();
+        return Result::Complete(advance(&i, line.len()), modifier);
+    }
+    if let Some(end) = closing_paren(&line) {
+        let modifier = String::from_utf8_lossy(&line[1..end]).to_string();
+        return Result::Complete(advance(&i, end + 1), modifier);
+    }
+    // NOTE(jwall): This either fails because there is no modifier or aborts
+    // with an error about the missing `)`.
+    match unclosed_ingredient_modifier(i) {
+        Result::Complete(i, modifier) => Result::Complete(i, modifier.to_owned()),
+        Result::Fail(e) => Result::Fail(e),
+        Result::Abort(e) => Result::Abort(e),
+        Result::Incomplete(i) => Result::Incomplete(i),
+    }
+}
+
 make_fn!(
     pub ingredient<StrIter, Ingredient>,
     do_each!(
         _ => optional!(ws),
         measure => measure,
-        name => ingredient_name,
+        name => ingredient_name_parts,
         modifier => optional!(ingredient_modifier),
         _ => optional!(ws),
-        (Ingredient::new(name, modifier.map(|s| s.to_owned()), measure))
+        ({
+            let (name, display_name) = name;
+            let (form, divided) = match modifier {
+                Some(modifier) => split_divided(&modifier),
+                None => (None, false),
+            };
+            let mut ingredient = Ingredient::new(name, form, measure);
+            ingredient.display_name = display_name;
+            ingredient.divided = divided;
+            ingredient
+        })
     )
 );
 
@@ -470,3 +1005,43 @@ make_fn!(
     pub ingredient_list<StrIter, Vec<Ingredient>>,
     separated!(text_token!("\n"), ingredient)
 );
+
+/// A `recipe:<id>` line in a step's ingredient list. It uses another recipe as
+/// part of this one.
+pub fn recipe_ref(i: StrIter) -> abortable_parser::Result<StrIter, String> {
+    let line = peek_line(&i);
+    let text = String::from_utf8_lossy(&line);
+    let id = match text.trim_start().strip_prefix(RECIPE_REF_PREFIX) {
+        Some(id) => id.trim(),
+        None => return Result::Fail(Error::new("Not a recipe reference".to_owned(), Box::new(i))),
+    };
+    if id.is_empty() {
+        return Result::Abort(Error::new("Missing recipe id".to_owned(), Box::new(i)));
+    }
+    Result::Complete(advance(&i, line.len()), id.to_owned())
+}
+
+/// A line in a step's ingredient list.
+enum StepLine {
+    Ingredient(Ingredient),
+    Recipe(String),
+}
+
+make_fn!(
+    step_line<StrIter, StepLine>,
+    either!(
+        do_each!(
+            id => recipe_ref,
+            (StepLine::Recipe(id))
+        ),
+        do_each!(
+            i => ingredient,
+            (StepLine::Ingredient(i))
+        )
+    )
+);
+
+make_fn!(
+    step_line_list<StrIter, Vec<StepLine>>,
+    separated!(text_token!("\n"), step_line)
+);
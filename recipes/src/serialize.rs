@@ -0,0 +1,204 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+/*!
+Serialize parsed recipes back into the canonical recipe text format. Text
+produced here can be parsed again with the `parse` module.
+*/
+use std::time::Duration;
+
+use crate::{Ingredient, Recipe, Step, RECIPE_REF_PREFIX};
+
+/// Serialize a recipe into canonical recipe text.
+pub fn as_recipe_text(recipe: &Recipe) -> String {
+    let mut text = format!("title: {}\n", recipe.title.trim());
+    if !recipe.tags.is_empty() {
+        text.push_str(&format!("tags: {}\n", recipe.tags.join(", ")));
+    }
+    match recipe
+        .desc
+        .as_ref()
+        .map(|d| collapse_paragraphs(d))
+        .filter(|d| !d.is_empty())
+    {
+        Some(desc) => text.push_str(&format!("\n{}\n\n", desc)),
+        None => text.push_str("\n\n"),
+    }
+    let mut section = None;
+    let steps = recipe
+        .steps
+        .iter()
+        .map(|step| {
+            // NOTE(jwall): Sections carry over to the following steps so we
+            // only write them when they change.
+            match &step.section {
+                Some(name) if section.as_ref() != Some(name) => {
+                    section = step.section.clone();
+                    format!("section: {}\n{}", name, as_step_text(step))
+                }
+                _ => as_step_text(step),
+            }
+        })
+        .collect::<Vec<String>>();
+    text.push_str(&steps.join("\n"));
+    text
+}
+
+/// Serialize a single recipe step into canonical recipe text.
+pub fn as_step_text(step: &Step) -> String {
+    let mut text = String::from("step:");
+    if let Some(dur) = step.prep_time.filter(|d| d.as_secs() > 0) {
+        text.push(' ');
+        text.push_str(&as_duration_text(&dur));
+    }
+    text.push_str("\n\n");
+    if !step.ingredients.is_empty() || !step.sub_recipes.is_empty() {
+        let ingredients = step
+            .ingredients
+            .iter()
+            .map(as_ingredient_text)
+            .chain(
+                step.sub_recipes
+                    .iter()
+                    .map(|id| format!("{}{}", RECIPE_REF_PREFIX, id)),
+            )
+            .collect::<Vec<String>>();
+        text.push_str(&ingredients.join("\n"));
+        text.push_str("\n\n");
+    } else {
+        text.push('\n');
+    }
+    text.push_str(&collapse_paragraphs(&step.instructions));
+    text.push('\n');
+    text
+}
+
+/// Serialize an ingredient into a single line of recipe text.
+pub fn as_ingredient_text(ingredient: &Ingredient) -> String {
+    match &ingredient.display_name {
+        // NOTE(jwall): The name has to be quoted again to keep its casing.
+        // Recipe text can only carry the casing of a name so a display name
+        // that doesn't match the key is dropped in favor of the key.
+        Some(display_name) if display_name.to_lowercase() == ingredient.name => {
+            let mut text = format!("{} \"{}\"", ingredient.amt, display_name);
+            if let Some(form) = ingredient.form_text() {
+                text.push_str(&format!(" ({})", form));
+            }
+            text
+        }
+        _ => {
+            let mut text = format!("{} {}", ingredient.amt, ingredient.name);
+            if let Some(form) = ingredient.form_text() {
+                text.push_str(&format!(" ({})", form));
+            }
+            text
+        }
+    }
+}
+
+/// Render a recipe as markdown for sharing. Ingredients are a checklist and
+/// steps are numbered.
+pub fn as_markdown(recipe: &Recipe) -> String {
+    let mut text = format!("# {}\n", recipe.title.trim());
+    if let Some(desc) = recipe
+        .desc
+        .as_ref()
+        .map(|d| d.trim())
+        .filter(|d| !d.is_empty())
+    {
+        text.push_str(&format!("\n{}\n", desc));
+    }
+    let has_sections = recipe.steps.iter().any(|s| s.section.is_some());
+    let step_heading = if has_sections { "###" } else { "##" };
+    let mut section = None;
+    for (idx, step) in recipe.steps.iter().enumerate() {
+        if let Some(name) = step
+            .section
+            .as_ref()
+            .filter(|s| section.as_ref() != Some(*s))
+        {
+            text.push_str(&format!("\n## {}\n", name));
+            section = step.section.clone();
+        }
+        text.push_str(&format!("\n{} {}\n", step_heading, step_title(idx, step)));
+        if !step.ingredients.is_empty() {
+            text.push('\n');
+            for i in step.ingredients.iter() {
+                text.push_str(&format!("- [ ] {}\n", i));
+            }
+        }
+        text.push_str(&format!("\n{}\n", step.instructions.trim()));
+    }
+    text
+}
+
+/// Render a recipe as plain text for sharing.
+pub fn as_plain_text(recipe: &Recipe) -> String {
+    let mut text = format!("{}\n", recipe.title.trim());
+    if let Some(desc) = recipe
+        .desc
+        .as_ref()
+        .map(|d| d.trim())
+        .filter(|d| !d.is_empty())
+    {
+        text.push_str(&format!("\n{}\n", desc));
+    }
+    let mut section = None;
+    for (idx, step) in recipe.steps.iter().enumerate() {
+        if let Some(name) = step
+            .section
+            .as_ref()
+            .filter(|s| section.as_ref() != Some(*s))
+        {
+            text.push_str(&format!("\n{}:\n", name));
+            section = step.section.clone();
+        }
+        text.push_str(&format!("\n{}\n", step_title(idx, step)));
+        for i in step.ingredients.iter() {
+            text.push_str(&format!("  * {}\n", i));
+        }
+        text.push_str(&format!("\n{}\n", step.instructions.trim()));
+    }
+    text
+}
+
+/// The numbered title of a step along with its prep time if it has one.
+/// e.g. `Step 1 (10 min)`
+fn step_title(idx: usize, step: &Step) -> String {
+    match step.prep_time.filter(|d| d.as_secs() > 0) {
+        Some(dur) => format!("Step {} ({})", idx + 1, as_duration_text(&dur)),
+        None => format!("Step {}", idx + 1),
+    }
+}
+
+/// Serialize a step duration using the largest unit that represents it exactly.
+pub fn as_duration_text(dur: &Duration) -> String {
+    let secs = dur.as_secs();
+    if secs > 0 && secs % (60 * 60) == 0 {
+        format!("{} hrs", secs / (60 * 60))
+    } else if secs > 0 && secs % 60 == 0 {
+        format!("{} min", secs / 60)
+    } else {
+        format!("{} sec", secs)
+    }
+}
+
+// NOTE(jwall): Blank lines separate the sections of a recipe so we can't
+// allow them inside of a description or instructions.
+fn collapse_paragraphs(text: &str) -> String {
+    text.lines()
+        .map(|l| l.trim_end())
+        .filter(|l| !l.trim().is_empty())
+        .collect::<Vec<&str>>()
+        .join("\n")
+}
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 use crate::VolumeMeasure::*;
+use crate::WeightMeasure::*;
 use crate::*;
 
 use std::convert::Into;
@@ -64,6 +65,54 @@ fn test_volume_math() {
     assert_eq!(tsp - tsp, Tsp(0.into()));
 }
 
+#[test]
+fn test_volume_scaling() {
+    let cup = Cup(1.into());
+    assert!(matches!(cup * Quantity::from(2), Pint(qty) if qty == 1.into()));
+    assert!(matches!(cup / Quantity::from(2), Tbsp(qty) if qty == 8.into()));
+    let ml = ML(500.into());
+    assert!(matches!(ml * Quantity::from(3), Ltr(qty) if qty == Ratio::new(3, 2).into()));
+    assert!(matches!(ml / Quantity::from(5), ML(qty) if qty == 100.into()));
+}
+
+#[test]
+fn test_weight_scaling() {
+    let oz = WeightMeasure::Oz(8.into());
+    assert!(matches!(oz * Quantity::from(4), WeightMeasure::Pound(_)));
+    assert!(matches!(oz / Quantity::from(2), WeightMeasure::Oz(_)));
+    let grams = WeightMeasure::Gram(500.into());
+    assert!(matches!(
+        grams * Quantity::from(2),
+        WeightMeasure::Kilogram(qty) if qty == 1.into()
+    ));
+    assert!(matches!(
+        grams / Quantity::from(2),
+        WeightMeasure::Gram(qty) if qty == 250.into()
+    ));
+}
+
+#[test]
+fn test_measure_scaling() {
+    let half: Quantity = Ratio::new(1, 2).into();
+    assert_eq!(Measure::count(3) * Quantity::from(2), Measure::count(6));
+    assert_eq!(
+        Measure::pkg("can", 2.into()) * half,
+        Measure::pkg("can", 1.into())
+    );
+    assert_eq!(
+        Measure::pkg("can", 2.into()) / Quantity::from(4),
+        Measure::pkg("can", half)
+    );
+    assert!(matches!(
+        Measure::tbsp(1.into()) * Quantity::from(3),
+        Measure::Volume(Tbsp(qty)) if qty == 3.into()
+    ));
+    assert!(matches!(
+        &Measure::gram(500.into()) * Quantity::from(4),
+        Measure::Weight(WeightMeasure::Kilogram(qty)) if qty == 2.into()
+    ));
+}
+
 macro_rules! assert_normalize {
     ($typ:path, $conv:ident, $msg:expr) => {
         if let $typ(qty) = $typ(1.into()).$conv().normalize() {
@@ -85,6 +134,82 @@ fn test_volume_normalize() {
     assert_normalize!(Gal, into_tsp, "not a gal after normalize call");
 }
 
+#[test]
+fn test_pinch_and_dash() {
+    assert_eq!(Pinch(16.into()), Tsp(1.into()));
+    assert_eq!(Dash(2.into()), Pinch(4.into()));
+    assert!(matches!(Pinch(16.into()).normalize(), Tsp(qty) if qty == 1.into()));
+    assert!(matches!(Pinch(32.into()).normalize(), Tsp(qty) if qty == 2.into()));
+    assert!(matches!(Pinch(2.into()).normalize(), Pinch(qty) if qty == 2.into()));
+    assert_eq!(format!("{}", Pinch(1.into())), "1 pinch");
+    assert_eq!(format!("{}", Pinch(2.into())), "2 pinches");
+    assert_eq!(format!("{}", Dash(2.into())), "2 dashes");
+    assert_eq!(
+        parse::as_measure("1 pinch").expect("Failed to parse measure"),
+        Volume(Pinch(1.into()))
+    );
+    // Units and their aliases can end the input.
+    assert_eq!(
+        parse::as_measure("2 T").expect("Failed to parse measure"),
+        Volume(Tbsp(2.into()))
+    );
+    match parse::ingredient(StrIter::new("2 dashes hot sauce")) {
+        ParseResult::Complete(_, ing) => assert_eq!(
+            ing,
+            Ingredient::new("hot sauce", None, Volume(Dash(2.into())))
+        ),
+        err => assert!(false, "{:?}", err),
+    }
+}
+
+#[test]
+fn test_imperial_volumes() {
+    assert_eq!(ImpPint(1.into()), ImpFloz(20.into()));
+    assert_eq!(
+        ImpPint(1.into()).get_ml(),
+        Quantity::Frac(Ratio::new(454609, 800))
+    );
+    assert_ne!(ImpPint(1.into()), Pint(1.into()));
+    assert_eq!(ImpPint(1.into()).system(), VolumeSystem::British);
+    assert!(!ImpPint(1.into()).metric());
+    // Math stays in the imperial system.
+    assert!(matches!(ImpFloz(10.into()) + ImpFloz(10.into()), ImpPint(qty) if qty == 1.into()));
+    assert!(matches!(ImpPint(1.into()) / 4.into(), ImpFloz(qty) if qty == 5.into()));
+    assert!(matches!(ImpFloz(5.into()).normalize(), ImpFloz(qty) if qty == 5.into()));
+    // US and metric math doesn't change.
+    assert!(matches!(Floz(16.into()) + Floz(16.into()), Qrt(qty) if qty == 1.into()));
+    assert!(matches!(ML(500.into()) + ML(500.into()), Ltr(qty) if qty == 1.into()));
+    assert_eq!(format!("{}", ImpPint(1.into())), "1 imp pint");
+    assert_eq!(format!("{}", ImpPint(2.into())), "2 imp pints");
+    assert_eq!(format!("{}", ImpFloz(3.into())), "3 imp floz");
+    assert_eq!(
+        ImpPint(Quantity::frac(1, 1, 4)).fmt_compound(),
+        "1 imp pint 5 imp floz"
+    );
+    let cases = vec![
+        ("1 imp pint milk", ImpPint(1.into())),
+        ("2 imperial pints milk", ImpPint(2.into())),
+        ("3 imp fl oz milk", ImpFloz(3.into())),
+        ("3 imperial floz milk", ImpFloz(3.into())),
+        ("1 pint milk", Pint(1.into())),
+    ];
+    for (i, expected) in cases {
+        match parse::ingredient(StrIter::new(i)) {
+            ParseResult::Complete(_, ing) => {
+                assert_eq!(ing.name, "milk", "parsing {}", i);
+                match ing.amt {
+                    Volume(vm) => {
+                        assert_eq!(vm, expected, "parsing {}", i);
+                        assert_eq!(vm.system(), expected.system(), "parsing {}", i);
+                    }
+                    amt => assert!(false, "parsing {} gave {:?}", i, amt),
+                }
+            }
+            err => assert!(false, "parsing {}: {:?}", i, err),
+        }
+    }
+}
+
 #[test]
 fn test_ingredient_display() {
     let cases = vec![
@@ -208,12 +333,38 @@ fn test_ratio_parse() {
     }
 }
 
+#[test]
+fn test_unicode_ratio_parse() {
+    for (i, expected) in vec![
+        ("½", Ratio::new(1, 2)),
+        ("⅓", Ratio::new(1, 3)),
+        ("⅔", Ratio::new(2, 3)),
+        ("¼", Ratio::new(1, 4)),
+        ("¾", Ratio::new(3, 4)),
+        ("⅝", Ratio::new(5, 8)),
+    ] {
+        match parse::ratio(StrIter::new(i)) {
+            ParseResult::Complete(_, rat) => assert_eq!(rat, expected),
+            err => assert!(false, "{:?}", err),
+        }
+    }
+}
+
 #[test]
 fn test_quantity_parse() {
     for (i, expected) in vec![
         ("1 ", Quantity::Whole(1)),
         ("1/2 ", Quantity::Frac(Ratio::new(1, 2))),
         ("1 1/2 ", Quantity::Frac(Ratio::new(3, 2))),
+        ("½ ", Quantity::Frac(Ratio::new(1, 2))),
+        ("¼ ", Quantity::Frac(Ratio::new(1, 4))),
+        ("¾ ", Quantity::Frac(Ratio::new(3, 4))),
+        ("⅓ ", Quantity::Frac(Ratio::new(1, 3))),
+        ("⅛ ", Quantity::Frac(Ratio::new(1, 8))),
+        ("1½ ", Quantity::Frac(Ratio::new(3, 2))),
+        ("2¾ ", Quantity::Frac(Ratio::new(11, 4))),
+        ("1 ½ ", Quantity::Frac(Ratio::new(3, 2))),
+        ("3 ⅔ ", Quantity::Frac(Ratio::new(11, 3))),
     ] {
         match parse::quantity(StrIter::new(i)) {
             ParseResult::Complete(_, qty) => assert_eq!(qty, expected),
@@ -232,6 +383,253 @@ fn test_ingredient_name_parse() {
     }
 }
 
+#[test]
+fn test_ingredient_names_with_parens() {
+    for (i, expected) in vec![
+        (
+            "1 cup milk (2%) (cold)",
+            Ingredient::new(
+                "milk (2%)",
+                Some("cold".to_owned()),
+                Volume(Cup(Quantity::Whole(1))),
+            ),
+        ),
+        (
+            "1 cup milk (2%) (cold) ",
+            Ingredient::new(
+                "milk (2%)",
+                Some("cold".to_owned()),
+                Volume(Cup(Quantity::Whole(1))),
+            ),
+        ),
+        (
+            "1 can (Acme) tomato sauce",
+            Ingredient::new(
+                "(Acme) tomato sauce",
+                None,
+                Measure::pkg("can", Quantity::Whole(1)),
+            ),
+        ),
+        (
+            "2 cups chicken (thighs) stock (warm (not hot))",
+            Ingredient::new(
+                "chicken (thighs) stock",
+                Some("warm (not hot)".to_owned()),
+                Volume(Cup(Quantity::Whole(2))),
+            ),
+        ),
+    ] {
+        match parse::ingredient(StrIter::new(i)) {
+            ParseResult::Complete(_, ing) => assert_eq!(ing, expected, "parsing {}", i),
+            err => assert!(false, "{:?}", err),
+        }
+    }
+}
+
+#[test]
+fn test_quoted_ingredient_names_keep_their_casing() {
+    for (i, unquoted, display_name, form) in vec![
+        (
+            "1 cup \"Parmigiano-Reggiano\"",
+            "1 cup Parmigiano-Reggiano",
+            "Parmigiano-Reggiano",
+            None,
+        ),
+        (
+            "2 cups \"Acme  Brand Oats\" (rolled)",
+            "2 cups Acme Brand Oats (rolled)",
+            "Acme Brand Oats",
+            Some("rolled".to_owned()),
+        ),
+    ] {
+        let plain = match parse::ingredient(StrIter::new(unquoted)) {
+            ParseResult::Complete(_, ing) => ing,
+            err => panic!("{:?}", err),
+        };
+        match parse::ingredient(StrIter::new(i)) {
+            ParseResult::Complete(_, ing) => {
+                // The quotes only change how the name is displayed.
+                assert_eq!(ing.name, plain.name, "parsing {}", i);
+                assert_eq!(ing.key(), plain.key(), "parsing {}", i);
+                assert_eq!(ing.display_name.as_deref(), Some(display_name));
+                assert_eq!(ing.name_for_display(), display_name);
+                assert_eq!(ing.form, form);
+            }
+            err => assert!(false, "{:?}", err),
+        }
+    }
+    let ing = parse::ingredient(StrIter::new("1 cup Parmigiano-Reggiano"));
+    match ing {
+        ParseResult::Complete(_, ing) => {
+            assert_eq!(ing.name, "parmigiano-reggiano");
+            assert_eq!(ing.display_name, None);
+        }
+        err => assert!(false, "{:?}", err),
+    }
+}
+
+#[test]
+fn test_quoted_ingredient_names_merge_by_key() {
+    let pasta =
+        parse::as_recipe("title: pasta\n\n\nstep:\n\n1 cup parmigiano-reggiano\n\nGrate.\n")
+            .expect("pasta should parse");
+    let salad =
+        parse::as_recipe("title: salad\n\n\nstep:\n\n1/2 cup \"Parmigiano-Reggiano\"\n\nShave.\n")
+            .expect("salad should parse");
+    let mut acc = IngredientAccumulator::new();
+    acc.accumulate_from(&pasta);
+    acc.accumulate_from(&salad);
+    let ingredients = acc.ingredients();
+    assert_eq!(ingredients.len(), 1);
+    let (ing, recipes) = ingredients.values().next().unwrap();
+    assert_eq!(ing.name_for_display(), "Parmigiano-Reggiano");
+    assert_eq!(ing.amt, Measure::cup(Quantity::frac(1, 1, 2)));
+    assert_eq!(recipes.len(), 2);
+    assert_eq!(format!("{}", ing), "1 1/2 cups Parmigiano-Reggiano");
+    assert_eq!(
+        serialize::as_ingredient_text(ing),
+        "1 1/2 cups \"Parmigiano-Reggiano\""
+    );
+}
+
+#[test]
+fn test_display_name_differs_from_key() {
+    let plain = Ingredient::new("green onion", None, Measure::count(2));
+    let named = Ingredient::new("green onion", None, Measure::count(3))
+        .with_display_name("Green Onions (Scallions)");
+    assert_eq!(plain.key(), named.key());
+    assert_eq!(named.key().name(), "green onion");
+    assert_eq!(named.name_for_display(), "Green Onions (Scallions)");
+    assert_eq!(format!("{}", named), "3 Green Onions (Scallions)");
+    // The display name can't be written in recipe text so the key is used.
+    assert_eq!(serialize::as_ingredient_text(&named), "3 green onion");
+}
+
+#[test]
+fn test_accumulation_uses_key_not_display_name() {
+    let mut acc = IngredientAccumulator::new();
+    acc.accumulate_ingredients_for(
+        "soup",
+        [Ingredient::new("green onion", None, Measure::count(2))].iter(),
+    );
+    acc.accumulate_ingredients_for(
+        "salad",
+        [Ingredient::new("green onion", None, Measure::count(3))
+            .with_display_name("Green Onions (Scallions)")]
+        .iter(),
+    );
+    acc.accumulate_ingredients_for(
+        "stew",
+        [Ingredient::new("scallion", None, Measure::count(1))
+            .with_display_name("Green Onions (Scallions)")]
+        .iter(),
+    );
+    let ingredients = acc.ingredients();
+    assert_eq!(ingredients.len(), 2);
+    let (ing, recipes) = ingredients
+        .get(&IngredientKey::new(
+            "green onion".to_owned(),
+            None,
+            "Count".to_owned(),
+        ))
+        .expect("green onions should be keyed by name");
+    assert_eq!(ing.amt, Measure::count(5));
+    assert_eq!(ing.name_for_display(), "Green Onions (Scallions)");
+    assert_eq!(recipes.len(), 2);
+}
+
+#[test]
+fn test_ingredient_key_normalizes_case_and_plurals() {
+    let mut acc = IngredientAccumulator::new();
+    acc.accumulate_ingredients_for(
+        "salad",
+        [
+            Ingredient::new("Green Onions", None, Measure::count(1)),
+            Ingredient::new("green onion", None, Measure::count(2)),
+            Ingredient::new("green onions ", None, Measure::count(3)),
+        ]
+        .iter(),
+    );
+    let ingredients = acc.ingredients();
+    assert_eq!(ingredients.len(), 1);
+    let (ing, _) = ingredients
+        .get(&IngredientKey::new(
+            "green onion".to_owned(),
+            None,
+            "Count".to_owned(),
+        ))
+        .expect("all spellings should share a key");
+    assert_eq!(ing.amt, Measure::count(6));
+}
+
+#[test]
+fn test_ingredient_key_normalizes_form() {
+    let chopped = Ingredient::new("onion", Some("Chopped ".to_owned()), Measure::count(1));
+    let lower = Ingredient::new("onion", Some("chopped".to_owned()), Measure::count(1));
+    let blank = Ingredient::new("onion", Some(" ".to_owned()), Measure::count(1));
+    let none = Ingredient::new("onion", None, Measure::count(1));
+    assert_eq!(chopped.key(), lower.key());
+    assert_eq!(lower.key().form(), "chopped");
+    assert_eq!(blank.key(), none.key());
+}
+
+#[test]
+fn test_ingredient_key_normalizes_when_deserialized() {
+    let key: IngredientKey = serde_json::from_str(r#"["Green  Onions","Chopped ","Count"]"#)
+        .expect("Failed to deserialize key");
+    assert_eq!(
+        key,
+        IngredientKey::new(
+            "green onion".to_owned(),
+            Some("chopped".to_owned()),
+            "Count".to_owned()
+        )
+    );
+    let json = serde_json::to_string(&key).expect("Failed to serialize key");
+    assert_eq!(json, r#"["green onion","chopped","Count"]"#);
+}
+
+#[test]
+fn test_ingredient_list_with_parens() {
+    let ingredients = parse::as_ingredient_list("1 cup milk (2%) (cold)\n2 eggs (beaten)")
+        .expect("ingredients with parens in the names should parse");
+    assert_eq!(ingredients.len(), 2);
+    assert_eq!(ingredients[0].name, "milk (2%)");
+    assert_eq!(ingredients[0].form, Some("cold".to_owned()));
+    assert_eq!(ingredients[1].name, "eggs");
+    assert_eq!(ingredients[1].form, Some("beaten".to_owned()));
+}
+
+#[test]
+fn test_ingredient_comma_modifier() {
+    let ingredients = parse::as_ingredient_list(
+        "1 onion, chopped\n1 onion (chopped)\n2 cups flour, sifted \n1 cup milk (2%, cold)\n1 cup milk (2%), cold",
+    )
+    .expect("ingredients with comma modifiers should parse");
+    assert_eq!(ingredients.len(), 5);
+    assert_eq!(ingredients[0], ingredients[1]);
+    assert_eq!(ingredients[0].key(), ingredients[1].key());
+    assert_eq!(ingredients[0].name, "onion");
+    assert_eq!(ingredients[0].form, Some("chopped".to_owned()));
+    assert_eq!(
+        ingredients[2],
+        Ingredient::new(
+            "flour",
+            Some("sifted".to_owned()),
+            Volume(Cup(Quantity::Whole(2)))
+        )
+    );
+    assert_eq!(ingredients[3].name, "milk");
+    assert_eq!(ingredients[3].form, Some("2%, cold".to_owned()));
+    assert_eq!(ingredients[4].name, "milk (2%)");
+    assert_eq!(ingredients[4].form, Some("cold".to_owned()));
+}
+
+#[test]
+fn test_ingredient_unclosed_modifier_is_an_error() {
+    assert!(parse::as_ingredient_list("1 cup apple (chopped").is_err());
+}
+
 #[test]
 fn test_ingredient_parse() {
     for (i, expected) in vec![
@@ -299,6 +697,22 @@ fn test_ingredient_parse() {
                 Package("bag".into(), Quantity::Whole(1)),
             ),
         ),
+        (
+            "1½ cups flour",
+            Ingredient::new(
+                "flour",
+                None,
+                Volume(Cup(Quantity::Frac(Ratio::new(3, 2)))),
+            ),
+        ),
+        (
+            "½ tsp salt",
+            Ingredient::new(
+                "salt",
+                None,
+                Volume(Tsp(Quantity::Frac(Ratio::new(1, 2)))),
+            ),
+        ),
         (
             "1 can baked beans",
             Ingredient::new(
@@ -397,6 +811,45 @@ until thickens. Set aside to cool."
     }
 }
 
+#[test]
+fn test_step_time() {
+    for (input, secs) in [
+        ("90 min", 90 * 60),
+        ("1 hr 30 min", 90 * 60),
+        ("1 h 5 m", 65 * 60),
+        ("1hr30min", 90 * 60),
+        ("2 hours 15 minutes", 135 * 60),
+        ("1 minute 30 seconds", 90),
+        ("3 hrs", 3 * 60 * 60),
+    ] {
+        match parse::step_time(StrIter::new(input)) {
+            ParseResult::Complete(_, dur) => {
+                assert_eq!(dur, std::time::Duration::from_secs(secs), "{}", input)
+            }
+            err => assert!(false, "{}: {:?}", input, err),
+        }
+    }
+}
+
+#[test]
+fn test_step_with_compound_duration() {
+    let step = "step: 1 hr 30 min
+
+1 lb beef
+
+Braise.";
+    match parse::step(StrIter::new(step)) {
+        ParseResult::Complete(_, step) => {
+            assert_eq!(
+                step.prep_time,
+                Some(std::time::Duration::from_secs(90 * 60))
+            );
+            assert_eq!(step.instructions, "Braise.");
+        }
+        err => assert!(false, "{:?}", err),
+    }
+}
+
 #[test]
 fn test_multiple_steps() {
     let steps = "step:
@@ -480,6 +933,22 @@ A simple gooey apple bake recipe.
     }
 }
 
+#[test]
+fn test_recipe_missing_steps_detailed_error() {
+    let recipe = "title: gooey apple bake
+
+A simple gooey apple bake recipe.
+";
+    let err = parse::as_recipe_detailed(recipe).expect_err("Recipe without steps should fail");
+    assert_eq!(err.message, "Missing recipe steps");
+    assert_eq!(err.line, 4);
+    assert_eq!(err.offset, recipe.len());
+    assert_eq!(
+        parse::as_recipe(recipe).expect_err("Recipe without steps should fail"),
+        err.to_string()
+    );
+}
+
 #[test]
 fn test_step_no_ingredients_parse_failure() {
     let step = "step: 
@@ -496,6 +965,43 @@ step: ";
     }
 }
 
+#[test]
+fn test_singular_exceptions() {
+    for (name, expected) in [
+        ("hummus", "hummus"),
+        ("Molasses", "molasses"),
+        ("green asparagus", "green asparagus"),
+        ("Swiss", "swiss"),
+        ("couscous", "couscous"),
+        ("Green Onions", "green onion"),
+        ("Roma Tomatoes", "roma tomato"),
+    ] {
+        assert_eq!(parse::normalize_key_name(name), expected);
+    }
+    assert_eq!(
+        parse::normalize_key_name_with("green onions", &["onions"]),
+        "green onions"
+    );
+    assert_eq!(parse::normalize_key_name_with("hummus", &[]), "hummu");
+}
+
+#[test]
+fn test_categories_use_key_normalization() {
+    let line = "Pantry: Molasses|hummus|dried beans\nDairy: swiss";
+    match parse::as_categories(line) {
+        Ok(map) => {
+            let molasses = Ingredient::new("molasses", None, Measure::tbsp(2.into()));
+            assert_eq!(map.get(molasses.key().name()), Some(&"Pantry".to_owned()));
+            assert_eq!(map.get("hummus"), Some(&"Pantry".to_owned()));
+            assert_eq!(map.get("dried bean"), Some(&"Pantry".to_owned()));
+            assert_eq!(map.get("swiss"), Some(&"Dairy".to_owned()));
+        }
+        Err(e) => {
+            assert!(false, "{:?}", e);
+        }
+    }
+}
+
 #[test]
 fn test_category_single_line_happy_path() {
     let line = "Produce: onion|green pepper|bell pepper|corn|potato|green onion|scallions|lettuce";
@@ -587,3 +1093,1571 @@ fn test_ingredients_list_happy_path() {
         }
     }
 }
+
+#[test]
+fn test_quantity_serde_round_trip() {
+    for qty in vec![
+        Quantity::Whole(3),
+        Quantity::Frac(Ratio::new(1, 2)),
+        // Unreduced ratios keep their exact numerator and denominator.
+        Quantity::Frac(Ratio::new_raw(2, 4)),
+    ] {
+        let json = serde_json::to_string(&qty).expect("Failed to serialize quantity");
+        let round_tripped: Quantity =
+            serde_json::from_str(&json).expect("Failed to deserialize quantity");
+        assert_eq!(format!("{:?}", qty), format!("{:?}", round_tripped));
+    }
+}
+
+#[test]
+fn test_measure_serde_round_trip() {
+    for measure in vec![
+        Measure::Volume(Cup(Quantity::Frac(Ratio::new(3, 2)))),
+        Measure::Volume(ML(Quantity::Whole(250))),
+        Measure::Weight(WeightMeasure::Pound(Quantity::Whole(2))),
+        Measure::Count(Quantity::Whole(4)),
+        Measure::pkg("can", Quantity::Whole(1)),
+    ] {
+        let json = serde_json::to_string(&measure).expect("Failed to serialize measure");
+        let round_tripped: Measure =
+            serde_json::from_str(&json).expect("Failed to deserialize measure");
+        // NOTE(jwall): Volume and Weight equality is by converted amount so we
+        // compare the debug output to make sure the units round trip as well.
+        assert_eq!(format!("{:?}", measure), format!("{:?}", round_tripped));
+    }
+}
+
+#[test]
+fn test_every_unit_serde_round_trip() {
+    let qty = Quantity::Frac(Ratio::new(1, 3));
+    for volume in vec![
+        Pinch(qty),
+        Dash(qty),
+        Tsp(qty),
+        Tbsp(qty),
+        Cup(qty),
+        Pint(qty),
+        Qrt(qty),
+        Gal(qty),
+        Floz(qty),
+        ImpFloz(qty),
+        ImpPint(qty),
+        ML(qty),
+        Ltr(qty),
+    ] {
+        let json = serde_json::to_string(&volume).expect("Failed to serialize volume");
+        let round_tripped: VolumeMeasure =
+            serde_json::from_str(&json).expect("Failed to deserialize volume");
+        assert_eq!(format!("{:?}", volume), format!("{:?}", round_tripped));
+    }
+    for weight in vec![
+        WeightMeasure::Gram(qty),
+        WeightMeasure::Kilogram(qty),
+        WeightMeasure::Pound(qty),
+        WeightMeasure::Oz(qty),
+    ] {
+        let json = serde_json::to_string(&weight).expect("Failed to serialize weight");
+        let round_tripped: WeightMeasure =
+            serde_json::from_str(&json).expect("Failed to deserialize weight");
+        assert_eq!(format!("{:?}", weight), format!("{:?}", round_tripped));
+    }
+    // The units are tagged by variant name and ratios are [numer, denom].
+    assert_eq!(
+        serde_json::to_string(&Measure::Volume(Cup(qty))).unwrap(),
+        r#"{"Volume":{"Cup":{"Frac":[1,3]}}}"#
+    );
+}
+
+#[test]
+fn test_recipe_serde_round_trip() {
+    let mut recipe = parse::as_recipe(
+        "title: gooey apple bake
+
+A simple gooey apple bake recipe.
+
+step: 10 min
+
+1 tbsp butter
+2 granny smith apples (diced)
+1½ cups flour
+1 can baked beans
+
+Saute apples in butter until golden brown. Add flour slowly
+until thickened. Set aside to cool.
+
+step:
+
+2 oz heavy cream
+1 lb sugar
+
+Whip the cream with the sugar.
+",
+    )
+    .expect("Failed to parse recipe");
+    recipe.serving_count = Some(4);
+    let json = serde_json::to_string(&recipe).expect("Failed to serialize recipe");
+    let round_tripped: Recipe = serde_json::from_str(&json).expect("Failed to deserialize recipe");
+    assert_eq!(recipe, round_tripped);
+    assert_eq!(format!("{:?}", recipe), format!("{:?}", round_tripped));
+}
+
+#[test]
+fn test_tags_parse() {
+    for (line, expected) in vec![
+        ("tags: vegetarian, quick\n", vec!["vegetarian", "quick"]),
+        ("tags:holiday\n", vec!["holiday"]),
+        (
+            "tags: quick , , weeknight dinner,\n",
+            vec!["quick", "weeknight dinner"],
+        ),
+    ] {
+        match parse::tags(StrIter::new(line)) {
+            ParseResult::Complete(_, tags) => assert_eq!(tags, expected),
+            err => assert!(false, "{:?}", err),
+        }
+    }
+}
+
+#[test]
+fn test_recipe_with_tags() {
+    let recipe = "title: gooey apple bake
+tags: vegetarian, holiday
+
+A simple gooey apple bake recipe.
+
+step:
+
+1 tbsp flour
+2 tbsp butter
+
+Saute apples in butter until golden brown.
+";
+    let recipe = parse::as_recipe(recipe).expect("Failed to parse recipe with tags");
+    assert_eq!(recipe.title, "gooey apple bake");
+    assert_eq!(recipe.tags, vec!["vegetarian", "holiday"]);
+    assert!(recipe.desc.is_some());
+    assert_eq!(recipe.steps.len(), 1);
+}
+
+#[test]
+fn test_recipe_entry_tags_merge_with_text_tags() {
+    let mut entry = RecipeEntry::new(
+        "apple_bake",
+        "title: gooey apple bake
+tags: vegetarian
+
+step:
+
+1 tbsp flour
+
+Mix it.
+",
+    );
+    entry.set_tags(vec!["vegetarian", "quick"]);
+    let recipe = Recipe::try_from(&entry).expect("Failed to parse recipe entry");
+    assert_eq!(recipe.tags, vec!["vegetarian", "quick"]);
+}
+
+#[test]
+fn test_step_without_ingredients() {
+    let recipe = "title: gooey apple bake
+
+A simple gooey apple bake recipe.
+
+step:
+
+1 tbsp flour
+2 tbsp butter
+
+Saute apples in butter until golden brown.
+
+step:
+
+
+Let it cool before serving.
+";
+    let recipe = parse::as_recipe(recipe).expect("Failed to parse recipe");
+    assert_eq!(recipe.steps.len(), 2);
+    assert_eq!(recipe.steps[0].ingredients.len(), 2);
+    assert!(recipe.steps[1].ingredients.is_empty());
+    assert_eq!(
+        recipe.steps[1].instructions.trim(),
+        "Let it cool before serving."
+    );
+    // Without the blank line the instructions would be the ingredient list.
+    match parse::step(StrIter::new("step:\n\nLet it cool before serving.\n")) {
+        ParseResult::Abort(e) => assert_eq!(e.get_msg(), "Missing ingredient list"),
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn test_import_from_json_ld() {
+    let json = r#"{
+        "@context": "https://schema.org",
+        "@graph": [
+            {"@type": "WebPage", "name": "Not a recipe"},
+            {
+                "@type": "Recipe",
+                "name": "Apple Crisp",
+                "description": "A crispy apple dessert.",
+                "recipeYield": ["6", "6 servings"],
+                "recipeCategory": "Dessert",
+                "keywords": "baking, fall",
+                "recipeIngredient": [
+                    "2 Cups sliced apples",
+                    "1½ cup flour (sifted)",
+                    "salt to taste",
+                    "1/2 cup (about 4 oz) chopped nuts"
+                ],
+                "recipeInstructions": [
+                    {"@type": "HowToStep", "text": "1. Preheat the oven."},
+                    {
+                        "@type": "HowToSection",
+                        "itemListElement": [
+                            {"@type": "HowToStep", "text": "Toss the apples\nwith the flour."},
+                            {"@type": "HowToStep", "text": "Bake until golden."}
+                        ]
+                    }
+                ]
+            }
+        ]
+    }"#;
+    let entry = import::from_json_ld(json).expect("Failed to import recipe");
+    assert_eq!(entry.recipe_id(), "apple_crisp");
+    assert_eq!(entry.category(), Some(&"Dessert".to_owned()));
+    assert_eq!(entry.serving_count(), Some(6));
+    assert_eq!(entry.tags(), &vec!["baking".to_owned(), "fall".to_owned()]);
+
+    let recipe = Recipe::try_from(&entry).expect("Imported recipe should parse");
+    assert_eq!(recipe.title, "Apple Crisp");
+    assert_eq!(recipe.steps.len(), 3);
+    assert_eq!(recipe.steps[0].instructions.trim(), "Preheat the oven.");
+    assert_eq!(
+        recipe.steps[1].instructions.trim(),
+        "Toss the apples with the flour."
+    );
+    assert!(recipe.steps[1].ingredients.is_empty());
+    assert!(recipe.steps[2].ingredients.is_empty());
+
+    let ingredients = &recipe.steps[0].ingredients;
+    // Nothing gets dropped.
+    assert_eq!(ingredients.len(), 4);
+    assert_eq!(ingredients[0].name, "sliced apples");
+    assert_eq!(ingredients[0].amt, Measure::Volume(Cup(Quantity::Whole(2))));
+    assert_eq!(ingredients[1].name, "flour");
+    assert_eq!(ingredients[1].form, Some("sifted".to_owned()));
+    assert_eq!(
+        ingredients[1].amt,
+        Measure::Volume(Cup(Quantity::Frac(Ratio::new(3, 2))))
+    );
+    // Unparseable ingredients are imported as a count of 1.
+    assert_eq!(ingredients[2].name, "salt to taste");
+    assert_eq!(ingredients[2].amt, Measure::count(1));
+    // A leading parenthetical stays part of the name.
+    assert_eq!(ingredients[3].name, "(about 4 oz) chopped nuts");
+    assert_eq!(
+        ingredients[3].amt,
+        Measure::Volume(Cup(Quantity::Frac(Ratio::new(1, 2))))
+    );
+}
+
+#[test]
+fn test_import_from_json_ld_keeps_case_sensitive_units() {
+    let json = r#"{
+        "@type": "Recipe",
+        "name": "Cookies",
+        "recipeIngredient": ["1 T Sugar", "1 t salt", "2 Tbsp Butter"],
+        "recipeInstructions": "Mix."
+    }"#;
+    let entry = import::from_json_ld(json).expect("Failed to import recipe");
+    let recipe = Recipe::try_from(&entry).expect("Imported recipe should parse");
+    let ingredients = &recipe.steps[0].ingredients;
+    assert_eq!(ingredients[0].name, "sugar");
+    assert_eq!(
+        ingredients[0].amt,
+        Measure::Volume(Tbsp(Quantity::Whole(1)))
+    );
+    assert_eq!(ingredients[1].name, "salt");
+    assert_eq!(ingredients[1].amt, Measure::Volume(Tsp(Quantity::Whole(1))));
+    assert_eq!(ingredients[2].name, "butter");
+    assert_eq!(
+        ingredients[2].amt,
+        Measure::Volume(Tbsp(Quantity::Whole(2)))
+    );
+}
+
+#[test]
+fn test_import_from_json_ld_errors() {
+    assert!(matches!(
+        import::from_json_ld("not json"),
+        Err(import::Error::Json(_))
+    ));
+    assert!(matches!(
+        import::from_json_ld(r#"{"@type": "WebPage"}"#),
+        Err(import::Error::NoRecipe)
+    ));
+    assert!(matches!(
+        import::from_json_ld(r#"{"@type": "Recipe", "name": "Toast"}"#),
+        Err(import::Error::MissingField("recipeInstructions"))
+    ));
+}
+
+#[test]
+fn test_strip_placeholders() {
+    assert_eq!(strip_placeholders("title: {{name}}\n"), "title: name\n");
+    assert_eq!(strip_placeholders("{{a}}b{{ c }}d"), "abcd");
+    assert_eq!(strip_placeholders("no placeholders"), "no placeholders");
+    // Unterminated placeholders are left alone.
+    assert_eq!(strip_placeholders("a {{b"), "a {{b");
+}
+
+#[test]
+fn test_recipe_template_validate_and_render() {
+    let template = RecipeTemplate::new(
+        "bread",
+        "title: {{name}}
+tags: bread
+
+{{description}}
+
+step:
+
+500 g flour
+10 g salt
+
+Mix and knead.
+",
+    );
+    assert_eq!(template.validate(), Ok(()));
+    let rendered = template.render("country loaf");
+    assert!(rendered.starts_with("title: country loaf\n"));
+    assert!(rendered.contains("{{description}}"));
+
+    let broken = RecipeTemplate::new("broken", "title: {{name}}\n\nNo steps here.\n");
+    assert!(broken.validate().is_err());
+    let unnamed = RecipeTemplate::new(" ", template.text.clone());
+    assert!(unnamed.validate().is_err());
+}
+
+#[test]
+fn test_ingredient_breakdown_sums_to_total() {
+    let soup = Recipe::new("soup", None).with_steps(vec![
+        Step::new(None, "Mix").with_ingredients(vec![
+            Ingredient::new("flour", None, Volume(Cup(1.into()))),
+            Ingredient::new("onion", None, Count(1.into())),
+        ]),
+        Step::new(None, "Thicken").with_ingredients(vec![Ingredient::new(
+            "flour",
+            None,
+            Volume(Tbsp(2.into())),
+        )]),
+    ]);
+    let bread = Recipe::new("bread", None).with_steps(vec![Step::new(None, "Knead")
+        .with_ingredients(vec![Ingredient::new("flour", None, Volume(Cup(3.into())))])]);
+    let mut acc = IngredientAccumulator::new();
+    acc.accumulate_from(&soup);
+    acc.accumulate_from(&soup);
+    acc.accumulate_from(&bread);
+    let ingredients = acc.ingredients_with_breakdown();
+    assert_eq!(ingredients.len(), 2);
+    for (_, (i, breakdown)) in ingredients.iter() {
+        let total = breakdown
+            .values()
+            .cloned()
+            .reduce(|acc, amt| acc.checked_add(&amt).unwrap())
+            .unwrap();
+        assert_eq!(total, i.amt);
+    }
+    let (flour, breakdown) = ingredients
+        .values()
+        .find(|(i, _)| i.name == "flour")
+        .unwrap();
+    assert_eq!(flour.amt, Volume(Tbsp(84.into())));
+    assert_eq!(breakdown.len(), 2);
+    assert_eq!(breakdown["soup"], Volume(Tbsp(36.into())));
+    assert_eq!(breakdown["bread"], Volume(Cup(3.into())));
+    let (onion, breakdown) = ingredients
+        .values()
+        .find(|(i, _)| i.name == "onion")
+        .unwrap();
+    assert_eq!(onion.amt, Count(2.into()));
+    assert_eq!(breakdown.keys().collect::<Vec<&String>>(), vec!["soup"]);
+}
+
+#[test]
+fn test_quantity_approx_f32() {
+    // Regression test for integer division truncating the fraction.
+    assert_eq!(Quantity::Frac(Ratio::new(1, 2)).approx_f32(), 0.5);
+    assert_eq!(Quantity::Frac(Ratio::new(3, 2)).approx_f32(), 1.5);
+    assert!((Quantity::Frac(Ratio::new(2, 3)).approx_f32() - 2.0 / 3.0).abs() < f32::EPSILON);
+    assert_eq!(Quantity::frac(1, 1, 4).approx_f32(), 1.25);
+    assert_eq!(Quantity::Whole(3).approx_f32(), 3.0);
+}
+
+#[test]
+fn test_quantity_decimal_display() {
+    assert_eq!(Quantity::frac(1, 1, 2).decimal_string(), "1.5");
+    assert_eq!(Quantity::Whole(2).decimal_string(), "2");
+    assert_eq!(Quantity::Whole(10).decimal_string(), "10");
+    assert_eq!(Quantity::Frac(Ratio::new(1, 3)).decimal_string(), "0.33");
+    assert_eq!(
+        Measure::cup(Quantity::frac(1, 1, 2)).display_with(QuantityDisplay::Decimal),
+        "1.5 cups"
+    );
+    assert_eq!(
+        Measure::cup(Quantity::frac(1, 1, 2)).display_with(QuantityDisplay::Fraction),
+        "1 1/2 cups"
+    );
+    assert_eq!(
+        Measure::pkg("can", Quantity::frac(0, 3, 4)).display_with(QuantityDisplay::Decimal),
+        "0.75 can"
+    );
+}
+
+#[test]
+fn test_count_display_with_each() {
+    assert_eq!(
+        Measure::count(3).display_with_each(QuantityDisplay::Fraction),
+        "3 each"
+    );
+    assert_eq!(
+        Count(Quantity::frac(1, 1, 2)).display_with_each(QuantityDisplay::Decimal),
+        "1.5 each"
+    );
+    assert_eq!(format!("{}", Measure::count(3)), "3");
+    assert_eq!(
+        Measure::cup(Quantity::Whole(2)).display_with_each(QuantityDisplay::Fraction),
+        "2 cups"
+    );
+}
+
+#[test]
+fn test_weight_fmt_compound() {
+    assert_eq!(
+        WeightMeasure::Pound(Quantity::frac(2, 7, 20)).fmt_compound(),
+        "2 lbs 5.6 oz"
+    );
+    assert_eq!(
+        WeightMeasure::Pound(Quantity::frac(1, 1, 4)).fmt_compound(),
+        "1 lb 4 oz"
+    );
+    assert_eq!(WeightMeasure::Oz(20.into()).fmt_compound(), "1 lb 4 oz");
+    assert_eq!(WeightMeasure::Pound(2.into()).fmt_compound(), "2 lbs");
+    assert_eq!(
+        WeightMeasure::Pound(Quantity::frac(0, 1, 2)).fmt_compound(),
+        "1/2 lb"
+    );
+    assert_eq!(
+        WeightMeasure::Kilogram(Quantity::frac(1, 1, 2)).fmt_compound(),
+        "1 1/2 kilograms"
+    );
+    // The existing Display is untouched.
+    assert_eq!(
+        format!("{}", WeightMeasure::Pound(Quantity::frac(1, 1, 4))),
+        "1 1/4 lbs"
+    );
+}
+
+#[test]
+fn test_volume_fmt_compound() {
+    assert_eq!(Qrt(Quantity::frac(1, 1, 4)).fmt_compound(), "1 qrt 1 cup");
+    assert_eq!(Qrt(Quantity::frac(2, 1, 2)).fmt_compound(), "2 qrts 2 cups");
+    assert_eq!(Gal(Quantity::frac(1, 1, 2)).fmt_compound(), "1 gal 2 qrts");
+    assert_eq!(
+        Pint(Quantity::frac(1, 1, 4)).fmt_compound(),
+        "1 pint 0.5 cup"
+    );
+    assert_eq!(Cup(Quantity::frac(2, 1, 2)).fmt_compound(), "2 1/2 cups");
+    assert_eq!(Ltr(Quantity::frac(1, 1, 2)).fmt_compound(), "1 1/2 ltr");
+}
+
+#[test]
+fn test_measure_display_compound() {
+    assert_eq!(
+        Measure::lb(Quantity::frac(1, 1, 4)).display_compound(QuantityDisplay::Fraction),
+        "1 lb 4 oz"
+    );
+    assert_eq!(
+        Measure::cup(Quantity::frac(2, 1, 2)).display_compound(QuantityDisplay::Decimal),
+        "2.5 cups"
+    );
+    assert_eq!(
+        Measure::count(3).display_compound(QuantityDisplay::Fraction),
+        "3"
+    );
+}
+
+#[test]
+fn test_ingredient_unit_aliases() {
+    for (i, expected) in vec![
+        (
+            "1 T butter",
+            Ingredient::new("butter", None, Volume(Tbsp(Quantity::Whole(1)))),
+        ),
+        (
+            "1 tbl butter",
+            Ingredient::new("butter", None, Volume(Tbsp(Quantity::Whole(1)))),
+        ),
+        (
+            "2 t vanilla",
+            Ingredient::new("vanilla", None, Volume(Tsp(Quantity::Whole(2)))),
+        ),
+        (
+            "1 c flour",
+            Ingredient::new("flour", None, Volume(Cup(Quantity::Whole(1)))),
+        ),
+        (
+            "1 pt cream",
+            Ingredient::new("cream", None, Volume(Pint(Quantity::Whole(1)))),
+        ),
+        (
+            "2 qt stock",
+            Ingredient::new("stock", None, Volume(Qrt(Quantity::Whole(2)))),
+        ),
+        (
+            "4 fl oz milk",
+            Ingredient::new("milk", None, Volume(Floz(Quantity::Whole(4)))),
+        ),
+        (
+            "10 g. yeast",
+            Ingredient::new("yeast", None, Weight(Gram(Quantity::Whole(10)))),
+        ),
+        (
+            "8 oz. cheese",
+            Ingredient::new("cheese", None, Weight(Oz(Quantity::Whole(8)))),
+        ),
+        (
+            "1/2 t salt",
+            Ingredient::new("salt", None, Volume(Tsp(Quantity::Frac(Ratio::new(1, 2))))),
+        ),
+        (
+            "3 each eggs",
+            Ingredient::new("eggs", None, Count(Quantity::Whole(3))),
+        ),
+        (
+            "2 ea lemons",
+            Ingredient::new("lemons", None, Count(Quantity::Whole(2))),
+        ),
+        (
+            "2 ea. limes",
+            Ingredient::new("limes", None, Count(Quantity::Whole(2))),
+        ),
+    ] {
+        match parse::ingredient(StrIter::new(i)) {
+            ParseResult::Complete(_, ing) => assert_eq!(ing, expected, "parsing {}", i),
+            err => assert!(false, "{:?}", err),
+        }
+    }
+}
+
+#[test]
+fn test_ingredient_unit_aliases_dont_mangle_names() {
+    for (i, expected) in vec![
+        // Single letter aliases must be followed by whitespace.
+        (
+            "1 tomato",
+            Ingredient::new("tomato", None, Count(Quantity::Whole(1))),
+        ),
+        (
+            "2 cloves garlic",
+            Ingredient::new("cloves garlic", None, Count(Quantity::Whole(2))),
+        ),
+        (
+            "1 thyme sprig",
+            Ingredient::new("thyme sprig", None, Count(Quantity::Whole(1))),
+        ),
+        (
+            "3 potatoes",
+            Ingredient::new("potatoes", None, Count(Quantity::Whole(3))),
+        ),
+        (
+            "1 quince",
+            Ingredient::new("quince", None, Count(Quantity::Whole(1))),
+        ),
+        (
+            "1 garlic clove",
+            Ingredient::new("garlic clove", None, Count(Quantity::Whole(1))),
+        ),
+        (
+            "1 earl grey tea bag",
+            Ingredient::new("earl grey tea bag", None, Count(Quantity::Whole(1))),
+        ),
+        // The full names still win over the aliases.
+        (
+            "1 tsp salt",
+            Ingredient::new("salt", None, Volume(Tsp(Quantity::Whole(1)))),
+        ),
+        (
+            "1 tbsp butter",
+            Ingredient::new("butter", None, Volume(Tbsp(Quantity::Whole(1)))),
+        ),
+        (
+            "2 oz cheese",
+            Ingredient::new("cheese", None, Weight(Oz(Quantity::Whole(2)))),
+        ),
+    ] {
+        match parse::ingredient(StrIter::new(i)) {
+            ParseResult::Complete(_, ing) => assert_eq!(ing, expected, "parsing {}", i),
+            err => assert!(false, "{:?}", err),
+        }
+    }
+}
+
+#[test]
+fn test_measure_handles_every_unit_without_panicking() {
+    // NOTE(jwall): Every unit the parser accepts must map to a measure. This
+    // used to end in unreachable!() for units that didn't.
+    let units = "tsps,tsp,teaspoons,teaspoon,tablespoons,tablespoon,tbsps,tbsp,floz,fl oz,\
+        ml,ltr,pound,lbs,lb,oz,cups,cup,c,qrts,qrt,quarts,quart,qt,pints,pint,pnt,pt,gals,gal,\
+        cnt,each,kilograms,kilogram,kg,grams,gram,g,pkg,package,bottle,bot,bag,can,T,t,\
+        pinches,pinch,dashes,dash,imp pint,imp pints,imperial pint,imperial pints,imp fl oz,\
+        imp floz,imperial fl oz,imperial floz";
+    for unit in units.split(',') {
+        let i = format!("2 {} flour", unit);
+        match parse::ingredient(StrIter::new(&i)) {
+            ParseResult::Complete(_, ing) => {
+                assert_eq!(ing.name, "flour", "parsing {}", i);
+                assert!(
+                    !matches!(ing.amt, Count(_)) || unit == "cnt" || unit == "each",
+                    "parsing {} gave {:?}",
+                    i,
+                    ing.amt
+                );
+            }
+            err => assert!(false, "parsing {}: {:?}", i, err),
+        }
+    }
+}
+
+#[test]
+fn test_unknown_unit_is_part_of_the_name() {
+    assert_eq!(
+        parse::as_measure("2 fortnight").expect("Failed to parse measure"),
+        Count(Quantity::Whole(2))
+    );
+    match parse::ingredient(StrIter::new("2 fortnight garlic")) {
+        ParseResult::Complete(_, ing) => assert_eq!(
+            ing,
+            Ingredient::new("fortnight garlic", None, Count(Quantity::Whole(2)))
+        ),
+        err => assert!(false, "{:?}", err),
+    }
+}
+
+#[test]
+fn test_divided_ingredient_parse() {
+    let sugar = parse::as_ingredient_list("1 cup sugar (divided)\n1 onion (chopped, divided)")
+        .expect("divided ingredients should parse");
+    assert_eq!(sugar[0].form, None);
+    assert!(sugar[0].divided);
+    assert_eq!(sugar[1].form, Some("chopped".to_owned()));
+    assert!(sugar[1].divided);
+    assert_eq!(
+        serialize::as_ingredient_text(&sugar[0]),
+        "1 cup sugar (divided)"
+    );
+    assert_eq!(format!("{}", sugar[1]), "1 onion (chopped, divided)");
+    let plain = parse::as_ingredient_list("1 onion (chopped)").expect("should parse");
+    assert!(!plain[0].divided);
+}
+
+#[test]
+fn test_divided_ingredient_counted_once_per_recipe() {
+    let recipe = |marker: &str| {
+        parse::as_recipe(&format!(
+            "title: cake
+
+step:
+
+1 cup sugar{0}
+
+Whisk half the sugar into the eggs.
+
+step:
+
+1/2 cup sugar{0}
+
+Fold in the rest of the sugar.
+",
+            marker
+        ))
+        .expect("recipe should parse")
+    };
+    let sugar = |recipe: &Recipe, times: usize| {
+        let mut acc = IngredientAccumulator::new();
+        for _ in 0..times {
+            acc.accumulate_from(recipe);
+        }
+        acc.ingredients()
+            .into_values()
+            .map(|(i, _)| i.amt)
+            .collect::<Vec<Measure>>()
+    };
+    // Without the marker every listing is counted.
+    assert_eq!(
+        sugar(&recipe(""), 1),
+        vec![Measure::cup(Quantity::frac(1, 1, 2))]
+    );
+    // With it only the first listing is counted.
+    assert_eq!(
+        sugar(&recipe(" (divided)"), 1),
+        vec![Measure::cup(Quantity::Whole(1))]
+    );
+    // But still once for every time the recipe is planned.
+    assert_eq!(
+        sugar(&recipe(" (divided)"), 2),
+        vec![Measure::cup(Quantity::Whole(2))]
+    );
+}
+
+#[test]
+fn test_accumulator_keeps_mismatched_packages_separate() {
+    let staples = vec![Ingredient::new(
+        "olives",
+        None,
+        Measure::pkg("can", Quantity::Whole(1)),
+    )];
+    let tapenade = Recipe::new("tapenade", None).with_steps(vec![Step::new(None, "Chop")
+        .with_ingredients(vec![Ingredient::new(
+            "olives",
+            None,
+            Measure::pkg("bag", Quantity::Whole(2)),
+        )])]);
+    let mut acc = IngredientAccumulator::new();
+    acc.accumulate_ingredients_for("Staples", staples.iter());
+    acc.accumulate_from(&tapenade);
+    acc.accumulate_from(&tapenade);
+    assert_eq!(acc.warnings().len(), 1);
+    assert!(acc.warnings().iter().next().unwrap().contains("olives"));
+    let ingredients = acc.ingredients_with_breakdown();
+    assert_eq!(ingredients.len(), 2);
+    let amts = ingredients
+        .values()
+        .map(|(i, _)| i.amt.clone())
+        .collect::<Vec<Measure>>();
+    assert!(amts.contains(&Measure::pkg("can", Quantity::Whole(1))));
+    assert!(amts.contains(&Measure::pkg("bag", Quantity::Whole(4))));
+}
+
+#[test]
+fn test_staples_parse_detailed() {
+    let staples = "1 tsp salt\n2 cups milk\n1 pkg butter\n";
+    let parsed = parse::as_ingredient_list_detailed(staples).expect("staples should parse");
+    assert_eq!(parsed.len(), 3);
+    assert_eq!(parsed[0].name, "salt");
+    assert_eq!(parsed[2].amt, Measure::pkg("pkg", Quantity::Whole(1)));
+
+    let err = parse::as_ingredient_list_detailed("1 tsp salt\n2 cups milk\nbutter\n")
+        .expect_err("staples should not parse");
+    assert_eq!(err.line, 3);
+    assert_eq!(err.column, 1);
+    assert_eq!(
+        format!("{}", err),
+        "Invalid ingredient at line 3 column 1".to_owned()
+    );
+}
+
+#[test]
+fn test_merge_ingredients_combines_staples() {
+    let staples = parse::as_ingredient_list("1 tsp salt\n2 cups milk\n1/2 tsp salt\n")
+        .expect("staples should parse");
+    let merged = merge_ingredients(staples);
+    assert_eq!(merged.len(), 2);
+    assert_eq!(merged[0].name, "milk");
+    assert_eq!(merged[1].name, "salt");
+    assert_eq!(merged[1].amt, Measure::tsp(Quantity::frac(1, 1, 2)));
+    assert_eq!(merged[1].to_string(), "1 1/2 tsps salt");
+}
+
+#[test]
+fn test_round_converted_quantity() {
+    let grams = Weight(Oz(Quantity::Whole(1)).into_gram());
+    // The exact conversion is 28.34952 grams.
+    assert_eq!(
+        grams.round_to(1),
+        Weight(Gram(Quantity::Frac(Ratio::new(283, 10))))
+    );
+    assert_eq!(
+        grams.round_to(2),
+        Weight(Gram(Quantity::Frac(Ratio::new(2835, 100))))
+    );
+    assert_eq!(
+        grams.round_to(1).display_with(QuantityDisplay::Decimal),
+        "28.3 grams"
+    );
+    assert_eq!(
+        grams.round_to(2).display_with(QuantityDisplay::Decimal),
+        "28.35 grams"
+    );
+    // Rounding doesn't touch the exact value.
+    assert_eq!(grams, Weight(Oz(Quantity::Whole(1)).into_gram()));
+    // Whole quantities are left alone.
+    assert_eq!(Measure::count(3).round_to(1), Measure::count(3));
+    // Large quantities and precisions don't overflow while rounding.
+    let big = Quantity::Frac(Ratio::new(u32::MAX - 1, 3));
+    assert!((big.round_to(2).approx_f32() - big.approx_f32()).abs() < 1.0);
+    let third = Quantity::Frac(Ratio::new(1, 3));
+    assert_eq!(third.round_to(40), third);
+}
+
+#[test]
+fn test_density_merges_volume_and_weight() {
+    let bread = Recipe::new("bread", None).with_steps(vec![Step::new(None, "Mix")
+        .with_ingredients(vec![
+            Ingredient::new("flour", None, Volume(Cup(Quantity::Whole(1)))),
+            Ingredient::new("flour", None, Weight(Gram(Quantity::Whole(120)))),
+        ])]);
+    let mut acc = IngredientAccumulator::new();
+    acc.accumulate_from(&bread);
+    assert_eq!(acc.ingredients().len(), 2);
+
+    let mut acc = IngredientAccumulator::new().with_densities(density::DensityTable::default());
+    acc.accumulate_from(&bread);
+    assert!(acc.warnings().is_empty());
+    let ingredients = acc.ingredients();
+    assert_eq!(ingredients.len(), 1);
+    let (flour, _) = ingredients.values().next().unwrap();
+    match &flour.amt {
+        Weight(wm) => assert_eq!(wm.get_grams(), Quantity::Whole(245)),
+        amt => panic!("Expected a weight measure but got {:?}", amt),
+    }
+}
+
+#[test]
+fn test_density_leaves_unmixed_volumes_alone() {
+    let cake =
+        Recipe::new("cake", None).with_steps(vec![Step::new(None, "Mix").with_ingredients(vec![
+            Ingredient::new("flour", None, Volume(Cup(Quantity::Whole(2)))),
+        ])]);
+    let bread =
+        Recipe::new("bread", None).with_steps(vec![Step::new(None, "Mix").with_ingredients(vec![
+            Ingredient::new("flour", None, Weight(Gram(Quantity::Whole(120)))),
+        ])]);
+    let mut acc = IngredientAccumulator::new().with_densities(density::DensityTable::default());
+    acc.accumulate_from(&cake);
+    let ingredients = acc.ingredients();
+    assert_eq!(ingredients.len(), 1);
+    let (flour, _) = ingredients.values().next().unwrap();
+    assert_eq!(flour.amt, Volume(Cup(Quantity::Whole(2))));
+
+    let mut acc = IngredientAccumulator::new().with_densities(density::DensityTable::default());
+    acc.accumulate_from(&cake);
+    acc.accumulate_from(&bread);
+    let ingredients = acc.ingredients_with_breakdown();
+    assert_eq!(ingredients.len(), 1);
+    let (flour, breakdown) = ingredients.values().next().unwrap();
+    let grams = |amt: &Measure| match amt {
+        Weight(wm) => wm.get_grams(),
+        amt => panic!("Expected a weight measure but got {:?}", amt),
+    };
+    assert_eq!(grams(&flour.amt), Quantity::Whole(370));
+    assert_eq!(grams(&breakdown["cake"]), Quantity::Whole(250));
+    assert_eq!(grams(&breakdown["bread"]), Quantity::Whole(120));
+}
+
+#[test]
+fn test_density_table_is_extensible() {
+    let mut table = density::DensityTable::new();
+    assert!(table.get("honey").is_none());
+    table.insert("Honey", Quantity::Frac(Ratio::new(7, 5)));
+    assert_eq!(
+        table.to_weight("honey", &Volume(ML(Quantity::Whole(10)))),
+        Some(Weight(Gram(Quantity::Whole(14))))
+    );
+    assert_eq!(
+        table.to_weight("honey", &Weight(Gram(Quantity::Whole(10)))),
+        None
+    );
+}
+
+#[test]
+fn test_convert_with_density() {
+    let flour = Ratio::new(125, 240);
+    let cups = Measure::gram(Quantity::Whole(250))
+        .convert_with_density(flour)
+        .expect("Failed to convert grams");
+    assert_eq!(cups, Measure::cup(Quantity::Whole(2)));
+    assert!(matches!(cups, Volume(vm) if !vm.metric()));
+    assert_eq!(
+        Measure::cup(Quantity::Whole(1)).convert_with_density(flour),
+        Some(Measure::gram(Quantity::Whole(125)))
+    );
+    assert_eq!(Measure::count(1).convert_with_density(flour), None);
+    assert_eq!(
+        Measure::gram(Quantity::Whole(1)).convert_with_density(Ratio::new(0, 1)),
+        None
+    );
+    let table = density::DensityTable::default();
+    assert_eq!(
+        table.convert("Flour", &Measure::cup(Quantity::Whole(1))),
+        Some(Measure::gram(Quantity::Whole(125)))
+    );
+    assert_eq!(
+        table.convert("salt", &Measure::cup(Quantity::Whole(1))),
+        None
+    );
+}
+
+#[test]
+fn test_measure_type_override_forces_count() {
+    let garlic = [
+        Ingredient::new("garlic", None, Measure::count(2)),
+        Ingredient::new("garlic", None, Volume(Tsp(Quantity::Whole(1)))),
+    ];
+    let mut acc = IngredientAccumulator::new();
+    acc.accumulate_ingredients_for("pasta", garlic.iter());
+    assert_eq!(acc.ingredients().len(), 2);
+
+    let mut acc = IngredientAccumulator::new()
+        .with_measure_types(BTreeMap::from([("garlic".to_owned(), MeasureType::Count)]));
+    acc.accumulate_ingredients_for("pasta", garlic.iter());
+    assert!(acc.warnings().is_empty());
+    let ingredients = acc.ingredients();
+    assert_eq!(ingredients.len(), 1);
+    let (key, (ing, _)) = ingredients.iter().next().unwrap();
+    assert_eq!(key.measure_type(), "Count");
+    assert_eq!(ing.amt, Measure::count(3));
+}
+
+#[test]
+fn test_measure_type_override_forces_volume() {
+    let flour = [
+        Ingredient::new("flour", None, Volume(Cup(Quantity::Whole(1)))),
+        Ingredient::new("flour", None, Weight(Gram(Quantity::Whole(120)))),
+    ];
+    let mut acc = IngredientAccumulator::new()
+        .with_densities(density::DensityTable::default())
+        .with_measure_types(BTreeMap::from([("flour".to_owned(), MeasureType::Volume)]));
+    acc.accumulate_ingredients_for("bread", flour.iter());
+    let ingredients = acc.ingredients();
+    assert_eq!(ingredients.len(), 1);
+    let (flour, _) = ingredients.values().next().unwrap();
+    match &flour.amt {
+        Volume(vm) => assert_eq!(vm.get_ml(), Quantity::Frac(Ratio::new(2352, 5))),
+        amt => panic!("Expected a volume measure but got {:?}", amt),
+    }
+}
+
+#[test]
+fn test_measure_type_names() {
+    for mt in [MeasureType::Volume, MeasureType::Count, MeasureType::Weight] {
+        assert_eq!(MeasureType::try_from(mt.to_string().as_str()), Ok(mt));
+    }
+    assert_eq!(
+        Measure::count(1).measure_type(),
+        MeasureType::Count.to_string()
+    );
+    assert!(MeasureType::try_from("Package").is_err());
+}
+
+#[test]
+fn test_quantity_checked_arithmetic() {
+    let max = Quantity::Whole(u32::MAX);
+    assert_eq!(max.checked_add(Quantity::Whole(1)), None);
+    assert_eq!(max.checked_mul(Quantity::Whole(2)), None);
+    assert_eq!(Quantity::Whole(1).checked_sub(Quantity::Whole(2)), None);
+    assert_eq!(Quantity::Whole(1).checked_div(Quantity::Whole(0)), None);
+    assert_eq!(
+        Quantity::Whole(1).checked_add(Quantity::Frac(Ratio::new(1, 2))),
+        Some(Quantity::Frac(Ratio::new(3, 2)))
+    );
+    // The operators saturate instead of panicking.
+    assert_eq!(max + Quantity::Whole(1), max);
+    assert_eq!(Quantity::Whole(1) - Quantity::Whole(2), Quantity::Whole(0));
+    // Denominators that are too large are approximated.
+    let approx = Quantity::Frac(Ratio::new(1, 65537))
+        .checked_add(Quantity::Frac(Ratio::new(1, 65539)))
+        .expect("sum should be approximated");
+    assert!((approx.approx_f32() - (1.0 / 65537.0 + 1.0 / 65539.0)).abs() < 0.000001);
+}
+
+#[test]
+fn test_accumulating_pathological_ingredient_does_not_panic() {
+    let feast = Recipe::new("feast", None).with_steps(vec![Step::new(None, "Cook")
+        .with_ingredients(vec![
+            Ingredient::new("water", None, Volume(Gal(Quantity::Whole(1_000_000)))),
+            Ingredient::new(
+                "cheese",
+                None,
+                Weight(Oz(Quantity::Frac(Ratio::new(1000, 997)))),
+            ),
+        ])]);
+    let mut acc = IngredientAccumulator::new();
+    for _ in 0..5000 {
+        acc.accumulate_from(&feast);
+    }
+    assert_eq!(acc.warnings().len(), 1);
+    assert!(acc.warnings().iter().next().unwrap().contains("water"));
+    let ingredients = acc.ingredients();
+    assert_eq!(ingredients.len(), 2);
+}
+
+#[test]
+fn test_serialize_edited_recipe_round_trips() {
+    let text = "title: gooey apple bake
+tags: dessert
+
+A simple gooey apple bake recipe.
+
+step: 10 min
+
+1 tbsp flour
+2 cups apples (sliced)
+
+Saute apples in butter until golden brown.
+
+step:
+
+
+Bake until gooey.
+";
+    let mut recipe = parse::as_recipe(text).expect("Failed to parse recipe");
+    // Edit the recipe like the structured editor would.
+    recipe.steps.swap(0, 1);
+    recipe.steps[1].ingredients.remove(0);
+    recipe.steps[1].ingredients.push(Ingredient::new(
+        "brown sugar",
+        None,
+        Volume(Cup(Quantity::Frac(Ratio::new(1, 2)))),
+    ));
+    recipe.steps[1].instructions = "Mix everything.\n\nThen saute.".to_owned();
+    recipe.steps[0].prep_time = Some(std::time::Duration::from_secs(2 * 60 * 60));
+
+    let serialized = serialize::as_recipe_text(&recipe);
+    let reparsed = parse::as_recipe(&serialized).expect("Failed to parse serialized recipe");
+    assert_eq!(reparsed.title, recipe.title);
+    assert_eq!(reparsed.tags, recipe.tags);
+    assert_eq!(reparsed.steps.len(), 2);
+    assert_eq!(
+        reparsed.steps[0].prep_time,
+        Some(std::time::Duration::from_secs(2 * 60 * 60))
+    );
+    assert!(reparsed.steps[0].ingredients.is_empty());
+    assert_eq!(reparsed.steps[0].instructions.trim(), "Bake until gooey.");
+    assert_eq!(
+        reparsed.steps[1].prep_time,
+        Some(std::time::Duration::from_secs(10 * 60))
+    );
+    assert_eq!(reparsed.steps[1].ingredients, recipe.steps[1].ingredients);
+    assert_eq!(
+        reparsed.steps[1].instructions.trim(),
+        "Mix everything.\nThen saute."
+    );
+    // Serializing the reparsed recipe gives us the same text back.
+    assert_eq!(serialize::as_recipe_text(&reparsed), serialized);
+}
+
+#[test]
+fn test_serialize_duration() {
+    for (secs, expected) in vec![
+        (30, "30 sec"),
+        (90, "90 sec"),
+        (120, "2 min"),
+        (3600, "1 hrs"),
+    ] {
+        assert_eq!(
+            serialize::as_duration_text(&std::time::Duration::from_secs(secs)),
+            expected
+        );
+    }
+}
+
+#[test]
+fn test_recipe_sections() {
+    let text = "title: pizza
+
+section: Dough
+step:
+
+2 cups flour
+1 cup water
+
+Knead the dough.
+
+step:
+
+
+Let it rise.
+
+section: Sauce
+step:
+
+1 cup tomato
+1 tsp salt
+
+Simmer the sauce.
+
+section: Dough
+step:
+
+1 tsp salt
+
+Stretch the dough.
+";
+    let recipe = parse::as_recipe(text).expect("Failed to parse recipe with sections");
+    assert_eq!(recipe.desc, None);
+    let sections = recipe
+        .steps
+        .iter()
+        .map(|s| s.section.as_deref())
+        .collect::<Vec<Option<&str>>>();
+    assert_eq!(
+        sections,
+        vec![Some("Dough"), Some("Dough"), Some("Sauce"), Some("Dough")]
+    );
+    let by_section = recipe.get_ingredients_by_section();
+    assert_eq!(by_section.len(), 2);
+    assert_eq!(by_section[0].0.as_deref(), Some("Dough"));
+    assert_eq!(by_section[0].1.len(), 3);
+    assert_eq!(by_section[1].0.as_deref(), Some("Sauce"));
+    assert_eq!(by_section[1].1.len(), 2);
+    // Sections survive serializing the recipe.
+    let serialized = serialize::as_recipe_text(&recipe);
+    let reparsed = parse::as_recipe(&serialized).expect("Failed to parse serialized recipe");
+    assert_eq!(reparsed.steps, recipe.steps);
+}
+
+#[test]
+fn test_recipe_without_sections() {
+    let text = "title: soup
+
+A soup.
+
+step:
+
+1 cup water
+
+Boil the water.
+";
+    let recipe = parse::as_recipe(text).expect("Failed to parse recipe");
+    assert_eq!(recipe.steps[0].section, None);
+    let by_section = recipe.get_ingredients_by_section();
+    assert_eq!(by_section.len(), 1);
+    assert_eq!(by_section[0].0, None);
+    assert_eq!(by_section[0].1, recipe.get_ingredients());
+}
+
+#[test]
+fn test_fractional_count_display() {
+    for (qty, expected) in vec![
+        (Quantity::Frac(Ratio::new(3, 2)), "1 1/2"),
+        (Quantity::Frac(Ratio::new(6, 4)), "1 1/2"),
+        (Quantity::Frac(Ratio::new(4, 2)), "2"),
+        (Quantity::Frac(Ratio::new(1, 2)), "1/2"),
+        (Quantity::Whole(3), "3"),
+    ] {
+        assert_eq!(Count(qty).to_string(), expected);
+    }
+}
+
+#[test]
+fn test_describe_plural() {
+    for (amt, name, expected) in vec![
+        (Measure::count(1), "onions", "1 onion"),
+        (
+            Count(Quantity::Frac(Ratio::new(1, 2))),
+            "onion",
+            "1/2 onion",
+        ),
+        (Measure::count(2), "onion", "2 onions"),
+        (Measure::count(2), "onions", "2 onions"),
+        (
+            Count(Quantity::Frac(Ratio::new(3, 2))),
+            "Green Onion",
+            "1 1/2 Green Onions",
+        ),
+        (Measure::count(2), "hummus", "2 hummus"),
+        (Measure::count(2), "cloves garlic", "2 cloves garlic"),
+        (Measure::count(1), "cloves garlic", "1 cloves garlic"),
+        (Measure::count(2), "egg (beaten)", "2 eggs (beaten)"),
+        (Measure::count(1), "tomatoes, diced", "1 tomato, diced"),
+        (Measure::count(2), "tomato", "2 tomatoes"),
+        (Volume(Cup(Quantity::Whole(2))), "flour", "2 cups flour"),
+    ] {
+        assert_eq!(amt.describe_plural(name), expected);
+    }
+}
+
+#[test]
+fn test_accumulated_fractional_counts() {
+    let half = Ingredient::new("onion", None, Count(Quantity::Frac(Ratio::new(1, 2))));
+    let mut acc = IngredientAccumulator::new();
+    acc.accumulate_ingredients_for("soup", vec![half.clone()].iter());
+    acc.accumulate_ingredients_for("stew", vec![half.clone(), half].iter());
+    let ingredients = acc.ingredients();
+    assert_eq!(ingredients.len(), 1);
+    let (onion, _) = ingredients.values().next().unwrap();
+    assert_eq!(onion.amt.to_string(), "1 1/2");
+    assert_eq!(onion.amt.describe_plural(&onion.name), "1 1/2 onions");
+}
+
+#[test]
+fn test_recipe_unit_conversion() {
+    let recipe = Recipe::new("bread", None).with_steps(vec![Step::new(None, "Mix.")
+        .with_ingredients(vec![
+            Ingredient::new("flour", None, Volume(Cup(Quantity::Whole(2)))),
+            Ingredient::new(
+                "butter",
+                None,
+                Weight(WeightMeasure::Pound(Quantity::Whole(1))),
+            ),
+            Ingredient::new(
+                "sugar",
+                None,
+                Weight(WeightMeasure::Gram(Quantity::Whole(1000))),
+            ),
+            Ingredient::new("egg", None, Measure::count(2)),
+            Ingredient::new("yeast", None, Package("pkg".into(), Quantity::Whole(1))),
+        ])]);
+    let metric = recipe.to_metric();
+    let amts = metric.steps[0]
+        .ingredients
+        .iter()
+        .map(|i| i.amt.clone())
+        .collect::<Vec<Measure>>();
+    assert_eq!(amts[0].to_string(), "480 ml");
+    assert!(matches!(amts[1], Weight(WeightMeasure::Gram(_))));
+    assert_eq!(amts[2].to_string(), "1 kilogram");
+    assert_eq!(amts[3], Measure::count(2));
+    assert_eq!(amts[4], Package("pkg".into(), Quantity::Whole(1)));
+
+    let us = metric.to_us();
+    let amts = us.steps[0]
+        .ingredients
+        .iter()
+        .map(|i| i.amt.clone())
+        .collect::<Vec<Measure>>();
+    assert_eq!(amts[0].to_string(), "1 pint");
+    assert!(matches!(amts[1], Weight(WeightMeasure::Pound(_))));
+    assert!(matches!(amts[2], Weight(WeightMeasure::Pound(_))));
+    assert_eq!(amts[3], Measure::count(2));
+    // The original recipe is left alone.
+    assert_eq!(recipe.steps[0].ingredients[0].amt.to_string(), "2 cups");
+}
+
+#[test]
+fn test_convert_to_system() {
+    assert_eq!(
+        Measure::cup(Quantity::Whole(2))
+            .convert_to_system(UnitSystem::Metric)
+            .to_string(),
+        "480 ml"
+    );
+    assert_eq!(
+        Measure::ml(Quantity::Whole(480))
+            .convert_to_system(UnitSystem::US)
+            .to_string(),
+        "1 pint"
+    );
+    assert!(matches!(
+        Weight(WeightMeasure::Gram(Quantity::Whole(1000))).convert_to_system(UnitSystem::US),
+        Weight(WeightMeasure::Pound(_))
+    ));
+    assert_eq!(
+        Measure::count(2).convert_to_system(UnitSystem::Metric),
+        Measure::count(2)
+    );
+    assert_eq!(
+        Package("pkg".into(), Quantity::Whole(1)).convert_to_system(UnitSystem::US),
+        Package("pkg".into(), Quantity::Whole(1))
+    );
+    assert_eq!(UnitSystem::try_from("metric"), Ok(UnitSystem::Metric));
+    assert_eq!(UnitSystem::try_from("us"), Ok(UnitSystem::US));
+    assert!(UnitSystem::try_from("imperial").is_err());
+    // Units saved before the US variant was renamed still load.
+    assert_eq!(
+        serde_json::from_str::<UnitSystem>(r#""Imperial""#).expect("Failed to parse units"),
+        UnitSystem::US
+    );
+    assert!(UnitSystem::try_from("cubits").is_err());
+}
+
+#[test]
+fn test_total_prep_time() {
+    let recipe = Recipe::new("stew", None).with_steps(vec![
+        Step::new(Some(std::time::Duration::from_secs(600)), "Chop."),
+        Step::new(None, "Stir."),
+        Step::new(Some(std::time::Duration::from_secs(3600)), "Simmer."),
+    ]);
+    assert_eq!(
+        recipe.total_prep_time(),
+        std::time::Duration::from_secs(4200)
+    );
+    assert_eq!(
+        Recipe::new("toast", None).total_prep_time(),
+        std::time::Duration::ZERO
+    );
+}
+
+#[test]
+fn test_recipe_markdown_and_text_export() {
+    let text = "title: gooey apple bake
+
+A simple gooey apple bake recipe.
+
+step: 10 min
+
+1 tbsp flour
+2 cups apples (sliced)
+
+Saute apples in butter until golden brown.
+
+step:
+
+
+Bake until gooey.
+";
+    let recipe = parse::as_recipe(text).expect("Failed to parse recipe");
+    assert_eq!(
+        recipe.to_markdown(),
+        "# gooey apple bake
+
+A simple gooey apple bake recipe.
+
+## Step 1 (10 min)
+
+- [ ] 1 tbsp flour
+- [ ] 2 cups apples (sliced)
+
+Saute apples in butter until golden brown.
+
+## Step 2
+
+Bake until gooey.
+"
+    );
+    assert_eq!(
+        recipe.to_text(),
+        "gooey apple bake
+
+A simple gooey apple bake recipe.
+
+Step 1 (10 min)
+  * 1 tbsp flour
+  * 2 cups apples (sliced)
+
+Saute apples in butter until golden brown.
+
+Step 2
+
+Bake until gooey.
+"
+    );
+}
+
+#[test]
+fn test_recipe_markdown_sections() {
+    let recipe = Recipe::new("pizza", None).with_steps(vec![
+        Step::new(None, "Knead.").with_section("Dough"),
+        Step::new(None, "Rise.").with_section("Dough"),
+        Step::new(None, "Simmer.").with_section("Sauce"),
+    ]);
+    assert_eq!(
+        recipe.to_markdown(),
+        "# pizza
+
+## Dough
+
+### Step 1
+
+Knead.
+
+### Step 2
+
+Rise.
+
+## Sauce
+
+### Step 3
+
+Simmer.
+"
+    );
+}
+
+#[test]
+fn test_mealplan_days() {
+    let monday = NaiveDate::from_ymd_opt(2023, 1, 2).unwrap();
+    let tuesday = NaiveDate::from_ymd_opt(2023, 1, 3).unwrap();
+    let lasagna = Recipe::new("lasagna", None).with_steps(vec![Step::new(None, "Bake.")
+        .with_ingredients(vec![Ingredient::new("onion", None, Measure::count(1))])]);
+    let soup = Recipe::new("soup", None).with_steps(vec![Step::new(None, "Simmer.")
+        .with_ingredients(vec![Ingredient::new("onion", None, Measure::count(2))])]);
+    let mut plan = Mealplan::new().with_start_date(monday);
+    plan.add_recipe_on(monday, soup.clone(), 1);
+    assert!(plan.add_recipe_on_day(1, lasagna.clone(), 2));
+    assert!(!Mealplan::new().add_recipe_on_day(1, lasagna.clone(), 2));
+
+    assert_eq!(plan.recipes_for(monday), vec![(&soup, 1)]);
+    assert_eq!(plan.recipes_for(tuesday), vec![(&lasagna, 2)]);
+    assert!(plan.recipes_for(tuesday.succ_opt().unwrap()).is_empty());
+
+    let onion = |acc: IngredientAccumulator| {
+        acc.ingredients()
+            .into_values()
+            .map(|(i, _)| i.amt)
+            .collect::<Vec<Measure>>()
+    };
+    assert_eq!(
+        onion(plan.shopping_list_between(tuesday, tuesday)),
+        vec![Measure::count(2)]
+    );
+    assert_eq!(
+        onion(plan.shopping_list_between(monday, tuesday)),
+        vec![Measure::count(4)]
+    );
+
+    let json = serde_json::to_string(&plan).expect("Failed to serialize plan");
+    assert_eq!(
+        serde_json::from_str::<Mealplan>(&json).expect("Failed to deserialize plan"),
+        plan
+    );
+}
+
+#[test]
+fn test_recipe_ingredients_by_step() {
+    let recipe = parse::as_recipe(
+        "title: soup
+
+step:
+
+1 cup onion
+1/2 cup onion
+1 tsp salt
+
+Saute the onion.
+
+step:
+
+1 cup onion
+1 qrt stock
+
+Simmer.
+
+step:
+
+
+Serve.
+",
+    )
+    .expect("Failed to parse recipe");
+    let steps = recipe
+        .ingredients_by_step()
+        .into_iter()
+        .map(|(idx, ingredients)| {
+            (
+                idx,
+                ingredients
+                    .into_values()
+                    .map(|i| format!("{}", i))
+                    .collect::<Vec<String>>(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        steps,
+        vec![
+            (
+                0,
+                vec!["1 1/2 cups onion".to_owned(), "1 tsp salt".to_owned()]
+            ),
+            (1, vec!["1 cup onion".to_owned(), "1 qrt stock".to_owned()]),
+            (2, vec![]),
+        ]
+    );
+    // The grand total still combines the steps.
+    let onion = recipe
+        .get_ingredients()
+        .into_values()
+        .find(|i| i.name == "onion")
+        .expect("No onion");
+    assert_eq!(onion.amt, Measure::cup(Quantity::frac(2, 1, 2)));
+}
+
+#[test]
+fn test_sub_recipe_ingredients_are_accumulated() {
+    let pizza = parse::as_recipe(
+        "title: Pizza
+
+step:
+
+1 cup tomato sauce
+recipe:pizza_dough
+
+Spread the sauce on the dough.
+",
+    )
+    .expect("recipe should parse");
+    assert_eq!(pizza.steps[0].ingredients.len(), 1);
+    assert_eq!(pizza.steps[0].sub_recipes, vec!["pizza_dough".to_owned()]);
+    assert_eq!(
+        parse::as_recipe(&serialize::as_recipe_text(&pizza)).expect("should round trip"),
+        pizza
+    );
+    let dough = parse::as_recipe(
+        "title: Pizza Dough
+
+step:
+
+3 cup flour
+1 cup water
+
+Knead.
+",
+    )
+    .expect("recipe should parse");
+    let recipes = BTreeMap::from([
+        ("pizza".to_owned(), pizza.clone()),
+        ("pizza_dough".to_owned(), dough),
+    ]);
+    let mut acc = IngredientAccumulator::new();
+    acc.accumulate_from_with(&pizza, &recipes);
+    assert!(acc.warnings().is_empty());
+    let names = acc
+        .ingredients()
+        .into_values()
+        .map(|(i, _)| i.name)
+        .collect::<Vec<String>>();
+    assert_eq!(names, vec!["flour", "tomato sauce", "water"]);
+}
+
+#[test]
+fn test_sub_recipe_cycles_are_not_expanded() {
+    let recipe = |title: &str, uses: &str| {
+        Recipe::new(title, None).with_steps(vec![Step::new(None, "Mix.")
+            .with_ingredients(vec![Ingredient::new(
+                title.to_lowercase(),
+                None,
+                Measure::count(1),
+            )])
+            .with_sub_recipes(vec![uses.to_owned()])])
+    };
+    let recipes = BTreeMap::from([
+        ("a".to_owned(), recipe("A", "b")),
+        ("b".to_owned(), recipe("B", "a")),
+    ]);
+    let mut acc = IngredientAccumulator::new();
+    acc.accumulate_from_with(&recipes["a"], &recipes);
+    assert_eq!(acc.warnings().len(), 1);
+    let amts = acc
+        .ingredients()
+        .into_values()
+        .map(|(i, _)| (i.name, i.amt))
+        .collect::<Vec<(String, Measure)>>();
+    assert_eq!(
+        amts,
+        vec![
+            ("a".to_owned(), Measure::count(1)),
+            ("b".to_owned(), Measure::count(1))
+        ]
+    );
+
+    let mut acc = IngredientAccumulator::new();
+    acc.accumulate_from_with(&recipe("C", "missing"), &recipes);
+    assert_eq!(
+        acc.warnings().iter().collect::<Vec<&String>>(),
+        vec!["C uses unknown recipe missing"]
+    );
+    assert!(parse::as_recipe("title: D\n\nstep:\n\nrecipe:\n\nMix.\n").is_err());
+}
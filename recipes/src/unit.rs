@@ -26,11 +26,16 @@ use std::{
 };
 
 use num_rational::Ratio;
+use serde::{Deserialize, Serialize};
 
-#[derive(Copy, Clone, Debug, PartialOrd, Eq, Ord)]
+#[derive(Copy, Clone, Debug, PartialOrd, Eq, Ord, Serialize, Deserialize)]
 /// Volume Measurements for ingredients in a recipe.
 pub enum VolumeMeasure {
-    // Imperial volume measurements. US.
+    // US customary volume measurements.
+    /// Pinch measurements.
+    Pinch(Quantity), // 5/16 ml
+    /// Dash measurements.
+    Dash(Quantity), // 5/8 ml
     /// Teaspoon measurements.
     Tsp(Quantity), // 5 ml
     /// Tablespoon measurements.
@@ -45,15 +50,32 @@ pub enum VolumeMeasure {
     Gal(Quantity), // 3800 ml
     /// Fluid Ounces
     Floz(Quantity), // 30 ml
+    // British imperial volume measurements.
+    /// Imperial fluid ounces.
+    ImpFloz(Quantity), // 28.4130625 ml
+    /// Imperial pints.
+    ImpPint(Quantity), // 568.26125 ml
     // Metric volume measurements.
     /// Milliliter Measurements.
     ML(Quantity), // Base unit
     // Liter Measurements.
     Ltr(Quantity), // 1000 ml
 }
-use VolumeMeasure::{Cup, Floz, Gal, Ltr, Pint, Qrt, Tbsp, Tsp, ML};
+use VolumeMeasure::{Cup, Dash, Floz, Gal, ImpFloz, ImpPint, Ltr, Pinch, Pint, Qrt, Tbsp, Tsp, ML};
+
+/// The system of units a volume is measured in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VolumeSystem {
+    Metric,
+    /// US customary units.
+    US,
+    /// British imperial units.
+    British,
+}
 
 // multiplier contants for various units into milliliter. Used in conversion functions.
+const PINCH: Quantity = Quantity::Frac(Ratio::new_raw(5, 16));
+const DASH: Quantity = Quantity::Frac(Ratio::new_raw(5, 8));
 const TSP: Quantity = Quantity::Whole(5);
 const TBSP: Quantity = Quantity::Whole(15);
 const FLOZ: Quantity = Quantity::Whole(30);
@@ -62,6 +84,8 @@ const PINT: Quantity = Quantity::Whole(480);
 const QRT: Quantity = Quantity::Whole(960);
 const LTR: Quantity = Quantity::Whole(1000);
 const GAL: Quantity = Quantity::Whole(3840);
+const IMP_FLOZ: Quantity = Quantity::Frac(Ratio::new_raw(454609, 16000));
+const IMP_PINT: Quantity = Quantity::Frac(Ratio::new_raw(454609, 800));
 
 // multiplier constants for various units into grams
 const LB: Quantity = Quantity::Frac(Ratio::new_raw(4535924, 10000));
@@ -75,9 +99,13 @@ impl VolumeMeasure {
     pub fn get_ml(&self) -> Quantity {
         match self {
             ML(qty) => *qty,
+            Pinch(qty) => *qty * PINCH,
+            Dash(qty) => *qty * DASH,
             Tsp(qty) => *qty * TSP,
             Tbsp(qty) => *qty * TBSP,
             Floz(qty) => *qty * FLOZ,
+            ImpFloz(qty) => *qty * IMP_FLOZ,
+            ImpPint(qty) => *qty * IMP_PINT,
             Cup(qty) => *qty * CUP,
             Pint(qty) => *qty * PINT,
             Qrt(qty) => *qty * QRT,
@@ -86,17 +114,83 @@ impl VolumeMeasure {
         }
     }
 
+    /// Get this measures `Quantity` as milliliters. Returns None if it's too
+    /// large to represent.
+    pub fn checked_ml(&self) -> Option<Quantity> {
+        match self {
+            ML(qty) => Some(*qty),
+            Pinch(qty) => qty.checked_mul(PINCH),
+            Dash(qty) => qty.checked_mul(DASH),
+            Tsp(qty) => qty.checked_mul(TSP),
+            Tbsp(qty) => qty.checked_mul(TBSP),
+            Floz(qty) => qty.checked_mul(FLOZ),
+            ImpFloz(qty) => qty.checked_mul(IMP_FLOZ),
+            ImpPint(qty) => qty.checked_mul(IMP_PINT),
+            Cup(qty) => qty.checked_mul(CUP),
+            Pint(qty) => qty.checked_mul(PINT),
+            Qrt(qty) => qty.checked_mul(QRT),
+            Gal(qty) => qty.checked_mul(GAL),
+            Ltr(qty) => qty.checked_mul(LTR),
+        }
+    }
+
+    /// Add two volume measures. Returns None if the sum is too large to
+    /// represent.
+    pub fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        let result = ML(self.checked_ml()?.checked_add(rhs.checked_ml()?)?);
+        Some(self.normalize_like(result))
+    }
+
     pub fn metric(&self) -> bool {
+        self.system() == VolumeSystem::Metric
+    }
+
+    /// The system of units this volume is measured in.
+    pub fn system(&self) -> VolumeSystem {
         match self {
-            ML(_) | Ltr(_) => true,
-            _ => false,
+            ML(_) | Ltr(_) => VolumeSystem::Metric,
+            ImpFloz(_) | ImpPint(_) => VolumeSystem::British,
+            _ => VolumeSystem::US,
+        }
+    }
+
+    /// Convert a volume into the best fitting unit in this volume's system.
+    fn normalize_like(&self, volume: Self) -> Self {
+        match self.system() {
+            VolumeSystem::Metric => volume.into_metric(),
+            VolumeSystem::US => volume.into_us(),
+            VolumeSystem::British => volume.into_imp_floz().normalize(),
         }
     }
 
     pub fn plural(&self) -> bool {
+        self.quantity().plural()
+    }
+
+    /// The `Quantity` of this measure in its own unit.
+    pub fn quantity(&self) -> Quantity {
         match self {
-            Tsp(qty) | Tbsp(qty) | Cup(qty) | Pint(qty) | Qrt(qty) | Gal(qty) | Floz(qty)
-            | ML(qty) | Ltr(qty) => qty.plural(),
+            Pinch(qty) | Dash(qty) | Tsp(qty) | Tbsp(qty) | Cup(qty) | Pint(qty) | Qrt(qty)
+            | Gal(qty) | Floz(qty) | ImpFloz(qty) | ImpPint(qty) | ML(qty) | Ltr(qty) => *qty,
+        }
+    }
+
+    /// Round the quantity to the given number of decimal places.
+    pub fn round_to(&self, places: u32) -> Self {
+        match *self {
+            Pinch(qty) => Pinch(qty.round_to(places)),
+            Dash(qty) => Dash(qty.round_to(places)),
+            Tsp(qty) => Tsp(qty.round_to(places)),
+            Tbsp(qty) => Tbsp(qty.round_to(places)),
+            Cup(qty) => Cup(qty.round_to(places)),
+            Pint(qty) => Pint(qty.round_to(places)),
+            Qrt(qty) => Qrt(qty.round_to(places)),
+            Gal(qty) => Gal(qty.round_to(places)),
+            Floz(qty) => Floz(qty.round_to(places)),
+            ImpFloz(qty) => ImpFloz(qty.round_to(places)),
+            ImpPint(qty) => ImpPint(qty.round_to(places)),
+            ML(qty) => ML(qty.round_to(places)),
+            Ltr(qty) => Ltr(qty.round_to(places)),
         }
     }
 
@@ -105,6 +199,16 @@ impl VolumeMeasure {
         ML(self.get_ml())
     }
 
+    /// Convert into pinches.
+    pub fn into_pinch(self) -> Self {
+        Pinch(self.get_ml() / PINCH)
+    }
+
+    /// Convert into dashes.
+    pub fn into_dash(self) -> Self {
+        Dash(self.get_ml() / DASH)
+    }
+
     /// Convert into teaspoons.
     pub fn into_tsp(self) -> Self {
         Tsp(self.get_ml() / TSP)
@@ -120,6 +224,16 @@ impl VolumeMeasure {
         Floz(self.get_ml() / FLOZ)
     }
 
+    /// Convert into imperial fluid oz.
+    pub fn into_imp_floz(self) -> Self {
+        ImpFloz(self.get_ml() / IMP_FLOZ)
+    }
+
+    /// Convert into imperial pints.
+    pub fn into_imp_pint(self) -> Self {
+        ImpPint(self.get_ml() / IMP_PINT)
+    }
+
     /// Convert into cups.
     pub fn into_cup(self) -> Self {
         Cup(self.get_ml() / CUP)
@@ -145,10 +259,35 @@ impl VolumeMeasure {
         Ltr(self.get_ml() / LTR)
     }
 
+    /// Convert into the best fitting metric unit.
+    pub fn into_metric(self) -> Self {
+        self.into_ml().normalize()
+    }
+
+    /// Convert into the best fitting US customary unit.
+    pub fn into_us(self) -> Self {
+        self.into_tsp().normalize()
+    }
+
     pub fn normalize(&self) -> Self {
-        // We try to maintain metric vs not metric in our normalization logic.
+        // We try to maintain the system of units in our normalization logic.
         let metric = self.metric();
         let ml = self.get_ml();
+        if self.system() == VolumeSystem::British {
+            // NOTE(jwall): British recipes use the same spoons as everyone
+            // else so small amounts are normalized to those.
+            return if (ml / IMP_PINT) >= ONE {
+                self.clone().into_imp_pint()
+            } else if (ml / IMP_FLOZ) >= ONE {
+                self.clone().into_imp_floz()
+            } else if (ml / TBSP) >= ONE {
+                self.clone().into_tbsp()
+            } else if (ml / TSP) >= ONE {
+                self.clone().into_tsp()
+            } else {
+                self.clone()
+            };
+        }
         if (ml / GAL) >= ONE && !metric {
             return self.clone().into_gal();
         }
@@ -176,6 +315,32 @@ impl VolumeMeasure {
             self.clone()
         };
     }
+
+    /// Split an imperial volume into a whole amount of its unit and the
+    /// leftover in the next smaller unit. e.g. `1 1/4 qrts` becomes `1 qrt`
+    /// and `1 cup`. Returns None for cups and smaller, metric volumes, and
+    /// amounts that don't split.
+    pub fn compound_parts(&self) -> Option<(Self, Self)> {
+        match *self {
+            Gal(qty) => split_compound(qty, 4).map(|(whole, rest)| (Gal(whole), Qrt(rest))),
+            Qrt(qty) => split_compound(qty, 4).map(|(whole, rest)| (Qrt(whole), Cup(rest))),
+            Pint(qty) => split_compound(qty, 2).map(|(whole, rest)| (Pint(whole), Cup(rest))),
+            ImpPint(qty) => {
+                split_compound(qty, 20).map(|(whole, rest)| (ImpPint(whole), ImpFloz(rest)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Display this volume using compound imperial units. e.g. `1 qrt 1 cup`
+    /// instead of `1 1/4 qrts`. Volumes that don't split display the same as
+    /// `Display`.
+    pub fn fmt_compound(&self) -> String {
+        match self.compound_parts() {
+            Some((whole, rest)) => fmt_compound_parts(whole, Volume(rest)),
+            None => format!("{}", self),
+        }
+    }
 }
 
 macro_rules! volume_op {
@@ -186,11 +351,7 @@ macro_rules! volume_op {
             fn $method(self, lhs: Self) -> Self::Output {
                 let (l, r) = (self.get_ml(), lhs.get_ml());
                 let result = ML($trait::$method(l, r));
-                if self.metric() {
-                    result.normalize()
-                } else {
-                    result.into_tsp().normalize()
-                }
+                self.normalize_like(result)
             }
         }
 
@@ -200,11 +361,7 @@ macro_rules! volume_op {
             fn $method(self, lhs: Self) -> Self::Output {
                 let (l, r) = (self.get_ml(), lhs.get_ml());
                 let result = ML($trait::$method(l, r));
-                if self.metric() {
-                    result.normalize()
-                } else {
-                    result.into_tsp().normalize()
-                }
+                self.normalize_like(result)
             }
         }
     };
@@ -213,6 +370,30 @@ macro_rules! volume_op {
 volume_op!(Add, add);
 volume_op!(Sub, sub);
 
+macro_rules! volume_scalar_op {
+    ($trait:ident, $method:ident) => {
+        impl $trait<Quantity> for &VolumeMeasure {
+            type Output = VolumeMeasure;
+
+            fn $method(self, rhs: Quantity) -> Self::Output {
+                let result = ML($trait::$method(self.get_ml(), rhs));
+                self.normalize_like(result)
+            }
+        }
+
+        impl $trait<Quantity> for VolumeMeasure {
+            type Output = Self;
+
+            fn $method(self, rhs: Quantity) -> Self::Output {
+                $trait::$method(&self, rhs)
+            }
+        }
+    };
+}
+
+volume_scalar_op!(Mul, mul);
+volume_scalar_op!(Div, div);
+
 impl PartialEq for VolumeMeasure {
     fn eq(&self, lhs: &Self) -> bool {
         let rhs = self.get_ml();
@@ -224,6 +405,8 @@ impl PartialEq for VolumeMeasure {
 impl Display for VolumeMeasure {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            Pinch(qty) => write!(f, "{} pinch{}", qty, if qty.plural() { "es" } else { "" }),
+            Dash(qty) => write!(f, "{} dash{}", qty, if qty.plural() { "es" } else { "" }),
             Tsp(qty) => write!(f, "{} tsp{}", qty, if qty.plural() { "s" } else { "" }),
             Tbsp(qty) => write!(f, "{} tbsp{}", qty, if qty.plural() { "s" } else { "" }),
             Cup(qty) => write!(f, "{} cup{}", qty, if qty.plural() { "s" } else { "" }),
@@ -231,13 +414,15 @@ impl Display for VolumeMeasure {
             Qrt(qty) => write!(f, "{} qrt{}", qty, if qty.plural() { "s" } else { "" }),
             Gal(qty) => write!(f, "{} gal{}", qty, if qty.plural() { "s" } else { "" }),
             Floz(qty) => write!(f, "{} floz", qty),
+            ImpFloz(qty) => write!(f, "{} imp floz", qty),
+            ImpPint(qty) => write!(f, "{} imp pint{}", qty, if qty.plural() { "s" } else { "" }),
             ML(qty) => write!(f, "{} ml", qty),
             Ltr(qty) => write!(f, "{} ltr", qty),
         }
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialOrd, Eq, Ord)]
+#[derive(Copy, Clone, Debug, PartialOrd, Eq, Ord, Serialize, Deserialize)]
 pub enum WeightMeasure {
     Gram(Quantity),
     Kilogram(Quantity),
@@ -255,6 +440,28 @@ impl WeightMeasure {
         }
     }
 
+    /// Get this measures `Quantity` as grams. Returns None if it's too large
+    /// to represent.
+    pub fn checked_grams(&self) -> Option<Quantity> {
+        match self {
+            &Self::Gram(qty) => Some(qty),
+            &Self::Kilogram(qty) => qty.checked_mul(KG),
+            &Self::Pound(qty) => qty.checked_mul(LB),
+            &Self::Oz(qty) => qty.checked_mul(OZ),
+        }
+    }
+
+    /// Add two weight measures. Returns None if the sum is too large to
+    /// represent.
+    pub fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        let result = Self::Gram(self.checked_grams()?.checked_add(rhs.checked_grams()?)?);
+        Some(if self.metric() {
+            result.normalize()
+        } else {
+            result.into_oz().normalize()
+        })
+    }
+
     pub fn metric(&self) -> bool {
         match self {
             Gram(_) | Kilogram(_) => true,
@@ -263,10 +470,23 @@ impl WeightMeasure {
     }
 
     pub fn plural(&self) -> bool {
+        self.quantity().plural()
+    }
+
+    /// The `Quantity` of this measure in its own unit.
+    pub fn quantity(&self) -> Quantity {
         match self {
-            &Self::Gram(qty) | &Self::Kilogram(qty) | &Self::Pound(qty) | &Self::Oz(qty) => {
-                qty.plural()
-            }
+            &Self::Gram(qty) | &Self::Kilogram(qty) | &Self::Pound(qty) | &Self::Oz(qty) => qty,
+        }
+    }
+
+    /// Round the quantity to the given number of decimal places.
+    pub fn round_to(&self, places: u32) -> Self {
+        match *self {
+            Self::Gram(qty) => Self::Gram(qty.round_to(places)),
+            Self::Kilogram(qty) => Self::Kilogram(qty.round_to(places)),
+            Self::Pound(qty) => Self::Pound(qty.round_to(places)),
+            Self::Oz(qty) => Self::Oz(qty.round_to(places)),
         }
     }
 
@@ -286,6 +506,16 @@ impl WeightMeasure {
         Self::Oz(self.get_grams() / OZ)
     }
 
+    /// Convert into the best fitting metric unit.
+    pub fn into_metric(self) -> Self {
+        self.into_gram().normalize()
+    }
+
+    /// Convert into the best fitting US customary unit.
+    pub fn into_us(self) -> Self {
+        self.into_oz().normalize()
+    }
+
     pub fn normalize(&self) -> Self {
         let metric = self.metric();
         let grams = self.get_grams();
@@ -304,6 +534,48 @@ impl WeightMeasure {
             self.clone()
         };
     }
+
+    /// Split an imperial weight into whole pounds and the leftover ounces.
+    /// e.g. `2 7/20 lbs` becomes `2 lbs` and `5 3/5 oz`. Returns None for
+    /// metric weights and amounts that don't split.
+    pub fn compound_parts(&self) -> Option<(Self, Self)> {
+        match *self {
+            Self::Pound(qty) => {
+                split_compound(qty, 16).map(|(whole, rest)| (Self::Pound(whole), Self::Oz(rest)))
+            }
+            Self::Oz(qty) if qty >= Whole(16) => Self::Pound(qty / Whole(16)).compound_parts(),
+            _ => None,
+        }
+    }
+
+    /// Display this weight using compound imperial units. e.g. `2 lbs 5.6 oz`
+    /// instead of `2 7/20 lbs`. Weights that don't split display the same as
+    /// `Display`.
+    pub fn fmt_compound(&self) -> String {
+        match self.compound_parts() {
+            Some((whole, rest)) => fmt_compound_parts(whole, Weight(rest)),
+            None => format!("{}", self),
+        }
+    }
+}
+
+/// Split a quantity into its whole part and its fractional part scaled into
+/// a smaller unit. Returns None if either part would be empty.
+fn split_compound(qty: Quantity, factor: u32) -> Option<(Quantity, Quantity)> {
+    let (whole, frac) = qty.extract_parts();
+    let rest = Frac(frac) * Whole(factor);
+    // NOTE(jwall): A leftover that rounds away in display isn't worth
+    // showing as a separate unit.
+    if whole == 0 || rest.decimal_string() == "0" {
+        return None;
+    }
+    Some((Whole(whole), rest))
+}
+
+/// Display the parts of a compound measure. The leftover is shown as a decimal
+/// since it rarely comes out to a tidy fraction of the smaller unit.
+fn fmt_compound_parts<D: Display>(whole: D, rest: Measure) -> String {
+    format!("{} {}", whole, rest.display_with(QuantityDisplay::Decimal))
 }
 
 macro_rules! weight_op {
@@ -341,6 +613,34 @@ macro_rules! weight_op {
 weight_op!(Add, add);
 weight_op!(Sub, sub);
 
+macro_rules! weight_scalar_op {
+    ($trait:ident, $method:ident) => {
+        impl $trait<Quantity> for &WeightMeasure {
+            type Output = WeightMeasure;
+
+            fn $method(self, rhs: Quantity) -> Self::Output {
+                let result = WeightMeasure::Gram($trait::$method(self.get_grams(), rhs));
+                if self.metric() {
+                    result.normalize()
+                } else {
+                    result.into_oz().normalize()
+                }
+            }
+        }
+
+        impl $trait<Quantity> for WeightMeasure {
+            type Output = Self;
+
+            fn $method(self, rhs: Quantity) -> Self::Output {
+                $trait::$method(&self, rhs)
+            }
+        }
+    };
+}
+
+weight_scalar_op!(Mul, mul);
+weight_scalar_op!(Div, div);
+
 impl PartialEq for WeightMeasure {
     fn eq(&self, lhs: &Self) -> bool {
         let rhs = self.get_grams();
@@ -364,7 +664,7 @@ impl Display for WeightMeasure {
 
 use WeightMeasure::{Gram, Kilogram, Oz, Pound};
 
-#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord)]
+#[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Serialize, Deserialize)]
 /// Measurements in a Recipe with associated units for them.
 pub enum Measure {
     /// Volume measurements as meter cubed base unit
@@ -378,7 +678,47 @@ pub enum Measure {
 
 use Measure::{Count, Package, Volume, Weight};
 
+/// The kinds of measure an ingredient can be forced into. These use the same
+/// names as `Measure::measure_type`.
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Serialize, Deserialize)]
+pub enum MeasureType {
+    Volume,
+    Count,
+    Weight,
+}
+
+impl Display for MeasureType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MeasureType::Volume => write!(f, "Volume"),
+            MeasureType::Count => write!(f, "Count"),
+            MeasureType::Weight => write!(f, "Weight"),
+        }
+    }
+}
+
+impl TryFrom<&str> for MeasureType {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "Volume" => Ok(MeasureType::Volume),
+            "Count" => Ok(MeasureType::Count),
+            "Weight" => Ok(MeasureType::Weight),
+            _ => Err(format!("Invalid measure type: {}", value)),
+        }
+    }
+}
+
 impl Measure {
+    pub fn pinch(qty: Quantity) -> Self {
+        Volume(Pinch(qty))
+    }
+
+    pub fn dash(qty: Quantity) -> Self {
+        Volume(Dash(qty))
+    }
+
     pub fn tsp(qty: Quantity) -> Self {
         Volume(Tsp(qty))
     }
@@ -391,6 +731,14 @@ impl Measure {
         Volume(Floz(qty))
     }
 
+    pub fn imp_floz(qty: Quantity) -> Self {
+        Volume(ImpFloz(qty))
+    }
+
+    pub fn imp_pint(qty: Quantity) -> Self {
+        Volume(ImpPint(qty))
+    }
+
     pub fn ml(qty: Quantity) -> Self {
         Volume(ML(qty))
     }
@@ -441,6 +789,20 @@ impl Measure {
         Package(name.into(), qty)
     }
 
+    /// Add two measures of the same kind. Returns None if the measures can't
+    /// be combined or the sum is too large to represent.
+    pub fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        match (self, rhs) {
+            (Volume(lvm), Volume(rvm)) => lvm.checked_add(rvm).map(Volume),
+            (Count(lqty), Count(rqty)) => lqty.checked_add(*rqty).map(Count),
+            (Weight(lwm), Weight(rwm)) => lwm.checked_add(rwm).map(Weight),
+            (Package(lnm, lqty), Package(rnm, rqty)) if lnm == rnm => {
+                lqty.checked_add(*rqty).map(|qty| Package(lnm.clone(), qty))
+            }
+            _ => None,
+        }
+    }
+
     pub fn measure_type(&self) -> String {
         match self {
             Volume(_) => "Volume",
@@ -452,11 +814,118 @@ impl Measure {
     }
 
     pub fn plural(&self) -> bool {
+        self.quantity().plural()
+    }
+
+    /// The `Quantity` of this measure in its own unit.
+    pub fn quantity(&self) -> Quantity {
+        match self {
+            Volume(vm) => vm.quantity(),
+            Count(qty) => *qty,
+            Weight(wm) => wm.quantity(),
+            Package(_, qty) => *qty,
+        }
+    }
+
+    /// Round the quantity to the given number of decimal places for display.
+    /// Conversions can produce unwieldy fractions so use this before
+    /// displaying a converted measure.
+    pub fn round_to(&self, places: u32) -> Self {
+        match self {
+            Volume(vm) => Volume(vm.round_to(places)),
+            Count(qty) => Count(qty.round_to(places)),
+            Weight(wm) => Weight(wm.round_to(places)),
+            Package(nm, qty) => Package(nm.clone(), qty.round_to(places)),
+        }
+    }
+
+    /// Display this measure with the quantity rendered in the requested style.
+    pub fn display_with(&self, style: QuantityDisplay) -> String {
+        let display = format!("{}", self);
+        match style {
+            QuantityDisplay::Fraction => display,
+            QuantityDisplay::Decimal => {
+                // NOTE(jwall): Every measure displays its quantity first so we
+                // just swap it out for the decimal version.
+                let qty = self.quantity();
+                let frac = format!("{}", qty);
+                format!("{}{}", qty.decimal_string(), &display[frac.len()..])
+            }
+        }
+    }
+
+    /// Convert volumes and weights into the best fitting metric unit. Counts
+    /// and packages are left alone.
+    pub fn into_metric(self) -> Self {
+        match self {
+            Volume(vm) => Volume(vm.into_metric()),
+            Weight(wm) => Weight(wm.into_metric()),
+            other => other,
+        }
+    }
+
+    /// Convert volumes and weights into the best fitting US customary unit.
+    /// Counts and packages are left alone.
+    pub fn into_us(self) -> Self {
         match self {
-            Volume(vm) => vm.plural(),
-            Count(qty) => qty.plural(),
-            Weight(wm) => wm.plural(),
-            Package(_, qty) => qty.plural(),
+            Volume(vm) => Volume(vm.into_us()),
+            Weight(wm) => Weight(wm.into_us()),
+            other => other,
+        }
+    }
+
+    /// Convert volumes and weights into the best fitting unit of `system`.
+    pub fn convert_to_system(self, system: UnitSystem) -> Self {
+        match system {
+            UnitSystem::Metric => self.into_metric(),
+            UnitSystem::US => self.into_us(),
+        }
+    }
+
+    /// Convert between weight and volume using a density in grams per ml.
+    /// Weights become US volumes and volumes become metric weights since those
+    /// are the usual units for a quick check. Returns None for counts,
+    /// packages, a zero density, or amounts too large to represent.
+    pub fn convert_with_density(&self, density: Ratio<u32>) -> Option<Measure> {
+        let density = Frac(density);
+        match self {
+            Volume(vm) => Some(Weight(
+                Gram(vm.checked_ml()?.checked_mul(density)?).normalize(),
+            )),
+            Weight(wm) => Some(Volume(
+                ML(wm.checked_grams()?.checked_div(density)?).into_us(),
+            )),
+            Count(_) | Package(..) => None,
+        }
+    }
+
+    /// Describe this amount of the named item. Count measures pluralize the
+    /// name to match the quantity. e.g. `1/2 onion` or `2 onions`. Other
+    /// measures already pluralize their unit. e.g. `2 cups flour`
+    pub fn describe_plural(&self, name: &str) -> String {
+        match self {
+            Count(qty) => format!("{} {}", qty, crate::parse::inflect_name(name, qty.plural())),
+            _ => format!("{} {}", self, name),
+        }
+    }
+
+    /// Display this measure like `display_with` but with count measures
+    /// labeled with "each". e.g. `3 each`
+    pub fn display_with_each(&self, style: QuantityDisplay) -> String {
+        let display = self.display_with(style);
+        match self {
+            Count(_) => format!("{} each", display),
+            _ => display,
+        }
+    }
+
+    /// Display this measure using compound imperial units where they apply.
+    /// e.g. `2 lbs 5.6 oz`. Everything else displays like `display_with`.
+    pub fn display_compound(&self, style: QuantityDisplay) -> String {
+        match self {
+            Volume(vm) if vm.compound_parts().is_some() => vm.fmt_compound(),
+            Weight(wm) if wm.compound_parts().is_some() => wm.fmt_compound(),
+            _ => self.display_with(style),
         }
     }
 
@@ -470,6 +939,36 @@ impl Measure {
     }
 }
 
+macro_rules! measure_scalar_op {
+    ($trait:ident, $method:ident) => {
+        impl $trait<Quantity> for &Measure {
+            type Output = Measure;
+
+            fn $method(self, rhs: Quantity) -> Self::Output {
+                match self {
+                    Volume(vm) => Volume($trait::$method(vm, rhs)),
+                    Count(qty) => Count($trait::$method(*qty, rhs)),
+                    Weight(wm) => Weight($trait::$method(wm, rhs)),
+                    Package(nm, qty) => Package(nm.clone(), $trait::$method(*qty, rhs)),
+                }
+            }
+        }
+
+        impl $trait<Quantity> for Measure {
+            type Output = Self;
+
+            fn $method(self, rhs: Quantity) -> Self::Output {
+                $trait::$method(&self, rhs)
+            }
+        }
+    };
+}
+
+// NOTE(jwall): Scaling a measure keeps it metric or imperial the same way that
+// normalize does.
+measure_scalar_op!(Mul, mul);
+measure_scalar_op!(Div, div);
+
 impl Display for Measure {
     fn fmt(&self, w: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -481,8 +980,59 @@ impl Display for Measure {
     }
 }
 
+/// The default number of decimal places to round converted quantities to for
+/// display.
+pub const DEFAULT_PRECISION: u32 = 2;
+
+/// How a `Quantity` should be rendered for display.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QuantityDisplay {
+    /// Whole numbers and fractions. e.g. `1 1/2`
+    Fraction,
+    /// Decimals with trailing zeros trimmed. e.g. `1.5`
+    Decimal,
+}
+
+impl Default for QuantityDisplay {
+    fn default() -> Self {
+        QuantityDisplay::Fraction
+    }
+}
+
+/// The system of units amounts should be converted into for display.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UnitSystem {
+    Metric,
+    /// US customary units.
+    // NOTE(jwall): This used to be called Imperial so we still accept that
+    // name for units saved before the rename.
+    #[serde(alias = "Imperial")]
+    US,
+}
+
+impl Display for UnitSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnitSystem::Metric => write!(f, "metric"),
+            UnitSystem::US => write!(f, "us"),
+        }
+    }
+}
+
+impl TryFrom<&str> for UnitSystem {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "metric" => Ok(UnitSystem::Metric),
+            "us" => Ok(UnitSystem::US),
+            _ => Err(format!("Unknown units {}. Expected metric or us", value)),
+        }
+    }
+}
+
 /// Represents a Quantity for an ingredient of a recipe.
-#[derive(Copy, Clone, Debug, Eq, Ord)]
+#[derive(Copy, Clone, Debug, Eq, Ord, Serialize, Deserialize)]
 pub enum Quantity {
     /// Whole or non fractional quantities of an ingredient in a recipe.
     Whole(u32),
@@ -524,23 +1074,116 @@ impl Quantity {
     }
 
     /// Approximate a quantity as a float. This will lose precision in the case
-    /// of fractional quantities.
+    /// of fractional quantities that can't be represented exactly.
     pub fn approx_f32(self) -> f32 {
         match self {
             Whole(v) => v as f32,
-            Frac(v) => (*v.numer() / *v.denom()) as f32,
+            Frac(v) => *v.numer() as f32 / *v.denom() as f32,
         }
     }
 
+    /// Round the quantity to the given number of decimal places.
+    pub fn round_to(self, places: u32) -> Self {
+        match self {
+            Whole(_) => self,
+            // NOTE(jwall): We scale with u128 so that large quantities or
+            // many places can't overflow. If it still doesn't fit we leave
+            // the quantity unrounded.
+            Frac(_) => {
+                let wide = self.wide_ratio();
+                let (numer, denom) = (*wide.numer(), *wide.denom());
+                10u128
+                    .checked_pow(places)
+                    .and_then(|scale| Some((numer.checked_mul(scale)?, scale)))
+                    .map(|(scaled, scale)| {
+                        let rounded = scaled / denom + u128::from(scaled % denom * 2 >= denom);
+                        Ratio::new(rounded, scale)
+                    })
+                    .and_then(narrow_ratio)
+                    .map(Quantity::from)
+                    .unwrap_or(self)
+            }
+        }
+    }
+
+    /// Render the quantity as a decimal rounded to two places with any
+    /// trailing zeros trimmed. e.g. `1 1/2` becomes `1.5`.
+    pub fn decimal_string(self) -> String {
+        let decimal = format!("{:.2}", self.approx_f32());
+        decimal
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_owned()
+    }
+
     pub fn plural(&self) -> bool {
         match self {
             Whole(v) => *v > 1,
             Frac(r) => *r > Ratio::new(1, 1),
         }
     }
+
+    /// Add two quantities. Returns None if the sum is too large to represent.
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        self.checked_op(rhs, |l, r| Some(l + r))
+    }
+
+    /// Subtract two quantities. Returns None if the result would be negative.
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.checked_op(rhs, |l, r| if l >= r { Some(l - r) } else { None })
+    }
+
+    /// Multiply two quantities. Returns None if the product is too large to
+    /// represent.
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        self.checked_op(rhs, |l, r| Some(l * r))
+    }
+
+    /// Divide two quantities. Returns None if dividing by zero or the result
+    /// is too large to represent.
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        self.checked_op(rhs, |l, r| if *r.numer() == 0 { None } else { Some(l / r) })
+    }
+
+    // NOTE(jwall): We do the math with u128 ratios so that the intermediate
+    // values can't overflow and then narrow the result back down to u32.
+    fn checked_op<F>(self, rhs: Self, op: F) -> Option<Self>
+    where
+        F: FnOnce(Ratio<u128>, Ratio<u128>) -> Option<Ratio<u128>>,
+    {
+        op(self.wide_ratio(), rhs.wide_ratio())
+            .and_then(narrow_ratio)
+            .map(Frac)
+    }
+
+    fn wide_ratio(self) -> Ratio<u128> {
+        match self {
+            Whole(v) => Ratio::from_integer(v as u128),
+            Frac(v) => Ratio::new_raw(*v.numer() as u128, *v.denom() as u128),
+        }
+    }
 }
 use Quantity::{Frac, Whole};
 
+/// Narrow a u128 ratio down to a u32 ratio. If the ratio can't be represented
+/// exactly it is approximated by the closest ratio with a denominator that
+/// fits. Returns None if the whole part of the ratio doesn't fit in a u32.
+fn narrow_ratio(r: Ratio<u128>) -> Option<Ratio<u32>> {
+    let max = u32::MAX as u128;
+    let (numer, denom) = (*r.numer(), *r.denom());
+    if numer <= max && denom <= max {
+        return Some(Ratio::new_raw(numer as u32, denom as u32));
+    }
+    let whole = numer / denom;
+    if whole >= max {
+        return None;
+    }
+    // Use the largest denominator that keeps the numerator in range.
+    let approx_denom = std::cmp::min(denom, max / (whole + 1));
+    let approx_numer = (numer * approx_denom + denom / 2) / denom;
+    Some(Ratio::new(approx_numer as u32, approx_denom as u32))
+}
+
 pub struct ConversionError {
     pub err_message: String,
 }
@@ -570,24 +1213,12 @@ impl TryFrom<f32> for Quantity {
 }
 
 macro_rules! quantity_op {
-    ($trait:ident, $method:ident) => {
+    ($trait:ident, $method:ident, $checked:ident, $saturated:expr) => {
         impl $trait for &Quantity {
             type Output = Quantity;
 
             fn $method(self, lhs: Self) -> Self::Output {
-                match (self, lhs) {
-                    (Whole(rhs), Whole(lhs)) => Frac($trait::$method(
-                        Ratio::from_integer(*rhs),
-                        Ratio::from_integer(*lhs),
-                    )),
-                    (Frac(rhs), Frac(lhs)) => Frac($trait::$method(rhs, lhs)),
-                    (Whole(rhs), Frac(lhs)) => {
-                        Frac($trait::$method(Ratio::from_integer(*rhs), lhs))
-                    }
-                    (Frac(rhs), Whole(lhs)) => {
-                        Frac($trait::$method(rhs, Ratio::from_integer(*lhs)))
-                    }
-                }
+                self.$checked(*lhs).unwrap_or($saturated)
             }
         }
 
@@ -595,24 +1226,18 @@ macro_rules! quantity_op {
             type Output = Self;
 
             fn $method(self, lhs: Self) -> Self::Output {
-                match (self, lhs) {
-                    (Whole(rhs), Whole(lhs)) => Frac($trait::$method(
-                        Ratio::from_integer(rhs),
-                        Ratio::from_integer(lhs),
-                    )),
-                    (Frac(rhs), Frac(lhs)) => Frac($trait::$method(rhs, lhs)),
-                    (Whole(rhs), Frac(lhs)) => Frac($trait::$method(Ratio::from_integer(rhs), lhs)),
-                    (Frac(rhs), Whole(lhs)) => Frac($trait::$method(rhs, Ratio::from_integer(lhs))),
-                }
+                self.$checked(lhs).unwrap_or($saturated)
             }
         }
     };
 }
 
-quantity_op!(Add, add);
-quantity_op!(Sub, sub);
-quantity_op!(Mul, mul);
-quantity_op!(Div, div);
+// NOTE(jwall): The math operators saturate instead of panicking on overflow.
+// Use the checked methods if you need to know when that happens.
+quantity_op!(Add, add, checked_add, Whole(u32::MAX));
+quantity_op!(Sub, sub, checked_sub, Whole(0));
+quantity_op!(Mul, mul, checked_mul, Whole(u32::MAX));
+quantity_op!(Div, div, checked_div, Whole(u32::MAX));
 
 impl PartialOrd for Quantity {
     fn partial_cmp(&self, lhs: &Self) -> Option<Ordering> {
@@ -0,0 +1,71 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use client_api::*;
+use tracing::{debug, error, instrument};
+
+use crate::api::{token68, Error, HttpStore};
+
+impl HttpStore {
+    // NOTE(jwall): We do **not** want to record the password in our logs.
+    #[instrument(skip_all, fields(?self, user))]
+    pub async fn authenticate(&self, user: String, pass: String) -> Option<UserData> {
+        debug!("attempting login request against api.");
+        let mut path = self.v2_path();
+        path.push_str("/auth");
+        let request = gloo_net::http::Request::get(&path)
+            .header(
+                "authorization",
+                format!("Basic {}", token68(user, pass)).as_str(),
+            )
+            .mode(web_sys::RequestMode::SameOrigin)
+            .credentials(web_sys::RequestCredentials::SameOrigin)
+            .build()
+            .expect("Failed to build request");
+        debug!(?request, "Sending auth request");
+        let result = request.send().await;
+        if let Ok(resp) = &result {
+            if resp.status() == 200 {
+                let user_data = resp
+                    .json::<AccountResponse>()
+                    .await
+                    .expect("Unparseable authentication response")
+                    .as_success();
+                self.session.authorized();
+                return user_data;
+            }
+            error!(status = resp.status(), "Login was unsuccessful")
+        } else {
+            error!(err=?result.unwrap_err(), "Failed to send auth request");
+        }
+        return None;
+    }
+
+    #[instrument]
+    pub async fn fetch_user_data(&self) -> Option<UserData> {
+        debug!("Retrieving User Account data");
+        match self.get::<UserData>("/account").await {
+            Ok(user_data) => user_data,
+            Err(err) => {
+                error!(?err, "Failed to fetch user account data");
+                None
+            }
+        }
+    }
+
+    pub async fn logout(&self) -> Result<(), Error> {
+        debug!("Sending logout request");
+        // NOTE(jwall): fetch sends same origin credentials by default.
+        self.delete("/auth").await
+    }
+}
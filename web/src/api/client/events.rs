@@ -0,0 +1,50 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use client_api::ChangeEvent;
+use tracing::{error, warn};
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::{EventSource, MessageEvent};
+
+use crate::api::{Error, HttpStore};
+
+impl HttpStore {
+    /// Subscribe to the changes made to the user's data from other sessions.
+    /// Unlike the other endpoints this keeps a connection open and calls
+    /// `on_change` for each change until the returned `EventSource` is
+    /// closed.
+    pub fn subscribe_changes<F>(&self, on_change: F) -> Result<EventSource, Error>
+    where
+        F: Fn(ChangeEvent) + 'static,
+    {
+        let url = format!("{}/events?session={}", self.v2_path(), self.origin());
+        let source = EventSource::new(&url).map_err(|e| Error::Other(format!("{:?}", e)))?;
+        let on_message = Closure::<dyn Fn(MessageEvent)>::new(move |msg: MessageEvent| {
+            let data = msg.data().as_string().unwrap_or_default();
+            match serde_json::from_str::<ChangeEvent>(&data) {
+                Ok(event) => on_change(event),
+                Err(err) => warn!(?err, data, "Ignoring unknown change event"),
+            }
+        });
+        source.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        // NOTE(jwall): The EventSource holds on to the handler for as long as
+        // it's open so we let it own the closure.
+        on_message.forget();
+        let on_error = Closure::<dyn Fn(web_sys::Event)>::new(|err: web_sys::Event| {
+            error!(?err, "Change event stream failed");
+        });
+        source.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+        on_error.forget();
+        Ok(source)
+    }
+}
@@ -0,0 +1,191 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::NaiveDate;
+use client_api::*;
+use recipes::{unit::MeasureType, IngredientKey};
+use tracing::{debug, error, instrument};
+use wasm_bindgen::JsValue;
+
+use crate::{
+    api::{CachedCollection, Error, HttpStore},
+    app_state::AppState,
+    js_lib,
+};
+
+impl HttpStore {
+    //#[instrument]
+    pub async fn fetch_categories(&self) -> Result<Option<Vec<(String, String)>>, Error> {
+        // NOTE(jwall): When we can't reach the server this is an error so
+        // that the categories from the LocalStore are still reported as cached.
+        match self.get::<Vec<(String, String)>>("/category_map").await {
+            Ok(categories) => {
+                self.record_fetch(CachedCollection::Categories).await;
+                Ok(categories)
+            }
+            Err(Error::NotFound) => {
+                debug!("Categories returned 404");
+                self.record_fetch(CachedCollection::Categories).await;
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    #[instrument(skip(categories))]
+    pub async fn store_categories(&self, categories: &Vec<(String, String)>) -> Result<(), Error> {
+        self.post("/category_map", categories).await
+    }
+
+    /// Send the categories with `navigator.sendBeacon` so they still reach
+    /// the server while the page is unloading. Returns false if the browser
+    /// wouldn't queue them.
+    pub fn beacon_categories(&self, categories: &Vec<(String, String)>) -> bool {
+        let body = match serde_json::to_string(categories) {
+            Ok(body) => body,
+            Err(err) => {
+                error!(?err, "Failed to serialize categories");
+                return false;
+            }
+        };
+        let blob = web_sys::Blob::new_with_str_sequence_and_options(
+            &js_sys::Array::of1(&JsValue::from_str(&body)),
+            web_sys::BlobPropertyBag::new().type_("application/json"),
+        );
+        let url = format!("{}/category_map", self.v2_path());
+        match blob {
+            Ok(blob) => js_lib::get_window()
+                .navigator()
+                .send_beacon_with_opt_blob(&url, Some(&blob))
+                .unwrap_or(false),
+            Err(err) => {
+                error!(?err, "Failed to build the categories beacon");
+                false
+            }
+        }
+    }
+
+    pub async fn fetch_measure_types(&self) -> Result<Vec<(String, MeasureType)>, Error> {
+        Ok(self
+            .get::<Vec<(String, MeasureType)>>("/measure_types")
+            .await?
+            .unwrap_or_default())
+    }
+
+    #[instrument(skip(measure_types))]
+    pub async fn store_measure_types(
+        &self,
+        measure_types: &Vec<(String, MeasureType)>,
+    ) -> Result<(), Error> {
+        self.post("/measure_types", measure_types).await
+    }
+
+    pub async fn fetch_category_order(&self) -> Result<Vec<String>, Error> {
+        Ok(self
+            .get::<Vec<String>>("/category_order")
+            .await?
+            .unwrap_or_default())
+    }
+
+    #[instrument]
+    pub async fn store_category_order(&self, order: &Vec<String>) -> Result<(), Error> {
+        self.post("/category_order", order).await
+    }
+
+    pub async fn fetch_inventory_for_date(&self, date: &NaiveDate) -> Result<InventoryData, Error> {
+        let inventory = self
+            .get::<InventoryData>(&format!("/inventory/at/{}", date))
+            .await?
+            .ok_or("Failed to fetch inventory")?;
+        self.record_fetch(CachedCollection::Inventory).await;
+        Ok(inventory)
+    }
+
+    pub async fn fetch_inventory_data(&self) -> Result<InventoryData, Error> {
+        let inventory = self
+            .get::<InventoryData>("/inventory")
+            .await?
+            .ok_or("Failed to fetch inventory")?;
+        self.record_fetch(CachedCollection::Inventory).await;
+        Ok(inventory)
+    }
+
+    #[instrument]
+    pub async fn store_inventory_data_for_date(
+        &self,
+        filtered_ingredients: BTreeSet<IngredientKey>,
+        modified_amts: BTreeMap<IngredientKey, String>,
+        extra_items: Vec<(String, String)>,
+        checked_items: BTreeSet<IngredientKey>,
+        purchased_amts: BTreeMap<IngredientKey, String>,
+        date: &NaiveDate,
+    ) -> Result<(), Error> {
+        let inventory = SaveInventoryData(
+            filtered_ingredients.into_iter().collect(),
+            modified_amts.into_iter().collect(),
+            extra_items,
+            Some(checked_items.into_iter().collect()),
+            Some(purchased_amts.into_iter().collect()),
+        );
+        debug!("Storing inventory data via API");
+        self.post(&format!("/inventory/at/{}", date), &inventory)
+            .await
+    }
+
+    #[instrument]
+    pub async fn store_inventory_data(
+        &self,
+        filtered_ingredients: BTreeSet<IngredientKey>,
+        modified_amts: BTreeMap<IngredientKey, String>,
+        extra_items: Vec<(String, String)>,
+    ) -> Result<(), Error> {
+        let filtered_ingredients: Vec<IngredientKey> = filtered_ingredients.into_iter().collect();
+        let modified_amts: Vec<(IngredientKey, String)> = modified_amts.into_iter().collect();
+        debug!("Storing inventory data via API");
+        self.post(
+            "/inventory",
+            &(filtered_ingredients, modified_amts, extra_items),
+        )
+        .await
+    }
+
+    /// Save the checked off items for the selected plan date. Only the
+    /// checked items are sent so checking an item off stays cheap.
+    #[instrument(skip_all)]
+    pub async fn store_checked_items(&self, state: &AppState) -> Result<(), Error> {
+        if let Some(cached_plan_date) = &state.selected_plan_date {
+            debug!("Saving checked items");
+            let checked_items: Vec<&IngredientKey> = state.checked_off.iter().collect();
+            self.post(
+                &format!("/inventory/at/{}/checked", cached_plan_date),
+                &checked_items,
+            )
+            .await
+        } else {
+            Ok(())
+        }
+    }
+
+    pub async fn fetch_staples(&self) -> Result<Option<String>, Error> {
+        Ok(self.get::<Option<String>>("/staples").await?.flatten())
+    }
+
+    pub async fn store_staples<S: AsRef<str> + serde::Serialize>(
+        &self,
+        content: S,
+    ) -> Result<(), Error> {
+        self.post("/staples", &content).await
+    }
+}
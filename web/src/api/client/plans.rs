@@ -0,0 +1,143 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use chrono::NaiveDate;
+use client_api::*;
+use tracing::{debug, instrument};
+
+use crate::{
+    api::{saved_extras, CachedCollection, Error, HttpStore},
+    app_state::AppState,
+};
+
+impl HttpStore {
+    #[instrument(skip_all)]
+    pub async fn store_app_state(&self, state: &AppState) -> Result<(), Error> {
+        let mut plan = Vec::new();
+        for (key, count) in state.recipe_counts.iter() {
+            plan.push((key.clone(), *count as i32));
+        }
+        if let Some(cached_plan_date) = &state.selected_plan_date {
+            debug!(?plan, "Saving plan data");
+            self.store_plan_for_date(plan, cached_plan_date).await?;
+            debug!("Saving inventory data");
+            self.store_inventory_data_for_date(
+                state.filtered_ingredients.clone(),
+                state.modified_amts.clone(),
+                saved_extras(&state.extras),
+                state.checked_off.clone(),
+                state.purchased_amts.clone(),
+                cached_plan_date,
+            )
+            .await
+        } else {
+            debug!("Saving plan data");
+            self.store_plan(plan).await?;
+            debug!("Saving inventory data");
+            self.store_inventory_data(
+                state.filtered_ingredients.clone(),
+                state.modified_amts.clone(),
+                saved_extras(&state.extras),
+            )
+            .await
+        }
+    }
+
+    pub async fn store_plan(&self, plan: Vec<(String, i32)>) -> Result<(), Error> {
+        self.post("/plan", &plan).await
+    }
+
+    pub async fn store_plan_for_date(
+        &self,
+        plan: Vec<(String, i32)>,
+        date: &NaiveDate,
+    ) -> Result<(), Error> {
+        self.post(&format!("/plan/at/{}", date), &plan).await
+    }
+
+    pub async fn fetch_plan_dates(&self) -> Result<Option<Vec<NaiveDate>>, Error> {
+        let plan = self.get::<Vec<NaiveDate>>("/plan/all").await?;
+        self.record_fetch(CachedCollection::Plans).await;
+        Ok(plan)
+    }
+
+    /// Fetch the summaries of the plans from `start` to `end` inclusive,
+    /// newest first.
+    pub async fn fetch_plan_summaries(
+        &self,
+        start: &NaiveDate,
+        end: &NaiveDate,
+    ) -> Result<Vec<PlanSummary>, Error> {
+        Ok(self
+            .get::<Vec<PlanSummary>>(&format!("/plan/summary?start={}&end={}", start, end))
+            .await?
+            .unwrap_or_default())
+    }
+
+    pub async fn delete_plan_for_date(&self, date: &NaiveDate) -> Result<(), Error> {
+        self.delete(&format!("/plan/at/{}", date)).await
+    }
+
+    pub async fn fetch_plan_for_date(
+        &self,
+        date: &NaiveDate,
+    ) -> Result<Option<Vec<(String, i32)>>, Error> {
+        let plan = self
+            .get::<Vec<(String, i32)>>(&format!("/plan/at/{}", date))
+            .await?;
+        self.record_fetch(CachedCollection::Plans).await;
+        Ok(plan)
+    }
+
+    pub async fn fetch_plan_templates(&self) -> Result<Vec<PlanTemplate>, Error> {
+        Ok(self
+            .get::<Vec<PlanTemplate>>("/plan/templates")
+            .await?
+            .unwrap_or_default())
+    }
+
+    pub async fn store_plan_template(&self, template: &PlanTemplate) -> Result<(), Error> {
+        self.post("/plan/templates", template).await
+    }
+
+    /// Replace the meal plan for a date with a plan template. Returns the
+    /// applied recipe counts.
+    pub async fn apply_plan_template(
+        &self,
+        name: &str,
+        date: &NaiveDate,
+    ) -> Result<Vec<(String, i32)>, Error> {
+        let path = format!(
+            "/plan/templates/{}/apply/{}",
+            String::from(js_sys::encode_uri_component(name)),
+            date
+        );
+        Ok(self
+            .request::<(), Vec<(String, i32)>>(gloo_net::http::Method::POST, &path, None)
+            .await?
+            .unwrap_or_default())
+    }
+
+    /// Fetch the meal plan and inventory for a date in one request.
+    pub async fn fetch_full_plan_for_date(
+        &self,
+        date: &NaiveDate,
+    ) -> Result<Option<FullPlanData>, Error> {
+        let plan = self
+            .get::<FullPlanData>(&format!("/plan/at/{}/full", date))
+            .await?;
+        self.record_fetch(CachedCollection::Plans).await;
+        self.record_fetch(CachedCollection::Inventory).await;
+        Ok(plan)
+    }
+}
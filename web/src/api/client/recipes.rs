@@ -0,0 +1,172 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::collections::{BTreeMap, BTreeSet};
+
+use client_api::{RecipeChanges, Response};
+use gloo_net::http::Method;
+use recipes::{parse::ParseError, RecipeEntry, RecipeTemplate};
+use tracing::{debug, error, instrument};
+
+use crate::{
+    api::{CachedCollection, Error, Fetched, HttpStore},
+    routing,
+};
+
+impl HttpStore {
+    #[instrument]
+    /// Sync the recipes from the server. Once the LocalStore has a copy of
+    /// the recipes only the ones that changed since the last sync are fetched.
+    /// If the server can't be reached the recipes in the LocalStore are
+    /// returned instead.
+    pub async fn fetch_recipes(&self) -> Result<Fetched<Option<Vec<RecipeEntry>>>, Error> {
+        let cached = self.local_store.get_recipes().await;
+        // NOTE(jwall): The fetched-at timestamp for recipes is the server's
+        // as_of cursor from the last sync so clock skew between us and the
+        // server can't make us miss a change.
+        let fetched_at = self
+            .local_store
+            .get_fetched_at()
+            .await
+            .get(&CachedCollection::Recipes)
+            .cloned();
+        let since = match (&cached, fetched_at) {
+            (Some(_), Some(cursor)) => cursor as i64,
+            _ => 0,
+        };
+        let path = format!("/recipes/since/{}", since);
+        match self.get::<RecipeChanges>(&path).await {
+            Ok(changes) => {
+                let mut entries = if since == 0 {
+                    BTreeMap::new()
+                } else {
+                    cached
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|entry| (entry.recipe_id().to_owned(), entry))
+                        .collect::<BTreeMap<String, RecipeEntry>>()
+                };
+                entries.extend(
+                    changes
+                        .changed
+                        .into_iter()
+                        .map(|entry| (entry.recipe_id().to_owned(), entry)),
+                );
+                // NOTE(jwall): Anything the server no longer has was deleted.
+                let ids = changes.ids.iter().collect::<BTreeSet<&String>>();
+                entries.retain(|id, _| ids.contains(id));
+                self.local_store
+                    .set_fetched_at(CachedCollection::Recipes, changes.as_of as f64)
+                    .await;
+                Ok(Fetched::Server(Some(entries.into_values().collect())))
+            }
+            Err(Error::Network(err)) => {
+                error!(?err, "Error hitting api");
+                Ok(Fetched::Cached(cached, fetched_at))
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    pub async fn fetch_recipe_text<S: AsRef<str> + std::fmt::Display>(
+        &self,
+        id: S,
+    ) -> Result<Option<RecipeEntry>, Error> {
+        let path = format!("/recipe/{}", routing::encode_recipe_id(id.as_ref()));
+        match self.get::<Option<RecipeEntry>>(&path).await {
+            Ok(entry) => {
+                let entry = entry.flatten();
+                if let Some(ref entry) = entry {
+                    self.local_store.set_recipe_entry(entry).await;
+                }
+                Ok(entry)
+            }
+            Err(Error::NotFound) => {
+                debug!("Recipe doesn't exist");
+                Ok(None)
+            }
+            Err(Error::Network(err)) => {
+                error!(path, ?err, "Error hitting api");
+                Ok(self.local_store.get_recipe_entry(id.as_ref()).await)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Check that recipe text parses on the server. Returns the parse error
+    /// with its line and column if it doesn't.
+    #[instrument(skip_all)]
+    pub async fn validate_recipe(&self, text: &str) -> Result<Option<ParseError>, Error> {
+        let resp = self
+            .send_unchecked(Method::POST, "/recipe/validate", Some(text))
+            .await?;
+        match resp.status() {
+            200 => {
+                self.session.authorized();
+                Ok(None)
+            }
+            422 => match resp
+                .json::<Response<()>>()
+                .await
+                .map_err(|e| Error::Other(format!("{}", e)))?
+            {
+                Response::Invalid(err) => Ok(Some(err)),
+                resp => Err(Error::Other(format!("Unexpected response {:?}", resp))),
+            },
+            status => Err(self.session.status_error(status)),
+        }
+    }
+
+    #[instrument]
+    pub async fn delete_recipe<S>(&self, recipe: S) -> Result<(), Error>
+    where
+        S: AsRef<str> + std::fmt::Debug,
+    {
+        // NOTE(jwall): The ui confirms the delete with the user first so we
+        // always force it even if the recipe is in a saved meal plan.
+        let path = format!(
+            "/recipe/{}?force=true",
+            routing::encode_recipe_id(recipe.as_ref())
+        );
+        self.delete(&path).await
+    }
+
+    #[instrument(skip(recipes), fields(count=recipes.len()))]
+    pub async fn store_recipes(&self, recipes: Vec<RecipeEntry>) -> Result<(), Error> {
+        for r in recipes.iter() {
+            if r.recipe_id().is_empty() {
+                return Err("Recipe Ids can not be empty".into());
+            }
+        }
+        self.post("/recipes", &recipes).await
+    }
+
+    pub async fn fetch_templates(&self) -> Result<Vec<RecipeTemplate>, Error> {
+        Ok(self
+            .get::<Vec<RecipeTemplate>>("/templates")
+            .await?
+            .unwrap_or_default())
+    }
+
+    pub async fn store_template(&self, template: &RecipeTemplate) -> Result<(), Error> {
+        self.post("/templates", template).await
+    }
+
+    pub async fn delete_template<S: AsRef<str>>(&self, name: S) -> Result<(), Error> {
+        let path = format!(
+            "/template/{}",
+            String::from(js_sys::encode_uri_component(name.as_ref()))
+        );
+        self.delete(&path).await
+    }
+}
@@ -0,0 +1,29 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use client_api::*;
+
+use crate::api::{Error, HttpStore};
+
+impl HttpStore {
+    pub async fn fetch_settings(&self) -> Result<UserSettings, Error> {
+        Ok(self
+            .get::<UserSettings>("/settings")
+            .await?
+            .unwrap_or_default())
+    }
+
+    pub async fn store_settings(&self, settings: &UserSettings) -> Result<(), Error> {
+        self.post("/settings", settings).await
+    }
+}
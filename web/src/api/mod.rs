@@ -0,0 +1,816 @@
+// Copyright 2022 Jeremy Wall
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+mod client;
+
+use std::cell::Cell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use base64::{self, Engine};
+use gloo_net::{
+    self,
+    http::{Method, RequestBuilder, Response as HttpResponse},
+};
+// TODO(jwall): Remove this when we have gone a few migrations past.
+use serde_json::from_str;
+use sycamore::prelude::*;
+use tracing::{debug, error, instrument, warn};
+
+use anyhow::Result;
+use client_api::*;
+use recipes::RecipeEntry;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_wasm_bindgen::{from_value, Serializer};
+use wasm_bindgen::JsValue;
+// TODO(jwall): Remove this when we have gone a few migrations past.
+use web_sys::Storage;
+
+fn to_js<T: serde::ser::Serialize>(value: T) -> Result<JsValue, serde_wasm_bindgen::Error> {
+    let s = Serializer::new().serialize_maps_as_objects(true);
+    value.serialize(&s)
+}
+
+use crate::{
+    app_state::{parse_recipes, AppState},
+    js_lib::{self, DBFactory},
+    search_index::SearchIndex,
+};
+
+#[allow(dead_code)]
+#[derive(Debug)]
+pub enum Error {
+    /// The server rejected our session.
+    Unauthorized,
+    /// The server doesn't have what we asked for.
+    NotFound,
+    /// We couldn't reach the server.
+    Network(String),
+    Other(String),
+}
+
+impl From<std::io::Error> for Error {
+    fn from(item: std::io::Error) -> Self {
+        Error::Other(format!("{:?}", item))
+    }
+}
+
+impl From<Error> for String {
+    fn from(item: Error) -> Self {
+        format!("{:?}", item)
+    }
+}
+
+impl From<JsValue> for Error {
+    fn from(item: JsValue) -> Self {
+        Error::Other(format!("{:?}", item))
+    }
+}
+
+impl From<String> for Error {
+    fn from(item: String) -> Self {
+        Error::Other(item)
+    }
+}
+
+impl From<&'static str> for Error {
+    fn from(item: &'static str) -> Self {
+        Error::Other(item.to_owned())
+    }
+}
+
+impl From<std::string::FromUtf8Error> for Error {
+    fn from(item: std::string::FromUtf8Error) -> Self {
+        Error::Other(format!("{:?}", item))
+    }
+}
+
+impl From<gloo_net::Error> for Error {
+    fn from(item: gloo_net::Error) -> Self {
+        match item {
+            gloo_net::Error::JsError(err) => Error::Network(format!("{:?}", err)),
+            item => Error::Other(format!("{:?}", item)),
+        }
+    }
+}
+
+fn token68(user: String, pass: String) -> String {
+    base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", user, pass))
+}
+
+fn convert_to_io_error<V, E>(res: Result<V, E>) -> Result<V, std::io::Error>
+where
+    E: Into<Box<dyn std::error::Error>> + std::fmt::Debug,
+{
+    match res {
+        Ok(v) => Ok(v),
+        Err(e) => Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            format!("{:?}", e),
+        )),
+    }
+}
+
+/// The extras to send to the server. Rows without a name yet are left out
+/// since the server rejects extras without a name. They stay in the app state
+/// so they are saved once they are filled in.
+fn saved_extras(extras: &Vec<(String, String)>) -> Vec<(String, String)> {
+    extras
+        .iter()
+        .filter(|(_, name)| !text::clean(name).is_empty())
+        .cloned()
+        .collect()
+}
+
+static STORAGE_DEGRADED: AtomicBool = AtomicBool::new(false);
+
+/// Whether the LocalStore couldn't use IndexedDB and is only keeping state in
+/// memory.
+pub fn is_storage_degraded() -> bool {
+    STORAGE_DEGRADED.load(Ordering::Relaxed)
+}
+
+/// Failures to use IndexedDB put the LocalStore in a degraded memory only mode
+/// instead of crashing the app. Reads return nothing and writes are dropped.
+trait OrDegraded<T> {
+    fn or_degraded(self, msg: &str) -> T;
+}
+
+impl<T: Default> OrDegraded<T> for indexed_db::Result<T, std::io::Error> {
+    fn or_degraded(self, msg: &str) -> T {
+        match self {
+            Ok(v) => v,
+            Err(err) => {
+                warn!(?err, msg);
+                // NOTE(jwall): We only warn the user the first time so they
+                // don't get a banner for every write.
+                if !STORAGE_DEGRADED.swap(true, Ordering::Relaxed) {
+                    error!("Local storage is unavailable. Changes will be lost when you close the page.");
+                }
+                T::default()
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LocalStore {
+    // TODO(zaphar): Remove this when it's safe to delete the migration
+    old_store: Option<Storage>,
+    store: DBFactory<'static>,
+}
+
+const APP_STATE_KEY: &'static str = "app-state";
+const USER_DATA_KEY: &'static str = "user_data";
+const SEARCH_INDEX_KEY: &'static str = "search-index";
+const FETCHED_AT_KEY: &'static str = "fetched-at";
+
+/// The collections cached in the LocalStore that we track the fetch time of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum CachedCollection {
+    Recipes,
+    Plans,
+    Inventory,
+    Categories,
+}
+
+impl CachedCollection {
+    pub const ALL: [CachedCollection; 4] = [
+        CachedCollection::Recipes,
+        CachedCollection::Plans,
+        CachedCollection::Inventory,
+        CachedCollection::Categories,
+    ];
+}
+
+/// Data returned by a fetch that may have been served from the LocalStore.
+#[derive(Debug)]
+pub enum Fetched<T> {
+    Server(T),
+    /// Cached data along with when it was fetched from the server if we know.
+    Cached(T, Option<f64>),
+}
+
+impl<T> Fetched<T> {
+    pub fn data(&self) -> &T {
+        match self {
+            Fetched::Server(data) | Fetched::Cached(data, _) => data,
+        }
+    }
+}
+
+impl LocalStore {
+    pub fn new() -> Self {
+        Self {
+            store: DBFactory::default(),
+            old_store: js_lib::get_storage(),
+        }
+    }
+
+    pub async fn migrate(&self) {
+        let old_store = match &self.old_store {
+            Some(old_store) => old_store,
+            None => return,
+        };
+        // 1. migrate app-state from localstore to indexeddb
+        debug!("Peforming localstorage migration");
+        if let Ok(Some(v)) = old_store.get("app_state") {
+            if let Ok(Some(local_state)) = from_str::<Option<AppState>>(&v) {
+                self.store_app_state(&local_state).await;
+            }
+        }
+        let _ = old_store.remove_item("app_state");
+        // 2. migrate user-state from localstore to indexeddb
+        if let Ok(Some(v)) = old_store.get(USER_DATA_KEY) {
+            if let Ok(local_user_data) = from_str::<Option<UserData>>(&v) {
+                self.set_user_data(local_user_data.as_ref()).await;
+            }
+        }
+        let _ = old_store.remove_item(USER_DATA_KEY);
+        // 3. Recipes
+        let store_len = old_store.length().unwrap_or(0);
+        let mut key_list = Vec::new();
+        for i in 0..store_len {
+            if let Ok(Some(key)) = old_store.key(i) {
+                if key.starts_with("recipe:") {
+                    key_list.push(key);
+                }
+            }
+        }
+        for k in key_list {
+            if let Ok(Some(recipe)) = old_store.get(&k) {
+                if let Ok(recipe) = from_str::<RecipeEntry>(&recipe) {
+                    self.set_recipe_entry(&recipe).await;
+                }
+            }
+            let _ = old_store.delete(&k);
+        }
+    }
+
+    #[instrument(skip_all)]
+    pub async fn store_app_state(&self, state: &AppState) {
+        let state = match to_js(state) {
+            Ok(state) => state,
+            Err(err) => {
+                error!(?err, ?state, "Error deserializing app_state");
+                return;
+            }
+        };
+        web_sys::console::log_1(&state);
+        let key = to_js(APP_STATE_KEY).expect("Failed to serialize key");
+        self.store
+            .rw_transaction(&[js_lib::STATE_STORE_NAME], |trx| async move {
+                let object_store = trx.object_store(js_lib::STATE_STORE_NAME)?;
+                object_store.put_kv(&key, &state).await?;
+                Ok(())
+            })
+            .await
+            .or_degraded("Failed to store app-state");
+    }
+
+    #[instrument]
+    pub async fn fetch_app_state(&self) -> Option<AppState> {
+        debug!("Loading state from local store");
+        let recipes = parse_recipes(&self.get_recipes().await).expect("Failed to parse recipes");
+        self.store
+            .ro_transaction(&[js_lib::STATE_STORE_NAME], |trx| async move {
+                let key = convert_to_io_error(to_js(APP_STATE_KEY))?;
+                let object_store = trx.object_store(js_lib::STATE_STORE_NAME)?;
+                let mut app_state: AppState = match object_store.get(&key).await? {
+                    Some(s) => convert_to_io_error(from_value(s))?,
+                    None => return Ok(None),
+                };
+
+                if let Some(recipes) = recipes {
+                    debug!("Populating recipes");
+                    for (id, recipe) in recipes {
+                        debug!(id, "Adding recipe from local storage");
+                        app_state.recipes.insert(id, recipe);
+                    }
+                }
+                Ok(Some(app_state))
+            })
+            .await
+            .or_degraded("Failed to fetch app-state")
+    }
+
+    #[instrument]
+    /// Gets user data from local storage.
+    pub async fn get_user_data(&self) -> Option<UserData> {
+        self.store
+            .ro_transaction(&[js_lib::STATE_STORE_NAME], |trx| async move {
+                let key = to_js(USER_DATA_KEY).expect("Failed to serialize key");
+                let object_store = trx.object_store(js_lib::STATE_STORE_NAME)?;
+                let user_data: UserData = match object_store.get(&key).await? {
+                    Some(s) => convert_to_io_error(from_value(s))?,
+                    None => return Ok(None),
+                };
+                Ok(Some(user_data))
+            })
+            .await
+            .or_degraded("Failed to fetch user_data")
+    }
+
+    #[instrument]
+    // Set's user data to local storage.
+    pub async fn set_user_data(&self, data: Option<&UserData>) {
+        let key = to_js(USER_DATA_KEY).expect("Failed to serialize key");
+        if let Some(data) = data {
+            let data = data.clone();
+            self.store
+                .rw_transaction(&[js_lib::STATE_STORE_NAME], |trx| async move {
+                    let object_store = trx.object_store(js_lib::STATE_STORE_NAME)?;
+                    object_store
+                        .put_kv(&key, &convert_to_io_error(to_js(&data))?)
+                        .await?;
+                    Ok(())
+                })
+                .await
+                .or_degraded("Failed to set user_data");
+        } else {
+            self.store
+                .rw_transaction(&[js_lib::STATE_STORE_NAME], |trx| async move {
+                    let object_store = trx.object_store(js_lib::STATE_STORE_NAME)?;
+                    object_store.delete(&key).await?;
+                    Ok(())
+                })
+                .await
+                .or_degraded("Failed to delete user_data");
+        }
+    }
+
+    #[instrument]
+    async fn get_recipe_keys(&self) -> impl Iterator<Item = String> {
+        self.store
+            .ro_transaction(&[js_lib::RECIPE_STORE_NAME], |trx| async move {
+                let mut keys = Vec::new();
+                let object_store = trx.object_store(js_lib::RECIPE_STORE_NAME)?;
+                let key_vec = object_store.get_all_keys(None).await?;
+                for k in key_vec {
+                    if let Ok(v) = from_value(k) {
+                        keys.push(v);
+                    }
+                }
+                Ok(keys)
+            })
+            .await
+            .or_degraded("Failed to get storage keys")
+            .into_iter()
+    }
+
+    #[instrument]
+    /// Gets all the recipes from local storage.
+    pub async fn get_recipes(&self) -> Option<Vec<RecipeEntry>> {
+        self.store
+            .ro_transaction(&[js_lib::RECIPE_STORE_NAME], |trx| async move {
+                let mut recipe_list = Vec::new();
+                let object_store = trx.object_store(js_lib::RECIPE_STORE_NAME)?;
+                let mut c = object_store.cursor().open().await?;
+                while let Some(value) = c.value() {
+                    recipe_list.push(convert_to_io_error(from_value(value))?);
+                    c.advance(1).await?;
+                }
+                if recipe_list.is_empty() {
+                    return Ok(None);
+                }
+                Ok(Some(recipe_list))
+            })
+            .await
+            .or_degraded("Failed to get recipes")
+    }
+
+    #[instrument]
+    pub async fn get_recipe_entry(&self, id: &str) -> Option<RecipeEntry> {
+        let key = to_js(id).expect("Failed to serialize key");
+        self.store
+            .ro_transaction(&[js_lib::RECIPE_STORE_NAME], |trx| async move {
+                let object_store = trx.object_store(js_lib::RECIPE_STORE_NAME)?;
+                let entry: Option<RecipeEntry> = match object_store.get(&key).await? {
+                    Some(v) => convert_to_io_error(from_value(v))?,
+                    None => None,
+                };
+                Ok(entry)
+            })
+            .await
+            .or_degraded("Failed to get recipes")
+    }
+
+    #[instrument]
+    /// Sets the set of recipes to the entries passed in. Deletes any recipes not
+    /// in the list.
+    pub async fn set_all_recipes(&self, entries: &Vec<RecipeEntry>) {
+        for recipe_key in self.get_recipe_keys().await {
+            let key = to_js(&recipe_key).expect("Failed to serialize key");
+            self.store
+                .rw_transaction(&[js_lib::STATE_STORE_NAME], |trx| async move {
+                    let object_store = trx.object_store(js_lib::STATE_STORE_NAME)?;
+                    object_store.delete(&key).await?;
+                    Ok(())
+                })
+                .await
+                .or_degraded("Failed to delete user_data");
+        }
+        for entry in entries {
+            let entry = entry.clone();
+            let key = to_js(entry.recipe_id()).expect("Failed to serialize recipe key");
+            self.store
+                .rw_transaction(&[js_lib::RECIPE_STORE_NAME], |trx| async move {
+                    let object_store = trx.object_store(js_lib::RECIPE_STORE_NAME)?;
+                    object_store
+                        .put_kv(&key, &convert_to_io_error(to_js(&entry))?)
+                        .await?;
+                    Ok(())
+                })
+                .await
+                .or_degraded("Failed to store recipe entry");
+        }
+        self.update_search_index(|index| {
+            let ids = entries
+                .iter()
+                .map(|e| e.recipe_id())
+                .collect::<BTreeSet<&str>>();
+            for id in index.recipe_ids() {
+                if !ids.contains(id.as_str()) {
+                    index.remove_entry(&id);
+                }
+            }
+            for entry in entries {
+                index.update_entry(entry);
+            }
+        })
+        .await;
+    }
+
+    #[instrument]
+    /// Set recipe entry in local storage.
+    pub async fn set_recipe_entry(&self, entry: &RecipeEntry) {
+        self.update_search_index(|index| index.update_entry(entry))
+            .await;
+        let entry = entry.clone();
+        let key = to_js(entry.recipe_id()).expect("Failed to serialize recipe key");
+        self.store
+            .rw_transaction(&[js_lib::RECIPE_STORE_NAME], |trx| async move {
+                let object_store = trx.object_store(js_lib::RECIPE_STORE_NAME)?;
+                object_store
+                    .put_kv(&key, &convert_to_io_error(to_js(&entry))?)
+                    .await?;
+                Ok(())
+            })
+            .await
+            .or_degraded("Failed to store recipe entry");
+    }
+
+    #[instrument]
+    /// Delete recipe entry from local storage.
+    pub async fn delete_recipe_entry(&self, recipe_id: &str) {
+        let key = to_js(recipe_id).expect("Failed to serialize key");
+        self.store
+            .rw_transaction(&[js_lib::RECIPE_STORE_NAME], |trx| async move {
+                let object_store = trx.object_store(js_lib::RECIPE_STORE_NAME)?;
+                object_store.delete(&key).await?;
+                Ok(())
+            })
+            .await
+            .or_degraded("Failed to delete user_data");
+        self.update_search_index(|index| index.remove_entry(recipe_id))
+            .await;
+    }
+
+    #[instrument]
+    /// Gets the recipe search index from local storage. If the stored index is
+    /// missing or out of date with the stored recipes it gets rebuilt.
+    pub async fn get_search_index(&self) -> SearchIndex {
+        let entries = self.get_recipes().await.unwrap_or_default();
+        match self.get_stored_search_index().await {
+            Some(index) if !index.is_stale(&entries) => index,
+            _ => {
+                debug!("Rebuilding stale recipe search index");
+                let index = SearchIndex::build(entries.iter());
+                self.store_search_index(&index).await;
+                index
+            }
+        }
+    }
+
+    async fn get_stored_search_index(&self) -> Option<SearchIndex> {
+        let key = to_js(SEARCH_INDEX_KEY).expect("Failed to serialize key");
+        self.store
+            .ro_transaction(&[js_lib::STATE_STORE_NAME], |trx| async move {
+                let object_store = trx.object_store(js_lib::STATE_STORE_NAME)?;
+                // NOTE(jwall): An index we can't deserialize is treated as
+                // missing so it gets rebuilt.
+                Ok(match object_store.get(&key).await? {
+                    Some(v) => from_value(v).ok(),
+                    None => None,
+                })
+            })
+            .await
+            .or_degraded("Failed to fetch search index")
+    }
+
+    async fn store_search_index(&self, index: &SearchIndex) {
+        let key = to_js(SEARCH_INDEX_KEY).expect("Failed to serialize key");
+        let index = index.clone();
+        self.store
+            .rw_transaction(&[js_lib::STATE_STORE_NAME], |trx| async move {
+                let object_store = trx.object_store(js_lib::STATE_STORE_NAME)?;
+                object_store
+                    .put_kv(&key, &convert_to_io_error(to_js(&index))?)
+                    .await?;
+                Ok(())
+            })
+            .await
+            .or_degraded("Failed to store search index");
+    }
+
+    /// Apply an incremental update to the stored search index.
+    async fn update_search_index<F: FnOnce(&mut SearchIndex)>(&self, update: F) {
+        let mut index = self.get_stored_search_index().await.unwrap_or_default();
+        update(&mut index);
+        self.store_search_index(&index).await;
+    }
+
+    #[instrument]
+    /// Gets when each cached collection was last fetched from the server in
+    /// milliseconds since the epoch.
+    pub async fn get_fetched_at(&self) -> BTreeMap<CachedCollection, f64> {
+        let key = to_js(FETCHED_AT_KEY).expect("Failed to serialize key");
+        self.store
+            .ro_transaction(&[js_lib::STATE_STORE_NAME], |trx| async move {
+                let object_store = trx.object_store(js_lib::STATE_STORE_NAME)?;
+                // NOTE(jwall): Timestamps we can't deserialize are treated as
+                // unknown.
+                Ok(match object_store.get(&key).await? {
+                    Some(v) => from_value(v).unwrap_or_default(),
+                    None => BTreeMap::new(),
+                })
+            })
+            .await
+            .or_degraded("Failed to fetch fetched-at timestamps")
+    }
+
+    #[instrument]
+    /// Records when a cached collection was fetched from the server.
+    pub async fn set_fetched_at(&self, collection: CachedCollection, timestamp: f64) {
+        let mut fetched_at = self.get_fetched_at().await;
+        fetched_at.insert(collection, timestamp);
+        let key = to_js(FETCHED_AT_KEY).expect("Failed to serialize key");
+        self.store
+            .rw_transaction(&[js_lib::STATE_STORE_NAME], |trx| async move {
+                let object_store = trx.object_store(js_lib::STATE_STORE_NAME)?;
+                object_store
+                    .put_kv(&key, &convert_to_io_error(to_js(&fetched_at))?)
+                    .await?;
+                Ok(())
+            })
+            .await
+            .or_degraded("Failed to store fetched-at timestamps");
+    }
+}
+
+/// The number of 401 responses in a row before we decide our session has
+/// expired on the server.
+const SESSION_EXPIRED_LIMIT: u32 = 2;
+
+/// Watches for our session expiring on the server while we still think we
+/// are logged in.
+#[derive(Clone, Debug)]
+pub struct SessionWatch {
+    unauthorized: Rc<Cell<u32>>,
+    expired: RcSignal<bool>,
+}
+
+impl SessionWatch {
+    pub fn new() -> Self {
+        Self {
+            unauthorized: Rc::new(Cell::new(0)),
+            expired: create_rc_signal(false),
+        }
+    }
+
+    /// Turn an unsuccessful response status into an Error, counting
+    /// consecutive 401s.
+    pub fn status_error(&self, status: u16) -> Error {
+        if status == 401 {
+            let count = self.unauthorized.get() + 1;
+            self.unauthorized.set(count);
+            if count >= SESSION_EXPIRED_LIMIT && !*self.expired.get_untracked() {
+                self.expired.set(true);
+            }
+            Error::Unauthorized
+        } else if status == 404 {
+            self.unauthorized.set(0);
+            Error::NotFound
+        } else {
+            self.unauthorized.set(0);
+            Error::Other(format!("Status: {}", status))
+        }
+    }
+
+    /// Record that the server accepted our session.
+    pub fn authorized(&self) {
+        self.unauthorized.set(0);
+    }
+
+    /// Set once the session has expired. Reset with `acknowledge`.
+    pub fn expired(&self) -> &RcSignal<bool> {
+        &self.expired
+    }
+
+    /// Acknowledge the expired session and start counting again.
+    pub fn acknowledge(&self) {
+        self.unauthorized.set(0);
+        self.expired.set(false);
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HttpStore {
+    root: String,
+    local_store: LocalStore,
+    session: SessionWatch,
+    /// Identifies this page load to the server so the changes it makes
+    /// aren't sent back to it as change events.
+    origin: String,
+}
+
+impl HttpStore {
+    pub fn new(root: String) -> Self {
+        Self {
+            root,
+            local_store: LocalStore::new(),
+            session: SessionWatch::new(),
+            origin: js_lib::new_session_id(),
+        }
+    }
+
+    pub fn session(&self) -> &SessionWatch {
+        &self.session
+    }
+
+    pub fn origin(&self) -> &str {
+        &self.origin
+    }
+
+    /// Record that a collection was just fetched from the server.
+    async fn record_fetch(&self, collection: CachedCollection) {
+        self.local_store
+            .set_fetched_at(collection, js_lib::get_ms_timestamp())
+            .await;
+    }
+
+    pub fn v2_path(&self) -> String {
+        let mut path = self.root.clone();
+        path.push_str("/v2");
+        path
+    }
+
+    pub fn provide_context<S: Into<String>>(cx: Scope, root: S) {
+        provide_context(cx, std::rc::Rc::new(Self::new(root.into())));
+    }
+
+    pub fn get_from_context(cx: Scope) -> std::rc::Rc<Self> {
+        use_context::<std::rc::Rc<Self>>(cx).clone()
+    }
+
+    /// Send a request to the v2 api at `path` with an optional json body
+    /// without checking the status of the response.
+    async fn send_unchecked<B>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&B>,
+    ) -> Result<HttpResponse, Error>
+    where
+        B: Serialize + ?Sized,
+    {
+        let url = format!("{}{}", self.v2_path(), path);
+        let request = RequestBuilder::new(&url)
+            .method(method)
+            .header(ORIGIN_SESSION_HEADER, &self.origin);
+        Ok(match body {
+            Some(body) => request.json(body)?.send().await?,
+            None => request.send().await?,
+        })
+    }
+
+    /// Send a request to the v2 api at `path` with an optional json body.
+    /// Statuses other than 200 are turned into an `Error`.
+    #[instrument(skip(self, body))]
+    async fn send<B>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&B>,
+    ) -> Result<HttpResponse, Error>
+    where
+        B: Serialize + ?Sized,
+    {
+        let resp = self.send_unchecked(method, path, body).await?;
+        if resp.status() != 200 {
+            debug!(status = resp.status(), "Invalid response back");
+            return Err(self.session.status_error(resp.status()));
+        }
+        debug!("We got a valid response back!");
+        self.session.authorized();
+        Ok(resp)
+    }
+
+    /// Send a request and decode the api `Response` in the body. Returns None
+    /// if the `Response` wasn't a success.
+    async fn request<B, T>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&B>,
+    ) -> Result<Option<T>, Error>
+    where
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        let resp = self.send(method, path, body).await?;
+        Ok(resp
+            .json::<Response<T>>()
+            .await
+            .map_err(|e| Error::Other(format!("{}", e)))?
+            .as_success())
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<Option<T>, Error> {
+        self.request(Method::GET, path, None::<&()>).await
+    }
+
+    async fn post<B: Serialize + ?Sized>(&self, path: &str, body: &B) -> Result<(), Error> {
+        self.send(Method::POST, path, Some(body)).await?;
+        Ok(())
+    }
+
+    async fn delete(&self, path: &str) -> Result<(), Error> {
+        self.send(Method::DELETE, path, None::<&()>).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_saved_extras_skip_rows_without_a_name() {
+        let extras = vec![
+            ("2".to_owned(), "lemons".to_owned()),
+            (" ".to_owned(), "".to_owned()),
+            ("1".to_owned(), " ".to_owned()),
+            ("".to_owned(), "salt".to_owned()),
+        ];
+        assert_eq!(
+            saved_extras(&extras),
+            vec![
+                ("2".to_owned(), "lemons".to_owned()),
+                ("".to_owned(), "salt".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_failing_factory_degrades_instead_of_panicking() {
+        let result: indexed_db::Result<Option<AppState>, std::io::Error> =
+            Err(js_lib::open_error(anyhow::anyhow!("IndexedDB is disabled")));
+        assert_eq!(result.or_degraded("Failed to fetch app-state"), None);
+        assert!(is_storage_degraded());
+        let result: indexed_db::Result<Vec<String>, std::io::Error> =
+            Err(js_lib::open_error(anyhow::anyhow!("IndexedDB is disabled")));
+        assert!(result.or_degraded("Failed to get storage keys").is_empty());
+    }
+
+    #[test]
+    fn test_session_expires_after_consecutive_unauthorized() {
+        let session = SessionWatch::new();
+        assert!(matches!(session.status_error(401), Error::Unauthorized));
+        assert!(!*session.expired().get());
+        // Any other response breaks the streak.
+        assert!(matches!(session.status_error(500), Error::Other(_)));
+        assert!(matches!(session.status_error(404), Error::NotFound));
+        session.status_error(401);
+        assert!(!*session.expired().get());
+        session.authorized();
+        session.status_error(401);
+        session.status_error(401);
+        assert!(*session.expired().get());
+        session.acknowledge();
+        assert!(!*session.expired().get());
+        session.status_error(401);
+        assert!(!*session.expired().get());
+    }
+}
@@ -12,29 +12,42 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 use std::{
+    cell::RefCell,
     collections::{BTreeMap, BTreeSet},
     fmt::Debug,
+    rc::Rc,
 };
 
 use chrono::NaiveDate;
-use client_api::UserData;
-use recipes::{parse, Ingredient, IngredientKey, Recipe, RecipeEntry};
+use client_api::{ChangeEvent, FullPlanData, PlanTemplate, UserData, UserSettings};
+use recipes::{
+    merge_ingredients, parse,
+    unit::{MeasureType, QuantityDisplay, UnitSystem, DEFAULT_PRECISION},
+    Ingredient, IngredientKey, Recipe, RecipeEntry,
+};
 use serde::{Deserialize, Serialize};
-use sycamore::futures::spawn_local_scoped;
+use sycamore::futures::{spawn_local, spawn_local_scoped};
 use sycamore::prelude::*;
 use sycamore_state::{Handler, MessageMapper};
 use tracing::{debug, error, info, instrument, warn};
-use wasm_bindgen::throw_str;
+use wasm_bindgen::{closure::Closure, throw_str, JsCast};
 
 use crate::{
-    api::{HttpStore, LocalStore},
+    api::{CachedCollection, Fetched, HttpStore, LocalStore},
+    batch::{UpdateBatch, BATCH_WINDOW_MS},
+    js_lib,
     linear::LinearSignal,
+    routing,
 };
 
 fn bool_true() -> bool {
     true
 }
 
+fn default_precision() -> u32 {
+    DEFAULT_PRECISION
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AppState {
     pub recipe_counts: BTreeMap<String, u32>,
@@ -47,6 +60,13 @@ pub struct AppState {
     #[serde(skip_deserializing, skip_serializing)]
     pub recipes: BTreeMap<String, Recipe>,
     pub category_map: BTreeMap<String, String>,
+    /// Measure types forced for ingredients when building the shopping list.
+    #[serde(default)]
+    pub measure_types: BTreeMap<String, MeasureType>,
+    /// The order the shopping list shows categories in. Categories that
+    /// aren't in it are sorted alphabetically after the ones that are.
+    #[serde(default)]
+    pub category_order: Vec<String>,
     pub filtered_ingredients: BTreeSet<IngredientKey>,
     pub modified_amts: BTreeMap<IngredientKey, String>,
     pub auth: Option<UserData>,
@@ -54,6 +74,55 @@ pub struct AppState {
     pub selected_plan_date: Option<NaiveDate>,
     #[serde(default = "bool_true")]
     pub use_staples: bool,
+    #[serde(default)]
+    pub quantity_display: QuantityDisplay,
+    /// The number of decimal places to round displayed amounts to.
+    #[serde(default = "default_precision")]
+    pub display_precision: u32,
+    /// Whether the shopping list is in shopping mode.
+    #[serde(default)]
+    pub shopping_mode: bool,
+    /// Whether count amounts are labeled with "each" in the shopping list.
+    #[serde(default)]
+    pub count_each: bool,
+    /// Whether US amounts are shown in compound units in the shopping
+    /// list. e.g. `1 lb 4 oz`
+    #[serde(default)]
+    pub compound_units: bool,
+    /// The system of units the shopping list converts amounts into. None
+    /// shows amounts in the units the recipes used.
+    #[serde(default)]
+    pub unit_system: Option<UnitSystem>,
+    /// Ingredients that have been checked off while shopping. Unlike
+    /// `filtered_ingredients` these are still on the shopping list. They are
+    /// saved with the inventory for the selected plan date.
+    #[serde(default)]
+    pub checked_off: BTreeSet<IngredientKey>,
+    /// What ingredients were actually bought as for the selected plan date.
+    /// e.g. `1 pint` for `1 3/4 cups` of cream.
+    #[serde(default)]
+    pub purchased_amts: BTreeMap<IngredientKey, String>,
+    /// The most recent amount ingredients were bought as in earlier plans.
+    #[serde(default)]
+    pub purchase_hints: BTreeMap<IngredientKey, String>,
+    /// Whether recipes are edited with the structured editor instead of as text.
+    #[serde(default)]
+    pub structured_editor: bool,
+    /// The names of the saved meal plan templates.
+    #[serde(default)]
+    pub plan_templates: BTreeSet<String>,
+    /// The route to land on after logging in. One of `client_api::LANDING_ROUTES`.
+    #[serde(default)]
+    pub default_route: Option<String>,
+    /// The collections we are showing LocalStore data for because they
+    /// couldn't be synchronized with the server, along with when they were
+    /// last fetched if we know.
+    #[serde(skip_deserializing, skip_serializing)]
+    pub cached_since: BTreeMap<CachedCollection, Option<f64>>,
+    /// Whether the recipes have been loaded from the LocalStore or the
+    /// server. Pages that need them show a loading state until they have.
+    #[serde(skip_deserializing, skip_serializing)]
+    pub loaded: bool,
 }
 
 impl AppState {
@@ -65,12 +134,28 @@ impl AppState {
             staples: None,
             recipes: BTreeMap::new(),
             category_map: BTreeMap::new(),
+            measure_types: BTreeMap::new(),
+            category_order: Vec::new(),
             filtered_ingredients: BTreeSet::new(),
             modified_amts: BTreeMap::new(),
             auth: None,
             plan_dates: BTreeSet::new(),
             selected_plan_date: None,
             use_staples: true,
+            quantity_display: QuantityDisplay::default(),
+            display_precision: DEFAULT_PRECISION,
+            shopping_mode: false,
+            count_each: false,
+            compound_units: false,
+            unit_system: None,
+            checked_off: BTreeSet::new(),
+            purchased_amts: BTreeMap::new(),
+            purchase_hints: BTreeMap::new(),
+            structured_editor: false,
+            plan_templates: BTreeSet::new(),
+            default_route: None,
+            cached_since: BTreeMap::new(),
+            loaded: false,
         }
     }
 }
@@ -84,17 +169,39 @@ pub enum Message {
     SaveRecipe(RecipeEntry, Option<Box<dyn FnOnce()>>),
     RemoveRecipe(String, Option<Box<dyn FnOnce()>>),
     UpdateCategory(String, String, Option<Box<dyn FnOnce()>>),
+    UpdateMeasureType(String, Option<MeasureType>),
+    UpdateCategoryOrder(Vec<String>),
     ResetInventory,
     AddFilteredIngredient(IngredientKey),
     RemoveFilteredIngredient(IngredientKey),
     UpdateAmt(IngredientKey, String),
+    /// Record what an ingredient was bought as. An empty amount clears it.
+    UpdatePurchasedAmt(IngredientKey, String),
     SetUserData(UserData),
+    Logout,
+    AuthExpired,
     SaveState(Option<Box<dyn FnOnce()>>),
     LoadState(Option<Box<dyn FnOnce()>>),
+    /// Another session changed the user's data.
+    RemoteChange(ChangeEvent),
     UpdateStaples(String, Option<Box<dyn FnOnce()>>),
     DeletePlan(NaiveDate, Option<Box<dyn FnOnce()>>),
     SelectPlanDate(NaiveDate, Option<Box<dyn FnOnce()>>),
+    SaveTemplate(String, Option<Box<dyn FnOnce()>>),
+    ApplyTemplate(String, NaiveDate, Option<Box<dyn FnOnce()>>),
     UpdateUseStaples(bool), // TODO(jwall): Should this just be various settings?
+    UpdateQuantityDisplay(QuantityDisplay),
+    UpdateDisplayPrecision(u32),
+    UpdateShoppingMode(bool),
+    UpdateCountEach(bool),
+    UpdateCompoundUnits(bool),
+    UpdateUnitSystem(Option<UnitSystem>),
+    // NOTE(jwall): The checked off messages only touch the checked items.
+    // They must never modify the filtered_ingredients.
+    ToggleChecked(IngredientKey),
+    ClearCheckedOff,
+    UpdateStructuredEditor(bool),
+    UpdateDefaultRoute(Option<String>),
 }
 
 impl Debug for Message {
@@ -121,6 +228,14 @@ impl Debug for Message {
             Self::UpdateCategory(i, c, _) => {
                 f.debug_tuple("UpdateCategory").field(i).field(c).finish()
             }
+            Self::UpdateCategoryOrder(arg) => {
+                f.debug_tuple("UpdateCategoryOrder").field(arg).finish()
+            }
+            Self::UpdateMeasureType(i, mt) => f
+                .debug_tuple("UpdateMeasureType")
+                .field(i)
+                .field(mt)
+                .finish(),
             Self::ResetInventory => write!(f, "ResetInventory"),
             Self::AddFilteredIngredient(arg0) => {
                 f.debug_tuple("AddFilteredIngredient").field(arg0).finish()
@@ -131,20 +246,68 @@ impl Debug for Message {
             Self::UpdateAmt(arg0, arg1) => {
                 f.debug_tuple("UpdateAmt").field(arg0).field(arg1).finish()
             }
+            Self::UpdatePurchasedAmt(arg0, arg1) => f
+                .debug_tuple("UpdatePurchasedAmt")
+                .field(arg0)
+                .field(arg1)
+                .finish(),
             Self::SetUserData(arg0) => f.debug_tuple("SetUserData").field(arg0).finish(),
+            Self::Logout => write!(f, "Logout"),
+            Self::AuthExpired => write!(f, "AuthExpired"),
             Self::SaveState(_) => write!(f, "SaveState"),
             Self::LoadState(_) => write!(f, "LoadState"),
+            Self::RemoteChange(arg) => f.debug_tuple("RemoteChange").field(arg).finish(),
             Self::UpdateStaples(arg, _) => f.debug_tuple("UpdateStaples").field(arg).finish(),
             Self::UpdateUseStaples(arg) => f.debug_tuple("UpdateUseStaples").field(arg).finish(),
+            Self::UpdateQuantityDisplay(arg) => {
+                f.debug_tuple("UpdateQuantityDisplay").field(arg).finish()
+            }
+            Self::UpdateDisplayPrecision(arg) => {
+                f.debug_tuple("UpdateDisplayPrecision").field(arg).finish()
+            }
+            Self::UpdateShoppingMode(arg) => {
+                f.debug_tuple("UpdateShoppingMode").field(arg).finish()
+            }
+            Self::UpdateCountEach(arg) => f.debug_tuple("UpdateCountEach").field(arg).finish(),
+            Self::UpdateCompoundUnits(arg) => {
+                f.debug_tuple("UpdateCompoundUnits").field(arg).finish()
+            }
+            Self::UpdateUnitSystem(arg) => f.debug_tuple("UpdateUnitSystem").field(arg).finish(),
+            Self::ToggleChecked(arg) => f.debug_tuple("ToggleChecked").field(arg).finish(),
+            Self::ClearCheckedOff => write!(f, "ClearCheckedOff"),
+            Self::UpdateStructuredEditor(arg) => {
+                f.debug_tuple("UpdateStructuredEditor").field(arg).finish()
+            }
+            Self::UpdateDefaultRoute(arg) => {
+                f.debug_tuple("UpdateDefaultRoute").field(arg).finish()
+            }
             Self::SelectPlanDate(arg, _) => f.debug_tuple("SelectPlanDate").field(arg).finish(),
             Self::DeletePlan(arg, _) => f.debug_tuple("DeletePlan").field(arg).finish(),
+            Self::SaveTemplate(arg, _) => f.debug_tuple("SaveTemplate").field(arg).finish(),
+            Self::ApplyTemplate(arg0, arg1, _) => f
+                .debug_tuple("ApplyTemplate")
+                .field(arg0)
+                .field(arg1)
+                .finish(),
         }
     }
 }
 
+/// Where the user logs in.
+pub const LOGIN_ROUTE: &str = "/ui/login";
+
+/// Forget the user data for a session that has expired on the server. The
+/// rest of the state is kept so nothing is lost when they log back in.
+fn expire_auth(state: &mut AppState) {
+    state.auth = None;
+}
+
+type CategoryBatch = Rc<RefCell<UpdateBatch<String, String>>>;
+
 pub struct StateMachine {
     store: HttpStore,
     local_store: LocalStore,
+    categories: CategoryBatch,
 }
 
 #[instrument]
@@ -170,9 +333,115 @@ pub fn parse_recipes(
     }
 }
 
+/// Build the state to render on startup from the LocalStore snapshot and
+/// cached recipes. This lets the ui show the last known data right away while
+/// `Message::LoadState` synchronizes with the server in the background.
+pub fn warm_start_state(
+    snapshot: Option<AppState>,
+    recipe_entries: Option<Vec<RecipeEntry>>,
+) -> AppState {
+    let mut state = snapshot.unwrap_or_else(AppState::new);
+    if let Ok(Some(recipes)) = parse_recipes(&recipe_entries) {
+        if !recipes.is_empty() {
+            // NOTE(jwall): The pages expect every categorized recipe to exist.
+            state
+                .recipe_categories
+                .retain(|id, _| recipes.contains_key(id));
+            state.recipes = recipes;
+            state.loaded = true;
+        }
+    }
+    state
+}
+
 impl StateMachine {
     pub fn new(store: HttpStore, local_store: LocalStore) -> Self {
-        Self { store, local_store }
+        let categories = CategoryBatch::default();
+        Self::flush_on_page_hide(&store, &categories);
+        Self {
+            store,
+            local_store,
+            categories,
+        }
+    }
+
+    /// Send any queued category updates to the server. Updates that fail to
+    /// send are put back in the batch and another flush is scheduled.
+    async fn flush_categories(store: &HttpStore, categories: &CategoryBatch) {
+        let updates = categories.borrow_mut().take();
+        if updates.is_empty() {
+            return;
+        }
+        debug!(count = updates.len(), "Flushing category updates");
+        if let Err(e) = store.store_categories(&updates).await {
+            error!(?e, "Failed to save categories");
+            categories
+                .borrow_mut()
+                .requeue(updates, js_lib::get_ms_timestamp());
+            // NOTE(jwall): Updates queued after this only schedule a flush
+            // when the batch was empty so we have to schedule the retry.
+            Self::schedule_category_flush(store, categories);
+        }
+    }
+
+    /// Save the checked items in the background for the selected plan date.
+    fn store_checked_items(&self, cx: Scope, state: &AppState) {
+        let store = self.store.clone();
+        let state = state.clone();
+        spawn_local_scoped(cx, async move {
+            if let Err(e) = store.store_checked_items(&state).await {
+                error!(?e, "Failed to save checked items");
+            }
+        });
+    }
+
+    /// Save the user's settings in the background.
+    fn store_settings(&self, cx: Scope, state: &AppState) {
+        let store = self.store.clone();
+        let settings = UserSettings {
+            default_route: state.default_route.clone(),
+            use_staples: Some(state.use_staples),
+        };
+        spawn_local_scoped(cx, async move {
+            if let Err(e) = store.store_settings(&settings).await {
+                error!(?e, "Failed to save settings");
+            }
+        });
+    }
+
+    /// Flush the queued category updates once the batch window has passed.
+    fn schedule_category_flush(store: &HttpStore, categories: &CategoryBatch) {
+        let (store, categories) = (store.clone(), categories.clone());
+        let flush = Closure::once_into_js(move || {
+            spawn_local(async move { Self::flush_categories(&store, &categories).await });
+        });
+        let _ = js_lib::get_window().set_timeout_with_callback_and_timeout_and_arguments_0(
+            flush.unchecked_ref(),
+            BATCH_WINDOW_MS as i32,
+        );
+    }
+
+    /// Make sure queued category updates aren't lost when the user leaves
+    /// the page. A normal request may be cancelled when the page unloads so
+    /// these are sent as a beacon.
+    fn flush_on_page_hide(store: &HttpStore, categories: &CategoryBatch) {
+        let (store, categories) = (store.clone(), categories.clone());
+        let flush = Closure::<dyn FnMut()>::new(move || {
+            let updates = categories.borrow_mut().take();
+            if updates.is_empty() {
+                return;
+            }
+            debug!(count = updates.len(), "Beaconing category updates");
+            if !store.beacon_categories(&updates) {
+                error!("Browser refused the category updates beacon");
+                categories
+                    .borrow_mut()
+                    .requeue(updates, js_lib::get_ms_timestamp());
+            }
+        });
+        let _ = js_lib::get_window()
+            .add_event_listener_with_callback("pagehide", flush.as_ref().unchecked_ref());
+        flush.forget();
     }
 
     #[instrument(skip_all)]
@@ -184,24 +453,53 @@ impl StateMachine {
         // NOTE(jwall): We use a linear Signal in here to ensure that we only
         // call set on the signal once. When the LinearSignal get's dropped it
         // will call set on the contained Signal.
+        // NOTE(jwall): The recipes aren't part of the LocalStore snapshot so
+        // we keep the ones we warm started with.
+        let (warm_recipes, loaded) = {
+            let current = original.get_untracked();
+            (current.recipes.clone(), current.loaded)
+        };
         let mut original: LinearSignal<AppState> = original.into();
-        if let Some(state) = local_store.fetch_app_state().await {
+        if let Some(mut state) = local_store.fetch_app_state().await {
+            // NOTE(jwall): Until each collection is synchronized below we are
+            // showing the LocalStore copy of it.
+            let fetched_at = local_store.get_fetched_at().await;
+            state.cached_since = CachedCollection::ALL
+                .iter()
+                .map(|c| (*c, fetched_at.get(c).cloned()))
+                .collect();
+            state.recipes = warm_recipes;
+            state.loaded = loaded;
             original = original.update(state);
         }
         let mut state = original.get().as_ref().clone();
         info!("Synchronizing Recipes");
-        let recipe_entries = &store.fetch_recipes().await?;
+        let recipe_entries = match store.fetch_recipes().await? {
+            Fetched::Server(entries) => {
+                state.cached_since.remove(&CachedCollection::Recipes);
+                entries
+            }
+            Fetched::Cached(entries, fetched_at) => {
+                warn!("Using recipes from the local store");
+                state
+                    .cached_since
+                    .insert(CachedCollection::Recipes, fetched_at);
+                entries
+            }
+        };
+        let recipe_entries = &recipe_entries;
         let recipes = parse_recipes(&recipe_entries)?;
         debug!(?recipes, "Parsed Recipes");
         if let Some(recipes) = recipes {
             state.recipes = recipes;
         };
+        state.loaded = true;
 
         info!("Synchronizing staples");
         state.staples = if let Some(content) = store.fetch_staples().await? {
             // now we need to parse staples as ingredients
-            let mut staples = parse::as_ingredient_list(&content)?;
-            Some(staples.drain(0..).collect())
+            let staples = parse::as_ingredient_list(&content)?;
+            Some(merge_ingredients(staples).into_iter().collect())
         } else {
             Some(BTreeSet::new())
         };
@@ -225,17 +523,31 @@ impl StateMachine {
         }
 
         info!("Fetching meal plan list");
-        if let Some(mut plan_dates) = store.fetch_plan_dates().await? {
-            debug!(?plan_dates, "meal plan list");
-            state.plan_dates = BTreeSet::from_iter(plan_dates.drain(0..));
+        // NOTE(jwall): If we can't reach the server we keep showing the plan
+        // from the LocalStore along with when it was fetched.
+        let mut plans_synced = true;
+        match store.fetch_plan_dates().await {
+            Ok(Some(mut plan_dates)) => {
+                debug!(?plan_dates, "meal plan list");
+                state.plan_dates = BTreeSet::from_iter(plan_dates.drain(0..));
+            }
+            Ok(None) => {}
+            Err(e) => {
+                error!(?e, "Failed to fetch meal plan list");
+                plans_synced = false;
+            }
         }
 
         info!("Synchronizing meal plan");
         let plan = if let Some(ref cached_plan_date) = state.selected_plan_date {
-            store
-                .fetch_plan_for_date(cached_plan_date)
-                .await?
-                .or_else(|| Some(Vec::new()))
+            match store.fetch_plan_for_date(cached_plan_date).await {
+                Ok(plan) => Some(plan.unwrap_or_default()),
+                Err(e) => {
+                    error!(?e, "Failed to fetch meal plan");
+                    plans_synced = false;
+                    None
+                }
+            }
         } else {
             None
         };
@@ -255,10 +567,19 @@ impl StateMachine {
             // Initialize things to zero.
             if let Some(rs) = recipe_entries {
                 for r in rs {
-                    state.recipe_counts.insert(r.recipe_id().to_owned(), 0);
+                    let count = state
+                        .recipe_counts
+                        .entry(r.recipe_id().to_owned())
+                        .or_insert(0);
+                    if plans_synced {
+                        *count = 0;
+                    }
                 }
             }
         }
+        if plans_synced {
+            state.cached_since.remove(&CachedCollection::Plans);
+        }
         info!("Checking for user account data");
         if let Some(user_data) = store.fetch_user_data().await {
             debug!("Successfully got account data from server");
@@ -275,14 +596,57 @@ impl StateMachine {
                 debug!(categories=?categories_content);
                 let category_map = BTreeMap::from_iter(categories_content.drain(0..));
                 state.category_map = category_map;
+                state.cached_since.remove(&CachedCollection::Categories);
             }
             Ok(None) => {
                 warn!("There is no category file");
+                state.cached_since.remove(&CachedCollection::Categories);
             }
             Err(e) => {
                 error!("{:?}", e);
             }
         }
+        info!("Synchronizing measure types");
+        match store.fetch_measure_types().await {
+            Ok(measure_types) => {
+                state.measure_types = BTreeMap::from_iter(measure_types);
+            }
+            Err(e) => {
+                error!(?e, "Failed to fetch measure types");
+            }
+        }
+        info!("Synchronizing category order");
+        match store.fetch_category_order().await {
+            Ok(category_order) => {
+                state.category_order = category_order;
+            }
+            Err(e) => {
+                error!(?e, "Failed to fetch category order");
+            }
+        }
+        info!("Synchronizing plan templates");
+        match store.fetch_plan_templates().await {
+            Ok(templates) => {
+                state.plan_templates = templates.into_iter().map(|t| t.name).collect();
+            }
+            Err(e) => {
+                error!(?e, "Failed to fetch plan templates");
+            }
+        }
+        info!("Synchronizing settings");
+        match store.fetch_settings().await {
+            Ok(settings) => {
+                state.default_route = settings.default_route;
+                // NOTE(jwall): Until the user chooses we keep what's in the
+                // LocalStore.
+                if let Some(use_staples) = settings.use_staples {
+                    state.use_staples = use_staples;
+                }
+            }
+            Err(e) => {
+                error!(?e, "Failed to fetch settings");
+            }
+        }
         let inventory_data = if let Some(cached_plan_date) = &state.selected_plan_date {
             store.fetch_inventory_for_date(cached_plan_date).await
         } else {
@@ -290,10 +654,19 @@ impl StateMachine {
         };
         info!("Synchronizing inventory data");
         match inventory_data {
-            Ok((filtered_ingredients, modified_amts, extra_items)) => {
-                state.modified_amts = modified_amts;
-                state.filtered_ingredients = filtered_ingredients;
-                state.extras = extra_items;
+            Ok(inventory) => {
+                state.modified_amts = inventory.modified_amts.into_iter().collect();
+                state.filtered_ingredients = inventory.filtered_ingredients.into_iter().collect();
+                state.extras = inventory.extra_items;
+                // NOTE(jwall): Checks and purchases are only saved on the
+                // server for a plan date. Otherwise we keep the ones in the
+                // LocalStore.
+                if state.selected_plan_date.is_some() {
+                    state.checked_off = inventory.checked_items.into_iter().collect();
+                    state.purchased_amts = inventory.purchased_amts.into_iter().collect();
+                    state.purchase_hints = inventory.purchase_hints.into_iter().collect();
+                }
+                state.cached_since.remove(&CachedCollection::Inventory);
             }
             Err(e) => {
                 error!("{:?}", e);
@@ -304,6 +677,81 @@ impl StateMachine {
         original.update(state);
         Ok(())
     }
+
+    /// Refetch only the part of the state that another session changed.
+    async fn refresh_changed(
+        store: &HttpStore,
+        local_store: &LocalStore,
+        original: &Signal<AppState>,
+        event: ChangeEvent,
+    ) -> Result<(), crate::api::Error> {
+        match event {
+            ChangeEvent::RecipesChanged => {
+                let recipe_entries = match store.fetch_recipes().await? {
+                    Fetched::Server(entries) => entries,
+                    // NOTE(jwall): We couldn't reach the server so there is
+                    // nothing new to show.
+                    Fetched::Cached(_, _) => return Ok(()),
+                };
+                let recipes = parse_recipes(&recipe_entries)?;
+                // NOTE(jwall): We start from the current state since it may
+                // have changed while we were fetching.
+                let mut state = original.get_untracked().as_ref().clone();
+                if let Some(recipes) = recipes {
+                    state.recipes = recipes;
+                }
+                if let Some(recipe_entries) = &recipe_entries {
+                    local_store.set_all_recipes(recipe_entries).await;
+                    state.recipe_categories = recipe_entries
+                        .iter()
+                        .map(|entry| {
+                            (
+                                entry.recipe_id().to_owned(),
+                                entry
+                                    .category()
+                                    .cloned()
+                                    .unwrap_or_else(|| "Entree".to_owned()),
+                            )
+                        })
+                        .collect();
+                    for entry in recipe_entries {
+                        state
+                            .recipe_counts
+                            .entry(entry.recipe_id().to_owned())
+                            .or_insert(0);
+                    }
+                }
+                state.cached_since.remove(&CachedCollection::Recipes);
+                local_store.store_app_state(&state).await;
+                original.set(state);
+            }
+            ChangeEvent::PlanChanged(date) => {
+                let plan_dates = store.fetch_plan_dates().await?;
+                let selected = original.get_untracked().selected_plan_date == Some(date);
+                // NOTE(jwall): A deleted plan comes back empty which clears
+                // the counts.
+                let plan = if selected {
+                    Some(store.fetch_plan_for_date(&date).await?.unwrap_or_default())
+                } else {
+                    None
+                };
+                let mut state = original.get_untracked().as_ref().clone();
+                state.plan_dates = plan_dates.unwrap_or_default().into_iter().collect();
+                if let Some(plan) = plan {
+                    for count in state.recipe_counts.values_mut() {
+                        *count = 0;
+                    }
+                    for (id, count) in plan {
+                        state.recipe_counts.insert(id, count as u32);
+                    }
+                }
+                state.cached_since.remove(&CachedCollection::Plans);
+                local_store.store_app_state(&state).await;
+                original.set(state);
+            }
+        }
+        Ok(())
+    }
 }
 
 impl MessageMapper<Message, AppState> for StateMachine {
@@ -339,8 +787,12 @@ impl MessageMapper<Message, AppState> for StateMachine {
             },
             Message::SaveRecipe(entry, callback) => {
                 let recipe_id = entry.recipe_id().to_owned();
-                let recipe: Recipe = (&entry).try_into().expect("Failed to parse RecipeEntry");
-                original_copy.recipes.insert(recipe_id.clone(), recipe);
+                match Recipe::try_from(&entry) {
+                    Ok(recipe) => {
+                        original_copy.recipes.insert(recipe_id.clone(), recipe);
+                    }
+                    Err(err) => error!(?err, recipe_id, "Failed to parse RecipeEntry"),
+                }
                 if !original_copy.recipe_counts.contains_key(entry.recipe_id()) {
                     original_copy.recipe_counts.insert(recipe_id.clone(), 0);
                 }
@@ -381,12 +833,51 @@ impl MessageMapper<Message, AppState> for StateMachine {
                 original_copy
                     .category_map
                     .insert(ingredient.clone(), category.clone());
+                let now = js_lib::get_ms_timestamp();
+                let (was_empty, flush_now) = {
+                    let mut categories = self.categories.borrow_mut();
+                    let was_empty = categories.is_empty();
+                    let full = categories.push(ingredient, category, now);
+                    (was_empty, full || categories.is_due(now))
+                };
+                if flush_now {
+                    let (store, categories) = (self.store.clone(), self.categories.clone());
+                    spawn_local_scoped(cx, async move {
+                        Self::flush_categories(&store, &categories).await;
+                    });
+                } else if was_empty {
+                    Self::schedule_category_flush(&self.store, &self.categories);
+                }
+                callback.map(|f| f());
+            }
+            Message::UpdateMeasureType(ingredient, measure_type) => {
+                match measure_type {
+                    Some(measure_type) => {
+                        original_copy.measure_types.insert(ingredient, measure_type);
+                    }
+                    None => {
+                        original_copy.measure_types.remove(&ingredient);
+                    }
+                }
+                let measure_types = original_copy
+                    .measure_types
+                    .iter()
+                    .map(|(i, mt)| (i.clone(), *mt))
+                    .collect::<Vec<(String, MeasureType)>>();
                 let store = self.store.clone();
                 spawn_local_scoped(cx, async move {
-                    if let Err(e) = store.store_categories(&vec![(ingredient, category)]).await {
-                        error!(?e, "Failed to save categories");
+                    if let Err(e) = store.store_measure_types(&measure_types).await {
+                        error!(?e, "Failed to save measure types");
+                    }
+                });
+            }
+            Message::UpdateCategoryOrder(category_order) => {
+                original_copy.category_order = category_order.clone();
+                let store = self.store.clone();
+                spawn_local_scoped(cx, async move {
+                    if let Err(e) = store.store_category_order(&category_order).await {
+                        error!(?e, "Failed to save category order");
                     }
-                    callback.map(|f| f());
                 });
             }
             Message::ResetInventory => {
@@ -403,18 +894,76 @@ impl MessageMapper<Message, AppState> for StateMachine {
             Message::UpdateAmt(key, amt) => {
                 original_copy.modified_amts.insert(key, amt);
             }
+            Message::UpdatePurchasedAmt(key, amt) => {
+                let amt = amt.trim();
+                if amt.is_empty() {
+                    original_copy.purchased_amts.remove(&key);
+                } else {
+                    original_copy.purchased_amts.insert(key, amt.to_owned());
+                }
+            }
             Message::SetUserData(user_data) => {
                 let local_store = self.local_store.clone();
                 original_copy.auth = Some(user_data.clone());
                 spawn_local_scoped(cx, async move {
+                    // NOTE(jwall): The user data has to be in the LocalStore
+                    // before anything sees the new state.
                     local_store.set_user_data(Some(&user_data)).await;
+                    local_store.store_app_state(&original_copy).await;
+                    original.set(original_copy);
                 });
+                return;
+            }
+            Message::Logout => {
+                let store = self.store.clone();
+                let local_store = self.local_store.clone();
+                spawn_local_scoped(cx, async move {
+                    if let Err(err) = store.logout().await {
+                        // NOTE(jwall): If we can't reach the server we still
+                        // drop the session cookie so we end up logged out.
+                        error!(?err, "Error logging out");
+                        js_lib::clear_session_cookie();
+                    }
+                    original_copy.auth = None;
+                    local_store.set_user_data(None).await;
+                    local_store.store_app_state(&original_copy).await;
+                    original.set(original_copy);
+                });
+                // NOTE(jwall): Because we do our signal set above in the async block
+                // we have to return here to avoid lifetime issues and double setting
+                // the original signal.
+                return;
+            }
+            Message::AuthExpired => {
+                let store = self.store.clone();
+                let local_store = self.local_store.clone();
+                error!("Your session has expired. Please log in again.");
+                expire_auth(&mut original_copy);
+                js_lib::clear_session_cookie();
+                spawn_local_scoped(cx, async move {
+                    local_store.set_user_data(None).await;
+                    local_store.store_app_state(&original_copy).await;
+                    original.set(original_copy);
+                    store.session().acknowledge();
+                    let location = js_lib::get_window().location();
+                    let current = location.pathname().unwrap_or_default();
+                    if !current.starts_with(LOGIN_ROUTE) {
+                        let search = location.search().unwrap_or_default();
+                        sycamore_router::navigate(&routing::login_redirect(&format!(
+                            "{}{}",
+                            current, search
+                        )));
+                    }
+                });
+                return;
             }
             Message::SaveState(f) => {
                 let mut original_copy = original_copy.clone();
                 let store = self.store.clone();
                 let local_store = self.local_store.clone();
+                let categories = self.categories.clone();
                 spawn_local_scoped(cx, async move {
+                    Self::flush_categories(&store, &categories).await;
                     if original_copy.selected_plan_date.is_none() {
                         original_copy.selected_plan_date = Some(chrono::Local::now().date_naive());
                     }
@@ -443,11 +992,31 @@ impl MessageMapper<Message, AppState> for StateMachine {
                 spawn_local_scoped(cx, async move {
                     if let Err(err) = Self::load_state(&store, &local_store, original).await {
                         error!(?err, "Failed to load user state");
+                        // NOTE(jwall): Stop showing the loading state so the
+                        // pages can show whatever we did manage to load.
+                        if !original.get_untracked().loaded {
+                            let mut state = original.get_untracked().as_ref().clone();
+                            state.loaded = true;
+                            original.set(state);
+                        }
                     }
                     f.map(|f| f());
                 });
                 return;
             }
+            Message::RemoteChange(event) => {
+                let store = self.store.clone();
+                let local_store = self.local_store.clone();
+                debug!(?event, "Refreshing after a change from another session");
+                spawn_local_scoped(cx, async move {
+                    if let Err(err) =
+                        Self::refresh_changed(&store, &local_store, original, event).await
+                    {
+                        error!(?err, "Failed to refresh after a remote change");
+                    }
+                });
+                return;
+            }
             Message::UpdateStaples(content, callback) => {
                 let store = self.store.clone();
                 spawn_local_scoped(cx, async move {
@@ -461,34 +1030,170 @@ impl MessageMapper<Message, AppState> for StateMachine {
             }
             Message::UpdateUseStaples(value) => {
                 original_copy.use_staples = value;
+                // NOTE(jwall): The server needs this too for the shopping
+                // lists it builds. e.g. the pdf.
+                self.store_settings(cx, &original_copy);
+            }
+            Message::UpdateQuantityDisplay(value) => {
+                original_copy.quantity_display = value;
+            }
+            Message::UpdateDisplayPrecision(value) => {
+                original_copy.display_precision = value;
+            }
+            Message::UpdateShoppingMode(value) => {
+                original_copy.shopping_mode = value;
+            }
+            Message::UpdateCountEach(value) => {
+                original_copy.count_each = value;
+            }
+            Message::UpdateCompoundUnits(value) => {
+                original_copy.compound_units = value;
+            }
+            Message::UpdateUnitSystem(value) => {
+                original_copy.unit_system = value;
+            }
+            Message::UpdateStructuredEditor(value) => {
+                original_copy.structured_editor = value;
+            }
+            Message::UpdateDefaultRoute(default_route) => {
+                original_copy.default_route = default_route;
+                self.store_settings(cx, &original_copy);
+            }
+            Message::ToggleChecked(key) => {
+                if !original_copy.checked_off.remove(&key) {
+                    original_copy.checked_off.insert(key);
+                }
+                self.store_checked_items(cx, &original_copy);
+            }
+            Message::ClearCheckedOff => {
+                original_copy.checked_off = BTreeSet::new();
+                self.store_checked_items(cx, &original_copy);
             }
             Message::SelectPlanDate(date, callback) => {
                 let store = self.store.clone();
                 let local_store = self.local_store.clone();
                 spawn_local_scoped(cx, async move {
-                    if let Some(mut plan) = store
-                        .fetch_plan_for_date(&date)
-                        .await
-                        .expect("Failed to fetch plan for date")
+                    let plan_data = match store.fetch_full_plan_for_date(&date).await {
+                        Ok(plan_data) => plan_data,
+                        Err(err) => {
+                            error!(?err, "Failed to fetch plan for date");
+                            None
+                        }
+                    };
+                    if let Some(FullPlanData {
+                        recipe_counts,
+                        inventory,
+                    }) = plan_data
                     {
+                        original_copy.cached_since.remove(&CachedCollection::Plans);
+                        original_copy
+                            .cached_since
+                            .remove(&CachedCollection::Inventory);
                         // Note(jwall): This is a little unusual but because this
                         // is async code we can't rely on the set below.
-                        original_copy.recipe_counts =
-                            BTreeMap::from_iter(plan.drain(0..).map(|(k, v)| (k, v as u32)));
+                        original_copy.recipe_counts = BTreeMap::from_iter(
+                            recipe_counts.into_iter().map(|(k, v)| (k, v as u32)),
+                        );
+                        original_copy.modified_amts = inventory.modified_amts.into_iter().collect();
+                        original_copy.filtered_ingredients =
+                            inventory.filtered_ingredients.into_iter().collect();
+                        original_copy.extras = inventory.extra_items;
+                        original_copy.checked_off = inventory.checked_items.into_iter().collect();
+                        original_copy.purchased_amts =
+                            inventory.purchased_amts.into_iter().collect();
+                        original_copy.purchase_hints =
+                            inventory.purchase_hints.into_iter().collect();
+                    } else if original_copy.selected_plan_date.as_ref() != Some(&date) {
+                        // The checks belong to the shopping list for the old plan.
+                        original_copy.checked_off = BTreeSet::new();
+                        original_copy.purchased_amts = BTreeMap::new();
+                        original_copy.purchase_hints = BTreeMap::new();
                     }
-                    let (filtered, modified, extras) = store
-                        .fetch_inventory_for_date(&date)
-                        .await
-                        .expect("Failed to fetch inventory_data for date");
                     original_copy.plan_dates.insert(date.clone());
-                    original_copy.modified_amts = modified;
-                    original_copy.filtered_ingredients = filtered;
-                    original_copy.extras = extras;
                     original_copy.selected_plan_date = Some(date.clone());
-                    store
-                        .store_plan_for_date(vec![], &date)
-                        .await
-                        .expect("Failed to init meal plan for date");
+                    if let Err(err) = store.store_plan_for_date(vec![], &date).await {
+                        error!(?err, "Failed to init meal plan for date");
+                    }
+                    local_store.store_app_state(&original_copy).await;
+                    original.set(original_copy);
+
+                    callback.map(|f| f());
+                });
+                // NOTE(jwall): Because we do our signal set above in the async block
+                // we have to return here to avoid lifetime issues and double setting
+                // the original signal.
+                return;
+            }
+            Message::SaveTemplate(name, callback) => {
+                let store = self.store.clone();
+                let local_store = self.local_store.clone();
+                let template = PlanTemplate {
+                    name: name.clone(),
+                    recipe_counts: original_copy
+                        .recipe_counts
+                        .iter()
+                        .map(|(id, count)| (id.clone(), *count as i32))
+                        .collect(),
+                };
+                spawn_local_scoped(cx, async move {
+                    if let Err(err) = store.store_plan_template(&template).await {
+                        error!(?err, "Error saving plan template");
+                    } else {
+                        original_copy.plan_templates.insert(name);
+                        local_store.store_app_state(&original_copy).await;
+                        original.set(original_copy);
+
+                        callback.map(|f| f());
+                    }
+                });
+                // NOTE(jwall): Because we do our signal set above in the async block
+                // we have to return here to avoid lifetime issues and double setting
+                // the original signal.
+                return;
+            }
+            Message::ApplyTemplate(name, date, callback) => {
+                let store = self.store.clone();
+                let local_store = self.local_store.clone();
+                spawn_local_scoped(cx, async move {
+                    let recipe_counts = match store.apply_plan_template(&name, &date).await {
+                        Ok(recipe_counts) => recipe_counts,
+                        Err(err) => {
+                            error!(?err, "Error applying plan template");
+                            return;
+                        }
+                    };
+                    for (_, count) in original_copy.recipe_counts.iter_mut() {
+                        *count = 0;
+                    }
+                    for (id, count) in recipe_counts {
+                        original_copy.recipe_counts.insert(id, count as u32);
+                    }
+                    if original_copy.selected_plan_date.as_ref() != Some(&date) {
+                        // The inventory and checks belong to the old plan.
+                        match store.fetch_inventory_for_date(&date).await {
+                            Ok(inventory) => {
+                                original_copy.filtered_ingredients =
+                                    inventory.filtered_ingredients.into_iter().collect();
+                                original_copy.modified_amts =
+                                    inventory.modified_amts.into_iter().collect();
+                                original_copy.extras = inventory.extra_items;
+                                original_copy.checked_off =
+                                    inventory.checked_items.into_iter().collect();
+                                original_copy.purchased_amts =
+                                    inventory.purchased_amts.into_iter().collect();
+                                original_copy.purchase_hints =
+                                    inventory.purchase_hints.into_iter().collect();
+                            }
+                            Err(err) => {
+                                error!(?err, "Error fetching inventory for date");
+                                original_copy.checked_off = BTreeSet::new();
+                                original_copy.purchased_amts = BTreeMap::new();
+                                original_copy.purchase_hints = BTreeMap::new();
+                            }
+                        }
+                    }
+                    original_copy.plan_dates.insert(date);
+                    original_copy.selected_plan_date = Some(date);
                     local_store.store_app_state(&original_copy).await;
                     original.set(original_copy);
 
@@ -512,6 +1217,9 @@ impl MessageMapper<Message, AppState> for StateMachine {
                         original_copy.filtered_ingredients = BTreeSet::new();
                         original_copy.modified_amts = BTreeMap::new();
                         original_copy.extras = Vec::new();
+                        original_copy.checked_off = BTreeSet::new();
+                        original_copy.purchased_amts = BTreeMap::new();
+                        original_copy.purchase_hints = BTreeMap::new();
                         local_store.store_app_state(&original_copy).await;
                         original.set(original_copy);
 
@@ -543,3 +1251,53 @@ pub fn get_state_handler<'ctx>(
 ) -> StateHandler<'ctx> {
     Handler::new(cx, initial, StateMachine::new(store, LocalStore::new()))
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{components::loading::Loadable, selectors};
+
+    #[test]
+    fn test_expire_auth_keeps_plan() {
+        let mut state = AppState::new();
+        state.auth = Some(UserData {
+            user_id: "alice".to_owned(),
+        });
+        state.recipe_counts.insert("soup".to_owned(), 2);
+        expire_auth(&mut state);
+        assert_eq!(state.auth, None);
+        assert_eq!(state.recipe_counts.get("soup"), Some(&2));
+    }
+
+    #[test]
+    fn test_warm_start_shows_cached_data() {
+        let state = warm_start_state(None, None);
+        assert!(selectors::load_status(&state).is_loading());
+
+        let mut snapshot = AppState::new();
+        snapshot.recipe_counts.insert("soup".to_owned(), 2);
+        snapshot
+            .recipe_categories
+            .insert("soup".to_owned(), "Entree".to_owned());
+        snapshot
+            .recipe_categories
+            .insert("stew".to_owned(), "Entree".to_owned());
+        let entries = vec![RecipeEntry::new(
+            "soup",
+            "title: Soup\n\nstep:\n\n1 cup broth\n\nHeat.\n",
+        )];
+        let state = warm_start_state(Some(snapshot), Some(entries));
+        // The cached data is ready before the server has been asked for anything.
+        assert_eq!(selectors::load_status(&state), Loadable::Ready(()));
+        assert_eq!(
+            selectors::recipe_status(&state, "soup"),
+            Loadable::Ready(())
+        );
+        assert_eq!(state.recipe_counts.get("soup"), Some(&2));
+        assert_eq!(
+            state.recipe_categories.keys().collect::<Vec<&String>>(),
+            vec!["soup"]
+        );
+        assert!(!selectors::shopping_list(&state, false).is_empty());
+    }
+}
@@ -0,0 +1,135 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::collections::BTreeMap;
+
+/// How long updates are held before they get sent to the server.
+pub const BATCH_WINDOW_MS: f64 = 2000.0;
+/// How many updates can be held before they get sent to the server.
+pub const BATCH_MAX_ITEMS: usize = 20;
+
+/// Collects keyed updates so they can be sent to the server in a single
+/// request. Later updates for a key replace earlier ones. Times are passed in
+/// by the caller in milliseconds so the batch doesn't depend on a clock.
+#[derive(Debug, Clone)]
+pub struct UpdateBatch<K: Ord, V> {
+    pending: BTreeMap<K, V>,
+    opened_at: Option<f64>,
+    window_ms: f64,
+    max_items: usize,
+}
+
+impl<K: Ord, V> Default for UpdateBatch<K, V> {
+    fn default() -> Self {
+        Self::new(BATCH_WINDOW_MS, BATCH_MAX_ITEMS)
+    }
+}
+
+impl<K: Ord, V> UpdateBatch<K, V> {
+    pub fn new(window_ms: f64, max_items: usize) -> Self {
+        Self {
+            pending: BTreeMap::new(),
+            opened_at: None,
+            window_ms,
+            max_items,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Queue an update. Returns true if the batch is full and should be
+    /// flushed now.
+    pub fn push(&mut self, key: K, value: V, now_ms: f64) -> bool {
+        if self.opened_at.is_none() {
+            self.opened_at = Some(now_ms);
+        }
+        self.pending.insert(key, value);
+        self.pending.len() >= self.max_items
+    }
+
+    /// Whether the batch is full or has been held for the whole window.
+    pub fn is_due(&self, now_ms: f64) -> bool {
+        match self.opened_at {
+            Some(opened_at) => {
+                self.pending.len() >= self.max_items || now_ms - opened_at >= self.window_ms
+            }
+            None => false,
+        }
+    }
+
+    /// Take all of the pending updates leaving the batch empty.
+    pub fn take(&mut self) -> Vec<(K, V)> {
+        self.opened_at = None;
+        std::mem::take(&mut self.pending).into_iter().collect()
+    }
+
+    /// Put updates that failed to send back in the batch. Updates that were
+    /// queued since the failed flush win over the requeued ones.
+    pub fn requeue(&mut self, updates: Vec<(K, V)>, now_ms: f64) {
+        if updates.is_empty() {
+            return;
+        }
+        if self.opened_at.is_none() {
+            self.opened_at = Some(now_ms);
+        }
+        for (key, value) in updates {
+            self.pending.entry(key).or_insert(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_batch_flushes_after_window() {
+        let mut batch = UpdateBatch::new(2000.0, 20);
+        assert!(!batch.is_due(0.0));
+        assert!(!batch.push("onion", "produce", 100.0));
+        assert!(!batch.push("onion", "vegetables", 500.0));
+        assert!(!batch.is_due(2099.0));
+        assert!(batch.is_due(2100.0));
+        assert_eq!(batch.take(), vec![("onion", "vegetables")]);
+        assert!(batch.is_empty());
+        assert!(!batch.is_due(5000.0));
+    }
+
+    #[test]
+    fn test_batch_flushes_when_full() {
+        let mut batch = UpdateBatch::new(2000.0, 3);
+        assert!(!batch.push("a", 1, 0.0));
+        assert!(!batch.push("b", 1, 0.0));
+        assert!(batch.push("c", 1, 0.0));
+        assert!(batch.is_due(0.0));
+        assert_eq!(batch.take().len(), 3);
+    }
+
+    #[test]
+    fn test_batch_requeue_keeps_newer_updates() {
+        let mut batch = UpdateBatch::new(2000.0, 20);
+        batch.push("onion", "produce", 0.0);
+        batch.push("salt", "spices", 0.0);
+        let failed = batch.take();
+        batch.push("onion", "vegetables", 1000.0);
+        batch.requeue(failed, 1500.0);
+        assert!(!batch.is_due(2999.0));
+        assert!(batch.is_due(3000.0));
+        assert_eq!(
+            batch.take(),
+            vec![("onion", "vegetables"), ("salt", "spices")]
+        );
+    }
+}
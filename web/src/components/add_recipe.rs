@@ -15,7 +15,8 @@ use sycamore::{futures::spawn_local_scoped, prelude::*};
 use tracing::{error, info};
 
 use crate::app_state::{Message, StateHandler};
-use recipes::RecipeEntry;
+use crate::routing;
+use recipes::{RecipeEntry, RecipeTemplate};
 
 const STARTER_RECIPE: &'static str = "title: TITLE_PLACEHOLDER
 
@@ -34,6 +35,25 @@ pub fn AddRecipe<'ctx, G: Html>(cx: Scope<'ctx>, sh: StateHandler<'ctx>) -> View
     let category = create_signal(cx, String::new());
     let create_recipe_signal = create_signal(cx, ());
     let dirty = create_signal(cx, false);
+    let templates = create_signal(cx, Vec::<RecipeTemplate>::new());
+    let selected_template = create_signal(cx, String::new());
+
+    spawn_local_scoped(cx, {
+        let store = crate::api::HttpStore::get_from_context(cx);
+        async move {
+            match store.fetch_templates().await {
+                Ok(list) => templates.set(list),
+                Err(err) => error!(?err, "Failed to fetch recipe templates"),
+            }
+        }
+    });
+    let template_names = create_memo(cx, move || {
+        templates
+            .get()
+            .iter()
+            .map(|t| t.name.clone())
+            .collect::<Vec<String>>()
+    });
 
     let entry = create_memo(cx, || {
         let category = category.get().as_ref().to_owned();
@@ -49,11 +69,18 @@ pub fn AddRecipe<'ctx, G: Html>(cx: Scope<'ctx>, sh: StateHandler<'ctx>) -> View
                 .to_lowercase()
                 .replace(" ", "_")
                 .replace("\n", ""),
-            text: STARTER_RECIPE
-                .replace("TITLE_PLACEHOLDER", recipe_title.get().as_str())
-                .replace("\r", ""),
+            text: match templates
+                .get()
+                .iter()
+                .find(|t| t.name == *selected_template.get())
+            {
+                Some(template) => template.render(recipe_title.get().as_str()),
+                None => STARTER_RECIPE.replace("TITLE_PLACEHOLDER", recipe_title.get().as_str()),
+            }
+            .replace("\r", ""),
             category,
             serving_count: None,
+            tags: Vec::new(),
         }
     });
 
@@ -62,6 +89,19 @@ pub fn AddRecipe<'ctx, G: Html>(cx: Scope<'ctx>, sh: StateHandler<'ctx>) -> View
         input(bind:value=recipe_title, type="text", name="recipe_title", id="recipe_title", on:change=move |_| {
             dirty.set(true);
         })
+        label(for="recipe_template") { "Template" }
+        select(bind:value=selected_template, name="recipe_template", id="recipe_template") {
+            option(value="") { "Default" }
+            Indexed(
+                iterable=template_names,
+                view=|cx, name| {
+                    let value = name.clone();
+                    view! {cx,
+                        option(value=value) { (name) }
+                    }
+                }
+            )
+        }
         button(on:click=move |_| {
             create_recipe_signal.trigger_subscribers();
             if !*dirty.get_untracked() {
@@ -87,7 +127,7 @@ pub fn AddRecipe<'ctx, G: Html>(cx: Scope<'ctx>, sh: StateHandler<'ctx>) -> View
                         }
                     }
                     sh.dispatch(cx, Message::SaveRecipe((*entry).clone(), Some(Box::new({
-                        let path = format!("/ui/recipe/edit/{}", entry.recipe_id());
+                        let path = routing::recipe_edit_route(entry.recipe_id());
                         move || sycamore_router::navigate(path.as_str())
                     }))));
                 }
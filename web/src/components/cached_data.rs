@@ -0,0 +1,131 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::collections::BTreeMap;
+
+use sycamore::prelude::*;
+
+use crate::{api::CachedCollection, app_state::StateHandler, js_lib};
+
+const MINUTE_MS: f64 = 60.0 * 1000.0;
+const HOUR_MS: f64 = 60.0 * MINUTE_MS;
+const DAY_MS: f64 = 24.0 * HOUR_MS;
+
+/// Describe how long ago `then_ms` was in words. e.g. `3 days ago`
+pub fn describe_age(now_ms: f64, then_ms: f64) -> String {
+    let age = (now_ms - then_ms).max(0.0);
+    let (count, unit) = if age < MINUTE_MS {
+        return "just now".to_owned();
+    } else if age < HOUR_MS {
+        ((age / MINUTE_MS) as u64, "minute")
+    } else if age < DAY_MS {
+        ((age / HOUR_MS) as u64, "hour")
+    } else {
+        ((age / DAY_MS) as u64, "day")
+    };
+    if count == 1 {
+        format!("1 {} ago", unit)
+    } else {
+        format!("{} {}s ago", count, unit)
+    }
+}
+
+/// The oldest fetch time of the collections that are being shown from the
+/// LocalStore. Returns `None` if none of them are cached and `Some(None)` if
+/// we don't know when one of them was fetched.
+pub fn oldest_fetch(
+    cached_since: &BTreeMap<CachedCollection, Option<f64>>,
+    collections: &[CachedCollection],
+) -> Option<Option<f64>> {
+    let mut oldest = None;
+    for fetched_at in collections.iter().filter_map(|c| cached_since.get(c)) {
+        oldest = Some(match (oldest, fetched_at) {
+            (None, at) => *at,
+            (Some(Some(old)), Some(at)) => Some(f64::min(old, *at)),
+            _ => None,
+        });
+    }
+    oldest
+}
+
+#[derive(Props)]
+pub struct CachedDataProps<'ctx> {
+    sh: StateHandler<'ctx>,
+    collections: Vec<CachedCollection>,
+}
+
+/// Shows how old the data on a page is when it is being served from the
+/// LocalStore instead of the server.
+#[component]
+pub fn CachedDataBadge<'ctx, G: Html>(cx: Scope<'ctx>, props: CachedDataProps<'ctx>) -> View<G> {
+    let CachedDataProps { sh, collections } = props;
+    let cached = sh.get_selector(cx, move |state| {
+        oldest_fetch(&state.get().cached_since, &collections)
+    });
+    view! {cx,
+        (match *cached.get() {
+            None => View::empty(),
+            Some(fetched_at) => {
+                let msg = match fetched_at {
+                    Some(at) => format!("Offline: data from {}", describe_age(js_lib::get_ms_timestamp(), at)),
+                    None => "Offline: showing saved data".to_owned(),
+                };
+                view! {cx,
+                    span(class="no-print warning") { (msg) }
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_describe_age() {
+        let now = 10.0 * DAY_MS;
+        assert_eq!(describe_age(now, now - 1000.0), "just now");
+        assert_eq!(describe_age(now, now - MINUTE_MS), "1 minute ago");
+        assert_eq!(describe_age(now, now - 5.5 * HOUR_MS), "5 hours ago");
+        assert_eq!(describe_age(now, now - 3.0 * DAY_MS), "3 days ago");
+        assert_eq!(describe_age(now, now + DAY_MS), "just now");
+    }
+
+    #[test]
+    fn test_oldest_fetch() {
+        let cached_since = BTreeMap::from([
+            (CachedCollection::Recipes, Some(200.0)),
+            (CachedCollection::Plans, Some(100.0)),
+            (CachedCollection::Categories, None),
+        ]);
+        assert_eq!(
+            oldest_fetch(&cached_since, &[CachedCollection::Inventory]),
+            None
+        );
+        assert_eq!(
+            oldest_fetch(
+                &cached_since,
+                &[CachedCollection::Recipes, CachedCollection::Plans]
+            ),
+            Some(Some(100.0))
+        );
+        assert_eq!(
+            oldest_fetch(
+                &cached_since,
+                &[CachedCollection::Recipes, CachedCollection::Categories]
+            ),
+            Some(None)
+        );
+    }
+}
@@ -13,7 +13,12 @@
 // limitations under the License.
 use std::collections::{BTreeMap, BTreeSet};
 
-use crate::app_state::{Message, StateHandler};
+use crate::{
+    app_state::{Message, StateHandler},
+    routing, selectors,
+};
+use client_api::text;
+use recipes::unit::MeasureType;
 use sycamore::prelude::*;
 use tracing::instrument;
 
@@ -22,6 +27,7 @@ struct CategoryRowProps<'ctx> {
     sh: StateHandler<'ctx>,
     ingredient: String,
     category: String,
+    measure_type: Option<MeasureType>,
     ingredient_recipe_map: &'ctx ReadSignal<BTreeMap<String, BTreeSet<String>>>,
 }
 
@@ -32,9 +38,14 @@ fn CategoryRow<'ctx, G: Html>(cx: Scope<'ctx>, props: CategoryRowProps<'ctx>) ->
         sh,
         ingredient,
         category,
+        measure_type,
         ingredient_recipe_map,
     } = props;
     let category = create_signal(cx, category);
+    let measure_type = create_signal(
+        cx,
+        measure_type.map(|mt| mt.to_string()).unwrap_or_default(),
+    );
     let ingredient_clone = ingredient.clone();
     let ingredient_clone2 = ingredient.clone();
     let recipes = create_memo(cx, move || {
@@ -58,7 +69,7 @@ fn CategoryRow<'ctx, G: Html>(cx: Scope<'ctx>, props: CategoryRowProps<'ctx>) ->
                         let href = if recipe_name == "Staples" {
                             "/ui/manage/staples".to_owned()
                         } else {
-                            format!("/ui/recipe/edit/{}", r)
+                            routing::recipe_edit_route(&r)
                         };
                         view!{cx,
                             a(href=href) { (recipe_name) } br()
@@ -66,12 +77,26 @@ fn CategoryRow<'ctx, G: Html>(cx: Scope<'ctx>, props: CategoryRowProps<'ctx>) ->
                     }
                 )
             }
-            td() { input(type="text", list="category_options", bind:value=category, on:change={
+            td() { input(type="text", list="category_options", maxlength=text::MAX_NAME_CHARS, bind:value=category, on:change={
                 let ingredient_clone = ingredient.clone();
                 move |_| {
                     sh.dispatch(cx, Message::UpdateCategory(ingredient_clone.clone(), category.get_untracked().as_ref().clone(), None));
                 }
             }) }
+            td() {
+                select(bind:value=measure_type, on:change={
+                    let ingredient_clone = ingredient.clone();
+                    move |_| {
+                        let value = MeasureType::try_from(measure_type.get_untracked().as_str()).ok();
+                        sh.dispatch(cx, Message::UpdateMeasureType(ingredient_clone.clone(), value));
+                    }
+                }) {
+                    option(value="", selected=measure_type.get_untracked().is_empty()) { "As written" }
+                    option(value="Count", selected=*measure_type.get_untracked() == "Count") { "Count" }
+                    option(value="Volume", selected=*measure_type.get_untracked() == "Volume") { "Volume" }
+                    option(value="Weight", selected=*measure_type.get_untracked() == "Weight") { "Weight" }
+                }
+            }
         }
     }
 }
@@ -118,6 +143,7 @@ pub fn Categories<'ctx, G: Html>(cx: Scope<'ctx>, sh: StateHandler<'ctx>) -> Vie
     let rows = sh.get_selector(cx, |state| {
         let state = state.get();
         let category_map = state.category_map.clone();
+        let measure_types = state.measure_types.clone();
         let mut ingredients = BTreeSet::new();
         for (_, r) in state.recipes.iter() {
             for (_, i) in r.get_ingredients().iter() {
@@ -135,7 +161,7 @@ pub fn Categories<'ctx, G: Html>(cx: Scope<'ctx>, sh: StateHandler<'ctx>) -> Vie
                 .get(i)
                 .map(|v| v.clone())
                 .unwrap_or_else(|| "None".to_owned());
-            mapping_list.push((i.clone(), cat));
+            mapping_list.push((i.clone(), cat, measure_types.get(i).cloned()));
         }
         mapping_list.sort_by(|tpl1, tpl2| tpl1.1.cmp(&tpl2.1));
         mapping_list
@@ -145,13 +171,14 @@ pub fn Categories<'ctx, G: Html>(cx: Scope<'ctx>, sh: StateHandler<'ctx>) -> Vie
             tr {
                 th { "Ingredient" }
                 th { "Category" }
+                th { "Measure" }
             }
             Keyed(
                 iterable=rows,
-                view=move |cx, (i, c)| {
-                    view! {cx, CategoryRow(sh=sh, ingredient=i, category=c, ingredient_recipe_map=ingredient_recipe_map)}
+                view=move |cx, (i, c, mt)| {
+                    view! {cx, CategoryRow(sh=sh, ingredient=i, category=c, measure_type=mt, ingredient_recipe_map=ingredient_recipe_map)}
                 },
-                key=|(i, _)| i.clone()
+                key=|(i, _, _)| i.clone()
             )
         }
         datalist(id="category_options") {
@@ -167,3 +194,71 @@ pub fn Categories<'ctx, G: Html>(cx: Scope<'ctx>, sh: StateHandler<'ctx>) -> Vie
         }
     }
 }
+
+/// Move the category at `idx` one place earlier in the layout or one place
+/// later if `earlier` is false.
+fn move_category(mut layout: Vec<String>, idx: usize, earlier: bool) -> Vec<String> {
+    let other = if earlier {
+        idx.checked_sub(1)
+    } else {
+        Some(idx + 1)
+    };
+    if let Some(other) = other.filter(|other| *other < layout.len()) {
+        layout.swap(idx, other);
+    }
+    layout
+}
+
+/// Edit the order the shopping list shows categories in to match the aisles
+/// of a store.
+#[instrument(skip_all)]
+#[component]
+pub fn StoreLayout<'ctx, G: Html>(cx: Scope<'ctx>, sh: StateHandler<'ctx>) -> View<G> {
+    let layout = sh.get_selector(cx, |state| selectors::category_layout(state.get().as_ref()));
+    let rows = create_memo(cx, move || {
+        layout
+            .get()
+            .iter()
+            .cloned()
+            .enumerate()
+            .collect::<Vec<(usize, String)>>()
+    });
+    let move_to = move |idx: usize, earlier: bool| {
+        let layout = move_category(layout.get_untracked().as_ref().clone(), idx, earlier);
+        sh.dispatch(cx, Message::UpdateCategoryOrder(layout));
+    };
+    view! {cx,
+        h2 { "Store Layout" }
+        ol(class="store-layout") {
+            Keyed(
+                iterable=rows,
+                view=move |cx, (idx, category)| {
+                    view! {cx,
+                        li {
+                            (category) " "
+                            button(aria-label="Move earlier", on:click=move |_| move_to(idx, true)) { "↑" }
+                            button(aria-label="Move later", on:click=move |_| move_to(idx, false)) { "↓" }
+                        }
+                    }
+                },
+                key=|row| row.clone(),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_move_category() {
+        let layout = vec!["produce".to_owned(), "dairy".to_owned()];
+        assert_eq!(
+            move_category(layout.clone(), 1, true),
+            vec!["dairy".to_owned(), "produce".to_owned()]
+        );
+        assert_eq!(move_category(layout.clone(), 0, true), layout);
+        assert_eq!(move_category(layout.clone(), 1, false), layout);
+    }
+}
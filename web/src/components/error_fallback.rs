@@ -0,0 +1,118 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::any::Any;
+
+use tracing::error;
+
+use crate::js_lib;
+
+const FALLBACK_CLASS: &str = "error-fallback";
+const RELOAD_LABEL: &str = "Reload";
+
+/// The message a panic was raised with.
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        (*msg).to_owned()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "Unknown error".to_owned()
+    }
+}
+
+/// Tells the user something went wrong and offers to reload the page.
+///
+/// This is called from the panic hook. Panics abort in wasm so nothing can
+/// catch a render failure and recover from it. This builds the fallback with
+/// the DOM directly since the reactive runtime isn't usable anymore.
+pub fn show_panic_fallback(message: &str) {
+    let document = match js_lib::get_window().document() {
+        Some(document) => document,
+        None => return,
+    };
+    let build = || -> Result<(), wasm_bindgen::JsValue> {
+        let fallback = document.create_element("div")?;
+        fallback.set_class_name(FALLBACK_CLASS);
+        fallback.set_attribute("role", "alert")?;
+        let text = document.create_element("p")?;
+        text.set_text_content(Some(&format!("Something went wrong: {}", message)));
+        let button = document.create_element("button")?;
+        button.set_text_content(Some(RELOAD_LABEL));
+        button.set_attribute("onclick", "window.location.reload()")?;
+        fallback.append_child(&text)?;
+        fallback.append_child(&button)?;
+        if let Some(body) = document.body() {
+            body.prepend_with_node_1(&fallback)?;
+        }
+        Ok(())
+    };
+    if let Err(err) = build() {
+        error!(?err, "Failed to show the error fallback");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failing_child(id: &str) -> String {
+        panic!("Failed to find recipe {}", id);
+    }
+
+    #[test]
+    fn test_panic_message_is_the_panic_payload() {
+        let payload = std::panic::catch_unwind(|| failing_child("soup")).unwrap_err();
+        assert_eq!(
+            panic_message(payload.as_ref()),
+            "Failed to find recipe soup"
+        );
+        let payload = std::panic::catch_unwind(|| panic!("boom")).unwrap_err();
+        assert_eq!(panic_message(payload.as_ref()), "boom");
+        assert_eq!(panic_message(&42), "Unknown error");
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod browser_tests {
+    use wasm_bindgen_test::*;
+
+    use super::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    fn test_panic_fallback_offers_a_reload() {
+        show_panic_fallback("boom");
+        let document = js_lib::get_window().document().expect("No document");
+        let fallback = document
+            .query_selector(&format!(".{}", FALLBACK_CLASS))
+            .unwrap()
+            .expect("No error fallback was shown");
+        assert_eq!(fallback.get_attribute("role").as_deref(), Some("alert"));
+        let text = fallback.query_selector("p").unwrap().expect("No message");
+        assert_eq!(
+            text.text_content().as_deref(),
+            Some("Something went wrong: boom")
+        );
+        let button = fallback
+            .query_selector("button")
+            .unwrap()
+            .expect("No reload button");
+        assert_eq!(button.text_content().as_deref(), Some(RELOAD_LABEL));
+        assert_eq!(
+            button.get_attribute("onclick").as_deref(),
+            Some("window.location.reload()")
+        );
+    }
+}
@@ -0,0 +1,257 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! One search box for recipes, meal plans, and ingredients.
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::NaiveDate;
+use recipes::Recipe;
+use sycamore::{futures::spawn_local_scoped, prelude::*};
+
+use crate::{
+    api::LocalStore,
+    app_state::{Message, StateHandler},
+    routing,
+    search_index::SearchIndex,
+};
+
+/// The most results shown for each category.
+pub const MAX_RESULTS: usize = 5;
+
+/// Search results grouped by what they point to.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchResults {
+    /// Matching recipes as `(recipe_id, title)` sorted by title.
+    pub recipes: Vec<(String, String)>,
+    /// Matching meal plan dates, newest first.
+    pub plans: Vec<NaiveDate>,
+    /// Matching ingredient names and the `(recipe_id, title)` of the recipes
+    /// that use them.
+    pub ingredients: Vec<(String, Vec<(String, String)>)>,
+}
+
+impl SearchResults {
+    pub fn is_empty(&self) -> bool {
+        self.recipes.is_empty() && self.plans.is_empty() && self.ingredients.is_empty()
+    }
+}
+
+/// Categorize everything that matches the query.
+///
+/// `matches` are the recipe ids the search index found for the query. Recipes
+/// whose title contains the query match too so new recipes show up before the
+/// index has been rebuilt.
+pub fn search_everything(
+    query: &str,
+    matches: Option<&BTreeSet<String>>,
+    recipes: &BTreeMap<String, Recipe>,
+    plan_dates: &BTreeSet<NaiveDate>,
+) -> SearchResults {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return SearchResults::default();
+    }
+    let mut found_recipes = recipes
+        .iter()
+        .filter(|(id, r)| {
+            matches.map(|ids| ids.contains(*id)).unwrap_or(false)
+                || r.title.to_lowercase().contains(&query)
+        })
+        .map(|(id, r)| (id.clone(), r.title.clone()))
+        .collect::<Vec<(String, String)>>();
+    found_recipes.sort_by(|a, b| a.1.cmp(&b.1));
+    found_recipes.truncate(MAX_RESULTS);
+
+    let plans = plan_dates
+        .iter()
+        .rev()
+        .filter(|d| format!("{}", d).contains(&query))
+        .take(MAX_RESULTS)
+        .cloned()
+        .collect();
+
+    let mut ingredients: BTreeMap<String, BTreeSet<(String, String)>> = BTreeMap::new();
+    for (id, recipe) in recipes.iter() {
+        for i in recipe.steps.iter().flat_map(|s| s.ingredients.iter()) {
+            let name = i.name.to_lowercase();
+            if name.contains(&query) {
+                ingredients
+                    .entry(name)
+                    .or_default()
+                    .insert((id.clone(), recipe.title.clone()));
+            }
+        }
+    }
+    let ingredients = ingredients
+        .into_iter()
+        .take(MAX_RESULTS)
+        .map(|(name, used_in)| (name, used_in.into_iter().collect()))
+        .collect();
+
+    SearchResults {
+        recipes: found_recipes,
+        plans,
+        ingredients,
+    }
+}
+
+fn recipe_link<G: Html>(cx: Scope, id: String, title: String) -> View<G> {
+    view! {cx, a(href=routing::recipe_view_route(&id)) { (title) } }
+}
+
+/// A search box for the header that shows categorized results as you type.
+#[component]
+pub fn GlobalSearch<'ctx, G: Html>(cx: Scope<'ctx>, sh: StateHandler<'ctx>) -> View<G> {
+    let query = create_signal(cx, String::new());
+    let search_index = create_signal(cx, SearchIndex::new());
+    // NOTE(jwall): Recipes can change while the header is mounted so the
+    // index is refreshed each time the search box gets focus.
+    let refresh_index = move |_| {
+        spawn_local_scoped(cx, async move {
+            search_index.set(LocalStore::new().get_search_index().await);
+        });
+    };
+    let recipes = sh.get_selector(cx, |state| state.get().recipes.clone());
+    let plan_dates = sh.get_selector(cx, |state| state.get().plan_dates.clone());
+    let results = create_memo(cx, move || {
+        let query = query.get();
+        search_everything(
+            query.as_str(),
+            search_index.get().search(query.as_str()).as_ref(),
+            recipes.get().as_ref(),
+            plan_dates.get().as_ref(),
+        )
+    });
+    let clear = move |_| query.set(String::new());
+    view! {cx,
+        div(class="global-search") {
+            input(type="search", placeholder="Search", aria-label="Search recipes, plans, and ingredients", bind:value=query, on:focus=refresh_index)
+            (if query.get().trim().is_empty() {
+                View::empty()
+            } else if results.get().is_empty() {
+                view! {cx, div(class="search-results") { "No matches" } }
+            } else {
+                let SearchResults { recipes, plans, ingredients } = results.get().as_ref().clone();
+                let recipes = View::new_fragment(recipes.into_iter().map(|(id, title)| {
+                    view! {cx, li(on:click=clear) { (recipe_link(cx, id, title)) } }
+                }).collect());
+                let plans = View::new_fragment(plans.into_iter().map(|date| {
+                    view! {cx,
+                        li {
+                            button(class="outline", on:click=move |_| {
+                                query.set(String::new());
+                                sh.dispatch(cx, Message::SelectPlanDate(date, Some(Box::new(|| {
+                                    sycamore_router::navigate("/ui/planning/plan")
+                                }))));
+                            }) { (format!("{}", date)) }
+                        }
+                    }
+                }).collect());
+                let ingredients = View::new_fragment(ingredients.into_iter().map(|(name, used_in)| {
+                    let links = View::new_fragment(used_in.into_iter().map(|(id, title)| {
+                        view! {cx, " " (recipe_link(cx, id, title)) }
+                    }).collect());
+                    view! {cx, li(on:click=clear) { (name) ":" (links) } }
+                }).collect());
+                view! {cx,
+                    div(class="search-results") {
+                        h4 { "Recipes" }
+                        ul(class="no-list") { (recipes) }
+                        h4 { "Plans" }
+                        ul(class="no-list") { (plans) }
+                        h4 { "Ingredients" }
+                        ul(class="no-list") { (ingredients) }
+                    }
+                }
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use recipes::{unit::Measure, Ingredient, Step};
+
+    fn recipe(title: &str, ingredients: &[&str]) -> Recipe {
+        Recipe::new(title, None).with_steps(vec![Step::new(None, "Cook it.").with_ingredients(
+            ingredients
+                .iter()
+                .map(|n| Ingredient::new(*n, None, Measure::count(1))),
+        )])
+    }
+
+    fn fixtures() -> (BTreeMap<String, Recipe>, BTreeSet<NaiveDate>) {
+        let recipes = BTreeMap::from([
+            ("soup".to_owned(), recipe("Onion Soup", &["onion", "broth"])),
+            (
+                "salad".to_owned(),
+                recipe("Salad", &["red onion", "lettuce"]),
+            ),
+            ("bread".to_owned(), recipe("Bread", &["flour"])),
+        ]);
+        let dates = BTreeSet::from([
+            NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2023, 5, 6).unwrap(),
+            NaiveDate::from_ymd_opt(2023, 5, 13).unwrap(),
+        ]);
+        (recipes, dates)
+    }
+
+    #[test]
+    fn test_search_categorizes_results() {
+        let (recipes, dates) = fixtures();
+        let results = search_everything("onion", None, &recipes, &dates);
+        assert_eq!(
+            results.recipes,
+            vec![("soup".to_owned(), "Onion Soup".to_owned())]
+        );
+        assert!(results.plans.is_empty());
+        assert_eq!(
+            results.ingredients,
+            vec![
+                (
+                    "onion".to_owned(),
+                    vec![("soup".to_owned(), "Onion Soup".to_owned())]
+                ),
+                (
+                    "red onion".to_owned(),
+                    vec![("salad".to_owned(), "Salad".to_owned())]
+                ),
+            ]
+        );
+
+        let results = search_everything("2023-05", None, &recipes, &dates);
+        assert_eq!(
+            results.plans,
+            vec![
+                NaiveDate::from_ymd_opt(2023, 5, 13).unwrap(),
+                NaiveDate::from_ymd_opt(2023, 5, 6).unwrap(),
+            ]
+        );
+        assert!(results.recipes.is_empty());
+        assert!(results.ingredients.is_empty());
+    }
+
+    #[test]
+    fn test_search_uses_index_matches() {
+        let (recipes, dates) = fixtures();
+        let matches = BTreeSet::from(["bread".to_owned()]);
+        let results = search_everything("knead", Some(&matches), &recipes, &dates);
+        assert_eq!(
+            results.recipes,
+            vec![("bread".to_owned(), "Bread".to_owned())]
+        );
+        assert!(search_everything("  ", Some(&matches), &recipes, &dates).is_empty());
+    }
+}
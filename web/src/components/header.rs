@@ -14,7 +14,8 @@
 
 use sycamore::prelude::*;
 
-use crate::app_state::StateHandler;
+use crate::app_state::{Message, StateHandler};
+use crate::components::global_search::GlobalSearch;
 
 #[component]
 pub fn Header<'ctx, G: Html>(cx: Scope<'ctx>, h: StateHandler<'ctx>) -> View<G> {
@@ -22,13 +23,23 @@ pub fn Header<'ctx, G: Html>(cx: Scope<'ctx>, h: StateHandler<'ctx>) -> View<G>
         Some(id) => id.user_id.clone(),
         None => "Login".to_owned(),
     });
+    let logged_in = h.get_selector(cx, |sig| sig.get().auth.is_some());
     view! {cx,
         nav(class="no-print row-flex align-center header-bg heavy-bottom-border menu-font") {
             h1(class="title") { "Kitchen" }
+            GlobalSearch(h)
             ul(class="row-flex align-center no-list") {
+                li { a(href="/ui/home") { "Home" } }
                 li { a(href="/ui/planning/select") { "MealPlan" } }
                 li { a(href="/ui/manage/ingredients") { "Manage" } }
                 li { a(href="/ui/login") { (login.get()) } }
+                (if *logged_in.get() {
+                    view! {cx,
+                        li { button(on:click=move |_| h.dispatch(cx, Message::Logout)) { "Logout" } }
+                    }
+                } else {
+                    View::empty()
+                })
             }
         }
     }
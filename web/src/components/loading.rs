@@ -0,0 +1,65 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use sycamore::prelude::*;
+
+/// The status of the data a page is waiting on.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Loadable<T> {
+    Loading,
+    Ready(T),
+    Failed(String),
+}
+
+impl<T> Loadable<T> {
+    pub fn is_loading(&self) -> bool {
+        matches!(self, Loadable::Loading)
+    }
+}
+
+/// Placeholder lines shown in place of content that hasn't loaded yet.
+#[component]
+pub fn Skeleton<G: Html>(cx: Scope, lines: usize) -> View<G> {
+    let lines = View::new_fragment(
+        (0..lines)
+            .map(|_| view! {cx, div(class="skeleton-line") })
+            .collect(),
+    );
+    view! {cx,
+        div(class="skeleton", aria-busy="true", aria-label="Loading") { (lines) }
+    }
+}
+
+#[derive(Props)]
+pub struct LoadingProps<'ctx, G: Html> {
+    status: &'ctx ReadSignal<Loadable<()>>,
+    children: Children<'ctx, G>,
+}
+
+/// Shows a `Skeleton` until `status` is ready and then the children. If
+/// loading failed the reason is shown instead.
+#[component]
+pub fn Loading<'ctx, G: Html>(cx: Scope<'ctx>, props: LoadingProps<'ctx, G>) -> View<G> {
+    let LoadingProps { status, children } = props;
+    let children = children.call(cx);
+    view! {cx,
+        (match status.get().as_ref() {
+            Loadable::Loading => view! {cx, Skeleton(3) },
+            Loadable::Failed(msg) => {
+                let msg = msg.clone();
+                view! {cx, div(class="parse", role="alert") { (msg) } }
+            }
+            Loadable::Ready(()) => children.clone(),
+        })
+    }
+}
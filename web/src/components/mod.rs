@@ -12,9 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 pub mod add_recipe;
+pub mod cached_data;
 pub mod categories;
+pub mod error_fallback;
 pub mod footer;
+pub mod global_search;
 pub mod header;
+pub mod loading;
 pub mod number_field;
 pub mod plan_list;
 pub mod recipe;
@@ -23,7 +27,10 @@ pub mod recipe_plan;
 pub mod recipe_selection;
 pub mod shopping_list;
 pub mod staples;
+pub mod step_timer;
+pub mod structured_editor;
 pub mod tabs;
+pub mod toast;
 
 pub use header::*;
 pub use number_field::*;
@@ -11,11 +11,74 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+use std::collections::{BTreeMap, BTreeSet};
+
 use chrono::NaiveDate;
-use sycamore::prelude::*;
+use client_api::PlanSummary;
+use sycamore::{futures::spawn_local_scoped, prelude::*};
+use tracing::{error, instrument};
+use wasm_bindgen::JsCast;
+use web_sys::{Element, Event};
 
 use crate::app_state::{Message, StateHandler};
-use tracing::instrument;
+
+/// How many plans are shown and summarized at a time.
+const PAGE_SIZE: usize = 10;
+/// How close to the bottom of the list in pixels counts as the bottom.
+const SCROLL_THRESHOLD: i32 = 40;
+
+/// The range of dates to fetch summaries for so that the first `visible` of
+/// the newest first `dates` are all covered. Returns `None` when nothing is
+/// missing.
+fn summary_window(
+    dates: &[NaiveDate],
+    visible: usize,
+    have: &BTreeSet<NaiveDate>,
+) -> Option<(NaiveDate, NaiveDate)> {
+    let missing = dates
+        .iter()
+        .take(visible)
+        .filter(|d| !have.contains(d))
+        .collect::<Vec<&NaiveDate>>();
+    let start = missing.iter().min()?;
+    let end = missing.iter().max()?;
+    Some((**start, **end))
+}
+
+/// Whether the list has been scrolled to within `threshold` of the bottom.
+fn near_bottom(scroll_top: i32, client_height: i32, scroll_height: i32, threshold: i32) -> bool {
+    scroll_top + client_height + threshold >= scroll_height
+}
+
+fn scrolled_to_bottom(evt: &Event) -> bool {
+    evt.target()
+        .and_then(|t| t.dyn_into::<Element>().ok())
+        .map(|el| {
+            near_bottom(
+                el.scroll_top(),
+                el.client_height(),
+                el.scroll_height(),
+                SCROLL_THRESHOLD,
+            )
+        })
+        .unwrap_or(false)
+}
+
+fn summary_display(summary: Option<&PlanSummary>) -> String {
+    match summary {
+        Some(summary) => format!(
+            "{} recipes, {} servings{}",
+            summary.recipe_count,
+            summary.servings,
+            if summary.has_inventory {
+                ", shopping started"
+            } else {
+                ""
+            }
+        ),
+        None => "...".to_owned(),
+    }
+}
 
 #[derive(Props)]
 pub struct PlanListProps<'ctx> {
@@ -23,22 +86,79 @@ pub struct PlanListProps<'ctx> {
     list: &'ctx ReadSignal<Vec<NaiveDate>>,
 }
 
+/// Lists the saved plans newest first. Summaries of the plans are fetched a
+/// page at a time as the list is scrolled.
 #[instrument(skip_all, fields(dates=?props.list))]
 #[component]
 pub fn PlanList<'ctx, G: Html>(cx: Scope<'ctx>, props: PlanListProps<'ctx>) -> View<G> {
     let PlanListProps { sh, list } = props;
+    let store = crate::api::HttpStore::get_from_context(cx);
+    let visible = create_signal(cx, PAGE_SIZE);
+    let summaries = create_signal(cx, BTreeMap::<NaiveDate, PlanSummary>::new());
+    let requested = create_signal(cx, BTreeSet::<NaiveDate>::new());
+    create_effect(cx, move || {
+        let window = summary_window(
+            list.get().as_ref(),
+            *visible.get(),
+            requested.get_untracked().as_ref(),
+        );
+        if let Some((start, end)) = window {
+            requested.modify().extend(
+                list.get_untracked()
+                    .iter()
+                    .filter(|d| **d >= start && **d <= end),
+            );
+            let store = store.clone();
+            spawn_local_scoped(cx, async move {
+                match store.fetch_plan_summaries(&start, &end).await {
+                    Ok(fetched) => {
+                        let mut summaries = summaries.modify();
+                        for summary in fetched {
+                            summaries.insert(summary.date, summary);
+                        }
+                    }
+                    Err(err) => {
+                        error!(?err, "Failed to fetch plan summaries");
+                        // NOTE(jwall): Forget the request so the next scroll
+                        // or plan list change tries these dates again.
+                        requested.modify().retain(|d| *d < start || *d > end);
+                    }
+                }
+            });
+        }
+    });
+    let more = move || {
+        let len = list.get_untracked().len();
+        if *visible.get_untracked() < len {
+            visible.set((*visible.get_untracked() + PAGE_SIZE).min(len));
+        }
+    };
+    let rows = create_memo(cx, move || {
+        let summaries = summaries.get();
+        list.get()
+            .iter()
+            .take(*visible.get())
+            .map(|date| (*date, summary_display(summaries.get(date))))
+            .collect::<Vec<(NaiveDate, String)>>()
+    });
+    let has_more = create_memo(cx, move || *visible.get() < list.get().len());
     view! {cx,
         div() {
-            div(class="column-flex") {
+            div(class="column-flex plan-list", on:scroll=move |evt: Event| {
+                if scrolled_to_bottom(&evt) {
+                    more();
+                }
+            }) {
                 Indexed(
-                    iterable=list,
-                    view=move |cx, date| {
+                    iterable=rows,
+                    view=move |cx, (date, summary)| {
                         let date_display = format!("{}", date);
                         view!{cx,
                             div(class="row-flex margin-bot-half") {
                                 button(class="outline margin-right-1", on:click=move |_| {
                                     sh.dispatch(cx, Message::SelectPlanDate(date, None))
                                 }) { (date_display) }
+                                span(class="margin-right-1") { (summary) }
                                 button(class="destructive", on:click=move |_| {
                                     sh.dispatch(cx, Message::DeletePlan(date, None))
                                 }) { "Delete Plan" }
@@ -47,6 +167,45 @@ pub fn PlanList<'ctx, G: Html>(cx: Scope<'ctx>, props: PlanListProps<'ctx>) -> V
                     },
                 )
             }
+            (if *has_more.get() {
+                view! {cx, button(class="outline", on:click=move |_| more()) { "Show More" } }
+            } else {
+                View::empty()
+            })
+            a(href="/ui/planning/compare") { "Compare Plans" }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, d).unwrap()
+    }
+
+    #[test]
+    fn test_summary_window_covers_missing_visible_dates() {
+        let dates = vec![day(20), day(18), day(15), day(10), day(3)];
+        assert_eq!(
+            summary_window(&dates, 2, &BTreeSet::new()),
+            Some((day(18), day(20)))
+        );
+        let have = BTreeSet::from([day(20), day(18)]);
+        assert_eq!(summary_window(&dates, 2, &have), None);
+        assert_eq!(summary_window(&dates, 4, &have), Some((day(10), day(15))));
+        assert_eq!(summary_window(&dates, 10, &have), Some((day(3), day(15))));
+        // A plan added since the last fetch is picked up too.
+        let dates = vec![day(25), day(20), day(18)];
+        assert_eq!(summary_window(&dates, 3, &have), Some((day(25), day(25))));
+    }
+
+    #[test]
+    fn test_near_bottom() {
+        assert!(near_bottom(560, 400, 1000, 40));
+        assert!(!near_bottom(500, 400, 1000, 40));
+        // A list that doesn't scroll is always at the bottom.
+        assert!(near_bottom(0, 400, 300, 40));
+    }
+}
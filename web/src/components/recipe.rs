@@ -16,9 +16,16 @@ use tracing::{debug, error};
 
 use crate::{
     app_state::{Message, StateHandler},
+    components::{
+        loading::{Loadable, Loading},
+        step_timer::StepTimer,
+        structured_editor::StructuredEditor,
+    },
     js_lib,
+    routing::{self, set_unsaved_changes},
+    selectors,
 };
-use recipes::{self, RecipeEntry};
+use recipes::{self, serialize::as_duration_text, RecipeEntry};
 
 fn check_recipe_parses(
     text: &str,
@@ -61,22 +68,32 @@ pub fn Editor<'ctx, G: Html>(cx: Scope<'ctx>, props: RecipeComponentProps<'ctx>)
     let error_text = create_signal(cx, String::from("Parse results..."));
     let aria_hint = create_signal(cx, "false");
     let category = create_signal(cx, "Entree".to_owned());
+    let structured = sh.get_selector(cx, |state| state.get().structured_editor);
+
+    let status: &Signal<Loadable<()>> = create_signal(cx, Loadable::Loading);
 
     spawn_local_scoped(cx, {
         let store = store.clone();
         async move {
-            let entry = store
-                .fetch_recipe_text(recipe_id.as_str())
-                .await
-                .expect("Failure getting recipe");
-            if let Some(entry) = entry {
-                text.set(entry.recipe_text().to_owned());
-                if let Some(cat) = entry.category() {
-                    category.set(cat.clone());
+            match store.fetch_recipe_text(recipe_id.as_str()).await {
+                Ok(Some(entry)) => {
+                    text.set(entry.recipe_text().to_owned());
+                    if let Some(cat) = entry.category() {
+                        category.set(cat.clone());
+                    }
+                    recipe.set(entry);
+                    status.set(Loadable::Ready(()));
+                }
+                Ok(None) => {
+                    status.set(Loadable::Failed(format!(
+                        "Unable to find recipe {}",
+                        recipe_id
+                    )));
+                }
+                Err(err) => {
+                    error!(?err, "Failure getting recipe");
+                    status.set(Loadable::Failed("Failed to load recipe".to_owned()));
                 }
-                recipe.set(entry);
-            } else {
-                error_text.set("Unable to find recipe".to_owned());
             }
         }
     });
@@ -84,83 +101,164 @@ pub fn Editor<'ctx, G: Html>(cx: Scope<'ctx>, props: RecipeComponentProps<'ctx>)
     let id = create_memo(cx, || recipe.get().recipe_id().to_owned());
     let dirty = create_signal(cx, false);
     let ts = create_signal(cx, js_lib::get_ms_timestamp());
+    create_effect(cx, || set_unsaved_changes(*dirty.get()));
+    on_cleanup(cx, || set_unsaved_changes(false));
 
     debug!("creating editor view");
     view! {cx,
-        div {
-            label(for="recipe_category") { "Category" }
-            input(name="recipe_category", bind:value=category, on:change=move |_| dirty.set(true))
-        }
-        div {
-            label(for="serving_count") { "Serving Count" }
-            input(name="serving_count", bind:value=serving_count_str, on:change=move |_| dirty.set(true))
-        }
-        div {
-            div(class="row-flex") {
-                label(for="recipe_text", class="block align-stretch expand-height") { "Recipe: " }
-                textarea(class="width-third", name="recipe_text", bind:value=text, aria-invalid=aria_hint.get(), cols="50", rows=20, on:change=move |_| {
-                    dirty.set(true);
-                    check_recipe_parses(text.get_untracked().as_str(), error_text, aria_hint);
-                }, on:input=move |_| {
-                    let current_ts = js_lib::get_ms_timestamp();
-                    if (current_ts - *ts.get_untracked()) > 100 {
-                        check_recipe_parses(text.get_untracked().as_str(), error_text, aria_hint);
-                        ts.set(current_ts);
+        Loading(status=status) {
+            div {
+                label(for="recipe_category") { "Category" }
+                input(name="recipe_category", bind:value=category, on:change=move |_| dirty.set(true))
+            }
+            div {
+                label(for="serving_count") { "Serving Count" }
+                input(name="serving_count", bind:value=serving_count_str, on:change=move |_| dirty.set(true))
+            }
+            div {
+                label(for="structured_editor_cb") { "Structured editor" }
+                input(id="structured_editor_cb", type="checkbox", checked=*structured.get(), on:change=move |_| {
+                    let value = !*structured.get_untracked();
+                    sh.dispatch(cx, Message::UpdateStructuredEditor(value));
+                })
+            }
+            div {
+                (if *structured.get() {
+                    // NOTE(jwall): The structured editor is built from the recipe
+                    // text so we need to rebuild it once the recipe has loaded.
+                    recipe.track();
+                    view! {cx,
+                        StructuredEditor(text=text, dirty=dirty, error_text=error_text)
+                    }
+                } else {
+                    view! {cx,
+                        div(class="row-flex") {
+                            label(for="recipe_text", class="block align-stretch expand-height") { "Recipe: " }
+                            textarea(class="width-third", name="recipe_text", bind:value=text, aria-invalid=aria_hint.get(), cols="50", rows=20, on:change=move |_| {
+                                dirty.set(true);
+                                check_recipe_parses(text.get_untracked().as_str(), error_text, aria_hint);
+                            }, on:input=move |_| {
+                                let current_ts = js_lib::get_ms_timestamp();
+                                if (current_ts - *ts.get_untracked()) > 100.0 {
+                                    check_recipe_parses(text.get_untracked().as_str(), error_text, aria_hint);
+                                    ts.set(current_ts);
+                                }
+                            })
+                        }
                     }
                 })
+                div(class="parse") { (error_text.get()) }
             }
-            div(class="parse") { (error_text.get()) }
-        }
-        div {
-            button(on:click=move |_| {
-                let unparsed = text.get_untracked();
-                if check_recipe_parses(unparsed.as_str(), error_text, aria_hint) {
-                    debug!("triggering a save");
-                    if !*dirty.get_untracked() {
-                        debug!("Recipe text is unchanged");
-                        return;
+            div {
+                button(on:click=move |_| {
+                    let unparsed = text.get_untracked();
+                    if check_recipe_parses(unparsed.as_str(), error_text, aria_hint) {
+                        debug!("triggering a save");
+                        if !*dirty.get_untracked() {
+                            debug!("Recipe text is unchanged");
+                            return;
+                        }
+                        debug!("Recipe text is changed");
+                        let store = store.clone();
+                        spawn_local_scoped(cx, async move {
+                            // NOTE(jwall): The server validates with the same
+                            // parser as it saves with. If we can't reach it we
+                            // go with our own check.
+                            match store.validate_recipe(unparsed.as_str()).await {
+                                Ok(Some(err)) => {
+                                    error_text.set(format!("{}", err));
+                                    aria_hint.set("true");
+                                    return;
+                                }
+                                Ok(None) => (),
+                                Err(err) => error!(?err, "Failed to validate recipe"),
+                            }
+                            let category = category.get_untracked();
+                            let category = if category.is_empty() {
+                                None
+                            } else {
+                                Some(category.as_ref().clone())
+                            };
+                            let tags = recipes::parse::as_recipe(unparsed.as_str())
+                                .map(|recipe| recipe.tags)
+                                .unwrap_or_default();
+                            let recipe_entry = RecipeEntry {
+                                            id: id.get_untracked().as_ref().clone(),
+                                            text: unparsed.as_ref().clone(),
+                                            category,
+                                            serving_count: Some(*serving_count.get_untracked()),
+                                            tags,
+                            };
+                            sh.dispatch(cx, Message::SaveRecipe(recipe_entry, None));
+                            dirty.set(false);
+                        });
                     }
-                    debug!("Recipe text is changed");
-                    let category = category.get_untracked();
-                    let category = if category.is_empty() {
-                        None
-                    } else {
-                        Some(category.as_ref().clone())
-                    };
-                    let recipe_entry = RecipeEntry {
-                                    id: id.get_untracked().as_ref().clone(),
-                                    text: text.get_untracked().as_ref().clone(),
-                                    category,
-                                    serving_count: Some(*serving_count.get()),
-                    };
-                    sh.dispatch(cx, Message::SaveRecipe(recipe_entry, None));
-                    dirty.set(false);
-                }
-                // TODO(jwall): Show error message if trying to save when recipe doesn't parse.
-            }) { "Save" } " "
-            button(on:click=move |_| {
-                sh.dispatch(cx, Message::RemoveRecipe(id.get_untracked().as_ref().to_owned(), Some(Box::new(|| sycamore_router::navigate("/ui/planning/plan")))));
-            }) { "delete" } " "
+                }) { "Save" } " "
+                button(on:click=move |_| {
+                    let confirmed = js_lib::get_window()
+                        .confirm_with_message("Delete this recipe? It will also be removed from any saved meal plans.")
+                        .unwrap_or(false);
+                    if confirmed {
+                        sh.dispatch(cx, Message::RemoveRecipe(id.get_untracked().as_ref().to_owned(), Some(Box::new(|| sycamore_router::navigate("/ui/planning/plan")))));
+                    }
+                }) { "delete" } " "
+            }
         }
     }
 }
 
+#[derive(Props)]
+struct StepsProps {
+    steps: Vec<recipes::Step>,
+    /// Show a countdown timer for the steps that have a prep time.
+    timers: bool,
+}
+
 #[component]
-fn Steps<G: Html>(cx: Scope, steps: Vec<recipes::Step>) -> View<G> {
+fn Steps<G: Html>(cx: Scope, props: StepsProps) -> View<G> {
+    let StepsProps { steps, timers } = props;
     let step_fragments = View::new_fragment(steps.iter().enumerate().map(|(idx, step)| {
         let mut step = step.clone();
+        // NOTE(jwall): We only show a section heading where the section starts.
+        let section = step
+            .section
+            .clone()
+            .filter(|s| idx == 0 || steps[idx - 1].section.as_ref() != Some(s));
         let ingredient_fragments = View::new_fragment(step.ingredients.drain(0..).map(|i| {
+            let divided = if i.divided {
+                view! {cx, " " span(class="divided", title="Only part of this is used in this step") { "(divided)" } }
+            } else {
+                View::empty()
+            };
             view! {cx,
                 li {
-                    (i.amt) " " (i.name) " " (i.form.as_ref().map(|f| format!("({})", f)).unwrap_or(String::new()))
+                    (i.amt.describe_plural(i.name_for_display())) " " (i.form.as_ref().map(|f| format!("({})", f)).unwrap_or(String::new()))
+                    (divided)
                 }
             }
         }).collect());
+        let sub_recipes = View::new_fragment(step.sub_recipes.drain(0..).map(|id| {
+            view! {cx,
+                li { "See " a(href=routing::recipe_view_route(&id)) { (id) } }
+            }
+        }).collect());
+        let timer = match step.prep_time {
+            Some(duration) if timers && !duration.is_zero() => {
+                view! {cx, StepTimer(label=format!("Step {}", idx + 1), duration=duration) }
+            }
+            _ => View::empty(),
+        };
         view! {cx,
+            (match section.clone() {
+                Some(section) => view! {cx, h2(class="recipe_section") { (section) } },
+                None => View::empty(),
+            })
             div {
                 h3 { "Step " (idx + 1) }
+                (timer)
                 ul(class="ingredients no-list") {
                     (ingredient_fragments)
+                    (sub_recipes)
                 }
                 div(class="instructions") {
                     (step.instructions)
@@ -176,35 +274,64 @@ fn Steps<G: Html>(cx: Scope, steps: Vec<recipes::Step>) -> View<G> {
     }
 }
 
+#[derive(Props)]
+pub struct ViewerProps<'ctx> {
+    recipe_id: String,
+    sh: StateHandler<'ctx>,
+    /// Show countdown timers for the steps while cooking.
+    timers: bool,
+}
+
 #[component]
-pub fn Viewer<'ctx, G: Html>(cx: Scope<'ctx>, props: RecipeComponentProps<'ctx>) -> View<G> {
-    let RecipeComponentProps { recipe_id, sh } = props;
-    let view = create_signal(cx, View::empty());
+pub fn Viewer<'ctx, G: Html>(cx: Scope<'ctx>, props: ViewerProps<'ctx>) -> View<G> {
+    let ViewerProps {
+        recipe_id,
+        sh,
+        timers,
+    } = props;
+    let status_id = recipe_id.clone();
     let recipe_signal = sh.get_selector(cx, move |state| {
         if let Some(recipe) = state.get().recipes.get(&recipe_id) {
             let title = recipe.title.clone();
             let serving_count = recipe.serving_count.clone();
             let desc = recipe.desc.clone().unwrap_or_else(|| String::new());
+            let prep_time = recipe.total_prep_time();
             let steps = recipe.steps.clone();
-            Some((title, serving_count, desc, steps))
+            Some((title, serving_count, desc, prep_time, steps))
         } else {
             None
         }
     });
-    if let Some((title, serving_count, desc, steps)) = recipe_signal.get().as_ref().clone() {
-        debug!("Viewing recipe.");
-        view.set(view! {cx,
-            div(class="recipe") {
-                h1(class="recipe_title") { (title) }
-                 div(class="serving_count") {
-                     "Serving Count: " (serving_count.map(|v| format!("{}", v)).unwrap_or_else(|| "Unconfigured".to_owned()))
-                 }
-                 div(class="recipe_description") {
-                     (desc)
-                 }
-                Steps(steps)
-            }
-        });
+    let status = sh.get_selector(cx, move |state| {
+        selectors::recipe_status(state.get().as_ref(), &status_id)
+    });
+    // NOTE(jwall): The recipe may not have loaded yet when the viewer is
+    // created so we render it whenever it changes.
+    view! {cx,
+        Loading(status=status) {
+            (match recipe_signal.get().as_ref().clone() {
+                Some((title, serving_count, desc, prep_time, steps)) => {
+                    debug!("Viewing recipe.");
+                    view! {cx,
+                        div(class="recipe") {
+                            h1(class="recipe_title") { (title) }
+                             div(class="serving_count") {
+                                 "Serving Count: " (serving_count.map(|v| format!("{}", v)).unwrap_or_else(|| "Unconfigured".to_owned()))
+                             }
+                             (if prep_time.is_zero() {
+                                 View::empty()
+                             } else {
+                                 view! {cx, div(class="prep_time") { "Prep Time: " (as_duration_text(&prep_time)) } }
+                             })
+                             div(class="recipe_description") {
+                                 (desc)
+                             }
+                            Steps(steps=steps, timers=timers)
+                        }
+                    }
+                }
+                None => View::empty(),
+            })
+        }
     }
-    view! {cx, (view.get().as_ref()) }
 }
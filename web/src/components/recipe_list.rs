@@ -36,7 +36,7 @@ pub fn RecipeList<'ctx, G: Html>(cx: Scope<'ctx>, sh: StateHandler<'ctx>) -> Vie
                 view= move |cx, (id, _count)| {
                     debug!(id=%id, "Rendering recipe");
                     view ! {cx,
-                        Viewer(recipe_id=id, sh=sh)
+                        Viewer(recipe_id=id, sh=sh, timers=true)
                         hr()
                     }
                 }
@@ -14,11 +14,13 @@ use std::collections::BTreeMap;
 // See the License for the specific language governing permissions and
 // limitations under the License.
 use recipes::Recipe;
-use sycamore::prelude::*;
-use tracing::{debug, instrument};
+use sycamore::{futures::spawn_local_scoped, prelude::*};
+use tracing::{debug, error, instrument};
 
+use crate::api::LocalStore;
 use crate::app_state::{Message, StateHandler};
 use crate::components::recipe_selection::*;
+use crate::search_index::SearchIndex;
 
 #[derive(Props)]
 pub struct CategoryGroupProps<'ctx> {
@@ -89,30 +91,61 @@ pub fn RecipePlan<'ctx, G: Html>(cx: Scope<'ctx>, sh: StateHandler<'ctx>) -> Vie
             .iter()
             .fold(BTreeMap::new(), |mut map, (r, cat)| {
                 debug!(?cat, recipe_id=?r, "Accumulating recipe into category");
-                map.entry(cat.clone()).or_insert(Vec::new()).push((
-                    r.clone(),
-                    state
-                        .get()
-                        .recipes
-                        .get(r)
-                        .expect(&format!("Failed to find recipe {}", r))
-                        .clone(),
-                ));
+                match state.get().recipes.get(r) {
+                    Some(recipe) => map
+                        .entry(cat.clone())
+                        .or_insert(Vec::new())
+                        .push((r.clone(), recipe.clone())),
+                    None => error!(recipe_id=?r, "Failed to find recipe for category"),
+                }
                 map
             })
             .iter()
             .map(|(cat, rs)| (cat.clone(), rs.clone()))
             .collect::<Vec<(String, Vec<(String, Recipe)>)>>()
     });
+    let template_name = create_signal(cx, String::new());
+    let search_query = create_signal(cx, String::new());
+    let search_index = create_signal(cx, SearchIndex::new());
+    spawn_local_scoped(cx, async move {
+        search_index.set(LocalStore::new().get_search_index().await);
+    });
+    let matches = create_memo(cx, move || {
+        search_index.get().search(search_query.get().as_str())
+    });
+    let filtered_groups = create_memo(cx, move || {
+        let groups = recipe_category_groups.get();
+        match matches.get().as_ref() {
+            None => groups.as_ref().clone(),
+            Some(ids) => groups
+                .iter()
+                .map(|(cat, rs)| {
+                    (
+                        cat.clone(),
+                        rs.iter()
+                            .filter(|(id, _)| ids.contains(id))
+                            .cloned()
+                            .collect::<Vec<(String, Recipe)>>(),
+                    )
+                })
+                .filter(|(_, rs)| !rs.is_empty())
+                .collect(),
+        }
+    });
     view! {cx,
+        div(class="no-print") {
+            input(type="search", placeholder="Search recipes", bind:value=search_query)
+        }
         Keyed(
-            iterable=recipe_category_groups,
+            iterable=filtered_groups,
             view=move |cx, (cat, recipes)| {
                 view! {cx,
                     CategoryGroup(sh=sh, category=cat, recipes=recipes, row_size=4)
                 }
             },
-            key=|(ref cat, _)| cat.clone(),
+            key=|(ref cat, ref recipes)| {
+                (cat.clone(), recipes.iter().map(|(id, _)| id.clone()).collect::<Vec<String>>())
+            },
         )
         button(on:click=move |_| {
             sh.dispatch(cx, Message::LoadState(None));
@@ -124,5 +157,14 @@ pub fn RecipePlan<'ctx, G: Html>(cx: Scope<'ctx>, sh: StateHandler<'ctx>) -> Vie
             // Poor man's click event signaling.
             sh.dispatch(cx, Message::SaveState(None));
         }) { "Save Plan" } " "
+        input(type="text", placeholder="Template name", bind:value=template_name)
+        button(on:click=move |_| {
+            let name = template_name.get_untracked().trim().to_owned();
+            if name.is_empty() {
+                return;
+            }
+            sh.dispatch(cx, Message::SaveTemplate(name, None));
+            template_name.set(String::new());
+        }) { "Save as Template" }
     }
 }
@@ -18,6 +18,7 @@ use tracing::{debug, instrument};
 
 use crate::app_state::{Message, StateHandler};
 use crate::components::NumberField;
+use crate::routing;
 
 #[derive(Props)]
 pub struct RecipeCheckBoxProps<'ctx> {
@@ -62,7 +63,7 @@ pub fn RecipeSelection<'ctx, G: Html>(
     });
 
     let title = title.get().clone();
-    let href = format!("/ui/recipe/view/{}", id);
+    let href = routing::recipe_view_route(&id);
     let name = format!("recipe_id:{}", id);
     let for_id = name.clone();
     view! {cx,
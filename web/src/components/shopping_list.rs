@@ -11,221 +11,289 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
-use std::collections::BTreeSet;
+use std::collections::BTreeMap;
 
-use recipes::{IngredientAccumulator, IngredientKey};
+use client_api::text;
+use recipes::{
+    parse,
+    unit::{QuantityDisplay, UnitSystem},
+    IngredientKey,
+};
 use sycamore::prelude::*;
-use tracing::{debug, info, instrument};
+use tracing::{debug, error, info, instrument};
+use wasm_bindgen::JsCast;
+use web_sys::KeyboardEvent;
 
-use crate::app_state::{Message, StateHandler};
+use crate::{
+    app_state::{AppState, Message, StateHandler},
+    routing,
+    selectors::{self, ShoppingRow},
+};
 
 #[instrument(skip_all)]
-fn make_deleted_ingredients_rows<'ctx, G: Html>(
+fn make_empty_recipes_warning<'ctx, G: Html>(cx: Scope<'ctx>, sh: StateHandler<'ctx>) -> View<G> {
+    let empty_recipes = sh.get_selector(cx, |state| selectors::empty_recipes(state.get().as_ref()));
+    view! {cx,
+        (if empty_recipes.get().is_empty() {
+            View::empty()
+        } else {
+            view! {cx,
+                div(class="no-print warning") {
+                    "These planned recipes did not add anything to the shopping list:"
+                    ul {
+                        Indexed(
+                            iterable=empty_recipes,
+                            view=|cx, id| {
+                                let href = routing::recipe_edit_route(&id);
+                                view! {cx,
+                                    li { a(href=href) { (id) } }
+                                }
+                            }
+                        )
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// Show the recipes that contributed to an ingredient. Expanding it shows how
+/// much each recipe contributed.
+fn make_recipe_breakdown<'ctx, G: Html>(
+    cx: Scope<'ctx>,
+    breakdown: BTreeMap<String, String>,
+) -> View<G> {
+    let recipes = breakdown
+        .keys()
+        .fold(String::new(), |acc, s| format!("{}{},", acc, s))
+        .trim_end_matches(",")
+        .to_owned();
+    let amts = View::new_fragment(
+        breakdown
+            .into_iter()
+            .map(|(recipe, amt)| {
+                view! {cx,
+                    li { (recipe) ": " (amt) }
+                }
+            })
+            .collect(),
+    );
+    view! {cx,
+        details(class="recipe-breakdown") {
+            summary { (recipes) }
+            ul(class="no-list") { (amts) }
+        }
+    }
+}
+
+#[instrument(skip_all)]
+fn make_mismatch_warning<'ctx, G: Html>(
     cx: Scope<'ctx>,
     sh: StateHandler<'ctx>,
     show_staples: &'ctx ReadSignal<bool>,
 ) -> View<G> {
-    debug!("Making ingredients rows");
-    let ingredients = sh.get_selector(cx, move |state| {
-        let state = state.get();
-        let category_map = &state.category_map;
-        debug!("building ingredient list from state");
-        let mut acc = IngredientAccumulator::new();
-        for (id, count) in state.recipe_counts.iter() {
-            for _ in 0..(*count) {
-                acc.accumulate_from(
-                    state
-                        .recipes
-                        .get(id)
-                        .expect(&format!("No such recipe id exists: {}", id)),
-                );
+    let warnings = sh.get_selector(cx, move |state| {
+        selectors::shopping_warnings(state.get().as_ref(), *show_staples.get())
+    });
+    view! {cx,
+        (if warnings.get().is_empty() {
+            View::empty()
+        } else {
+            view! {cx,
+                div(class="no-print warning") {
+                    "These ingredients are listed more than once because their amounts can't be combined:"
+                    ul {
+                        Indexed(
+                            iterable=warnings,
+                            view=|cx, warning| {
+                                view! {cx,
+                                    li { (warning) }
+                                }
+                            }
+                        )
+                    }
+                }
             }
+        })
+    }
+}
+
+/// The ingredient rows a shopping list table shows and what its row button
+/// does to them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum RowMode {
+    /// Ingredients still in the inventory. These can be checked off or deleted.
+    Active,
+    /// Ingredients deleted from the inventory. These can be restored.
+    Deleted,
+}
+
+impl RowMode {
+    fn rows(&self, state: &AppState, show_staples: bool) -> Vec<ShoppingRow> {
+        match self {
+            RowMode::Active => selectors::shopping_list(state, show_staples),
+            RowMode::Deleted => selectors::deleted_ingredients(state, show_staples),
         }
-        if *show_staples.get() {
-            if let Some(staples) = &state.staples {
-                acc.accumulate_ingredients_for("Staples", staples.iter());
-            }
+    }
+
+    /// The class and label of the row button.
+    fn button(&self) -> (&'static str, &'static str) {
+        match self {
+            RowMode::Active => ("fit-content no-print destructive", "X"),
+            RowMode::Deleted => ("fit-content no-print", "Undo"),
         }
-        let mut ingredients = acc
-            .ingredients()
-            .into_iter()
-            // First we filter out any filtered ingredients
-            .filter(|(i, _)| state.filtered_ingredients.contains(i))
-            // Then we take into account our modified amts
-            .map(|(k, (i, rs))| {
-                let category = category_map
-                    .get(&i.name)
-                    .cloned()
-                    .unwrap_or_else(|| String::new());
-                if state.modified_amts.contains_key(&k) {
-                    (
-                        k.clone(),
-                        (
-                            i.name,
-                            i.form,
-                            category,
-                            state.modified_amts.get(&k).unwrap().clone(),
-                            rs,
-                        ),
-                    )
-                } else {
-                    (
-                        k.clone(),
-                        (
-                            i.name,
-                            i.form,
-                            category,
-                            format!("{}", i.amt.normalize()),
-                            rs,
-                        ),
-                    )
+    }
+
+    /// The message the row button dispatches.
+    fn action(&self, k: IngredientKey) -> Message {
+        match self {
+            RowMode::Active => Message::AddFilteredIngredient(k),
+            RowMode::Deleted => Message::RemoveFilteredIngredient(k),
+        }
+    }
+}
+
+/// The checkbox for checking off an ingredient in shopping mode.
+fn make_check_off_cell<'ctx, G: Html>(
+    cx: Scope<'ctx>,
+    sh: StateHandler<'ctx>,
+    shopping_mode: &'ctx ReadSignal<bool>,
+    checked: &'ctx ReadSignal<bool>,
+    k: IngredientKey,
+) -> View<G> {
+    view! {cx,
+        (if *shopping_mode.get() {
+            let k = k.clone();
+            view! {cx,
+                td {
+                    input(type="checkbox", class="no-print", checked=*checked.get(), on:change=move |_| {
+                        sh.dispatch(cx, Message::ToggleChecked(k.clone()));
+                    })
                 }
-            })
-            .collect::<Vec<(
-                IngredientKey,
-                (String, Option<String>, String, String, BTreeSet<String>),
-            )>>();
-        ingredients.sort_by(|tpl1, tpl2| (&tpl1.1 .2, &tpl1.1 .0).cmp(&(&tpl2.1 .2, &tpl2.1 .0)));
-        ingredients
+            }
+        } else {
+            View::empty()
+        })
+    }
+}
+
+/// What an ingredient was actually bought as along with what it was bought as
+/// last time if we haven't recorded it for this plan yet.
+fn make_purchase_input<'ctx, G: Html>(
+    cx: Scope<'ctx>,
+    sh: StateHandler<'ctx>,
+    k: IngredientKey,
+) -> View<G> {
+    let k_hint = k.clone();
+    let hint = sh.get_selector(cx, move |state| {
+        selectors::purchase_hint(state.get().as_ref(), &k_hint)
     });
-    view!(
-        cx,
-        Indexed(
-            iterable = ingredients,
-            view = move |cx, (k, (name, form, category, amt, rs))| {
-                let category = if category == "" {
-                    "other".to_owned()
-                } else {
-                    category
-                };
-                let amt_signal = create_signal(cx, amt);
-                let k_clone = k.clone();
-                let form = form.map(|form| format!("({})", form)).unwrap_or_default();
-                let recipes = rs
-                    .iter()
-                    .fold(String::new(), |acc, s| format!("{}{},", acc, s))
-                    .trim_end_matches(",")
-                    .to_owned();
-                view! {cx,
-                    tr {
-                        td {
-                            input(bind:value=amt_signal, class="width-5", type="text", on:change=move |_| {
-                                sh.dispatch(cx, Message::UpdateAmt(k_clone.clone(), amt_signal.get_untracked().as_ref().clone()));
-                            })
-                        }
-                        td {
-                            input(type="button", class="fit-content no-print", value="Undo", on:click={
-                                move |_| {
-                                    sh.dispatch(cx, Message::RemoveFilteredIngredient(k.clone()));
-                            }})
-                        }
-                        td {  (name) " " (form) "" br {} "" (category) "" }
-                        td { (recipes) }
-                    }
+    let k_saved = k.clone();
+    let saved = sh.get_selector(cx, move |state| {
+        state
+            .get()
+            .purchased_amts
+            .get(&k_saved)
+            .cloned()
+            .unwrap_or_default()
+    });
+    let purchased = create_signal(cx, saved.get_untracked().as_ref().clone());
+    view! {cx,
+        div(class="no-print purchase") {
+            input(bind:value=purchased, class="width-5", type="text", placeholder="Bought as", maxlength=text::MAX_AMOUNT_CHARS, on:change=move |_| {
+                sh.dispatch(cx, Message::UpdatePurchasedAmt(k.clone(), purchased.get_untracked().as_ref().clone()));
+            })
+            (match hint.get().as_ref() {
+                Some(hint) => {
+                    let hint = format!("Last bought as {}", hint);
+                    view! {cx, " " span(class="purchase-hint") { (hint) } }
                 }
-            }
-        )
-    )
+                None => View::empty(),
+            })
+        }
+    }
 }
 
-#[instrument(skip_all)]
+#[instrument(skip_all, fields(?mode))]
 fn make_ingredients_rows<'ctx, G: Html>(
     cx: Scope<'ctx>,
     sh: StateHandler<'ctx>,
+    mode: RowMode,
     show_staples: &'ctx ReadSignal<bool>,
+    shopping_mode: &'ctx ReadSignal<bool>,
 ) -> View<G> {
     debug!("Making ingredients rows");
     let ingredients = sh.get_selector(cx, move |state| {
-        let state = state.get();
-        let category_map = &state.category_map;
         debug!("building ingredient list from state");
-        let mut acc = IngredientAccumulator::new();
-        for (id, count) in state.recipe_counts.iter() {
-            for _ in 0..(*count) {
-                acc.accumulate_from(
-                    state
-                        .recipes
-                        .get(id)
-                        .expect(&format!("No such recipe id exists: {}", id)),
-                );
-            }
-        }
-        if *show_staples.get() {
-            if let Some(staples) = &state.staples {
-                acc.accumulate_ingredients_for("Staples", staples.iter());
-            }
-        }
-        let mut ingredients = acc
-            .ingredients()
-            .into_iter()
-            // First we filter out any filtered ingredients
-            .filter(|(i, _)| !state.filtered_ingredients.contains(i))
-            // Then we take into account our modified amts
-            .map(|(k, (i, rs))| {
-                let category = category_map
-                    .get(&i.name)
-                    .cloned()
-                    .unwrap_or_else(|| String::new());
-                if state.modified_amts.contains_key(&k) {
-                    (
-                        k.clone(),
-                        (
-                            i.name,
-                            i.form,
-                            category,
-                            state.modified_amts.get(&k).unwrap().clone(),
-                            rs,
-                        ),
-                    )
-                } else {
-                    (
-                        k.clone(),
-                        (
-                            i.name,
-                            i.form,
-                            category,
-                            format!("{}", i.amt.normalize()),
-                            rs,
-                        ),
-                    )
-                }
-            })
-            .collect::<Vec<(
-                IngredientKey,
-                (String, Option<String>, String, String, BTreeSet<String>),
-            )>>();
-        ingredients.sort_by(|tpl1, tpl2| (&tpl1.1 .2, &tpl1.1 .0).cmp(&(&tpl2.1 .2, &tpl2.1 .0)));
-        ingredients
+        mode.rows(state.get().as_ref(), *show_staples.get())
     });
+    let (button_class, button_label) = mode.button();
     view!(
         cx,
         Indexed(
             iterable = ingredients,
-            view = move |cx, (k, (name, form, category, amt, rs))| {
+            view = move |cx, (k, (name, form, category, amt, rs, alt))| {
                 let category = if category == "" {
                     "other".to_owned()
                 } else {
                     category
                 };
-                let amt_signal = create_signal(cx, amt);
+                let amt_signal = create_signal(cx, amt.clone());
+                // NOTE(jwall): Only ingredients with a known density get a toggle.
+                let unit_toggle = match alt {
+                    Some(alt) => {
+                        let showing_alt = create_signal(cx, false);
+                        view! {cx,
+                            input(type="button", class="no-print", value="⇄", title="Toggle weight and volume", on:click=move |_| {
+                                let show = !*showing_alt.get_untracked();
+                                showing_alt.set(show);
+                                amt_signal.set(if show { alt.clone() } else { amt.clone() });
+                            })
+                        }
+                    }
+                    None => View::empty(),
+                };
                 let k_clone = k.clone();
+                let k_checked = k.clone();
+                // NOTE(jwall): Only ingredients that are being bought can
+                // record what they were bought as.
+                let purchase = if mode == RowMode::Active {
+                    make_purchase_input(cx, sh, k.clone())
+                } else {
+                    View::empty()
+                };
+                let checked = sh.get_selector(cx, move |state| {
+                    mode == RowMode::Active && state.get().checked_off.contains(&k_checked)
+                });
                 let form = form.map(|form| format!("({})", form)).unwrap_or_default();
-                let recipes = rs
-                    .iter()
-                    .fold(String::new(), |acc, s| format!("{}{},", acc, s))
-                    .trim_end_matches(",")
-                    .to_owned();
+                let recipes = make_recipe_breakdown(cx, rs);
+                // NOTE(jwall): Only the active rows can be checked off.
+                let check_box = if mode == RowMode::Active {
+                    make_check_off_cell(cx, sh, shopping_mode, checked, k.clone())
+                } else {
+                    View::empty()
+                };
+                let row_class = create_memo(cx, move || {
+                    if *shopping_mode.get() && *checked.get() {
+                        "checked-off"
+                    } else {
+                        ""
+                    }
+                });
                 view! {cx,
-                    tr {
+                    tr(class=*row_class.get()) {
+                        (check_box)
                         td {
                             input(bind:value=amt_signal, class="width-5", type="text", on:change=move |_| {
                                 sh.dispatch(cx, Message::UpdateAmt(k_clone.clone(), amt_signal.get_untracked().as_ref().clone()));
                             })
+                            (unit_toggle)
+                            (purchase)
                         }
                         td {
-                            input(type="button", class="fit-content no-print destructive", value="X", on:click={
+                            input(type="button", class=button_class, value=button_label, on:click={
                                 move |_| {
-                                    sh.dispatch(cx, Message::AddFilteredIngredient(k.clone()));
+                                    sh.dispatch(cx, mode.action(k.clone()));
                             }})
                         }
                         td {  (name) " " (form) "" br {} "" (category) "" }
@@ -237,8 +305,27 @@ fn make_ingredients_rows<'ctx, G: Html>(
     )
 }
 
+/// Split quick add text like `2 lemons` into the amount and name of an extra.
+/// Text that doesn't parse as an ingredient goes entirely into the name.
+pub fn split_quick_extra(text: &str) -> (String, String) {
+    match parse::as_ingredient(text) {
+        Ok(ingredient) => {
+            let mut name = ingredient.name_for_display().to_owned();
+            if let Some(form) = &ingredient.form {
+                name.push_str(&format!(" ({})", form));
+            }
+            (ingredient.amt.to_string(), name)
+        }
+        Err(_) => (String::new(), text.trim().to_owned()),
+    }
+}
+
 #[instrument(skip_all)]
-fn make_extras_rows<'ctx, G: Html>(cx: Scope<'ctx>, sh: StateHandler<'ctx>) -> View<G> {
+fn make_extras_rows<'ctx, G: Html>(
+    cx: Scope<'ctx>,
+    sh: StateHandler<'ctx>,
+    shopping_mode: &'ctx ReadSignal<bool>,
+) -> View<G> {
     debug!("Making extras rows");
     let extras_read_signal = sh.get_selector(cx, |state| {
         state.get().extras.iter().cloned().enumerate().collect()
@@ -249,10 +336,21 @@ fn make_extras_rows<'ctx, G: Html>(cx: Scope<'ctx>, sh: StateHandler<'ctx>) -> V
             view= move |cx, (idx, (amt, name))| {
                 let amt_signal = create_signal(cx, amt.clone());
                 let name_signal = create_signal(cx, name.clone());
+                let name_invalid = create_memo(cx, || {
+                    match text::check(name_signal.get().as_str(), text::MAX_NAME_CHARS, true) {
+                        Ok(_) => "false",
+                        Err(_) => "true",
+                    }
+                });
                 view! {cx,
                     tr {
+                        (if *shopping_mode.get() {
+                            view! {cx, td(class="no-print") {} }
+                        } else {
+                            View::empty()
+                        })
                         td {
-                            input(bind:value=amt_signal, class="width-5", type="text", on:change=move |_| {
+                            input(bind:value=amt_signal, class="width-5", type="text", maxlength=text::MAX_AMOUNT_CHARS, on:change=move |_| {
                                 sh.dispatch(cx, Message::UpdateExtra(idx,
                                     amt_signal.get_untracked().as_ref().clone(),
                                     name_signal.get_untracked().as_ref().clone()));
@@ -264,7 +362,7 @@ fn make_extras_rows<'ctx, G: Html>(cx: Scope<'ctx>, sh: StateHandler<'ctx>) -> V
                             })
                         }
                         td {
-                            input(bind:value=name_signal, type="text", on:change=move |_| {
+                            input(bind:value=name_signal, type="text", maxlength=text::MAX_NAME_CHARS, aria-invalid=name_invalid.get(), on:change=move |_| {
                                 sh.dispatch(cx, Message::UpdateExtra(idx,
                                     amt_signal.get_untracked().as_ref().clone(),
                                     name_signal.get_untracked().as_ref().clone()));
@@ -282,19 +380,25 @@ fn make_shopping_table<'ctx, G: Html>(
     cx: Scope<'ctx>,
     sh: StateHandler<'ctx>,
     show_staples: &'ctx ReadSignal<bool>,
+    shopping_mode: &'ctx ReadSignal<bool>,
 ) -> View<G> {
     debug!("Making shopping table");
     view! {cx,
         table(class="pad-top shopping-list page-breaker container-fluid", role="grid") {
             tr {
+                (if *shopping_mode.get() {
+                    view! {cx, th(class="no-print") { " Got it " } }
+                } else {
+                    View::empty()
+                })
                 th { " Quantity " }
                 th { " Delete " }
                 th { " Ingredient " }
                 th { " Recipes " }
             }
             tbody {
-                (make_ingredients_rows(cx, sh, show_staples))
-                (make_extras_rows(cx, sh))
+                (make_ingredients_rows(cx, sh, RowMode::Active, show_staples, shopping_mode))
+                (make_extras_rows(cx, sh, shopping_mode))
             }
         }
     }
@@ -304,6 +408,7 @@ fn make_deleted_items_table<'ctx, G: Html>(
     cx: Scope<'ctx>,
     sh: StateHandler<'ctx>,
     show_staples: &'ctx ReadSignal<bool>,
+    shopping_mode: &'ctx ReadSignal<bool>,
 ) -> View<G> {
     view! {cx,
         h2 { "Deleted Items" }
@@ -315,25 +420,134 @@ fn make_deleted_items_table<'ctx, G: Html>(
                 th { " Recipes " }
             }
             tbody {
-                (make_deleted_ingredients_rows(cx, sh, show_staples))
+                (make_ingredients_rows(cx, sh, RowMode::Deleted, show_staples, shopping_mode))
             }
         }
     }
 }
 
+/// The progress bar and clear action for shopping mode.
+fn make_shopping_progress<'ctx, G: Html>(
+    cx: Scope<'ctx>,
+    sh: StateHandler<'ctx>,
+    shopping_mode: &'ctx ReadSignal<bool>,
+) -> View<G> {
+    let progress = sh.get_selector(cx, |state| {
+        selectors::shopping_progress(state.get().as_ref())
+    });
+    view! {cx,
+        (if *shopping_mode.get() {
+            let (checked, total) = *progress.get();
+            view! {cx,
+                div(class="no-print shopping-progress") {
+                    label(for="shopping_progress") { (checked) " of " (total) " items" }
+                    " "
+                    progress(id="shopping_progress", value=checked, max=total) {}
+                    " "
+                    button(on:click=move |_| {
+                        sh.dispatch(cx, Message::ClearCheckedOff);
+                    }) { "Clear checks" }
+                }
+            }
+        } else {
+            View::empty()
+        })
+    }
+}
+
 #[instrument(skip_all)]
 #[component]
 pub fn ShoppingList<'ctx, G: Html>(cx: Scope<'ctx>, sh: StateHandler<'ctx>) -> View<G> {
     let show_staples = sh.get_selector(cx, |state| state.get().use_staples);
+    let shopping_mode = sh.get_selector(cx, |state| state.get().shopping_mode);
+    let count_each = sh.get_selector(cx, |state| state.get().count_each);
+    let compound_units = sh.get_selector(cx, |state| state.get().compound_units);
+    let current_system = sh.get_selector(cx, |state| {
+        state
+            .get()
+            .unit_system
+            .map(|system| system.to_string())
+            .unwrap_or_default()
+    });
+    let unit_system = create_signal(cx, current_system.get_untracked().as_ref().clone());
+    let display_precision = sh.get_selector(cx, |state| state.get().display_precision);
+    let precision = create_signal(cx, format!("{}", display_precision.get()));
+    let quick_add = create_signal(cx, String::new());
+    let show_decimals = sh.get_selector(cx, |state| {
+        state.get().quantity_display == QuantityDisplay::Decimal
+    });
     view! {cx,
         h1 { "Shopping List " }
+        (make_empty_recipes_warning(cx, sh))
+        (make_mismatch_warning(cx, sh, show_staples))
         label(for="show_staples_cb") { "Show staples" }
         input(id="show_staples_cb", type="checkbox", checked=*show_staples.get(), on:change=move|_| {
             let value = !*show_staples.get_untracked();
             sh.dispatch(cx, Message::UpdateUseStaples(value));
         })
-        (make_shopping_table(cx, sh, show_staples))
-        (make_deleted_items_table(cx, sh, show_staples))
+        " "
+        label(for="show_decimals_cb") { "Show decimals" }
+        input(id="show_decimals_cb", type="checkbox", checked=*show_decimals.get(), on:change=move|_| {
+            let value = if *show_decimals.get_untracked() {
+                QuantityDisplay::Fraction
+            } else {
+                QuantityDisplay::Decimal
+            };
+            sh.dispatch(cx, Message::UpdateQuantityDisplay(value));
+        })
+        " "
+        label(for="count_each_cb") { "Show each for counts" }
+        input(id="count_each_cb", type="checkbox", checked=*count_each.get(), on:change=move|_| {
+            let value = !*count_each.get_untracked();
+            sh.dispatch(cx, Message::UpdateCountEach(value));
+        })
+        " "
+        label(for="compound_units_cb") { "Compound units" }
+        input(id="compound_units_cb", type="checkbox", checked=*compound_units.get(), on:change=move|_| {
+            let value = !*compound_units.get_untracked();
+            sh.dispatch(cx, Message::UpdateCompoundUnits(value));
+        })
+        " "
+        label(for="unit_system") { "Units" }
+        select(bind:value=unit_system, name="unit_system", id="unit_system", on:change=move |_| {
+            let value = UnitSystem::try_from(unit_system.get_untracked().as_str()).ok();
+            sh.dispatch(cx, Message::UpdateUnitSystem(value));
+        }) {
+            option(value="", selected=unit_system.get_untracked().is_empty()) { "As written" }
+            option(value="metric", selected=unit_system.get_untracked().as_str() == "metric") { "Metric" }
+            option(value="us", selected=unit_system.get_untracked().as_str() == "us") { "US" }
+        }
+        " "
+        label(for="precision_input") { "Decimal places" }
+        input(id="precision_input", type="number", min="0", max="6", class="width-5", bind:value=precision, on:change=move |_| {
+            match precision.get_untracked().parse::<u32>() {
+                Ok(value) if value <= 6 => sh.dispatch(cx, Message::UpdateDisplayPrecision(value)),
+                _ => error!(precision=%precision.get_untracked(), "Invalid display precision"),
+            }
+        })
+        " "
+        label(for="shopping_mode_cb") { "Shopping mode" }
+        input(id="shopping_mode_cb", type="checkbox", checked=*shopping_mode.get(), on:change=move|_| {
+            let value = !*shopping_mode.get_untracked();
+            sh.dispatch(cx, Message::UpdateShoppingMode(value));
+        })
+        (make_shopping_progress(cx, sh, shopping_mode))
+        (make_shopping_table(cx, sh, show_staples, shopping_mode))
+        (make_deleted_items_table(cx, sh, show_staples, shopping_mode))
+        label(for="quick_add_extra", class="no-print") { "Quick add" }
+        input(id="quick_add_extra", class="no-print", type="text", placeholder="2 lemons", bind:value=quick_add, on:keyup=move |evt: web_sys::Event| {
+            let is_enter = evt
+                .dyn_ref::<KeyboardEvent>()
+                .map(|evt| evt.key() == "Enter")
+                .unwrap_or(false);
+            if !is_enter || quick_add.get_untracked().trim().is_empty() {
+                return;
+            }
+            let (amt, name) = split_quick_extra(quick_add.get_untracked().as_str());
+            info!(%amt, %name, "Registering quick add item request for inventory");
+            sh.dispatch(cx, Message::AddExtra(amt, name));
+            quick_add.set(String::new());
+        })
         button(class="no-print", on:click=move |_| {
             info!("Registering add item request for inventory");
             sh.dispatch(cx, Message::AddExtra(String::new(), String::new()));
@@ -348,3 +562,76 @@ pub fn ShoppingList<'ctx, G: Html>(cx: Scope<'ctx>, sh: StateHandler<'ctx>) -> V
         }) { "Save" } " "
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use recipes::{unit::Measure, Ingredient, Recipe, Step};
+
+    #[test]
+    fn test_row_modes_split_the_shopping_list() {
+        let mut state = AppState::new();
+        let onion = Ingredient::new("onion", None, Measure::count(1));
+        let carrot = Ingredient::new("carrot", None, Measure::count(2));
+        state.recipes.insert(
+            "soup".to_owned(),
+            Recipe::new("soup", None)
+                .with_steps(vec![Step::new(None, "Simmer.")
+                    .with_ingredients(vec![onion.clone(), carrot.clone()])]),
+        );
+        state.recipe_counts.insert("soup".to_owned(), 1);
+        state.filtered_ingredients.insert(carrot.key());
+        let keys = |mode: RowMode| {
+            mode.rows(&state, false)
+                .into_iter()
+                .map(|(k, _)| k)
+                .collect::<Vec<IngredientKey>>()
+        };
+        assert_eq!(keys(RowMode::Active), vec![onion.key()]);
+        assert_eq!(keys(RowMode::Deleted), vec![carrot.key()]);
+        assert!(matches!(
+            RowMode::Active.action(onion.key()),
+            Message::AddFilteredIngredient(k) if k == onion.key()
+        ));
+        assert!(matches!(
+            RowMode::Deleted.action(carrot.key()),
+            Message::RemoveFilteredIngredient(k) if k == carrot.key()
+        ));
+    }
+
+    #[test]
+    fn test_split_quick_extra_count() {
+        assert_eq!(
+            split_quick_extra("2 lemons"),
+            ("2".to_owned(), "lemons".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_split_quick_extra_package() {
+        assert_eq!(
+            split_quick_extra("1 bag tortilla chips"),
+            ("1 bag".to_owned(), "tortilla chips".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_split_quick_extra_keeps_form() {
+        assert_eq!(
+            split_quick_extra("1 cup onion (chopped)"),
+            ("1 cup".to_owned(), "onion (chopped)".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_split_quick_extra_falls_back_to_name() {
+        assert_eq!(
+            split_quick_extra(" paper towels "),
+            (String::new(), "paper towels".to_owned())
+        );
+        assert_eq!(
+            split_quick_extra("2 lemons (zested"),
+            (String::new(), "2 lemons (zested".to_owned())
+        );
+    }
+}
@@ -54,15 +54,18 @@ pub fn IngredientsEditor<'ctx, G: Html>(
     spawn_local_scoped(cx, {
         let store = store.clone();
         async move {
-            let entry = store
-                .fetch_staples()
-                .await
-                .expect("Failure getting staples");
-            if let Some(entry) = entry {
-                check_ingredients_parses(entry.as_str(), error_text, aria_hint);
-                text.set(entry);
-            } else {
-                error_text.set("Unable to find staples".to_owned());
+            match store.fetch_staples().await {
+                Ok(Some(entry)) => {
+                    check_ingredients_parses(entry.as_str(), error_text, aria_hint);
+                    text.set(entry);
+                }
+                Ok(None) => {
+                    error_text.set("Unable to find staples".to_owned());
+                }
+                Err(err) => {
+                    error!(?err, "Failure getting staples");
+                    error_text.set("Failure getting staples".to_owned());
+                }
             }
         }
     });
@@ -77,7 +80,7 @@ pub fn IngredientsEditor<'ctx, G: Html>(
                 dirty.set(true);
             }, on:input=move |_| {
                 let current_ts = js_lib::get_ms_timestamp();
-                if (current_ts - *ts.get_untracked()) > 100 {
+                if (current_ts - *ts.get_untracked()) > 100.0 {
                     check_ingredients_parses(text.get_untracked().as_str(), error_text, aria_hint);
                     ts.set(current_ts);
                 }
@@ -0,0 +1,165 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! A countdown timer for the prep time of a recipe step.
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use recipes::serialize::as_duration_text;
+use sycamore::prelude::*;
+use tracing::error;
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::{Notification, NotificationPermission};
+
+use crate::{components::toast, js_lib};
+
+const TICK_MS: i32 = 1000;
+
+/// Formats the remaining seconds as `h:mm:ss` or `m:ss`.
+pub fn format_remaining(secs: u64) -> String {
+    let (hours, mins, secs) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, mins, secs)
+    } else {
+        format!("{}:{:02}", mins, secs)
+    }
+}
+
+/// The seconds left after one more tick or `None` once the timer has elapsed.
+pub fn next_remaining(secs: u64) -> Option<u64> {
+    match secs {
+        0 | 1 => None,
+        secs => Some(secs - 1),
+    }
+}
+
+fn request_permission() {
+    if Notification::permission() == NotificationPermission::Default {
+        if let Err(err) = Notification::request_permission() {
+            error!(?err, "Failed to request notification permission");
+        }
+    }
+}
+
+/// Tell the user a timer elapsed with a browser notification if we're allowed
+/// to and with a toast otherwise.
+fn notify(msg: &str) {
+    if Notification::permission() == NotificationPermission::Granted {
+        match Notification::new(msg) {
+            Ok(_) => return,
+            Err(err) => error!(?err, "Failed to show notification"),
+        }
+    }
+    toast::message(msg, None);
+}
+
+// NOTE(jwall): Stopping a timer bumps the generation so any tick that is
+// already scheduled for the old run does nothing.
+fn schedule_tick(
+    remaining: RcSignal<Option<u64>>,
+    generation: Rc<Cell<u32>>,
+    run: u32,
+    msg: String,
+) {
+    let tick = Closure::once_into_js(move || {
+        if generation.get() != run {
+            return;
+        }
+        let next = (*remaining.get_untracked()).and_then(next_remaining);
+        remaining.set(next);
+        match next {
+            Some(_) => schedule_tick(remaining, generation, run, msg),
+            None => notify(&msg),
+        }
+    });
+    if let Err(err) = js_lib::get_window()
+        .set_timeout_with_callback_and_timeout_and_arguments_0(tick.unchecked_ref(), TICK_MS)
+    {
+        error!(?err, "Failed to schedule timer tick");
+    }
+}
+
+/// A button that starts a countdown for `duration` and notifies the user when
+/// it elapses.
+#[component]
+pub fn StepTimer<G: Html>(cx: Scope, label: String, duration: Duration) -> View<G> {
+    let remaining = create_rc_signal(None::<u64>);
+    let generation = Rc::new(Cell::new(0));
+    on_cleanup(cx, {
+        let generation = generation.clone();
+        move || generation.set(generation.get() + 1)
+    });
+    let start = {
+        let remaining = remaining.clone();
+        let generation = generation.clone();
+        move |_| {
+            request_permission();
+            let run = generation.get() + 1;
+            generation.set(run);
+            remaining.set(Some(duration.as_secs()));
+            schedule_tick(
+                remaining.clone(),
+                generation.clone(),
+                run,
+                format!("{} is done", label),
+            );
+        }
+    };
+    let stop = {
+        let remaining = remaining.clone();
+        move |_| {
+            generation.set(generation.get() + 1);
+            remaining.set(None);
+        }
+    };
+    let display = create_memo(cx, move || *remaining.get());
+    view! {cx,
+        div(class="step-timer") {
+            (match *display.get() {
+                Some(secs) => {
+                    let stop = stop.clone();
+                    view! {cx,
+                        span(role="timer") { (format_remaining(secs)) } " "
+                        button(on:click=stop) { "Stop" }
+                    }
+                }
+                None => {
+                    let start = start.clone();
+                    view! {cx,
+                        button(on:click=start) { "Start timer (" (as_duration_text(&duration)) ")" }
+                    }
+                }
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_remaining() {
+        assert_eq!(format_remaining(0), "0:00");
+        assert_eq!(format_remaining(65), "1:05");
+        assert_eq!(format_remaining(3600 + 5 * 60 + 9), "1:05:09");
+    }
+
+    #[test]
+    fn test_next_remaining_elapses() {
+        assert_eq!(next_remaining(3), Some(2));
+        assert_eq!(next_remaining(1), None);
+        assert_eq!(next_remaining(0), None);
+    }
+}
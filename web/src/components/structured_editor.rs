@@ -0,0 +1,461 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! A structured editor for recipes. Steps are edited as cards and serialized
+//! back into recipe text as they change.
+use std::cell::RefCell;
+use std::time::Duration;
+
+use recipes::{parse, serialize, split_divided, unit::Measure, Ingredient, Recipe, Step};
+use sycamore::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{Event, HtmlInputElement, HtmlTextAreaElement};
+
+/// The editable fields of an ingredient.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct IngredientFields {
+    pub amount: String,
+    pub unit: String,
+    pub name: String,
+    pub form: String,
+}
+
+impl From<&Ingredient> for IngredientFields {
+    fn from(i: &Ingredient) -> Self {
+        let amount = i.amt.quantity().to_string();
+        let measure = i.amt.to_string();
+        let unit = measure
+            .strip_prefix(&amount)
+            .unwrap_or(measure.as_str())
+            .trim()
+            .to_owned();
+        Self {
+            amount,
+            unit,
+            name: match &i.display_name {
+                Some(display_name) => format!("\"{}\"", display_name),
+                None => i.name.clone(),
+            },
+            form: i.form_text().unwrap_or_default(),
+        }
+    }
+}
+
+impl IngredientFields {
+    pub fn to_ingredient(&self) -> Result<Ingredient, String> {
+        let name = self.name.trim();
+        if name.is_empty() {
+            return Err("Every ingredient needs a name".to_owned());
+        }
+        let unit = self.unit.trim();
+        let amt = parse::as_measure(&format!("{} {} ", self.amount.trim(), unit))
+            .map_err(|e| format!("Invalid amount for {}: {}", name, e))?;
+        if !unit.is_empty() && unit != "cnt" && matches!(amt, Measure::Count(_)) {
+            return Err(format!("Unknown unit {} for {}", unit, name));
+        }
+        let form = self.form.trim();
+        let (form, divided) = if form.is_empty() {
+            (None, false)
+        } else {
+            split_divided(form)
+        };
+        let mut ingredient = Ingredient::new(name, form, amt);
+        ingredient.divided = divided;
+        Ok(ingredient)
+    }
+}
+
+/// The editable fields of a recipe step.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct StepFields {
+    pub prep_time: Option<Duration>,
+    pub instructions: String,
+    pub ingredients: Vec<IngredientFields>,
+    pub section: Option<String>,
+    /// Sub-recipes aren't editable here but are kept so saving doesn't drop
+    /// them.
+    pub sub_recipes: Vec<String>,
+}
+
+impl From<&Step> for StepFields {
+    fn from(step: &Step) -> Self {
+        Self {
+            prep_time: step.prep_time,
+            instructions: step.instructions.trim().to_owned(),
+            ingredients: step
+                .ingredients
+                .iter()
+                .map(IngredientFields::from)
+                .collect(),
+            section: step.section.clone(),
+            sub_recipes: step.sub_recipes.clone(),
+        }
+    }
+}
+
+impl StepFields {
+    pub fn to_step(&self) -> Result<Step, String> {
+        if self.instructions.trim().is_empty() {
+            return Err("Every step needs instructions".to_owned());
+        }
+        let ingredients = self
+            .ingredients
+            .iter()
+            .map(IngredientFields::to_ingredient)
+            .collect::<Result<Vec<Ingredient>, String>>()?;
+        let mut step =
+            Step::new(self.prep_time, self.instructions.trim()).with_ingredients(ingredients);
+        step.section = self.section.clone();
+        step.sub_recipes = self.sub_recipes.clone();
+        Ok(step)
+    }
+}
+
+/// Serialize the edited steps of a recipe back into recipe text.
+pub fn fields_to_text(recipe: &Recipe, steps: &[StepFields]) -> Result<String, String> {
+    if steps.is_empty() {
+        return Err("A recipe needs at least one step".to_owned());
+    }
+    let mut recipe = recipe.clone();
+    recipe.steps = steps
+        .iter()
+        .map(StepFields::to_step)
+        .collect::<Result<Vec<Step>, String>>()?;
+    let text = serialize::as_recipe_text(&recipe);
+    // NOTE(jwall): Make sure we generated something we can actually use.
+    parse::as_recipe(&text)?;
+    Ok(text)
+}
+
+/// Move the item at idx one position up or down.
+fn move_item<T>(items: &mut Vec<T>, idx: usize, up: bool) {
+    if up && idx > 0 && idx < items.len() {
+        items.swap(idx, idx - 1);
+    } else if !up && idx + 1 < items.len() {
+        items.swap(idx, idx + 1);
+    }
+}
+
+fn input_value(evt: &Event) -> String {
+    evt.target()
+        .and_then(|t| t.dyn_into::<HtmlInputElement>().ok())
+        .map(|el| el.value())
+        .unwrap_or_default()
+}
+
+fn textarea_value(evt: &Event) -> String {
+    evt.target()
+        .and_then(|t| t.dyn_into::<HtmlTextAreaElement>().ok())
+        .map(|el| el.value())
+        .unwrap_or_default()
+}
+
+fn make_ingredient_row<'ctx, G: Html>(
+    cx: Scope<'ctx>,
+    steps: &'ctx RefCell<Vec<StepFields>>,
+    sidx: usize,
+    iidx: usize,
+    ingredient: IngredientFields,
+    sync: &'ctx dyn Fn(),
+    layout: &'ctx Signal<u32>,
+) -> View<G> {
+    let relayout = move || layout.set(*layout.get_untracked() + 1);
+    let amount = create_ref(cx, ingredient.amount);
+    let unit = create_ref(cx, ingredient.unit);
+    let name = create_ref(cx, ingredient.name);
+    let form = create_ref(cx, ingredient.form);
+    view! {cx,
+        tr {
+            td { input(type="text", size=6, value=amount, on:input=move |evt: Event| {
+                steps.borrow_mut()[sidx].ingredients[iidx].amount = input_value(&evt);
+                sync();
+            }) }
+            td { input(type="text", size=6, value=unit, on:input=move |evt: Event| {
+                steps.borrow_mut()[sidx].ingredients[iidx].unit = input_value(&evt);
+                sync();
+            }) }
+            td { input(type="text", value=name, on:input=move |evt: Event| {
+                steps.borrow_mut()[sidx].ingredients[iidx].name = input_value(&evt);
+                sync();
+            }) }
+            td { input(type="text", value=form, on:input=move |evt: Event| {
+                steps.borrow_mut()[sidx].ingredients[iidx].form = input_value(&evt);
+                sync();
+            }) }
+            td {
+                button(on:click=move |_| {
+                    move_item(&mut steps.borrow_mut()[sidx].ingredients, iidx, true);
+                    sync();
+                    relayout();
+                }) { "↑" } " "
+                button(on:click=move |_| {
+                    move_item(&mut steps.borrow_mut()[sidx].ingredients, iidx, false);
+                    sync();
+                    relayout();
+                }) { "↓" } " "
+                button(on:click=move |_| {
+                    steps.borrow_mut()[sidx].ingredients.remove(iidx);
+                    sync();
+                    relayout();
+                }) { "x" }
+            }
+        }
+    }
+}
+
+fn make_step_card<'ctx, G: Html>(
+    cx: Scope<'ctx>,
+    steps: &'ctx RefCell<Vec<StepFields>>,
+    sidx: usize,
+    step: StepFields,
+    sync: &'ctx dyn Fn(),
+    layout: &'ctx Signal<u32>,
+) -> View<G> {
+    let relayout = move || layout.set(*layout.get_untracked() + 1);
+    let ingredient_rows = View::new_fragment(
+        step.ingredients
+            .iter()
+            .cloned()
+            .enumerate()
+            .map(|(iidx, ingredient)| {
+                make_ingredient_row(cx, steps, sidx, iidx, ingredient, sync, layout)
+            })
+            .collect(),
+    );
+    let instructions = create_ref(cx, step.instructions);
+    view! {cx,
+        div(class="step-card") {
+            h3 { "Step " (sidx + 1) }
+            div {
+                button(on:click=move |_| {
+                    move_item(&mut steps.borrow_mut(), sidx, true);
+                    sync();
+                    relayout();
+                }) { "Move up" } " "
+                button(on:click=move |_| {
+                    move_item(&mut steps.borrow_mut(), sidx, false);
+                    sync();
+                    relayout();
+                }) { "Move down" } " "
+                button(on:click=move |_| {
+                    steps.borrow_mut().remove(sidx);
+                    sync();
+                    relayout();
+                }) { "Remove step" }
+            }
+            table(class="ingredients") {
+                tr { th { "Amount" } th { "Unit" } th { "Name" } th { "Form" } th { } }
+                (ingredient_rows)
+            }
+            button(on:click=move |_| {
+                steps.borrow_mut()[sidx].ingredients.push(IngredientFields::default());
+                relayout();
+            }) { "Add ingredient" }
+            div {
+                label { "Instructions" }
+                textarea(rows=5, cols=50, on:input=move |evt: Event| {
+                    steps.borrow_mut()[sidx].instructions = textarea_value(&evt);
+                    sync();
+                }) { (instructions) }
+            }
+        }
+    }
+}
+
+#[derive(Props)]
+pub struct StructuredEditorProps<'ctx> {
+    text: &'ctx Signal<String>,
+    dirty: &'ctx Signal<bool>,
+    error_text: &'ctx Signal<String>,
+}
+
+#[component]
+pub fn StructuredEditor<'ctx, G: Html>(
+    cx: Scope<'ctx>,
+    props: StructuredEditorProps<'ctx>,
+) -> View<G> {
+    let StructuredEditorProps {
+        text,
+        dirty,
+        error_text,
+    } = props;
+    let recipe = match parse::as_recipe(text.get_untracked().as_str()) {
+        Ok(recipe) => recipe,
+        Err(e) => {
+            error_text.set(e);
+            return view! {cx,
+                div(class="parse") { "Fix the recipe text before using the structured editor." }
+            };
+        }
+    };
+    let steps: &'ctx RefCell<Vec<StepFields>> = create_ref(
+        cx,
+        RefCell::new(recipe.steps.iter().map(StepFields::from).collect()),
+    );
+    let recipe = create_ref(cx, recipe);
+    // NOTE(jwall): Edits to the fields don't rerender the cards. Otherwise the
+    // inputs would lose focus as you type. Only structural changes like adding,
+    // removing, or reordering bump the layout.
+    let layout = create_signal(cx, 0u32);
+    let sync: &'ctx dyn Fn() = create_ref(cx, move || {
+        match fields_to_text(recipe, &steps.borrow()) {
+            Ok(serialized) => {
+                text.set(serialized);
+                error_text.set(String::from("No parse errors..."));
+            }
+            Err(e) => error_text.set(e),
+        }
+        dirty.set(true);
+    });
+    view! {cx,
+        div(class="structured-editor") {
+            ({
+                layout.track();
+                let current = steps.borrow().clone();
+                View::new_fragment(
+                    current
+                        .into_iter()
+                        .enumerate()
+                        .map(|(sidx, step)| make_step_card(cx, steps, sidx, step, sync, layout))
+                        .collect(),
+                )
+            })
+            button(on:click=move |_| {
+                steps.borrow_mut().push(StepFields::default());
+                layout.set(*layout.get_untracked() + 1);
+            }) { "Add step" }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use recipes::unit::{Quantity, VolumeMeasure};
+
+    const RECIPE: &str = "title: soup
+
+A hearty soup.
+
+step:
+
+1 cup broth
+2 carrots (diced)
+
+Simmer.
+
+step: 5 min
+
+1 tsp salt
+
+Season to taste.
+";
+
+    fn fields(recipe: &Recipe) -> Vec<StepFields> {
+        recipe.steps.iter().map(StepFields::from).collect()
+    }
+
+    #[test]
+    fn test_ingredient_fields_round_trip() {
+        let ingredient = Ingredient::new(
+            "carrots",
+            Some("diced".to_owned()),
+            Measure::Volume(VolumeMeasure::Cup(Quantity::frac(1, 1, 2))),
+        );
+        let fields = IngredientFields::from(&ingredient);
+        assert_eq!(fields.amount, "1 1/2");
+        assert_eq!(fields.unit, "cups");
+        assert_eq!(fields.form, "diced");
+        assert_eq!(fields.to_ingredient(), Ok(ingredient.clone()));
+
+        let divided = IngredientFields::from(&ingredient.divided());
+        assert_eq!(divided.form, "diced, divided");
+        assert_eq!(
+            divided.to_ingredient().map(|i| (i.form, i.divided)),
+            Ok((Some("diced".to_owned()), true))
+        );
+
+        let count = IngredientFields::from(&Ingredient::new("eggs", None, Measure::count(2)));
+        assert_eq!(count.unit, "");
+        let bad_unit = IngredientFields {
+            unit: "handfuls".to_owned(),
+            ..count.clone()
+        };
+        assert!(bad_unit.to_ingredient().is_err());
+        let no_name = IngredientFields {
+            name: " ".to_owned(),
+            ..count
+        };
+        assert!(no_name.to_ingredient().is_err());
+    }
+
+    #[test]
+    fn test_edited_fields_to_text() {
+        let recipe = parse::as_recipe(RECIPE).expect("Failed to parse recipe");
+        let mut steps = fields(&recipe);
+        // Reorder the steps, edit an ingredient, and add a new one.
+        move_item(&mut steps, 1, true);
+        steps[1].ingredients[0].amount = "2".to_owned();
+        steps[1].ingredients.push(IngredientFields {
+            amount: "1".to_owned(),
+            unit: "can".to_owned(),
+            name: "beans".to_owned(),
+            form: String::new(),
+        });
+        move_item(&mut steps[1].ingredients, 2, true);
+        steps[1].instructions = "Simmer slowly.".to_owned();
+
+        let text = fields_to_text(&recipe, &steps).expect("Failed to serialize recipe");
+        assert_eq!(
+            text,
+            "title: soup
+
+A hearty soup.
+
+step: 5 min
+
+1 tsp salt
+
+Season to taste.
+
+step:
+
+2 cups broth
+1 can beans
+2 carrots (diced)
+
+Simmer slowly.
+"
+        );
+        let reparsed = parse::as_recipe(&text).expect("Failed to parse serialized recipe");
+        assert_eq!(fields(&reparsed), steps_after_reparse(steps));
+    }
+
+    // NOTE(jwall): Reparsing normalizes the amounts and units so we do the same
+    // to our expected fields.
+    fn steps_after_reparse(steps: Vec<StepFields>) -> Vec<StepFields> {
+        steps
+            .iter()
+            .map(|s| StepFields::from(&s.to_step().expect("Invalid step")))
+            .collect()
+    }
+
+    #[test]
+    fn test_invalid_fields_are_rejected() {
+        let recipe = parse::as_recipe(RECIPE).expect("Failed to parse recipe");
+        let mut steps = fields(&recipe);
+        steps[0].instructions = "  ".to_owned();
+        assert!(fields_to_text(&recipe, &steps).is_err());
+        assert!(fields_to_text(&recipe, &[]).is_err());
+    }
+}
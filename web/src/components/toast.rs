@@ -0,0 +1,74 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Toast notifications shown in the `#toast-container` of the ui.
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::{Document, Element};
+
+use crate::js_lib;
+
+/// How long in milliseconds a toast is shown before it's removed. This should
+/// be a little longer than the `--toast-anim-duration` in app.css.
+pub const DEFAULT_TOAST_TIMEOUT: i32 = 4000;
+
+const TOAST_CONTAINER_ID: &str = "toast-container";
+
+fn get_container(document: &Document) -> Option<Element> {
+    if let Some(container) = document.get_element_by_id(TOAST_CONTAINER_ID) {
+        return Some(container);
+    }
+    let container = document.create_element("div").ok()?;
+    container.set_id(TOAST_CONTAINER_ID);
+    container.set_class_name("toast-container");
+    document.body()?.append_child(&container).ok()?;
+    Some(container)
+}
+
+// NOTE(jwall): This gets called from our tracing layer so it must never log
+// anything itself. Any failures are silently ignored.
+fn show_toast(msg: &str, class: &str, timeout: Option<i32>) {
+    let window = js_lib::get_window();
+    let document = match window.document() {
+        Some(document) => document,
+        None => return,
+    };
+    let container = match get_container(&document) {
+        Some(container) => container,
+        None => return,
+    };
+    let output = match document.create_element("output") {
+        Ok(output) => output,
+        Err(_) => return,
+    };
+    let _ = output.set_attribute("role", "status");
+    output.set_class_name(&format!("toast {}", class));
+    output.set_text_content(Some(msg));
+    if container.prepend_with_node_1(&output).is_err() {
+        return;
+    }
+    let remove = Closure::once_into_js(move || output.remove());
+    let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(
+        remove.unchecked_ref(),
+        timeout.unwrap_or(DEFAULT_TOAST_TIMEOUT),
+    );
+}
+
+/// Show an error message toast.
+pub fn error_message(msg: &str, timeout: Option<i32>) {
+    show_toast(msg, "error", timeout);
+}
+
+/// Show an informational message toast.
+pub fn message(msg: &str, timeout: Option<i32>) {
+    show_toast(msg, "info", timeout);
+}
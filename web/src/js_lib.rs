@@ -12,18 +12,19 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 use anyhow::{Context, Result};
+use client_api::SESSION_COOKIE_META;
 use indexed_db::{self, Database, Factory, Transaction};
 use js_sys::Date;
 use std::collections::HashSet;
 use std::future::Future;
 use tracing::error;
-use web_sys::{window, Window};
+use wasm_bindgen::JsCast;
+use web_sys::{window, HtmlDocument, Window};
 
-pub fn get_storage() -> web_sys::Storage {
-    get_window()
-        .local_storage()
-        .expect("Failed to get storage")
-        .expect("No storage available")
+/// Returns the browser's local storage if it is available. It isn't in some
+/// private browsing modes or when storage is disabled.
+pub fn get_storage() -> Option<web_sys::Storage> {
+    get_window().local_storage().ok().flatten()
 }
 
 pub const STATE_STORE_NAME: &'static str = "state-store";
@@ -67,6 +68,16 @@ async fn version1_setup<'db>(
     Ok(())
 }
 
+/// Turn a failure to open the database into a transaction error so callers
+/// can handle it instead of crashing the app. Private browsing modes and
+/// disabled storage both end up here.
+pub fn open_error(err: anyhow::Error) -> indexed_db::Error<std::io::Error> {
+    indexed_db::Error::User(std::io::Error::new(
+        std::io::ErrorKind::Other,
+        format!("Failed to open database: {:?}", err),
+    ))
+}
+
 impl<'name> DBFactory<'name> {
     pub async fn get_indexed_db(&self) -> Result<Database<std::io::Error>> {
         let factory = Factory::<std::io::Error>::get().context("opening IndexedDB")?;
@@ -100,13 +111,11 @@ impl<'name> DBFactory<'name> {
         RetFut: 'static + Future<Output = indexed_db::Result<Ret, std::io::Error>>,
         Ret: 'static,
     {
-        self.get_indexed_db()
-            .await
-            .expect("Failed to open database")
-            .transaction(stores)
-            .rw()
-            .run(transaction)
-            .await
+        let db = match self.get_indexed_db().await {
+            Ok(db) => db,
+            Err(err) => return Err(open_error(err)),
+        };
+        db.transaction(stores).rw().run(transaction).await
     }
 
     pub async fn ro_transaction<Fun, RetFut, Ret>(
@@ -119,17 +128,62 @@ impl<'name> DBFactory<'name> {
         RetFut: 'static + Future<Output = indexed_db::Result<Ret, std::io::Error>>,
         Ret: 'static,
     {
-        self.get_indexed_db()
-            .await
-            .expect("Failed to open database")
-            .transaction(stores)
-            .run(transaction)
-            .await
+        let db = match self.get_indexed_db().await {
+            Ok(db) => db,
+            Err(err) => return Err(open_error(err)),
+        };
+        db.transaction(stores).run(transaction).await
+    }
+}
+
+const DEFAULT_SESSION_COOKIE_NAME: &str = "kitchen-session-cookie";
+
+/// Returns the name of the session cookie the server uses. Instances sharing a
+/// host are configured with distinct names.
+pub fn get_session_cookie_name() -> String {
+    get_window()
+        .document()
+        .and_then(|d| {
+            d.query_selector(&format!("meta[name=\"{}\"]", SESSION_COOKIE_META))
+                .ok()?
+        })
+        .and_then(|m| m.get_attribute("content"))
+        .unwrap_or_else(|| DEFAULT_SESSION_COOKIE_NAME.to_owned())
+}
+
+/// Expire our session cookie in the browser. The attributes have to match the
+/// ones the server set the cookie with.
+pub fn clear_session_cookie() {
+    let window = get_window();
+    if let Some(doc) = window
+        .document()
+        .and_then(|d| d.dyn_into::<HtmlDocument>().ok())
+    {
+        let domain = window.location().hostname().unwrap_or_default();
+        let cookie = format!(
+            "{}=; Domain={}; Path=/; Max-Age=0; Secure; SameSite=Strict",
+            get_session_cookie_name(),
+            domain
+        );
+        if let Err(err) = doc.set_cookie(&cookie) {
+            error!(?err, "Failed to clear session cookie");
+        }
     }
 }
 
-pub fn get_ms_timestamp() -> u32 {
-    Date::new_0().get_milliseconds()
+/// Returns the current time in milliseconds since the unix epoch.
+pub fn get_ms_timestamp() -> f64 {
+    Date::now()
+}
+
+/// Returns a random id for this page load. It only has to tell the open
+/// sessions of one user apart.
+pub fn new_session_id() -> String {
+    format!(
+        "{:x}-{:x}",
+        Date::now() as u64,
+        (js_sys::Math::random() * u32::MAX as f64) as u32
+    )
 }
 
 pub fn get_window() -> Window {
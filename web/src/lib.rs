@@ -13,11 +13,18 @@
 // limitations under the License.
 mod api;
 mod app_state;
+mod batch;
 mod components;
 mod js_lib;
 mod linear;
+mod logging;
 mod pages;
+mod plan_compare;
 mod routing;
+mod search_index;
+mod selectors;
+#[cfg(test)]
+mod test_util;
 mod web;
 
 use sycamore::prelude::*;
@@ -25,30 +32,46 @@ use wasm_bindgen::prelude::wasm_bindgen;
 
 use web::UI;
 
+fn configure_panic_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        console_error_panic_hook::hook(info);
+        components::error_fallback::show_panic_fallback(
+            &components::error_fallback::panic_message(info.payload()),
+        );
+    }));
+}
+
 fn configure_tracing() {
-    console_error_panic_hook::set_once();
-    use tracing::Level;
     use tracing_subscriber::{filter::LevelFilter, fmt::format::Pretty, prelude::*};
     use tracing_web::{performance_layer, MakeConsoleWriter};
+    let runtime_filter = logging::get_runtime_log_filter();
+    let (filter, filter_err) = match logging::make_filter(runtime_filter.as_deref()) {
+        Ok(filter) => (filter, None),
+        Err(e) => (logging::default_filter(), Some(e)),
+    };
     let fmt_layer = tracing_subscriber::fmt::layer()
         .with_ansi(false)
         .without_time()
         .with_writer(MakeConsoleWriter) // write events to the console
-        .with_filter(LevelFilter::from(if cfg!(feature = "debug_logs") {
-            Level::DEBUG
-        } else {
-            Level::INFO
-        }));
+        .with_filter(filter);
     let perf_layer = performance_layer().with_details_from_fields(Pretty::default());
+    let toast_layer =
+        logging::ToastLayer::new(|msg: &str| components::toast::error_message(msg, None))
+            .with_filter(LevelFilter::ERROR);
 
     tracing_subscriber::registry()
         .with(perf_layer)
         .with(fmt_layer)
+        .with(toast_layer)
         .init();
+    if let Some(e) = filter_err {
+        tracing::warn!(err = %e, "Using the default log filter");
+    }
 }
 
 #[wasm_bindgen(start)]
 pub fn main() {
+    configure_panic_hook();
     configure_tracing();
     sycamore::render(|cx| view! { cx, UI() });
 }
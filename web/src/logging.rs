@@ -0,0 +1,190 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Tracing configuration for the ui.
+use std::fmt::Debug;
+use std::str::FromStr;
+
+use client_api::LOG_FILTER_META;
+use tracing::{
+    field::{Field, Visit},
+    Event, Level, Subscriber,
+};
+use tracing_subscriber::{filter::Targets, layer::Context, Layer};
+
+use crate::js_lib;
+
+/// Returns the log filter injected into the page by the server if there is one.
+pub fn get_runtime_log_filter() -> Option<String> {
+    js_lib::get_window()
+        .document()?
+        .query_selector(&format!("meta[name=\"{}\"]", LOG_FILTER_META))
+        .ok()??
+        .get_attribute("content")
+}
+
+/// The default per module log levels. Api requests are noisy so we only log
+/// their warnings unless this is a debug_logs build.
+pub fn default_filter() -> Targets {
+    if cfg!(feature = "debug_logs") {
+        Targets::new().with_default(Level::DEBUG)
+    } else {
+        Targets::new()
+            .with_default(Level::INFO)
+            .with_target("kitchen_wasm::api", Level::WARN)
+    }
+}
+
+/// Construct the log filter from a runtime filter like
+/// `info,kitchen_wasm::api=debug`. Uses the default filter if there is no
+/// runtime filter.
+pub fn make_filter(runtime_filter: Option<&str>) -> Result<Targets, String> {
+    match runtime_filter {
+        Some(filter) => {
+            Targets::from_str(filter).map_err(|e| format!("Invalid log filter {}: {}", filter, e))
+        }
+        None => Ok(default_filter()),
+    }
+}
+
+/// Collects the message and fields of an event.
+#[derive(Default)]
+struct EventFields {
+    message: Option<String>,
+    fields: Vec<(String, String)>,
+}
+
+impl Visit for EventFields {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if field.name() == "message" {
+            self.message = Some(format!("{:?}", value));
+        } else {
+            self.fields
+                .push((field.name().to_owned(), format!("{:?}", value)));
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = Some(value.to_owned());
+        } else {
+            self.fields
+                .push((field.name().to_owned(), value.to_owned()));
+        }
+    }
+}
+
+/// Maps an event to the message shown in the error banner. Only error events
+/// are shown.
+fn event_to_banner(event: &Event<'_>) -> Option<String> {
+    if *event.metadata().level() != Level::ERROR {
+        return None;
+    }
+    let mut fields = EventFields::default();
+    event.record(&mut fields);
+    let details = fields
+        .fields
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<String>>()
+        .join(", ");
+    match (fields.message, details.is_empty()) {
+        (Some(msg), true) => Some(msg),
+        (Some(msg), false) => Some(format!("{} ({})", msg, details)),
+        (None, false) => Some(details),
+        (None, true) => Some(format!("Error in {}", event.metadata().target())),
+    }
+}
+
+/// A tracing layer that routes error events into the toast error banner.
+pub struct ToastLayer<F> {
+    show: F,
+}
+
+impl<F> ToastLayer<F>
+where
+    F: Fn(&str) + Send + Sync + 'static,
+{
+    pub fn new(show: F) -> Self {
+        Self { show }
+    }
+}
+
+impl<S, F> Layer<S> for ToastLayer<F>
+where
+    S: Subscriber,
+    F: Fn(&str) + Send + Sync + 'static,
+{
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if let Some(msg) = event_to_banner(event) {
+            (self.show)(&msg);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tracing::{debug, error, info, warn};
+    use tracing_subscriber::prelude::*;
+
+    use super::*;
+
+    fn collect_banners<Body: FnOnce()>(body: Body) -> Vec<String> {
+        let banners = Arc::new(Mutex::new(Vec::new()));
+        let sink = banners.clone();
+        let subscriber = tracing_subscriber::registry().with(ToastLayer::new(move |msg: &str| {
+            sink.lock().unwrap().push(msg.to_owned())
+        }));
+        tracing::subscriber::with_default(subscriber, body);
+        let banners = banners.lock().unwrap().clone();
+        banners
+    }
+
+    #[test]
+    fn test_only_errors_become_banners() {
+        let banners = collect_banners(|| {
+            debug!("debugging");
+            info!("informing");
+            warn!("warning");
+            error!("Failed to save recipe");
+        });
+        assert_eq!(banners, vec!["Failed to save recipe".to_owned()]);
+    }
+
+    #[test]
+    fn test_error_fields_are_included_in_banner() {
+        let banners = collect_banners(|| {
+            error!(recipe = "soup", status = 500, "Failed to save recipe");
+            error!(err = ?"boom");
+        });
+        assert_eq!(
+            banners,
+            vec![
+                "Failed to save recipe (recipe=soup, status=500)".to_owned(),
+                "err=\"boom\"".to_owned(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_make_filter() {
+        let filter = make_filter(None).expect("default filter");
+        assert!(filter.would_enable("kitchen_wasm::app_state", &Level::INFO));
+        let filter = make_filter(Some("warn,kitchen_wasm::api=debug")).expect("valid filter");
+        assert!(filter.would_enable("kitchen_wasm::api", &Level::DEBUG));
+        assert!(!filter.would_enable("kitchen_wasm::app_state", &Level::INFO));
+        assert!(make_filter(Some("kitchen_wasm=loud")).is_err());
+    }
+}
@@ -0,0 +1,83 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use chrono::NaiveDate;
+use sycamore::prelude::*;
+
+use crate::{
+    app_state::{Message, StateHandler},
+    components::loading::Loading,
+    selectors,
+};
+
+fn today() -> NaiveDate {
+    chrono::offset::Local::now().naive_local().date()
+}
+
+/// An overview of the user's recipes and plans.
+#[component]
+pub fn HomePage<'ctx, G: Html>(cx: Scope<'ctx>, sh: StateHandler<'ctx>) -> View<G> {
+    let data = sh.get_selector(cx, |state| {
+        selectors::dashboard(state.get().as_ref(), today())
+    });
+    let status = sh.get_selector(cx, |state| selectors::load_status(state.get().as_ref()));
+    let recipe_count = create_memo(cx, move || data.get().recipe_count);
+    let planned = create_memo(cx, move || match data.get().selected_plan_date {
+        Some(date) => format!(
+            "{} recipes planned for {}",
+            data.get().planned_recipes,
+            date
+        ),
+        None => "No plan selected".to_owned(),
+    });
+    let upcoming = create_memo(cx, move || data.get().upcoming_plans.clone());
+    view! {cx,
+        div {
+            h2 { "Home" }
+            Loading(status=status) {
+                p { (recipe_count.get()) " recipes" }
+                p { (planned.get()) }
+                h3 { "Upcoming Plans" }
+                (if upcoming.get().is_empty() {
+                    view! {cx, p { "No upcoming plans" } }
+                } else {
+                    View::empty()
+                })
+                ul(class="no-list") {
+                    Indexed(
+                        iterable=upcoming,
+                        view=move |cx, date| {
+                            let date_display = format!("{}", date);
+                            view! {cx,
+                                li {
+                                    button(class="outline", on:click=move |_| {
+                                        sh.dispatch(cx, Message::SelectPlanDate(date, Some(Box::new(|| {
+                                            sycamore_router::navigate("/ui/planning/plan");
+                                        }))))
+                                    }) { (date_display) }
+                                }
+                            }
+                        },
+                    )
+                }
+                button(on:click=move |_| {
+                    sh.dispatch(cx, Message::SelectPlanDate(today(), Some(Box::new(|| {
+                        sycamore_router::navigate("/ui/planning/plan");
+                    }))))
+                }) {
+                    "Start Plan for Today"
+                }
+            }
+        }
+    }
+}
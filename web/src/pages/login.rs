@@ -11,11 +11,32 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+use client_api::UserSettings;
 use sycamore::futures::spawn_local_scoped;
 use sycamore::prelude::*;
-use tracing::{debug, info};
+use tracing::{debug, error, info};
 
-use crate::app_state::{Message, StateHandler};
+use crate::{
+    app_state::{Message, StateHandler},
+    js_lib, routing,
+};
+
+/// Where we land after logging in if the user hasn't chosen somewhere else.
+const DEFAULT_LANDING_ROUTE: &str = "/ui/home";
+
+/// The route to navigate to after logging in.
+fn landing_route(settings: Option<&UserSettings>) -> String {
+    settings
+        .and_then(|s| s.landing_route())
+        .unwrap_or(DEFAULT_LANDING_ROUTE)
+        .to_owned()
+}
+
+/// The route we were sent to the login page from if it still exists.
+fn next_route() -> Option<String> {
+    let search = js_lib::get_window().location().search().ok()?;
+    routing::return_route(&search)
+}
 
 #[component]
 pub fn LoginForm<'ctx, G: Html>(cx: Scope<'ctx>, sh: StateHandler<'ctx>) -> View<G> {
@@ -40,7 +61,16 @@ pub fn LoginForm<'ctx, G: Html>(cx: Scope<'ctx>, sh: StateHandler<'ctx>) -> View
                         debug!("authenticating against ui");
                         if let Some(user_data) = store.authenticate(username, password).await {
                             sh.dispatch(cx, Message::SetUserData(user_data));
-                            sh.dispatch(cx, Message::LoadState(Some(Box::new(|| sycamore_router::navigate("/ui/planning/plan")))));
+                            let settings = match store.fetch_settings().await {
+                                Ok(settings) => Some(settings),
+                                Err(err) => {
+                                    error!(?err, "Failed to fetch settings");
+                                    None
+                                }
+                            };
+                            let route = next_route()
+                                .unwrap_or_else(|| landing_route(settings.as_ref()));
+                            sh.dispatch(cx, Message::LoadState(Some(Box::new(move || sycamore_router::navigate(&route)))));
                         }
                     });
                 }
@@ -56,3 +86,27 @@ pub fn LoginPage<'ctx, G: Html>(cx: Scope<'ctx>, sh: StateHandler<'ctx>) -> View
             LoginForm(sh)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_landing_route() {
+        assert_eq!(landing_route(None), DEFAULT_LANDING_ROUTE);
+        assert_eq!(
+            landing_route(Some(&UserSettings::default())),
+            DEFAULT_LANDING_ROUTE
+        );
+        let settings = UserSettings {
+            default_route: Some("/ui/planning/inventory".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(landing_route(Some(&settings)), "/ui/planning/inventory");
+        let settings = UserSettings {
+            default_route: Some("https://example.com/".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(landing_route(Some(&settings)), DEFAULT_LANDING_ROUTE);
+    }
+}
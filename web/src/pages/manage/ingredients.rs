@@ -12,7 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 use super::ManagePage;
-use crate::{app_state::StateHandler, components::categories::*};
+use crate::{
+    api::CachedCollection,
+    app_state::StateHandler,
+    components::{cached_data::CachedDataBadge, categories::*},
+};
 
 use sycamore::prelude::*;
 
@@ -21,6 +25,10 @@ pub fn IngredientsPage<'ctx, G: Html>(cx: Scope<'ctx>, sh: StateHandler<'ctx>) -
     view! {cx,
         ManagePage(
             selected=Some("Ingredients".to_owned()),
-        ) { Categories(sh) }
+        ) {
+            CachedDataBadge(sh=sh, collections=vec![CachedCollection::Recipes, CachedCollection::Categories])
+            Categories(sh)
+            StoreLayout(sh)
+        }
     }
 }
@@ -16,10 +16,12 @@ use sycamore::prelude::*;
 
 pub mod add_recipe;
 pub mod ingredients;
+pub mod settings;
 pub mod staples;
 
 pub use add_recipe::*;
 pub use ingredients::*;
+pub use settings::*;
 pub use staples::*;
 
 #[derive(Props)]
@@ -36,6 +38,7 @@ pub fn ManagePage<'a, G: Html>(cx: Scope<'a>, state: PageState<'a, G>) -> View<G
         ("/ui/manage/ingredients".to_owned(), "Ingredients"),
         ("/ui/manage/staples".to_owned(), "Staples"),
         ("/ui/manage/new_recipe".to_owned(), "New Recipe"),
+        ("/ui/manage/settings".to_owned(), "Settings"),
     ];
 
     view! {cx,
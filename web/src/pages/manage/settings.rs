@@ -0,0 +1,71 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use super::ManagePage;
+use crate::app_state::{Message, StateHandler};
+
+use client_api::LANDING_ROUTES;
+use sycamore::prelude::*;
+
+/// A readable name for a landing route.
+fn route_label(route: &str) -> &str {
+    match route {
+        "/ui/home" => "Home",
+        "/ui/planning/select" => "Select Plan",
+        "/ui/planning/plan" => "Meal Plan",
+        "/ui/planning/inventory" => "Shopping List",
+        "/ui/planning/cook" => "Cook",
+        "/ui/manage/ingredients" => "Ingredients",
+        "/ui/manage/staples" => "Staples",
+        "/ui/manage/new_recipe" => "New Recipe",
+        route => route,
+    }
+}
+
+#[component()]
+pub fn SettingsPage<'ctx, G: Html>(cx: Scope<'ctx>, sh: StateHandler<'ctx>) -> View<G> {
+    let current_route = sh.get_selector(cx, |state| {
+        state.get().default_route.clone().unwrap_or_default()
+    });
+    let default_route = create_signal(cx, current_route.get_untracked().as_ref().clone());
+    let routes = create_signal(
+        cx,
+        LANDING_ROUTES
+            .iter()
+            .map(|r| r.to_string())
+            .collect::<Vec<String>>(),
+    );
+    view! {cx,
+        ManagePage(
+            selected=Some("Settings".to_owned()),
+        ) {
+            label(for="default_route") { "Landing Page" }
+            select(bind:value=default_route, name="default_route", id="default_route", on:change=move |_| {
+                let route = default_route.get_untracked().as_ref().clone();
+                sh.dispatch(cx, Message::UpdateDefaultRoute(if route.is_empty() { None } else { Some(route) }));
+            }) {
+                option(value="", selected=default_route.get_untracked().is_empty()) { "Default" }
+                Indexed(
+                    iterable=routes,
+                    view=move |cx, route| {
+                        let selected = *default_route.get_untracked() == route;
+                        let label = route_label(&route).to_owned();
+                        view! {cx,
+                            option(value=route, selected=selected) { (label) }
+                        }
+                    }
+                )
+            }
+        }
+    }
+}
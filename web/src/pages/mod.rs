@@ -11,11 +11,13 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+mod home;
 mod login;
 mod manage;
 mod planning;
 mod recipe;
 
+pub use home::*;
 pub use login::*;
 pub use manage::*;
 pub use planning::*;
@@ -0,0 +1,183 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use sycamore::{futures::spawn_local_scoped, prelude::*};
+use tracing::error;
+
+use super::PlanningPage;
+use crate::{
+    api::HttpStore,
+    app_state::{AppState, StateHandler},
+    plan_compare::{compare_plans, plan_counts, PlanComparison},
+    selectors::display_amt,
+};
+
+/// Fetch the recipe counts for a plan date.
+async fn fetch_counts(store: &HttpStore, date: &str) -> Option<BTreeMap<String, u32>> {
+    let date = match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+        Ok(date) => date,
+        Err(_) => return None,
+    };
+    match store.fetch_plan_for_date(&date).await {
+        Ok(plan) => Some(plan_counts(plan.unwrap_or_default())),
+        Err(err) => {
+            error!(?err, %date, "Failed to fetch plan");
+            None
+        }
+    }
+}
+
+fn recipe_title(state: &AppState, id: &str) -> String {
+    state
+        .recipes
+        .get(id)
+        .map(|r| r.title.clone())
+        .unwrap_or_else(|| id.to_owned())
+}
+
+#[component]
+pub fn ComparePage<'ctx, G: Html>(cx: Scope<'ctx>, sh: StateHandler<'ctx>) -> View<G> {
+    let store = HttpStore::get_from_context(cx);
+    let state = sh.get_selector(cx, |state| state.get().as_ref().clone());
+    let current_plan = sh.get_selector(cx, |state| state.get().selected_plan_date);
+    let plan_dates = sh.get_selector(cx, |state| {
+        let mut plans = state
+            .get()
+            .plan_dates
+            .iter()
+            .map(|d| format!("{}", d))
+            .collect::<Vec<String>>();
+        plans.sort_unstable_by(|d1, d2| d2.cmp(d1));
+        plans
+    });
+    let date_a = create_signal(cx, String::new());
+    let date_b = create_signal(cx, String::new());
+    let comparison = create_signal(cx, None::<PlanComparison>);
+    let date_options = move |cx: Scope<'ctx>| -> View<G> {
+        view! {cx,
+            option(value="") { "Choose a plan" }
+            Indexed(
+                iterable=plan_dates,
+                view=|cx, date| {
+                    let value = date.clone();
+                    view! {cx,
+                        option(value=value) { (date) }
+                    }
+                }
+            )
+        }
+    };
+    let rows = create_memo(cx, move || {
+        let state = state.get();
+        let comparison = match comparison.get().as_ref() {
+            Some(comparison) => comparison.clone(),
+            None => return None,
+        };
+        let recipes =
+            comparison
+                .only_in_a
+                .iter()
+                .map(|(id, count)| (recipe_title(&state, id), count.to_string(), "-".to_owned()))
+                .chain(comparison.only_in_b.iter().map(|(id, count)| {
+                    (recipe_title(&state, id), "-".to_owned(), count.to_string())
+                }))
+                .chain(
+                    comparison
+                        .count_changes
+                        .iter()
+                        .map(|(id, a, b)| (recipe_title(&state, id), a.to_string(), b.to_string())),
+                )
+                .collect::<Vec<(String, String, String)>>();
+        let ingredients = comparison
+            .ingredient_deltas
+            .iter()
+            .map(|delta| {
+                let amt = |amt: &Option<_>| {
+                    amt.as_ref()
+                        .map(|amt| display_amt(&state, amt))
+                        .unwrap_or_else(|| "-".to_owned())
+                };
+                (delta.name.clone(), amt(&delta.a), amt(&delta.b))
+            })
+            .collect::<Vec<(String, String, String)>>();
+        Some((recipes, ingredients))
+    });
+    let recipe_rows = create_memo(cx, move || {
+        rows.get()
+            .as_ref()
+            .as_ref()
+            .map(|(recipes, _)| recipes.clone())
+            .unwrap_or_default()
+    });
+    let ingredient_rows = create_memo(cx, move || {
+        rows.get()
+            .as_ref()
+            .as_ref()
+            .map(|(_, ingredients)| ingredients.clone())
+            .unwrap_or_default()
+    });
+    view! {cx,
+        PlanningPage(
+            selected=None,
+            plan_date = current_plan,
+        ) {
+            div(class="row-flex margin-bot-half") {
+                label(for="plan_a") { "Plan A" }
+                select(bind:value=date_a, name="plan_a", id="plan_a") { (date_options(cx)) }
+                label(for="plan_b") { "Plan B" }
+                select(bind:value=date_b, name="plan_b", id="plan_b") { (date_options(cx)) }
+                button(on:click=move |_| {
+                    let (a, b) = (date_a.get_untracked().as_ref().clone(), date_b.get_untracked().as_ref().clone());
+                    if a.is_empty() || b.is_empty() {
+                        return;
+                    }
+                    let store = store.clone();
+                    spawn_local_scoped(cx, async move {
+                        if let (Some(a), Some(b)) = (fetch_counts(&store, &a).await, fetch_counts(&store, &b).await) {
+                            comparison.set(Some(compare_plans(state.get_untracked().as_ref(), &a, &b)));
+                        }
+                    });
+                }) { "Compare" }
+            }
+            (if rows.get().is_some() {
+                view! {cx,
+                    h2() { "Recipes" }
+                    table() {
+                        tr() { th() { "Recipe" } th() { "Plan A" } th() { "Plan B" } }
+                        Indexed(
+                            iterable=recipe_rows,
+                            view=|cx, (name, a, b)| view! {cx,
+                                tr() { td() { (name) } td() { (a) } td() { (b) } }
+                            },
+                        )
+                    }
+                    h2() { "Shopping List" }
+                    table() {
+                        tr() { th() { "Ingredient" } th() { "Plan A" } th() { "Plan B" } }
+                        Indexed(
+                            iterable=ingredient_rows,
+                            view=|cx, (name, a, b)| view! {cx,
+                                tr() { td() { (name) } td() { (a) } td() { (b) } }
+                            },
+                        )
+                    }
+                }
+            } else {
+                View::empty()
+            })
+        }
+    }
+}
@@ -14,17 +14,26 @@
 use sycamore::prelude::*;
 
 use super::PlanningPage;
-use crate::{app_state::StateHandler, components::recipe_list::*};
+use crate::{
+    api::CachedCollection,
+    app_state::StateHandler,
+    components::{cached_data::CachedDataBadge, loading::Loading, recipe_list::*},
+    selectors,
+};
 
 #[component]
 pub fn CookPage<'ctx, G: Html>(cx: Scope<'ctx>, sh: StateHandler<'ctx>) -> View<G> {
     let current_plan = sh.get_selector(cx, |state| {
         state.get().selected_plan_date
     });
+    let status = sh.get_selector(cx, |state| selectors::load_status(state.get().as_ref()));
     view! {cx,
         PlanningPage(
             selected=Some("Cook".to_owned()),
             plan_date = current_plan,
-        ) { RecipeList(sh) }
+        ) {
+            CachedDataBadge(sh=sh, collections=vec![CachedCollection::Recipes, CachedCollection::Plans])
+            Loading(status=status) { RecipeList(sh) }
+        }
     }
 }
@@ -14,17 +14,26 @@
 use sycamore::prelude::*;
 
 use super::PlanningPage;
-use crate::{app_state::StateHandler, components::shopping_list::*};
+use crate::{
+    api::CachedCollection,
+    app_state::StateHandler,
+    components::{cached_data::CachedDataBadge, loading::Loading, shopping_list::*},
+    selectors,
+};
 
 #[component]
 pub fn InventoryPage<'ctx, G: Html>(cx: Scope<'ctx>, sh: StateHandler<'ctx>) -> View<G> {
     let current_plan = sh.get_selector(cx, |state| {
         state.get().selected_plan_date
     });
+    let status = sh.get_selector(cx, |state| selectors::load_status(state.get().as_ref()));
     view! {cx,
         PlanningPage(
             selected=Some("Inventory".to_owned()),
             plan_date = current_plan,
-        ) { ShoppingList(sh) }
+        ) {
+            CachedDataBadge(sh=sh, collections=CachedCollection::ALL.to_vec())
+            Loading(status=status) { ShoppingList(sh) }
+        }
     }
 }
@@ -15,11 +15,13 @@ use crate::components::tabs::*;
 use chrono::NaiveDate;
 use sycamore::prelude::*;
 
+pub mod compare;
 pub mod cook;
 pub mod inventory;
 pub mod plan;
 pub mod select;
 
+pub use compare::*;
 pub use cook::*;
 pub use inventory::*;
 pub use plan::*;
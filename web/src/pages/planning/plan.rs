@@ -12,7 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 use super::PlanningPage;
-use crate::{app_state::StateHandler, components::recipe_plan::*};
+use crate::{
+    api::CachedCollection,
+    app_state::StateHandler,
+    components::{cached_data::CachedDataBadge, loading::Loading, recipe_plan::*},
+    selectors,
+};
 
 use sycamore::prelude::*;
 
@@ -21,10 +26,14 @@ pub fn PlanPage<'ctx, G: Html>(cx: Scope<'ctx>, sh: StateHandler<'ctx>) -> View<
     let current_plan = sh.get_selector(cx, |state| {
         state.get().selected_plan_date
     });
+    let status = sh.get_selector(cx, |state| selectors::load_status(state.get().as_ref()));
     view! {cx,
         PlanningPage(
             selected=Some("Plan".to_owned()),
             plan_date = current_plan,
-        ) { RecipePlan(sh) }
+        ) {
+            CachedDataBadge(sh=sh, collections=vec![CachedCollection::Recipes, CachedCollection::Plans])
+            Loading(status=status) { RecipePlan(sh) }
+        }
     }
 }
@@ -14,7 +14,8 @@
 use super::PlanningPage;
 use crate::{
     app_state::{Message, StateHandler},
-    components::PlanList,
+    api::CachedCollection,
+    components::{cached_data::CachedDataBadge, PlanList},
 };
 
 use chrono::NaiveDate;
@@ -35,11 +36,21 @@ pub fn SelectPage<'ctx, G: Html>(cx: Scope<'ctx>, sh: StateHandler<'ctx>) -> Vie
     let current_plan = sh.get_selector(cx, |state| {
         state.get().selected_plan_date
     });
+    let templates = sh.get_selector(cx, |state| {
+        state
+            .get()
+            .plan_templates
+            .iter()
+            .cloned()
+            .collect::<Vec<String>>()
+    });
+    let selected_template = create_signal(cx, String::new());
     view! {cx,
         PlanningPage(
             selected=Some("Select".to_owned()),
             plan_date = current_plan,
         ) {
+            CachedDataBadge(sh=sh, collections=vec![CachedCollection::Recipes, CachedCollection::Plans])
             PlanList(sh=sh, list=plan_dates)
             button(on:click=move |_| {
                 sh.dispatch(cx, Message::SelectPlanDate(chrono::offset::Local::now().naive_local().date(), Some(Box::new(|| {
@@ -48,6 +59,30 @@ pub fn SelectPage<'ctx, G: Html>(cx: Scope<'ctx>, sh: StateHandler<'ctx>) -> Vie
             }) {
                 "Start Plan for Today"
             }
+            label(for="plan_template") { "Template" }
+            select(bind:value=selected_template, name="plan_template", id="plan_template") {
+                option(value="") { "Choose a template" }
+                Indexed(
+                    iterable=templates,
+                    view=|cx, name| {
+                        let value = name.clone();
+                        view! {cx,
+                            option(value=value) { (name) }
+                        }
+                    }
+                )
+            }
+            button(on:click=move |_| {
+                let name = selected_template.get_untracked().as_ref().clone();
+                if name.is_empty() {
+                    return;
+                }
+                sh.dispatch(cx, Message::ApplyTemplate(name, chrono::offset::Local::now().naive_local().date(), Some(Box::new(|| {
+                    sycamore_router::navigate("/ui/planning/plan");
+                }))))
+            }) {
+                "Start Plan for Today from Template"
+            }
         }
     }
 }
@@ -13,7 +13,7 @@
 // limitations under the License.
 use sycamore::prelude::*;
 
-use crate::{app_state::StateHandler, components::tabs::*};
+use crate::{app_state::StateHandler, components::tabs::*, routing};
 
 mod edit;
 mod view;
@@ -42,8 +42,8 @@ pub fn RecipePage<'ctx, G: Html>(cx: Scope<'ctx>, state: PageState<'ctx, G>) ->
     } = state;
     let children = children.call(cx);
     let recipe_tabs: Vec<(String, &'static str)> = vec![
-        (format!("/ui/recipe/view/{}", recipe), "View"),
-        (format!("/ui/recipe/edit/{}", recipe), "Edit"),
+        (routing::recipe_view_route(&recipe), "View"),
+        (routing::recipe_edit_route(&recipe), "Edit"),
     ];
     view! {cx,
         TabbedView(
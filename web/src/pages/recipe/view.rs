@@ -26,6 +26,6 @@ pub fn RecipeViewPage<'ctx, G: Html>(cx: Scope<'ctx>, props: RecipePageProps<'ct
         RecipePage(
             selected=Some("View".to_owned()),
             recipe=recipe.clone(),
-        ) { Viewer(recipe_id=recipe, sh=sh) }
+        ) { Viewer(recipe_id=recipe, sh=sh, timers=false) }
     }
 }
@@ -0,0 +1,186 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::collections::{BTreeMap, BTreeSet};
+
+use recipes::{unit::Measure, IngredientKey};
+
+use crate::{app_state::AppState, selectors::shopping_list_data};
+
+/// How an ingredient's total changed between two plans.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IngredientDelta {
+    pub key: IngredientKey,
+    pub name: String,
+    pub a: Option<Measure>,
+    pub b: Option<Measure>,
+}
+
+/// The differences between plan A and plan B.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PlanComparison {
+    /// Recipes and their counts that are only planned in A.
+    pub only_in_a: Vec<(String, u32)>,
+    /// Recipes and their counts that are only planned in B.
+    pub only_in_b: Vec<(String, u32)>,
+    /// Recipes planned in both with different counts. (recipe, count in A, count in B)
+    pub count_changes: Vec<(String, u32, u32)>,
+    /// Shopping list totals that differ between the plans.
+    pub ingredient_deltas: Vec<IngredientDelta>,
+}
+
+/// Turn a plan as returned by the server into recipe counts leaving out the
+/// recipes that aren't planned.
+pub fn plan_counts(plan: Vec<(String, i32)>) -> BTreeMap<String, u32> {
+    plan.into_iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(id, count)| (id, count as u32))
+        .collect()
+}
+
+/// Compare the recipe counts of two plans and the shopping lists they produce
+/// using the recipes in `state`. Staples are left out since they are the same
+/// for both plans.
+pub fn compare_plans(
+    state: &AppState,
+    a: &BTreeMap<String, u32>,
+    b: &BTreeMap<String, u32>,
+) -> PlanComparison {
+    let mut comparison = PlanComparison::default();
+    let planned = |counts: &BTreeMap<String, u32>, id: &str| {
+        counts.get(id).cloned().filter(|count| *count > 0)
+    };
+    let ids = a.keys().chain(b.keys()).collect::<BTreeSet<&String>>();
+    for id in ids {
+        match (planned(a, id), planned(b, id)) {
+            (Some(count), None) => comparison.only_in_a.push((id.clone(), count)),
+            (None, Some(count)) => comparison.only_in_b.push((id.clone(), count)),
+            (Some(a_count), Some(b_count)) if a_count != b_count => comparison
+                .count_changes
+                .push((id.clone(), a_count, b_count)),
+            _ => (),
+        }
+    }
+
+    let mut plan_state = state.clone();
+    plan_state.recipe_counts = a.clone();
+    let mut a_list = shopping_list_data(&plan_state, false).ingredients;
+    plan_state.recipe_counts = b.clone();
+    let mut b_list = shopping_list_data(&plan_state, false).ingredients;
+    let keys = a_list
+        .keys()
+        .chain(b_list.keys())
+        .cloned()
+        .collect::<BTreeSet<IngredientKey>>();
+    for key in keys {
+        let a_ingredient = a_list.remove(&key).map(|(i, _)| i);
+        let b_ingredient = b_list.remove(&key).map(|(i, _)| i);
+        let a_amt = a_ingredient.as_ref().map(|i| i.amt.normalize());
+        let b_amt = b_ingredient.as_ref().map(|i| i.amt.normalize());
+        if a_amt == b_amt {
+            continue;
+        }
+        let name = a_ingredient
+            .or(b_ingredient)
+            .map(|i| i.display_name.unwrap_or(i.name))
+            .unwrap_or_else(|| key.name().to_owned());
+        comparison.ingredient_deltas.push(IngredientDelta {
+            key,
+            name,
+            a: a_amt,
+            b: b_amt,
+        });
+    }
+    comparison
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::recipe_with_ingredients;
+    use recipes::Ingredient;
+
+    fn state() -> AppState {
+        let mut state = AppState::new();
+        state.recipes.insert(
+            "soup".to_owned(),
+            recipe_with_ingredients(
+                "soup",
+                vec![
+                    Ingredient::new("onion", None, Measure::count(1)),
+                    Ingredient::new("carrot", None, Measure::count(2)),
+                ],
+            ),
+        );
+        state.recipes.insert(
+            "salad".to_owned(),
+            recipe_with_ingredients(
+                "salad",
+                vec![Ingredient::new("lettuce", None, Measure::count(1))],
+            ),
+        );
+        state.recipes.insert(
+            "stew".to_owned(),
+            recipe_with_ingredients(
+                "stew",
+                vec![Ingredient::new("carrot", None, Measure::count(2))],
+            ),
+        );
+        state
+    }
+
+    #[test]
+    fn test_plan_counts_drops_unplanned() {
+        assert_eq!(
+            plan_counts(vec![("soup".to_owned(), 2), ("salad".to_owned(), 0)]),
+            BTreeMap::from([("soup".to_owned(), 2)])
+        );
+    }
+
+    #[test]
+    fn test_compare_plans() {
+        let a = BTreeMap::from([
+            ("soup".to_owned(), 1),
+            ("salad".to_owned(), 1),
+            ("stew".to_owned(), 1),
+        ]);
+        let b = BTreeMap::from([
+            ("soup".to_owned(), 2),
+            ("stew".to_owned(), 1),
+            ("salad".to_owned(), 0),
+        ]);
+        let comparison = compare_plans(&state(), &a, &b);
+        assert_eq!(comparison.only_in_a, vec![("salad".to_owned(), 1)]);
+        assert!(comparison.only_in_b.is_empty());
+        assert_eq!(comparison.count_changes, vec![("soup".to_owned(), 1, 2)]);
+        let deltas = comparison
+            .ingredient_deltas
+            .iter()
+            .map(|d| (d.name.as_str(), d.a.clone(), d.b.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            deltas,
+            vec![
+                ("carrot", Some(Measure::count(4)), Some(Measure::count(6))),
+                ("lettuce", Some(Measure::count(1)), None),
+                ("onion", Some(Measure::count(1)), Some(Measure::count(2))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compare_identical_plans() {
+        let a = BTreeMap::from([("soup".to_owned(), 1)]);
+        assert_eq!(compare_plans(&state(), &a, &a), PlanComparison::default());
+    }
+}
@@ -12,13 +12,25 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{app_state::StateHandler, components::Header, pages::*};
+use crate::{
+    app_state::{StateHandler, LOGIN_ROUTE},
+    components::Header,
+    pages::*,
+};
+use std::cell::{Cell, RefCell};
+
 use sycamore::prelude::*;
-use sycamore_router::{HistoryIntegration, Route, Router};
+use sycamore_router::{HistoryIntegration, Integration, Route, Router};
 use tracing::{debug, instrument};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::{BeforeUnloadEvent, Element, Event};
+
+use crate::js_lib;
 
 #[derive(Route, Debug)]
 pub enum Routes {
+    #[to("/ui/home")]
+    Home,
     #[to("/ui/planning/<_..>")]
     Planning(PlanningRoutes),
     #[to("/ui/recipe/<_..>")]
@@ -52,6 +64,8 @@ pub enum ManageRoutes {
     Ingredients,
     #[to("/staples")]
     Staples,
+    #[to("/settings")]
+    Settings,
     #[not_found]
     NotFound,
 }
@@ -66,10 +80,199 @@ pub enum PlanningRoutes {
     Inventory,
     #[to("/cook")]
     Cook,
+    #[to("/compare")]
+    Compare,
     #[not_found]
     NotFound,
 }
 
+/// Whether a path is a page we can navigate to. The login page doesn't count
+/// since there's no reason to go back to it.
+pub fn is_known_route(path: &str) -> bool {
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+    !matches!(
+        Routes::default().match_path(path),
+        Routes::Login
+            | Routes::NotFound
+            | Routes::Manage(ManageRoutes::NotFound)
+            | Routes::Planning(PlanningRoutes::NotFound)
+            | Routes::Recipe(RecipeRoutes::NotFound)
+    )
+}
+
+/// Percent encode everything but the unreserved characters and `/` if
+/// `keep_slash` is set.
+fn percent_encode(s: &str, keep_slash: bool) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(b as char)
+            }
+            b'/' if keep_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    encoded
+}
+
+/// Percent encode everything but the unreserved characters and `/`.
+fn encode_component(s: &str) -> String {
+    percent_encode(s, true)
+}
+
+/// Percent encode a recipe id so it stays a single path segment. Recipe ids
+/// have a `/` in them when the recipes are kept in subdirectories.
+pub fn encode_recipe_id(id: &str) -> String {
+    percent_encode(id, false)
+}
+
+/// The page that shows a recipe.
+pub fn recipe_view_route(id: &str) -> String {
+    format!("/ui/recipe/view/{}", encode_recipe_id(id))
+}
+
+/// The page that edits a recipe.
+pub fn recipe_edit_route(id: &str) -> String {
+    format!("/ui/recipe/edit/{}", encode_recipe_id(id))
+}
+
+/// The recipe id from a recipe route segment.
+fn decode_recipe_id(segment: &str) -> String {
+    decode_component(segment).unwrap_or_else(|| segment.to_owned())
+}
+
+fn decode_component(s: &str) -> Option<String> {
+    let mut decoded = Vec::with_capacity(s.len());
+    let mut bytes = s.bytes();
+    while let Some(b) = bytes.next() {
+        match b {
+            b'%' => {
+                let hex = [bytes.next()?, bytes.next()?];
+                decoded.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+            }
+            b'+' => decoded.push(b' '),
+            b => decoded.push(b),
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+/// The login page route that sends the user back to `path` after they log in.
+pub fn login_redirect(path: &str) -> String {
+    format!("{}?next={}", LOGIN_ROUTE, encode_component(path))
+}
+
+/// The route the login page should send the user back to given its query
+/// string. Routes that are off site or that don't exist anymore are ignored
+/// so the caller can fall back to the default route.
+pub fn return_route(search: &str) -> Option<String> {
+    let next = search
+        .trim_start_matches('?')
+        .split('&')
+        .find_map(|param| param.strip_prefix("next="))?;
+    decode_component(next)
+        .filter(|next| next.starts_with("/ui/") && !next.contains("//") && is_known_route(next))
+}
+
+thread_local! {
+    static UNSAVED_CHANGES: Cell<bool> = Cell::new(false);
+    // The url of the page we are showing so we can go back to it if the user
+    // stays on the page after using the browser's back or forward button.
+    static SHOWN_URL: RefCell<String> = RefCell::new(String::new());
+}
+
+/// Record whether the current page has edits that haven't been saved.
+pub fn set_unsaved_changes(unsaved: bool) {
+    UNSAVED_CHANGES.with(|c| c.set(unsaved));
+}
+
+/// Whether a route change should go ahead. We only ask the user to `confirm`
+/// when there are unsaved changes.
+pub fn allow_navigation<F: FnOnce() -> bool>(unsaved: bool, confirm: F) -> bool {
+    !unsaved || confirm()
+}
+
+fn confirm_leave() -> bool {
+    js_lib::get_window()
+        .confirm_with_message("You have unsaved changes. Leave this page anyway?")
+        .unwrap_or(false)
+}
+
+/// Remember the url of the page we are showing.
+fn remember_shown_url() {
+    let location = js_lib::get_window().location();
+    let url = format!(
+        "{}{}{}",
+        location.pathname().unwrap_or_default(),
+        location.search().unwrap_or_default(),
+        location.hash().unwrap_or_default()
+    );
+    SHOWN_URL.with(|u| *u.borrow_mut() = url);
+}
+
+/// A `HistoryIntegration` that asks before leaving unsaved changes by
+/// following a link, using the back or forward buttons, or leaving the app.
+struct GuardedHistoryIntegration(HistoryIntegration);
+
+impl GuardedHistoryIntegration {
+    fn new() -> Self {
+        // NOTE(jwall): Browsers show their own message when leaving the app
+        // so all we can do is ask them to.
+        let before_unload = Closure::<dyn Fn(BeforeUnloadEvent)>::new(|ev: BeforeUnloadEvent| {
+            if UNSAVED_CHANGES.with(|c| c.get()) {
+                ev.prevent_default();
+                ev.set_return_value("");
+            }
+        });
+        let _ = js_lib::get_window().add_event_listener_with_callback(
+            "beforeunload",
+            before_unload.as_ref().unchecked_ref(),
+        );
+        before_unload.forget();
+        Self(HistoryIntegration::new())
+    }
+}
+
+impl Integration for GuardedHistoryIntegration {
+    fn current_pathname(&self) -> String {
+        self.0.current_pathname()
+    }
+
+    fn on_popstate(&self, mut f: Box<dyn FnMut()>) {
+        self.0.on_popstate(Box::new(move || {
+            let unsaved = UNSAVED_CHANGES.with(|c| c.get());
+            if !allow_navigation(unsaved, confirm_leave) {
+                // NOTE(jwall): The browser has already changed the url by the
+                // time we hear about it so we put back the one we are showing.
+                let url = SHOWN_URL.with(|u| u.borrow().clone());
+                let _ = js_lib::get_window().history().and_then(|history| {
+                    history.push_state_with_url(&JsValue::NULL, "", Some(&url))
+                });
+                return;
+            }
+            f()
+        }))
+    }
+
+    fn click_handler(&self) -> Box<dyn Fn(Event)> {
+        let inner = self.0.click_handler();
+        Box::new(move |ev: Event| {
+            let is_link = ev
+                .target()
+                .and_then(|t| t.dyn_into::<Element>().ok())
+                .and_then(|el| el.closest("a[href]").ok().flatten())
+                .is_some();
+            let unsaved = UNSAVED_CHANGES.with(|c| c.get());
+            if is_link && !allow_navigation(unsaved, confirm_leave) {
+                ev.prevent_default();
+                return;
+            }
+            inner(ev)
+        })
+    }
+}
+
 #[derive(Props)]
 pub struct HandlerProps<'ctx> {
     sh: StateHandler<'ctx>,
@@ -78,9 +281,13 @@ pub struct HandlerProps<'ctx> {
 #[instrument(skip_all, fields(?route))]
 fn route_switch<'ctx, G: Html>(route: &Routes, cx: Scope<'ctx>, sh: StateHandler<'ctx>) -> View<G> {
     debug!("Handling route change");
+    remember_shown_url();
     use ManageRoutes::*;
     use PlanningRoutes::*;
     match route {
+        Routes::Home => view! {cx,
+            HomePage(sh)
+        },
         Routes::Planning(Select) => view! {cx,
             SelectPage(sh)
         },
@@ -93,14 +300,17 @@ fn route_switch<'ctx, G: Html>(route: &Routes, cx: Scope<'ctx>, sh: StateHandler
         Routes::Planning(Cook) => view! {cx,
             CookPage(sh)
         },
+        Routes::Planning(Compare) => view! {cx,
+            ComparePage(sh)
+        },
         Routes::Login => view! {cx,
             LoginPage(sh)
         },
         Routes::Recipe(RecipeRoutes::View(id)) => view! {cx,
-            RecipeViewPage(recipe=id.clone(), sh=sh)
+            RecipeViewPage(recipe=decode_recipe_id(id), sh=sh)
         },
         Routes::Recipe(RecipeRoutes::Edit(id)) => view! {cx,
-            RecipeEditPage(recipe=id.clone(), sh=sh)
+            RecipeEditPage(recipe=decode_recipe_id(id), sh=sh)
         },
         Routes::Manage(Categories) => view! {cx,
             IngredientsPage(sh)
@@ -114,6 +324,9 @@ fn route_switch<'ctx, G: Html>(route: &Routes, cx: Scope<'ctx>, sh: StateHandler
         Routes::Manage(Staples) => view! {cx,
             StaplesPage(sh)
         },
+        Routes::Manage(Settings) => view! {cx,
+            SettingsPage(sh)
+        },
         Routes::NotFound
         | Routes::Manage(ManageRoutes::NotFound)
         | Routes::Planning(PlanningRoutes::NotFound)
@@ -129,7 +342,7 @@ pub fn Handler<'ctx, G: Html>(cx: Scope<'ctx>, props: HandlerProps<'ctx>) -> Vie
     let HandlerProps { sh } = props;
     view! {cx,
         Router(
-            integration=HistoryIntegration::new(),
+            integration=GuardedHistoryIntegration::new(),
             view=move |cx: Scope, route: &ReadSignal<Routes>| {
                 view!{cx,
                   div(class="column-flex") {
@@ -141,3 +354,77 @@ pub fn Handler<'ctx, G: Html>(cx: Scope<'ctx>, props: HandlerProps<'ctx>) -> Vie
         )
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_is_known_route() {
+        assert!(is_known_route("/ui/home"));
+        assert!(is_known_route("/ui/planning/plan"));
+        assert!(is_known_route("/ui/recipe/view/soup"));
+        assert!(is_known_route("/ui/manage/settings?tab=1"));
+        assert!(!is_known_route("/ui/planning/gone"));
+        assert!(!is_known_route("/ui/nowhere"));
+        assert!(!is_known_route(LOGIN_ROUTE));
+    }
+
+    #[test]
+    fn test_navigation_guard() {
+        // Clean pages navigate without asking.
+        assert!(allow_navigation(false, || panic!("Should not ask")));
+        // Dirty pages ask and honor the answer.
+        assert!(allow_navigation(true, || true));
+        assert!(!allow_navigation(true, || false));
+    }
+
+    #[test]
+    fn test_login_redirect_round_trip() {
+        for path in [
+            "/ui/recipe/view/soup",
+            "/ui/recipe/view/mom's chili & rice",
+            "/ui/planning/inventory",
+        ] {
+            let redirect = login_redirect(path);
+            let (route, search) = redirect.split_once('?').expect("No query string");
+            assert_eq!(route, LOGIN_ROUTE);
+            let next = search.strip_prefix("next=").expect("No next param");
+            assert!(!next.contains(['&', ' ', '\'']), "{}", redirect);
+            assert_eq!(return_route(search), Some(path.to_owned()));
+        }
+    }
+
+    #[test]
+    fn test_recipe_routes_keep_the_id_in_one_segment() {
+        for id in ["soup", "desserts/pie", "mom's chili & rice"] {
+            for route in [recipe_view_route(id), recipe_edit_route(id)] {
+                let segment = route.rsplit('/').next().unwrap();
+                assert_eq!(decode_recipe_id(segment), id, "{}", route);
+                assert!(is_known_route(&route), "{}", route);
+            }
+        }
+        assert_eq!(
+            recipe_view_route("desserts/pie"),
+            "/ui/recipe/view/desserts%2Fpie"
+        );
+    }
+
+    #[test]
+    fn test_return_route_falls_back() {
+        assert_eq!(return_route(""), None);
+        assert_eq!(return_route("?other=1"), None);
+        assert_eq!(return_route("?next=%2Fui%2Flogin"), None);
+        assert_eq!(return_route("?next=%2Fui%2Fplanning%2Fgone"), None);
+        assert_eq!(
+            return_route("?next=https%3A%2F%2Fexample.com%2Fui%2Fplan"),
+            None
+        );
+        assert_eq!(return_route("?next=/ui//example.com"), None);
+        assert_eq!(return_route("?next=%ZZ"), None);
+        assert_eq!(
+            return_route("?foo=bar&next=%2Fui%2Fplanning%2Fcook"),
+            Some("/ui/planning/cook".to_owned())
+        );
+    }
+}
@@ -0,0 +1,273 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! An inverted index of recipe text so that recipe search works instantly and
+//! offline.
+//!
+//! Text is tokenized by lowercasing it and splitting on anything that isn't
+//! alphanumeric so punctuation never ends up in a token. Tokens shorter than
+//! [MIN_TOKEN_LEN] are dropped and tokens longer than [MAX_TOKEN_LEN] are
+//! truncated. Each recipe contributes at most [MAX_TOKENS_PER_RECIPE] distinct
+//! tokens so the index stays bounded at roughly
+//! `recipes * MAX_TOKENS_PER_RECIPE * MAX_TOKEN_LEN` bytes of words no matter
+//! how long the recipes are.
+use std::collections::{BTreeMap, BTreeSet};
+
+use recipes::RecipeEntry;
+use serde::{Deserialize, Serialize};
+
+/// Bump this when tokenization changes so stored indexes get rebuilt.
+pub const INDEX_VERSION: u32 = 1;
+pub const MIN_TOKEN_LEN: usize = 2;
+pub const MAX_TOKEN_LEN: usize = 24;
+pub const MAX_TOKENS_PER_RECIPE: usize = 1000;
+
+/// Split text into the set of lowercase search tokens it contains.
+pub fn tokenize(text: &str) -> BTreeSet<String> {
+    let mut tokens = BTreeSet::new();
+    for word in text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= MIN_TOKEN_LEN)
+    {
+        if tokens.len() >= MAX_TOKENS_PER_RECIPE {
+            break;
+        }
+        tokens.insert(word.to_lowercase().chars().take(MAX_TOKEN_LEN).collect());
+    }
+    tokens
+}
+
+/// A stable fingerprint of everything we index for an entry. It's used to
+/// tell when the index is out of date with the stored recipes.
+fn fingerprint(entry: &RecipeEntry) -> u64 {
+    // NOTE(jwall): This is FNV-1a. We can't use the std hasher since its
+    // output isn't guaranteed to be stable and these get persisted.
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let mut feed = |bytes: &[u8]| {
+        for b in bytes.iter().chain(std::iter::once(&0xff)) {
+            hash ^= *b as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    };
+    feed(entry.recipe_id().as_bytes());
+    feed(entry.recipe_text().as_bytes());
+    feed(
+        entry
+            .category()
+            .map(|c| c.as_str())
+            .unwrap_or("")
+            .as_bytes(),
+    );
+    for tag in entry.tags().iter() {
+        feed(tag.as_bytes());
+    }
+    hash
+}
+
+fn entry_tokens(entry: &RecipeEntry) -> BTreeSet<String> {
+    let mut text = entry.recipe_text().to_owned();
+    if let Some(cat) = entry.category() {
+        text.push('\n');
+        text.push_str(cat);
+    }
+    for tag in entry.tags().iter() {
+        text.push('\n');
+        text.push_str(tag);
+    }
+    tokenize(&text)
+}
+
+/// An inverted index from words to the ids of the recipes that contain them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchIndex {
+    version: u32,
+    words: BTreeMap<String, BTreeSet<String>>,
+    fingerprints: BTreeMap<String, u64>,
+}
+
+impl Default for SearchIndex {
+    fn default() -> Self {
+        Self {
+            version: INDEX_VERSION,
+            words: BTreeMap::new(),
+            fingerprints: BTreeMap::new(),
+        }
+    }
+}
+
+impl SearchIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build an index from scratch for these entries.
+    pub fn build<'a, I: IntoIterator<Item = &'a RecipeEntry>>(entries: I) -> Self {
+        let mut index = Self::new();
+        for entry in entries {
+            index.update_entry(entry);
+        }
+        index
+    }
+
+    /// Add or replace a single entry in the index.
+    pub fn update_entry(&mut self, entry: &RecipeEntry) {
+        let print = fingerprint(entry);
+        if self.fingerprints.get(entry.recipe_id()) == Some(&print) {
+            return;
+        }
+        self.remove_entry(entry.recipe_id());
+        for token in entry_tokens(entry) {
+            self.words
+                .entry(token)
+                .or_default()
+                .insert(entry.recipe_id().to_owned());
+        }
+        self.fingerprints
+            .insert(entry.recipe_id().to_owned(), print);
+    }
+
+    /// Remove a single entry from the index.
+    pub fn remove_entry(&mut self, recipe_id: &str) {
+        if self.fingerprints.remove(recipe_id).is_none() {
+            return;
+        }
+        self.words.retain(|_, ids| {
+            ids.remove(recipe_id);
+            !ids.is_empty()
+        });
+    }
+
+    /// Whether this index no longer matches the entries it should cover and
+    /// needs to be rebuilt.
+    pub fn is_stale(&self, entries: &[RecipeEntry]) -> bool {
+        self.version != INDEX_VERSION
+            || self.fingerprints.len() != entries.len()
+            || entries
+                .iter()
+                .any(|e| self.fingerprints.get(e.recipe_id()) != Some(&fingerprint(e)))
+    }
+
+    /// Returns the ids of the recipes matching every word in the query. Words
+    /// match as prefixes so results show up while the user is still typing. An
+    /// empty query returns `None` meaning nothing should be filtered.
+    pub fn search(&self, query: &str) -> Option<BTreeSet<String>> {
+        let tokens = tokenize(query);
+        if tokens.is_empty() {
+            return None;
+        }
+        let mut result: Option<BTreeSet<String>> = None;
+        for token in tokens {
+            let matches = self
+                .words
+                .range(token.clone()..)
+                .take_while(|(word, _)| word.starts_with(&token))
+                .flat_map(|(_, ids)| ids.iter().cloned())
+                .collect::<BTreeSet<String>>();
+            result = Some(match result {
+                Some(ids) => ids.intersection(&matches).cloned().collect(),
+                None => matches,
+            });
+        }
+        result
+    }
+
+    /// The ids of every recipe in the index.
+    pub fn recipe_ids(&self) -> Vec<String> {
+        self.fingerprints.keys().cloned().collect()
+    }
+
+    pub fn word_count(&self) -> usize {
+        self.words.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn entry(id: &str, text: &str) -> RecipeEntry {
+        RecipeEntry::new(id, text)
+    }
+
+    fn ids(list: &[&str]) -> Option<BTreeSet<String>> {
+        Some(list.iter().map(|s| s.to_string()).collect())
+    }
+
+    #[test]
+    fn test_tokenize_lowercases_and_strips_punctuation() {
+        assert_eq!(
+            tokenize("Chili: Beef, Beans & (Onions)! a"),
+            BTreeSet::from([
+                "beans".to_owned(),
+                "beef".to_owned(),
+                "chili".to_owned(),
+                "onions".to_owned(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_tokenize_bounds_token_length_and_count() {
+        let long = "x".repeat(MAX_TOKEN_LEN * 2);
+        assert_eq!(tokenize(&long), BTreeSet::from(["x".repeat(MAX_TOKEN_LEN)]));
+        let text = (0..MAX_TOKENS_PER_RECIPE * 2)
+            .map(|i| format!("word{}", i))
+            .collect::<Vec<String>>()
+            .join(" ");
+        assert_eq!(tokenize(&text).len(), MAX_TOKENS_PER_RECIPE);
+    }
+
+    #[test]
+    fn test_search_matches_all_words_by_prefix() {
+        let index = SearchIndex::build(&[
+            entry("chili", "title: Chili\n\nbeef and beans"),
+            entry("tacos", "title: Tacos\n\nbeef and tortillas"),
+        ]);
+        assert_eq!(index.search("beef"), ids(&["chili", "tacos"]));
+        assert_eq!(index.search("BEEF tort"), ids(&["tacos"]));
+        assert_eq!(index.search("beef, beans"), ids(&["chili"]));
+        assert_eq!(index.search("salmon"), ids(&[]));
+        assert_eq!(index.search("  ,"), None);
+    }
+
+    #[test]
+    fn test_update_and_remove_entries() {
+        let mut index = SearchIndex::build(&[entry("soup", "tomato soup")]);
+        index.update_entry(&entry("soup", "lentil soup"));
+        assert_eq!(index.search("tomato"), ids(&[]));
+        assert_eq!(index.search("lentil"), ids(&["soup"]));
+        index.remove_entry("soup");
+        assert_eq!(index.search("soup"), ids(&[]));
+        assert_eq!(index.word_count(), 0);
+    }
+
+    #[test]
+    fn test_category_and_tags_are_indexed() {
+        let mut e = entry("salad", "greens");
+        e.set_category("Sides");
+        e.set_tags(["vegetarian"]);
+        let index = SearchIndex::build(&[e]);
+        assert_eq!(index.search("sides veg"), ids(&["salad"]));
+    }
+
+    #[test]
+    fn test_stale_detection() {
+        let entries = vec![entry("soup", "tomato soup"), entry("stew", "beef stew")];
+        let mut index = SearchIndex::build(&entries);
+        assert!(!index.is_stale(&entries));
+        assert!(index.is_stale(&entries[0..1]));
+        assert!(index.is_stale(&[entries[0].clone(), entry("stew", "lamb stew")]));
+        index.version = INDEX_VERSION + 1;
+        assert!(index.is_stale(&entries));
+    }
+}
@@ -0,0 +1,704 @@
+// Copyright 2023 Jeremy Wall (Jeremy@marzhilsltudios.com)
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Data derived from the `AppState` for components to select. These don't
+//! touch the DOM so they can be shared between components and tested directly.
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::NaiveDate;
+use recipes::{
+    density::DensityTable,
+    unit::{Measure, QuantityDisplay},
+    Ingredient, IngredientAccumulator, IngredientKey,
+};
+use tracing::debug;
+
+use crate::{app_state::AppState, components::loading::Loadable};
+
+/// The accumulated shopping list for the current plan.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShoppingListData {
+    /// Accumulated ingredients along with the recipes that contributed to them.
+    pub ingredients: BTreeMap<IngredientKey, (Ingredient, BTreeSet<String>)>,
+    /// The amount each recipe contributed to an accumulated ingredient.
+    pub breakdown: BTreeMap<IngredientKey, BTreeMap<String, Measure>>,
+    /// Planned recipes that didn't contribute any ingredients. Either because
+    /// they failed to parse or because they have no ingredients.
+    pub empty_recipes: Vec<String>,
+    /// Ingredients whose amounts couldn't be combined with each other.
+    pub warnings: Vec<String>,
+}
+
+/// Accumulate the ingredients for every planned recipe in the state and
+/// optionally the staples.
+pub fn shopping_list_data(state: &AppState, use_staples: bool) -> ShoppingListData {
+    let mut acc = IngredientAccumulator::new()
+        .with_densities(DensityTable::default())
+        .with_measure_types(state.measure_types.clone());
+    let mut empty_recipes = Vec::new();
+    for (id, count) in state.recipe_counts.iter() {
+        if *count == 0 {
+            continue;
+        }
+        match state.recipes.get(id) {
+            Some(recipe)
+                if recipe
+                    .steps
+                    .iter()
+                    .any(|s| !s.ingredients.is_empty() || !s.sub_recipes.is_empty()) =>
+            {
+                for _ in 0..(*count) {
+                    acc.accumulate_from_with(recipe, &state.recipes);
+                }
+            }
+            _ => {
+                debug!(recipe_id = id, "Planned recipe contributes no ingredients");
+                empty_recipes.push(id.clone());
+            }
+        }
+    }
+    if use_staples {
+        if let Some(staples) = &state.staples {
+            acc.accumulate_ingredients_for("Staples", staples.iter());
+        }
+    }
+    let warnings = acc.warnings().iter().cloned().collect();
+    let mut ingredients = BTreeMap::new();
+    let mut breakdown = BTreeMap::new();
+    for (k, (i, amts)) in acc.ingredients_with_breakdown() {
+        ingredients.insert(k.clone(), (i, amts.keys().cloned().collect()));
+        breakdown.insert(k, amts);
+    }
+    ShoppingListData {
+        ingredients,
+        breakdown,
+        empty_recipes,
+        warnings,
+    }
+}
+
+/// A display row for the shopping list.
+/// (key, (name, form, category, amount, amount per recipe, alternate amount))
+/// The alternate amount is the amount converted between weight and volume
+/// when the density of the ingredient is known.
+pub type ShoppingRow = (
+    IngredientKey,
+    (
+        String,
+        Option<String>,
+        String,
+        String,
+        BTreeMap<String, String>,
+        Option<String>,
+    ),
+);
+
+/// Display an amount using the display settings in the state. Rounding and
+/// unit conversion only affect the displayed amount. Amounts are only rounded
+/// when they are displayed as decimals since fractions are exact.
+pub fn display_amt(state: &AppState, amt: &Measure) -> String {
+    let amt = match state.unit_system {
+        Some(system) => amt.clone().convert_to_system(system),
+        None => amt.normalize(),
+    };
+    let amt = match state.quantity_display {
+        QuantityDisplay::Decimal => amt.round_to(state.display_precision),
+        QuantityDisplay::Fraction => amt,
+    };
+    match amt {
+        Measure::Count(_) if state.count_each => amt.display_with_each(state.quantity_display),
+        _ if state.compound_units => amt.display_compound(state.quantity_display),
+        _ => amt.display_with(state.quantity_display),
+    }
+}
+
+/// Where a category sorts in the user's store layout. Categories missing from
+/// the layout sort after the ones in it.
+fn category_position(state: &AppState, category: &String) -> usize {
+    state
+        .category_order
+        .iter()
+        .position(|c| c == category)
+        .unwrap_or(state.category_order.len())
+}
+
+/// Every category in the order the shopping list shows them. The saved store
+/// layout comes first followed by the rest of the categories alphabetically.
+pub fn category_layout(state: &AppState) -> Vec<String> {
+    let mut layout = state.category_order.clone();
+    let rest = state
+        .category_map
+        .values()
+        .filter(|c| !layout.contains(c))
+        .cloned()
+        .collect::<BTreeSet<String>>();
+    layout.extend(rest);
+    layout
+}
+
+/// Build the rows for the shopping list sorted by category and then name.
+/// In shopping mode the checked off rows sort to the bottom of their category.
+/// If `deleted` is true then only the ingredients that have been filtered out
+/// are returned, otherwise only the ones that haven't.
+fn shopping_rows(state: &AppState, use_staples: bool, deleted: bool) -> Vec<ShoppingRow> {
+    let category_map = &state.category_map;
+    let ShoppingListData {
+        ingredients,
+        mut breakdown,
+        ..
+    } = shopping_list_data(state, use_staples);
+    let densities = DensityTable::default();
+    let mut ingredients = ingredients
+        .into_iter()
+        // First we filter out any filtered ingredients
+        .filter(|(i, _)| state.filtered_ingredients.contains(i) == deleted)
+        // Then we take into account our modified amts
+        .map(|(k, (i, _))| {
+            let category = category_map
+                .get(&i.name)
+                .or_else(|| category_map.get(k.name()))
+                .cloned()
+                .unwrap_or_else(|| String::new());
+            // NOTE(jwall): A modified amount is free text so we can't convert it.
+            let (amt, alt) = match state.modified_amts.get(&k) {
+                Some(amt) => (amt.clone(), None),
+                None => (
+                    display_amt(state, &i.amt),
+                    densities
+                        .convert(&i.name, &i.amt)
+                        .map(|alt| display_amt(state, &alt)),
+                ),
+            };
+            let rs = breakdown
+                .remove(&k)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(r, amt)| (r, display_amt(state, &amt)))
+                .collect();
+            let name = i.name_for_display().to_owned();
+            (k, (name, i.form, category, amt, rs, alt))
+        })
+        .collect::<Vec<ShoppingRow>>();
+    let checked = |k: &IngredientKey| state.shopping_mode && state.checked_off.contains(k);
+    ingredients.sort_by_cached_key(|(k, (name, _, category, _, _, _))| {
+        (
+            category_position(state, category),
+            category.clone(),
+            checked(k),
+            name.clone(),
+        )
+    });
+    ingredients
+}
+
+/// The rows of the shopping list that are still in the inventory.
+pub fn shopping_list(state: &AppState, show_staples: bool) -> Vec<ShoppingRow> {
+    shopping_rows(state, show_staples, false)
+}
+
+/// The rows of the shopping list that have been removed from the inventory.
+pub fn deleted_ingredients(state: &AppState, show_staples: bool) -> Vec<ShoppingRow> {
+    shopping_rows(state, show_staples, true)
+}
+
+/// Planned recipes that don't add anything to the shopping list. Staples
+/// don't have any effect on which recipes are empty.
+pub fn empty_recipes(state: &AppState) -> Vec<String> {
+    shopping_list_data(state, false).empty_recipes
+}
+
+/// Ingredients listed more than once because their amounts can't be combined.
+pub fn shopping_warnings(state: &AppState, show_staples: bool) -> Vec<String> {
+    shopping_list_data(state, show_staples).warnings
+}
+
+/// Count the checked off rows and the total rows of the shopping list.
+pub fn shopping_progress(state: &AppState) -> (usize, usize) {
+    let rows = shopping_list(state, state.use_staples);
+    let checked = rows
+        .iter()
+        .filter(|(k, _)| state.checked_off.contains(k))
+        .count();
+    (checked, rows.len())
+}
+
+/// The amount an ingredient was bought as the last time it was bought. There
+/// is no hint once a purchase has been recorded for the current plan.
+pub fn purchase_hint(state: &AppState, key: &IngredientKey) -> Option<String> {
+    if state.purchased_amts.contains_key(key) {
+        return None;
+    }
+    state.purchase_hints.get(key).cloned()
+}
+
+/// The most upcoming plans the dashboard shows.
+pub const MAX_UPCOMING_PLANS: usize = 5;
+
+/// A summary of the user's recipes and plans for the dashboard.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DashboardData {
+    pub recipe_count: usize,
+    /// The number of recipes planned for the selected plan date.
+    pub planned_recipes: u32,
+    pub selected_plan_date: Option<NaiveDate>,
+    /// Plan dates from `today` on, soonest first.
+    pub upcoming_plans: Vec<NaiveDate>,
+}
+
+/// Summarize the state for the dashboard as of `today`.
+pub fn dashboard(state: &AppState, today: NaiveDate) -> DashboardData {
+    DashboardData {
+        recipe_count: state.recipes.len(),
+        planned_recipes: state.recipe_counts.values().sum(),
+        selected_plan_date: state.selected_plan_date,
+        upcoming_plans: state
+            .plan_dates
+            .range(today..)
+            .take(MAX_UPCOMING_PLANS)
+            .cloned()
+            .collect(),
+    }
+}
+
+/// Whether the recipes for the pages that need them have loaded.
+pub fn load_status(state: &AppState) -> Loadable<()> {
+    if state.loaded {
+        Loadable::Ready(())
+    } else {
+        Loadable::Loading
+    }
+}
+
+/// Whether a recipe has loaded. A recipe that is still missing once
+/// everything has loaded doesn't exist.
+pub fn recipe_status(state: &AppState, recipe_id: &str) -> Loadable<()> {
+    if state.recipes.contains_key(recipe_id) {
+        Loadable::Ready(())
+    } else if state.loaded {
+        Loadable::Failed(format!("Unable to find recipe {}", recipe_id))
+    } else {
+        Loadable::Loading
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::recipe_with_ingredients;
+    use recipes::unit::{Quantity, UnitSystem, DEFAULT_PRECISION};
+
+    #[test]
+    fn test_shopping_list_data_flags_empty_recipes() {
+        let mut state = AppState::new();
+        state.recipes.insert(
+            "soup".to_owned(),
+            recipe_with_ingredients(
+                "soup",
+                vec![Ingredient::new("onion", None, Measure::count(1))],
+            ),
+        );
+        state
+            .recipes
+            .insert("empty".to_owned(), recipe_with_ingredients("empty", vec![]));
+        state.recipe_counts.insert("soup".to_owned(), 2);
+        state.recipe_counts.insert("empty".to_owned(), 1);
+        // A recipe that failed to parse has a count but no recipe entry.
+        state.recipe_counts.insert("unparsed".to_owned(), 1);
+        // Unplanned recipes are never flagged.
+        state.recipe_counts.insert("unplanned".to_owned(), 0);
+
+        let data = shopping_list_data(&state, false);
+        assert_eq!(
+            data.empty_recipes,
+            vec!["empty".to_owned(), "unparsed".to_owned()]
+        );
+        assert_eq!(data.ingredients.len(), 1);
+        let (onion, recipes) = data.ingredients.values().next().unwrap();
+        assert_eq!(onion.amt, Measure::count(2));
+        assert!(recipes.contains("soup"));
+        let breakdown = data.breakdown.values().next().unwrap();
+        assert_eq!(breakdown.get("soup"), Some(&Measure::count(2)));
+    }
+
+    #[test]
+    fn test_shopping_list_data_staples() {
+        let mut state = AppState::new();
+        state.staples = Some(BTreeSet::from_iter(vec![Ingredient::new(
+            "salt",
+            None,
+            Measure::count(1),
+        )]));
+        assert!(shopping_list_data(&state, false).ingredients.is_empty());
+        let data = shopping_list_data(&state, true);
+        assert_eq!(data.ingredients.len(), 1);
+        assert!(data.empty_recipes.is_empty());
+    }
+
+    #[test]
+    fn test_rows_show_display_name_and_match_on_key() {
+        let mut state = AppState::new();
+        let onion = Ingredient::new("green onion", None, Measure::count(2))
+            .with_display_name("Green Onions (Scallions)");
+        state.recipes.insert(
+            "soup".to_owned(),
+            recipe_with_ingredients("soup", vec![onion.clone()]),
+        );
+        state.recipe_counts.insert("soup".to_owned(), 1);
+        state
+            .category_map
+            .insert("green onion".to_owned(), "Produce".to_owned());
+        let rows = shopping_list(&state, false);
+        assert_eq!(rows.len(), 1);
+        let (key, (name, _, category, _, _, _)) = &rows[0];
+        assert_eq!(key, &onion.key());
+        assert_eq!(name, "Green Onions (Scallions)");
+        assert_eq!(category, "Produce");
+    }
+
+    #[test]
+    fn test_deleted_rows_sorted_by_category_and_name() {
+        let mut state = AppState::new();
+        let ingredients = vec![
+            Ingredient::new("onion", None, Measure::count(1)),
+            Ingredient::new("carrot", None, Measure::count(1)),
+            Ingredient::new("milk", None, Measure::count(1)),
+            Ingredient::new("butter", None, Measure::count(1)),
+            Ingredient::new("salt", None, Measure::count(1)),
+        ];
+        for i in ingredients.iter() {
+            if i.name != "salt" {
+                state.filtered_ingredients.insert(i.key());
+            }
+        }
+        state.recipes.insert(
+            "soup".to_owned(),
+            recipe_with_ingredients("soup", ingredients),
+        );
+        state.recipe_counts.insert("soup".to_owned(), 1);
+        state
+            .category_map
+            .insert("onion".to_owned(), "produce".to_owned());
+        state
+            .category_map
+            .insert("carrot".to_owned(), "produce".to_owned());
+        state
+            .category_map
+            .insert("milk".to_owned(), "dairy".to_owned());
+        state
+            .category_map
+            .insert("butter".to_owned(), "dairy".to_owned());
+
+        let deleted = deleted_ingredients(&state, false)
+            .into_iter()
+            .map(|(_, (name, _, category, _, _, _))| (category, name))
+            .collect::<Vec<(String, String)>>();
+        assert_eq!(
+            deleted,
+            vec![
+                ("dairy".to_owned(), "butter".to_owned()),
+                ("dairy".to_owned(), "milk".to_owned()),
+                ("produce".to_owned(), "carrot".to_owned()),
+                ("produce".to_owned(), "onion".to_owned()),
+            ]
+        );
+        let remaining = shopping_list(&state, false);
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].1 .0, "salt");
+    }
+
+    #[test]
+    fn test_rows_follow_custom_category_order() {
+        let mut state = AppState::new();
+        let ingredients = vec![
+            Ingredient::new("onion", None, Measure::count(1)),
+            Ingredient::new("milk", None, Measure::count(1)),
+            Ingredient::new("bread", None, Measure::count(1)),
+            Ingredient::new("salt", None, Measure::count(1)),
+        ];
+        state.recipes.insert(
+            "soup".to_owned(),
+            recipe_with_ingredients("soup", ingredients),
+        );
+        state.recipe_counts.insert("soup".to_owned(), 1);
+        for (name, category) in [
+            ("onion", "produce"),
+            ("milk", "dairy"),
+            ("bread", "bakery"),
+            ("salt", "spices"),
+        ] {
+            state
+                .category_map
+                .insert(name.to_owned(), category.to_owned());
+        }
+        let categories = |state: &AppState| {
+            shopping_list(state, false)
+                .into_iter()
+                .map(|(_, (_, _, category, _, _, _))| category)
+                .collect::<Vec<String>>()
+        };
+        // Without a custom order the categories are alphabetical.
+        assert_eq!(
+            categories(&state),
+            vec!["bakery", "dairy", "produce", "spices"]
+        );
+        // Categories missing from the order come last.
+        state.category_order = vec!["produce".to_owned(), "dairy".to_owned()];
+        assert_eq!(
+            categories(&state),
+            vec!["produce", "dairy", "bakery", "spices"]
+        );
+        assert_eq!(category_layout(&state), categories(&state));
+    }
+
+    #[test]
+    fn test_checked_off_rows_sort_last_in_category_in_shopping_mode() {
+        let mut state = AppState::new();
+        let ingredients = vec![
+            Ingredient::new("carrot", None, Measure::count(1)),
+            Ingredient::new("onion", None, Measure::count(1)),
+            Ingredient::new("potato", None, Measure::count(1)),
+        ];
+        for i in ingredients.iter() {
+            state
+                .category_map
+                .insert(i.name.clone(), "produce".to_owned());
+        }
+        let carrot = ingredients[0].key();
+        state.checked_off.insert(carrot.clone());
+        state.recipes.insert(
+            "soup".to_owned(),
+            recipe_with_ingredients("soup", ingredients),
+        );
+        state.recipe_counts.insert("soup".to_owned(), 1);
+        let names = |state: &AppState| {
+            shopping_list(state, false)
+                .into_iter()
+                .map(|(_, (name, _, _, _, _, _))| name)
+                .collect::<Vec<String>>()
+        };
+
+        // Checks don't affect the order outside of shopping mode.
+        assert_eq!(names(&state), vec!["carrot", "onion", "potato"]);
+        state.shopping_mode = true;
+        assert_eq!(names(&state), vec!["onion", "potato", "carrot"]);
+        // Checked off rows stay on the shopping list.
+        assert!(deleted_ingredients(&state, false).is_empty());
+        assert_eq!(shopping_progress(&state), (1, 3));
+        // Deleted rows don't count towards the progress even if checked off.
+        state.filtered_ingredients.insert(carrot);
+        assert_eq!(names(&state), vec!["onion", "potato"]);
+        assert_eq!(shopping_progress(&state), (0, 2));
+    }
+
+    #[test]
+    fn test_shopping_list_selector() {
+        let mut state = AppState::new();
+        let onion = Ingredient::new("onion", None, Measure::count(1));
+        state.recipes.insert(
+            "soup".to_owned(),
+            recipe_with_ingredients("soup", vec![onion.clone()]),
+        );
+        state.recipe_counts.insert("soup".to_owned(), 3);
+        state.staples = Some(BTreeSet::from_iter(vec![Ingredient::new(
+            "salt",
+            None,
+            Measure::count(1),
+        )]));
+        let amts = |state: &AppState, show_staples: bool| {
+            shopping_list(state, show_staples)
+                .into_iter()
+                .map(|(_, (name, _, _, amt, _, _))| (name, amt))
+                .collect::<Vec<(String, String)>>()
+        };
+        assert_eq!(
+            amts(&state, false),
+            vec![("onion".to_owned(), "3".to_owned())]
+        );
+        assert_eq!(
+            amts(&state, true),
+            vec![
+                ("onion".to_owned(), "3".to_owned()),
+                ("salt".to_owned(), "1".to_owned())
+            ]
+        );
+        // Modified amounts replace the computed ones.
+        state.modified_amts.insert(onion.key(), "2 bags".to_owned());
+        assert_eq!(
+            amts(&state, false),
+            vec![("onion".to_owned(), "2 bags".to_owned())]
+        );
+        assert_eq!(shopping_progress(&state), (0, 1));
+        assert!(empty_recipes(&state).is_empty());
+        assert!(shopping_warnings(&state, true).is_empty());
+    }
+
+    #[test]
+    fn test_shopping_list_alternate_amounts() {
+        let mut state = AppState::new();
+        let flour = Ingredient::new("flour", None, Measure::cup(Quantity::Whole(1)));
+        state.recipes.insert(
+            "bread".to_owned(),
+            recipe_with_ingredients(
+                "bread",
+                vec![
+                    flour.clone(),
+                    Ingredient::new("onion", None, Measure::count(1)),
+                ],
+            ),
+        );
+        state.recipe_counts.insert("bread".to_owned(), 1);
+        let alts = |state: &AppState| {
+            shopping_list(state, false)
+                .into_iter()
+                .map(|(_, (name, _, _, amt, _, alt))| (name, amt, alt))
+                .collect::<Vec<(String, String, Option<String>)>>()
+        };
+        // Volumes stay volumes and the weight is only offered as an alternate.
+        let cup = display_amt(&state, &flour.amt);
+        let grams = display_amt(&state, &Measure::gram(Quantity::Whole(125)));
+        assert_eq!(
+            alts(&state),
+            vec![
+                ("flour".to_owned(), cup, Some(grams)),
+                ("onion".to_owned(), "1".to_owned(), None)
+            ]
+        );
+        // Modified amounts are free text and don't get converted.
+        state.modified_amts.insert(flour.key(), "1 bag".to_owned());
+        assert_eq!(
+            alts(&state)[0],
+            ("flour".to_owned(), "1 bag".to_owned(), None)
+        );
+    }
+
+    #[test]
+    fn test_shopping_list_unit_system() {
+        let mut state = AppState::new();
+        state.recipes.insert(
+            "bread".to_owned(),
+            recipe_with_ingredients(
+                "bread",
+                vec![
+                    Ingredient::new("milk", None, Measure::cup(Quantity::Whole(2))),
+                    Ingredient::new("onion", None, Measure::count(1)),
+                ],
+            ),
+        );
+        state.recipe_counts.insert("bread".to_owned(), 1);
+        let amts = |state: &AppState| {
+            shopping_list(state, false)
+                .into_iter()
+                .map(|(_, (_, _, _, amt, _, _))| amt)
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(amts(&state), vec!["2 cups".to_owned(), "1".to_owned()]);
+        state.unit_system = Some(UnitSystem::Metric);
+        assert_eq!(amts(&state), vec!["480 ml".to_owned(), "1".to_owned()]);
+        state.unit_system = Some(UnitSystem::US);
+        assert_eq!(amts(&state), vec!["1 pint".to_owned(), "1".to_owned()]);
+    }
+
+    #[test]
+    fn test_display_amt_only_rounds_decimals() {
+        let mut state = AppState::new();
+        let third = Measure::cup(Quantity::frac(0, 1, 3));
+        let fraction = display_amt(&state, &third);
+        assert_eq!(
+            fraction,
+            third.normalize().display_with(QuantityDisplay::Fraction)
+        );
+        assert!(!fraction.contains("/100"), "{}", fraction);
+        state.quantity_display = QuantityDisplay::Decimal;
+        assert_eq!(
+            display_amt(&state, &third),
+            third
+                .normalize()
+                .round_to(DEFAULT_PRECISION)
+                .display_with(QuantityDisplay::Decimal)
+        );
+    }
+
+    #[test]
+    fn test_pages_are_loading_until_recipes_load() {
+        let mut state = AppState::new();
+        assert!(load_status(&state).is_loading());
+        assert!(recipe_status(&state, "soup").is_loading());
+        // A recipe is ready as soon as it is in the state.
+        state.recipes.insert(
+            "soup".to_owned(),
+            recipe_with_ingredients("soup", Vec::new()),
+        );
+        assert_eq!(recipe_status(&state, "soup"), Loadable::Ready(()));
+        assert!(load_status(&state).is_loading());
+        state.loaded = true;
+        assert_eq!(load_status(&state), Loadable::Ready(()));
+        assert_eq!(
+            recipe_status(&state, "stew"),
+            Loadable::Failed("Unable to find recipe stew".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_purchase_hint_until_a_purchase_is_recorded() {
+        let mut state = AppState::new();
+        let cream = Ingredient::new("heavy cream", None, Measure::cup(Quantity::frac(1, 3, 4)));
+        state
+            .purchase_hints
+            .insert(cream.key(), "1 pint".to_owned());
+        assert_eq!(
+            purchase_hint(&state, &cream.key()),
+            Some("1 pint".to_owned())
+        );
+        let onion = Ingredient::new("onion", None, Measure::count(1));
+        assert_eq!(purchase_hint(&state, &onion.key()), None);
+        state
+            .purchased_amts
+            .insert(cream.key(), "1 quart".to_owned());
+        assert_eq!(purchase_hint(&state, &cream.key()), None);
+    }
+
+    #[test]
+    fn test_dashboard_summarizes_state() {
+        let date = |d| NaiveDate::from_ymd_opt(2026, 10, d).unwrap();
+        let mut state = AppState::new();
+        assert_eq!(
+            dashboard(&state, date(18)),
+            DashboardData {
+                recipe_count: 0,
+                planned_recipes: 0,
+                selected_plan_date: None,
+                upcoming_plans: Vec::new(),
+            }
+        );
+        for id in ["soup", "stew", "salad"] {
+            state
+                .recipes
+                .insert(id.to_owned(), recipe_with_ingredients(id, vec![]));
+        }
+        state.recipe_counts.insert("soup".to_owned(), 2);
+        state.recipe_counts.insert("stew".to_owned(), 1);
+        state.recipe_counts.insert("salad".to_owned(), 0);
+        state.selected_plan_date = Some(date(18));
+        for d in [1, 17, 18, 19, 20, 21, 22, 23, 24] {
+            state.plan_dates.insert(date(d));
+        }
+        let data = dashboard(&state, date(18));
+        assert_eq!(data.recipe_count, 3);
+        assert_eq!(data.planned_recipes, 3);
+        assert_eq!(data.selected_plan_date, Some(date(18)));
+        assert_eq!(
+            data.upcoming_plans,
+            vec![date(18), date(19), date(20), date(21), date(22)]
+        );
+    }
+}
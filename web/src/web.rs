@@ -11,11 +11,13 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+use std::cell::RefCell;
+
 use sycamore::{futures::spawn_local_scoped, prelude::*};
-use tracing::{debug, info, instrument};
+use tracing::{debug, error, info, instrument};
 
 use crate::app_state::Message;
-use crate::{api, routing::Handler as RouteHandler};
+use crate::{api, js_lib, routing::Handler as RouteHandler};
 
 #[instrument]
 #[component]
@@ -26,20 +28,79 @@ pub fn UI<G: Html>(cx: Scope) -> View<G> {
     info!("Starting UI");
     spawn_local_scoped(cx, {
         async move {
+            let started_at = js_lib::get_ms_timestamp();
             let local_store = api::LocalStore::new();
             // TODO(jwall): At some point we can drop this potentially?
             local_store.migrate().await;
-            let app_state = if let Some(app_state) = local_store.fetch_app_state().await {
-                app_state
-            } else {
-                crate::app_state::AppState::new()
-            };
+            // NOTE(jwall): We render the last known state from the LocalStore
+            // right away and synchronize with the server in the background.
+            let app_state = crate::app_state::warm_start_state(
+                local_store.fetch_app_state().await,
+                local_store.get_recipes().await,
+            );
             debug!(?app_state, "Loaded app state from local storage");
+            let warm = app_state.loaded;
+            let session_expired = store.session().expired().clone();
             let sh = crate::app_state::get_state_handler(cx, app_state, store);
-            sh.dispatch(cx, Message::LoadState(None));
+            create_effect(cx, move || {
+                if *session_expired.get() {
+                    sh.dispatch(cx, Message::AuthExpired);
+                }
+            });
+            // NOTE(jwall): The EventSource handler has to be 'static so the
+            // changes reach the state handler through an RcSignal.
+            let changes = create_rc_signal(None);
+            let subscription = create_ref(cx, RefCell::new(None::<web_sys::EventSource>));
+            let user = sh.get_selector(cx, |state| {
+                state.get().auth.as_ref().map(|u| u.user_id.clone())
+            });
+            create_effect(cx, {
+                let changes = changes.clone();
+                move || {
+                    // NOTE(jwall): The event stream is rejected until we log
+                    // in and an EventSource doesn't retry after an error
+                    // status so we subscribe again whenever the user changes.
+                    user.track();
+                    if let Some(source) = subscription.borrow_mut().take() {
+                        source.close();
+                    }
+                    let changes = changes.clone();
+                    match api::HttpStore::get_from_context(cx)
+                        .subscribe_changes(move |event| changes.set(Some(event)))
+                    {
+                        Ok(source) => *subscription.borrow_mut() = Some(source),
+                        Err(err) => error!(?err, "Failed to subscribe to changes"),
+                    }
+                }
+            });
+            on_cleanup(cx, move || {
+                if let Some(source) = subscription.borrow_mut().take() {
+                    source.close();
+                }
+            });
+            create_effect(cx, move || {
+                if let Some(event) = changes.get().as_ref().clone() {
+                    untrack(|| sh.dispatch(cx, Message::RemoteChange(event)));
+                }
+            });
             view.set(view! { cx,
                 RouteHandler(sh=sh)
             });
+            if warm {
+                info!(
+                    elapsed_ms = js_lib::get_ms_timestamp() - started_at,
+                    "First paint with cached data"
+                );
+            }
+            sh.dispatch(
+                cx,
+                Message::LoadState(Some(Box::new(move || {
+                    info!(
+                        elapsed_ms = js_lib::get_ms_timestamp() - started_at,
+                        "Sync finished"
+                    );
+                }))),
+            );
         }
     });
 